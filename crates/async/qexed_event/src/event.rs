@@ -0,0 +1,89 @@
+use uuid::Uuid;
+
+/// 服务发布的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// 玩家完成进服,取消后玩家会被断开连接
+    PlayerJoin { uuid: Uuid, name: String },
+    /// 玩家离开,无法取消
+    PlayerQuit { uuid: Uuid, name: String },
+    /// 玩家发送聊天消息,可修改 message 或取消
+    Chat {
+        uuid: Uuid,
+        name: String,
+        message: String,
+    },
+    /// 玩家执行指令前,command 不含开头的 /,可修改或取消
+    CommandPreprocess {
+        uuid: Uuid,
+        name: String,
+        command: String,
+    },
+    /// 玩家破坏方块,取消后方块会在客户端恢复
+    BlockBreak {
+        uuid: Uuid,
+        name: String,
+        x: i32,
+        y: i32,
+        z: i32,
+    },
+}
+
+impl Event {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Event::PlayerJoin { .. } => EventKind::PlayerJoin,
+            Event::PlayerQuit { .. } => EventKind::PlayerQuit,
+            Event::Chat { .. } => EventKind::Chat,
+            Event::CommandPreprocess { .. } => EventKind::CommandPreprocess,
+            Event::BlockBreak { .. } => EventKind::BlockBreak,
+        }
+    }
+
+    /// 触发事件的玩家
+    pub fn player(&self) -> (Uuid, &str) {
+        match self {
+            Event::PlayerJoin { uuid, name }
+            | Event::PlayerQuit { uuid, name }
+            | Event::Chat { uuid, name, .. }
+            | Event::CommandPreprocess { uuid, name, .. }
+            | Event::BlockBreak { uuid, name, .. } => (*uuid, name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    PlayerJoin,
+    PlayerQuit,
+    Chat,
+    CommandPreprocess,
+    BlockBreak,
+}
+
+/// 监听优先级,按从低到高的顺序调用
+/// Monitor 最后调用,只用于观察最终结果,对事件的修改与取消都会被忽略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Lowest,
+    Low,
+    #[default]
+    Normal,
+    High,
+    Highest,
+    Monitor,
+}
+
+/// 在监听器之间传递的事件及其取消状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dispatch {
+    pub event: Event,
+    pub cancelled: bool,
+}
+
+impl Dispatch {
+    pub fn cancel(mut self) -> Self {
+        self.cancelled = true;
+        self
+    }
+}
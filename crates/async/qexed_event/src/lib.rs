@@ -0,0 +1,221 @@
+//! 事件总线
+//!
+//! 服务在执行默认行为前发布事件,原生监听器与插件按优先级依次处理,可以修改或取消事件。
+//! 事件被取消后,除 Monitor 外的后续监听器不再调用。
+//! 每个监听器都有超时限制,超时或出错时跳过该监听器,事件保持原样继续传递。
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+pub mod event;
+pub mod packet;
+
+pub use event::{Dispatch, Event, EventKind, Priority};
+pub use packet::PacketHooks;
+
+type Handler = Arc<
+    dyn Fn(Dispatch) -> Pin<Box<dyn Future<Output = anyhow::Result<Dispatch>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// 监听器编号,用于注销
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(u64);
+
+struct Listener {
+    id: ListenerId,
+    /// 注册者,如服务名或 plugin:<插件名>
+    owner: String,
+    kind: EventKind,
+    priority: Priority,
+    handler: Handler,
+}
+
+/// 事件总线,可克隆后分发给各服务
+#[derive(Clone)]
+pub struct EventBus {
+    listeners: Arc<RwLock<Vec<Arc<Listener>>>>,
+    next_id: Arc<AtomicU64>,
+    timeout: Duration,
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let listeners = self.listeners.read().map(|l| l.len()).unwrap_or_default();
+        f.debug_struct("EventBus")
+            .field("listeners", &listeners)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl EventBus {
+    pub fn new(config: &qexed_config::app::qexed_event::EventConfig) -> Self {
+        Self::with_timeout(Duration::from_millis(config.listener_timeout_ms))
+    }
+
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            listeners: Arc::new(RwLock::new(vec![])),
+            next_id: Arc::new(AtomicU64::new(1)),
+            timeout,
+        }
+    }
+
+    /// 注册监听器,同优先级按注册顺序调用
+    pub fn subscribe<F, Fut>(
+        &self,
+        owner: &str,
+        kind: EventKind,
+        priority: Priority,
+        handler: F,
+    ) -> ListenerId
+    where
+        F: Fn(Dispatch) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<Dispatch>> + Send + 'static,
+    {
+        let id = ListenerId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let listener = Arc::new(Listener {
+            id,
+            owner: owner.to_string(),
+            kind,
+            priority,
+            handler: Arc::new(move |dispatch| Box::pin(handler(dispatch))),
+        });
+        let mut listeners = self.listeners.write().unwrap_or_else(|e| e.into_inner());
+        let index = listeners.partition_point(|l| l.priority <= priority);
+        listeners.insert(index, listener);
+        id
+    }
+
+    pub fn unsubscribe(&self, id: ListenerId) {
+        self.listeners
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|l| l.id != id);
+    }
+
+    /// 注销某个注册者的全部监听器
+    pub fn unsubscribe_owner(&self, owner: &str) {
+        self.listeners
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|l| l.owner != owner);
+    }
+
+    /// 是否有监听器,服务可据此跳过构建事件
+    pub fn has_listeners(&self, kind: EventKind) -> bool {
+        self.listeners
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|l| l.kind == kind)
+    }
+
+    /// 发布事件,返回所有监听器处理后的结果
+    pub async fn publish(&self, event: Event) -> Dispatch {
+        let kind = event.kind();
+        let listeners: Vec<Arc<Listener>> = self
+            .listeners
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|l| l.kind == kind)
+            .cloned()
+            .collect();
+        let mut dispatch = Dispatch {
+            event,
+            cancelled: false,
+        };
+        for listener in listeners {
+            let monitor = listener.priority == Priority::Monitor;
+            if dispatch.cancelled && !monitor {
+                continue;
+            }
+            match tokio::time::timeout(self.timeout, (listener.handler)(dispatch.clone())).await {
+                Ok(Ok(result)) => {
+                    if monitor {
+                        continue;
+                    }
+                    if result.event.kind() != kind {
+                        log::warn!("[事件] {} 修改了 {:?} 的事件类型,已忽略", listener.owner, kind);
+                        continue;
+                    }
+                    dispatch = result;
+                }
+                Ok(Err(e)) => {
+                    log::warn!("[事件] {} 处理 {:?} 出错: {:#}", listener.owner, kind, e);
+                }
+                Err(_) => {
+                    log::warn!(
+                        "[事件] {} 处理 {:?} 超时({}ms),已跳过",
+                        listener.owner,
+                        kind,
+                        self.timeout.as_millis()
+                    );
+                }
+            }
+        }
+        dispatch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn chat(message: &str) -> Event {
+        Event::Chat {
+            uuid: Uuid::nil(),
+            name: "Steve".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn append(mut dispatch: Dispatch, suffix: &str) -> Dispatch {
+        if let Event::Chat { message, .. } = &mut dispatch.event {
+            message.push_str(suffix);
+        }
+        dispatch
+    }
+
+    #[tokio::test]
+    async fn priority_modify_and_cancel() {
+        let bus = EventBus::with_timeout(Duration::from_millis(50));
+        bus.subscribe("high", EventKind::Chat, Priority::High, |d| async move { Ok(append(d, "-high")) });
+        bus.subscribe("low", EventKind::Chat, Priority::Low, |d| async move { Ok(append(d, "-low")) });
+        bus.subscribe("monitor", EventKind::Chat, Priority::Monitor, |d| async move {
+            Ok(append(d, "-monitor").cancel())
+        });
+        bus.subscribe("slow", EventKind::Chat, Priority::Normal, |d| async move {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(d.cancel())
+        });
+        assert!(!bus.has_listeners(EventKind::BlockBreak));
+
+        let result = bus.publish(chat("hi")).await;
+        assert_eq!(result.event, chat("hi-low-high"));
+        assert!(!result.cancelled);
+
+        let blocker = bus.subscribe("blocker", EventKind::Chat, Priority::Lowest, |d| async move {
+            Ok(d.cancel())
+        });
+        let result = bus.publish(chat("hi")).await;
+        assert_eq!(result.event, chat("hi"));
+        assert!(result.cancelled);
+
+        bus.unsubscribe(blocker);
+        bus.unsubscribe_owner("slow");
+        bus.unsubscribe_owner("high");
+        let result = bus.publish(chat("hi")).await;
+        assert_eq!(result.event, chat("hi-low"));
+    }
+}
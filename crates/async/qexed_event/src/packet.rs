@@ -0,0 +1,399 @@
+//! 数据包钩子
+//!
+//! 连接层(tcp_connect)在读写循环中、分流层(packet_split)在分发前调用已注册的钩子,
+//! 钩子可以观察、改写或丢弃数据包。数据包为未压缩、未加密的 包ID + 内容。
+//! 没有钩子时只读取一个原子计数,不会复制数据包。
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use bytes::Bytes;
+use qexed_task::mailbox::{MailboxError, MailboxSender};
+use uuid::Uuid;
+
+/// 钩子所在的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HookPoint {
+    /// 连接层,可以看到配置与游玩阶段的双向数据包
+    Connection,
+    /// 分流层,只能看到游玩阶段客户端发来的数据包
+    Split,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// 客户端发往服务端
+    Serverbound,
+    /// 服务端发往客户端
+    Clientbound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum State {
+    Configuration,
+    Play,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookMode {
+    /// 只观察,在改写类钩子之后异步调用,看到的是最终发出的数据包
+    Listen,
+    /// 可以改写或丢弃,按注册顺序依次调用
+    Modify,
+}
+
+/// 钩子匹配条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HookFilter {
+    pub point: HookPoint,
+    pub direction: Direction,
+    pub state: State,
+    /// 为空表示匹配全部包ID
+    pub id: Option<i32>,
+    pub mode: HookMode,
+}
+
+/// 交给钩子处理的数据包
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub uuid: Uuid,
+    pub direction: Direction,
+    pub state: State,
+    pub id: i32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PacketAction {
+    Pass,
+    Replace(Vec<u8>),
+    Drop,
+}
+
+type Handler = Arc<
+    dyn Fn(Packet) -> Pin<Box<dyn Future<Output = anyhow::Result<PacketAction>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// 钩子编号,用于注销
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HookId(u64);
+
+struct Hook {
+    id: HookId,
+    owner: String,
+    filter: HookFilter,
+    handler: Handler,
+}
+
+/// 连接的注入通道
+struct Connection {
+    to_client: MailboxSender<Bytes>,
+    to_server: MailboxSender<Vec<u8>>,
+}
+
+/// 数据包钩子注册表,可克隆后分发给各服务
+#[derive(Clone)]
+pub struct PacketHooks {
+    hooks: Arc<RwLock<Vec<Arc<Hook>>>>,
+    // 每个位置的钩子数量,读写循环据此跳过钩子
+    connection_hooks: Arc<AtomicUsize>,
+    split_hooks: Arc<AtomicUsize>,
+    connections: Arc<RwLock<HashMap<Uuid, Connection>>>,
+    next_id: Arc<AtomicU64>,
+    timeout: Duration,
+}
+
+impl std::fmt::Debug for PacketHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PacketHooks")
+            .field("connection_hooks", &self.connection_hooks.load(Ordering::Relaxed))
+            .field("split_hooks", &self.split_hooks.load(Ordering::Relaxed))
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl PacketHooks {
+    pub fn new(config: &qexed_config::app::qexed_event::EventConfig) -> Self {
+        Self::with_timeout(Duration::from_millis(config.packet_hook_timeout_ms))
+    }
+
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            hooks: Arc::new(RwLock::new(vec![])),
+            connection_hooks: Arc::new(AtomicUsize::new(0)),
+            split_hooks: Arc::new(AtomicUsize::new(0)),
+            connections: Arc::new(RwLock::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            timeout,
+        }
+    }
+
+    fn counter(&self, point: HookPoint) -> &AtomicUsize {
+        match point {
+            HookPoint::Connection => &self.connection_hooks,
+            HookPoint::Split => &self.split_hooks,
+        }
+    }
+
+    /// 该位置是否有钩子,读写循环在复制数据包前先检查
+    pub fn is_active(&self, point: HookPoint) -> bool {
+        self.counter(point).load(Ordering::Relaxed) > 0
+    }
+
+    pub fn register<F, Fut>(&self, owner: &str, filter: HookFilter, handler: F) -> HookId
+    where
+        F: Fn(Packet) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<PacketAction>> + Send + 'static,
+    {
+        let id = HookId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let hook = Arc::new(Hook {
+            id,
+            owner: owner.to_string(),
+            filter,
+            handler: Arc::new(move |packet| Box::pin(handler(packet))),
+        });
+        self.hooks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(hook);
+        self.counter(filter.point).fetch_add(1, Ordering::Relaxed);
+        id
+    }
+
+    fn remove(&self, keep: impl Fn(&Hook) -> bool) {
+        let mut hooks = self.hooks.write().unwrap_or_else(|e| e.into_inner());
+        hooks.retain(|hook| {
+            let retained = keep(hook);
+            if !retained {
+                self.counter(hook.filter.point).fetch_sub(1, Ordering::Relaxed);
+            }
+            retained
+        });
+    }
+
+    pub fn unregister(&self, id: HookId) {
+        self.remove(|hook| hook.id != id);
+    }
+
+    /// 注销某个注册者的全部钩子
+    pub fn unregister_owner(&self, owner: &str) {
+        self.remove(|hook| hook.owner != owner);
+    }
+
+    /// 依次调用匹配的钩子,返回最终要发送的数据包,被丢弃时返回 None
+    /// 钩子出错或超时时数据包按原样继续传递
+    pub async fn apply(
+        &self,
+        uuid: Uuid,
+        point: HookPoint,
+        direction: Direction,
+        state: State,
+        data: Vec<u8>,
+    ) -> Option<Vec<u8>> {
+        let Some(id) = read_varint(&data) else {
+            return Some(data);
+        };
+        let hooks: Vec<Arc<Hook>> = self
+            .hooks
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|hook| {
+                let filter = &hook.filter;
+                filter.point == point
+                    && filter.direction == direction
+                    && filter.state == state
+                    && filter.id.is_none_or(|filter_id| filter_id == id)
+            })
+            .cloned()
+            .collect();
+        if hooks.is_empty() {
+            return Some(data);
+        }
+        let mut packet = Packet {
+            uuid,
+            direction,
+            state,
+            id,
+            data,
+        };
+        for hook in hooks.iter().filter(|h| h.filter.mode == HookMode::Modify) {
+            // 改写后的包ID可能变化,后续钩子按原匹配条件继续调用
+            match tokio::time::timeout(self.timeout, (hook.handler)(packet.clone())).await {
+                Ok(Ok(PacketAction::Pass)) => {}
+                Ok(Ok(PacketAction::Replace(data))) => match read_varint(&data) {
+                    Some(id) => {
+                        packet.id = id;
+                        packet.data = data;
+                    }
+                    None => log::warn!("[数据包] {} 改写的数据包缺少包ID,已忽略", hook.owner),
+                },
+                Ok(Ok(PacketAction::Drop)) => return None,
+                Ok(Err(e)) => {
+                    log::warn!("[数据包] {} 处理 0x{:02X} 出错: {:#}", hook.owner, id, e);
+                }
+                Err(_) => {
+                    log::warn!(
+                        "[数据包] {} 处理 0x{:02X} 超时({}ms),已放行",
+                        hook.owner,
+                        id,
+                        self.timeout.as_millis()
+                    );
+                }
+            }
+        }
+        for hook in hooks.iter().filter(|h| h.filter.mode == HookMode::Listen) {
+            let handler = hook.handler.clone();
+            let owner = hook.owner.clone();
+            let packet = packet.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handler(packet).await {
+                    log::warn!("[数据包] {} 观察数据包出错: {:#}", owner, e);
+                }
+            });
+        }
+        Some(packet.data)
+    }
+
+    /// 登记连接的注入通道,连接关闭时调用 detach
+    /// 两个方向都是有界邮箱,插件注入过快时返回错误而不是无限占用内存
+    pub fn attach(
+        &self,
+        uuid: Uuid,
+        to_client: MailboxSender<Bytes>,
+        to_server: MailboxSender<Vec<u8>>,
+    ) {
+        self.connections
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(uuid, Connection { to_client, to_server });
+    }
+
+    /// 只移除同一条连接,避免玩家重连后误删新连接
+    pub fn detach(&self, uuid: Uuid, to_client: &MailboxSender<Bytes>) {
+        let mut connections = self.connections.write().unwrap_or_else(|e| e.into_inner());
+        if connections
+            .get(&uuid)
+            .is_some_and(|c| c.to_client.same_channel(to_client))
+        {
+            connections.remove(&uuid);
+        }
+    }
+
+    /// 向玩家连接注入数据包,注入的数据包不经过连接层钩子
+    pub fn inject(&self, uuid: Uuid, direction: Direction, data: Vec<u8>) -> anyhow::Result<()> {
+        if read_varint(&data).is_none() {
+            anyhow::bail!("数据包缺少包ID");
+        }
+        let connections = self.connections.read().unwrap_or_else(|e| e.into_inner());
+        let connection = connections
+            .get(&uuid)
+            .ok_or_else(|| anyhow::anyhow!("玩家 {} 不在线", uuid))?;
+        match direction {
+            Direction::Clientbound => push(&connection.to_client, uuid, Bytes::from(data)),
+            Direction::Serverbound => push(&connection.to_server, uuid, data),
+        }
+    }
+}
+
+fn push<T>(mailbox: &MailboxSender<T>, uuid: Uuid, data: T) -> anyhow::Result<()> {
+    match mailbox.try_send(data) {
+        Ok(()) => Ok(()),
+        Err(MailboxError::Full(_)) => anyhow::bail!("玩家 {} 的注入队列已满", uuid),
+        Err(MailboxError::Closed(_)) => anyhow::bail!("玩家 {} 的连接已关闭", uuid),
+    }
+}
+
+/// 读取数据包开头的包ID
+pub fn read_varint(data: &[u8]) -> Option<i32> {
+    let mut value: u32 = 0;
+    for (i, byte) in data.iter().take(5).enumerate() {
+        value |= ((byte & 0x7F) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some(value as i32);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(id: Option<i32>, mode: HookMode) -> HookFilter {
+        HookFilter {
+            point: HookPoint::Split,
+            direction: Direction::Serverbound,
+            state: State::Play,
+            id,
+            mode,
+        }
+    }
+
+    #[tokio::test]
+    async fn replace_drop_and_fast_path() {
+        let hooks = PacketHooks::with_timeout(Duration::from_millis(50));
+        let apply = |data: Vec<u8>| {
+            hooks.apply(Uuid::nil(), HookPoint::Split, Direction::Serverbound, State::Play, data)
+        };
+        assert!(!hooks.is_active(HookPoint::Split));
+
+        hooks.register("rewrite", filter(Some(0x08), HookMode::Modify), |p| async move {
+            let mut data = p.data;
+            data.push(1);
+            Ok(PacketAction::Replace(data))
+        });
+        hooks.register("drop", filter(Some(0x1b), HookMode::Modify), |_| async move {
+            Ok(PacketAction::Drop)
+        });
+        hooks.register("slow", filter(None, HookMode::Modify), |_| async move {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(PacketAction::Drop)
+        });
+        assert!(hooks.is_active(HookPoint::Split));
+        assert!(!hooks.is_active(HookPoint::Connection));
+
+        assert_eq!(apply(vec![0x08, 0]).await, Some(vec![0x08, 0, 1]));
+        assert_eq!(apply(vec![0x1b]).await, None);
+        assert_eq!(apply(vec![0x06]).await, Some(vec![0x06]));
+
+        hooks.unregister_owner("slow");
+        hooks.unregister_owner("drop");
+        hooks.unregister_owner("rewrite");
+        assert!(!hooks.is_active(HookPoint::Split));
+        assert_eq!(read_varint(&[0xff, 0x01]), Some(0xff));
+    }
+
+    #[tokio::test]
+    async fn inject_rejects_when_full() {
+        let hooks = PacketHooks::with_timeout(Duration::from_millis(50));
+        let uuid = Uuid::new_v4();
+        let config =
+            qexed_task::mailbox::MailboxConfig::new(1, qexed_task::mailbox::Overflow::Reject);
+        let (to_client, mut client_read) = qexed_task::mailbox::channel::<Bytes>(config);
+        let (to_server, mut server_read) = qexed_task::mailbox::channel::<Vec<u8>>(config);
+        hooks.attach(uuid, to_client.clone(), to_server);
+
+        hooks.inject(uuid, Direction::Clientbound, vec![0x6c]).unwrap();
+        assert!(hooks.inject(uuid, Direction::Clientbound, vec![0x6c]).is_err());
+        assert_eq!(client_read.recv().await, Some(Bytes::from_static(&[0x6c])));
+        hooks.inject(uuid, Direction::Clientbound, vec![0x6c]).unwrap();
+
+        hooks.inject(uuid, Direction::Serverbound, vec![0x08]).unwrap();
+        assert!(hooks.inject(uuid, Direction::Serverbound, vec![0x08]).is_err());
+        assert_eq!(server_read.recv().await, Some(vec![0x08]));
+
+        hooks.detach(uuid, &to_client);
+        assert!(hooks.inject(uuid, Direction::Clientbound, vec![0x6c]).is_err());
+    }
+}
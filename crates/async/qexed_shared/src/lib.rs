@@ -8,6 +8,7 @@ use dashmap::DashMap;
 use qexed_task::{
     event::{task::TaskEvent, task_manage::TaskManageEvent}, 
     message::{MessageSender, MessageType, return_message::ReturnMessage, unreturn_message::UnReturnMessage}, 
+    supervisor::{self, Failure, SupervisorPolicy},
     task::{task::Task, task_manage::TaskManage}
 };
 use tokio::sync::{
@@ -15,6 +16,13 @@ use tokio::sync::{
     oneshot
 };
 use anyhow::anyhow;
+use serde::{Serialize, de::DeserializeOwned};
+
+#[cfg(feature = "distributed")]
+mod replication;
+pub mod snapshot;
+pub use snapshot::{Conflict, ConflictPolicy, SharedOptions, Versioned};
+use snapshot::{Hooks, Replica, Stamp};
 
 /// 内部消息结构体
 #[derive(Debug)]
@@ -44,12 +52,32 @@ where
     1:同步数据(发起者调用)
     2:注销子任务
     3:关闭管理器
+    4:其他节点同步的修改
+    5:子任务失败
     */
     mode: u8,
     api: Option<oneshot::Sender<TaskSharedTaskMessage<T>>>,
     data: Option<T>,
     id: Option<u64>,
-    task_r: Option<UnboundedSender<T>>
+    task_r: Option<UnboundedSender<T>>,
+    replica: Option<ReturnMessage<Replica<T>>>,
+}
+
+// 失败原因已由监督记录,管理器只需要移除停止的子任务
+impl<T> From<Failure> for TaskSharedManageMessage<T>
+where
+    T: Send + 'static + Sync + Debug + Unpin + Clone,
+{
+    fn from(_failure: Failure) -> Self {
+        TaskSharedManageMessage {
+            mode: 5,
+            api: None,
+            data: None,
+            id: None,
+            task_r: None,
+            replica: None,
+        }
+    }
 }
 
 /// TaskShared是更高级的Task与TaskManage的内部封装
@@ -133,7 +161,10 @@ where
     T: Send + 'static + Sync + Debug + Unpin + Clone,
 {
     pub async fn new(data: T) -> anyhow::Result<Self> {
-        let manager_actor = TaskSharedManage::new(data.clone());
+        Self::start(TaskSharedManage::new(data)).await
+    }
+
+    async fn start(manager_actor: TaskSharedManage<T>) -> anyhow::Result<Self> {
         let (manager_task, manager_sender) = TaskManage::new(manager_actor);
         manager_task.run().await?;
         
@@ -156,6 +187,7 @@ where
             data: None,
             id: None,
             task_r: Some(ts),
+            replica: None,
         })
         .post(&manager_sender)
         .await?;
@@ -331,6 +363,7 @@ where
                 data: None,
                 id: None,
                 task_r: None,
+                replica: None,
             })
             .post(api)
             .await?;
@@ -354,6 +387,46 @@ where
     }
 }
 
+impl<T> Shared<T>
+where
+    T: Send + 'static + Sync + Debug + Unpin + Clone + Serialize + DeserializeOwned,
+{
+    /// 按选项创建,可开启快照持久化与跨节点同步
+    pub async fn open(data: T, options: SharedOptions) -> anyhow::Result<Self> {
+        let mut manager = TaskSharedManage::new(data);
+        let mut hooks = Hooks {
+            node: options
+                .node
+                .clone()
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            conflict: options.conflict,
+            on_conflict: options.on_conflict.clone(),
+            ..Default::default()
+        };
+        if let Some(path) = options.snapshot.clone() {
+            if let Some(snapshot) = snapshot::load::<T>(&path).await? {
+                log::info!("从快照 {} 恢复共享数据,版本 {}", path.display(), snapshot.version);
+                manager.data = snapshot.data;
+                manager.stamp = Stamp {
+                    version: snapshot.version,
+                    timestamp: snapshot.timestamp,
+                    node: snapshot.node,
+                };
+            }
+            hooks.persist = Some(snapshot::writer(path));
+        }
+        #[cfg(feature = "distributed")]
+        let inbound = replication::setup(&options, &mut hooks).await?;
+        manager.hooks = hooks;
+        let shared = Self::start(manager).await?;
+        #[cfg(feature = "distributed")]
+        if let Some(manage_api) = &shared.manage_api {
+            replication::forward(inbound, manage_api.clone());
+        }
+        Ok(shared)
+    }
+}
+
 // 任务管理器
 #[derive(Debug)]
 struct TaskSharedManage<T>
@@ -364,6 +437,8 @@ where
     next_id: u64,
     recycled: Vec<u64>,
     task_senders: DashMap<u64, UnboundedSender<T>>,  // 存储每个任务的发送端
+    stamp: Stamp,
+    hooks: Hooks<T>,
 }
 
 impl<T> TaskSharedManage<T>
@@ -376,6 +451,8 @@ where
             next_id: 0,
             recycled: Vec::new(),
             task_senders: DashMap::new(),
+            stamp: Stamp::default(),
+            hooks: Hooks::default(),
         }
     }
 
@@ -394,6 +471,71 @@ where
         // 清理任务的发送端
         self.task_senders.remove(&id);
     }
+
+    // 向所有任务广播数据
+    fn broadcast(&self, task_map: &DashMap<u64, MessageSender<ReturnMessage<TaskSharedTaskMessage<T>>>>) {
+        for entry in task_map.iter() {
+            let _ = entry.value().send(ReturnMessage::build(TaskSharedTaskMessage {
+                mode: 2,  // 同步(接受者)
+                data: Some(self.data.clone()),
+                api: None,
+                id: None,
+                task_r: None,
+            }));
+        }
+        for entry in self.task_senders.iter() {
+            let _ = entry.value().send(self.data.clone());
+        }
+    }
+
+    // 本节点提交后生成新版本,写入快照并同步到其他节点
+    fn committed(&mut self) {
+        let base = self.stamp.next(&self.hooks.node);
+        if !self.hooks.enabled() {
+            return;
+        }
+        let versioned = self.stamp.versioned(base, self.data.clone());
+        self.hooks.persist(&versioned);
+        if let Some(replicate) = &self.hooks.replicate {
+            replicate(versioned);
+        }
+    }
+
+    // 处理其他节点的修改,返回给对方的回复
+    fn apply(
+        &mut self,
+        update: Versioned<T>,
+        task_map: &DashMap<u64, MessageSender<ReturnMessage<TaskSharedTaskMessage<T>>>>,
+    ) -> Replica<T> {
+        let accept = match self.hooks.conflict {
+            ConflictPolicy::LastWriterWins => {
+                (update.timestamp, &update.node) > (self.stamp.timestamp, &self.stamp.node)
+            }
+            ConflictPolicy::Report => update.base == self.stamp.version,
+        };
+        if !accept {
+            if self.hooks.conflict == ConflictPolicy::Report {
+                snapshot::report(
+                    &self.hooks.on_conflict,
+                    Conflict {
+                        node: update.node,
+                        local: self.stamp.version,
+                        remote: update.version,
+                    },
+                );
+            }
+            return Replica::Rejected(self.stamp.versioned(self.stamp.version, self.data.clone()));
+        }
+        self.stamp = Stamp {
+            version: self.stamp.version.max(update.version),
+            timestamp: update.timestamp,
+            node: update.node,
+        };
+        self.data = update.data;
+        self.hooks.persist(&self.stamp.versioned(update.base, self.data.clone()));
+        self.broadcast(task_map);
+        Replica::Accepted(self.stamp.version)
+    }
 }
 
 // 为TaskEvent trait实现
@@ -417,6 +559,7 @@ where
                     data: None,
                     id: None,
                     task_r: data.data.task_r.take(),
+                    replica: None,
                 })
                 .post(manage_api)
                 .await?;
@@ -429,6 +572,7 @@ where
                     data: data.data.data.take(),  // 传递请求者的数据
                     id: Some(self.id),
                     task_r: None,
+                    replica: None,
                 })
                 .post(manage_api)
                 .await?;
@@ -453,6 +597,7 @@ where
                     data: None,
                     id: Some(self.id),
                     task_r: None,
+                    replica: None,
                 })
                 .post(manage_api)
                 .await?;
@@ -502,7 +647,9 @@ where
                 };
                 
                 let (task, task_sand) = Task::new(_api.clone(), raw_task);
-                task.run().await?;
+                task.supervise(format!("shared-{}", id), SupervisorPolicy::stop())
+                    .run()
+                    .await?;
                 task_map.insert(id, task_sand.clone());
                 
                 // 保存任务的发送端用于广播
@@ -531,26 +678,11 @@ where
                 
                 // 更新管理器的数据
                 if let Some(new_data) = data.data.data {
-                    self.data = new_data.clone();
+                    self.data = new_data;
+                    self.committed();
                     
                     // 向所有任务广播更新
-                    for entry in task_map.iter() {
-                        let task_id = *entry.key();
-                        // if task_id != id {  // 不给自己发
-                            let _ = entry.value().send(ReturnMessage::build(TaskSharedTaskMessage {
-                                mode: 2,  // 同步(接受者)
-                                data: Some(new_data.clone()),  // 发送更新后的数据
-                                api: None,
-                                id: None,
-                                task_r: None,
-                            }));
-                        // }
-                    }
-                    
-                    // 向所有任务的task_s发送更新
-                    for entry in self.task_senders.iter() {
-                        let _ = entry.value().send(new_data.clone());
-                    }
+                    self.broadcast(task_map);
                 }
                 
                 // 确认同步完成
@@ -589,6 +721,25 @@ where
                 self.task_senders.clear();
                 return Ok(true);  // 管理器结束
             },
+            // 其他节点同步的修改
+            4 => {
+                if let Some(mut replica) = data.data.replica.take() {
+                    let send = replica.get_return_send().await?;
+                    let reply = match replica.data {
+                        Replica::Update(update) => self.apply(update, task_map),
+                        other => other,
+                    };
+                    if let Some(send) = send {
+                        let _ = send.send(reply);
+                    }
+                }
+            },
+            // 子任务失败
+            5 => {
+                for id in supervisor::prune(task_map) {
+                    self.release_id(id);
+                }
+            },
             _ => {
                 return Err(anyhow!("未知的管理器操作模式: {}", data.data.mode));
             }
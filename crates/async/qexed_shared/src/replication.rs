@@ -0,0 +1,185 @@
+// 跨节点同步
+// 本节点提交的修改按顺序发送给每个对端,收到的修改交给管理器按冲突策略处理
+use std::{fmt::Debug, time::Duration};
+
+use qexed_task::{
+    message::{MessageSender, MessageType, return_message::ReturnMessage, unreturn_message::UnReturnMessage},
+    remote::{self, Endpoint},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use crate::{
+    TaskSharedManageMessage,
+    snapshot::{Conflict, ConflictPolicy, Hooks, Replica, SharedOptions, Versioned, report},
+};
+
+const REPLICATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+type Inbound<T> = UnboundedReceiver<ReturnMessage<Replica<T>>>;
+
+/// 监听对端并设置同步钩子,返回收到的同步消息
+pub(crate) async fn setup<T>(options: &SharedOptions, hooks: &mut Hooks<T>) -> anyhow::Result<Inbound<T>>
+where
+    T: Send + 'static + Sync + Debug + Unpin + Clone + Serialize + DeserializeOwned,
+{
+    let (inbound, recv) = unbounded_channel();
+    let bound = options
+        .listener
+        .as_ref()
+        .and_then(|listener| listener.lock().unwrap_or_else(|e| e.into_inner()).take());
+    let listener = match (bound, &options.listen) {
+        (Some(listener), _) => Some(listener),
+        (None, Some(endpoint)) => Some(remote::Listener::bind(endpoint).await?),
+        (None, None) => None,
+    };
+    if let Some(listener) = listener {
+        log::info!("共享数据节点 {} 监听同步于 {}", hooks.node, listener.endpoint()?);
+        let local = inbound.clone();
+        let token = options.token.clone();
+        tokio::spawn(async move {
+            if let Err(e) = listener.serve(&token, local).await {
+                log::error!("共享数据同步监听已停止: {}", e);
+            }
+        });
+    }
+    if options.peers.is_empty() {
+        return Ok(recv);
+    }
+    let queues: Vec<_> = options
+        .peers
+        .iter()
+        .map(|endpoint| {
+            let (send, queue) = unbounded_channel();
+            tokio::spawn(push(
+                endpoint.clone(),
+                options.token.clone(),
+                queue,
+                inbound.clone(),
+                options.conflict,
+                options.on_conflict.clone(),
+            ));
+            send
+        })
+        .collect();
+    hooks.replicate = Some(Box::new(move |update: Versioned<T>| {
+        for queue in &queues {
+            let _ = queue.send(update.clone());
+        }
+    }));
+    Ok(recv)
+}
+
+// 按提交顺序逐条发送给一个对端,避免乱序被当作冲突
+async fn push<T>(
+    endpoint: Endpoint,
+    token: String,
+    mut queue: UnboundedReceiver<Versioned<T>>,
+    inbound: MessageSender<ReturnMessage<Replica<T>>>,
+    policy: ConflictPolicy,
+    on_conflict: Option<UnboundedSender<Conflict>>,
+) where
+    T: Send + 'static + Sync + Debug + Unpin + Clone + Serialize + DeserializeOwned,
+{
+    let peer = remote::connect::<ReturnMessage<Replica<T>>>(endpoint.clone(), &token);
+    while let Some(update) = queue.recv().await {
+        let version = update.version;
+        match ReturnMessage::build(Replica::Update(update))
+            .get_timeout(&peer, REPLICATE_TIMEOUT)
+            .await
+        {
+            // 对端数据较新,交给本地管理器按同样的规则采用
+            Ok(Replica::Rejected(newer)) if policy == ConflictPolicy::LastWriterWins => {
+                let _ = inbound.send(ReturnMessage::build(Replica::Update(newer)));
+            }
+            Ok(Replica::Rejected(current)) => report(
+                &on_conflict,
+                Conflict {
+                    node: current.node,
+                    local: version,
+                    remote: current.version,
+                },
+            ),
+            Ok(_) => {}
+            Err(e) => log::warn!("共享数据版本 {} 同步到 {} 失败: {}", version, endpoint, e),
+        }
+    }
+}
+
+/// 把收到的同步消息转交给管理器
+pub(crate) fn forward<T>(
+    mut inbound: Inbound<T>,
+    manage_api: MessageSender<UnReturnMessage<TaskSharedManageMessage<T>>>,
+) where
+    T: Send + 'static + Sync + Debug + Unpin + Clone,
+{
+    tokio::spawn(async move {
+        while let Some(replica) = inbound.recv().await {
+            let message = UnReturnMessage::build(TaskSharedManageMessage {
+                mode: 4,
+                api: None,
+                data: None,
+                id: None,
+                task_r: None,
+                replica: Some(replica),
+            });
+            if manage_api.send(message).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Shared;
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+    struct Counter {
+        n: i32,
+    }
+
+    #[tokio::test]
+    async fn replicate_and_report_conflict() -> anyhow::Result<()> {
+        // 绑定随机端口后直接交给节点,避免释放端口后被其他进程占用
+        let a_listener = remote::Listener::bind(&"127.0.0.1:0".parse()?).await?;
+        let b_listener = remote::Listener::bind(&"127.0.0.1:0".parse()?).await?;
+        let a_addr = a_listener.endpoint()?;
+        let b_addr = b_listener.endpoint()?;
+        let (conflict_s, mut conflict_r) = unbounded_channel();
+        let mut a = Shared::open(
+            Counter { n: 0 },
+            SharedOptions::new()
+                .node("a")
+                .listener(a_listener)
+                .peer(b_addr)
+                .conflict(ConflictPolicy::Report)
+                .on_conflict(conflict_s.clone()),
+        )
+        .await?;
+        let mut b = Shared::open(
+            Counter { n: 0 },
+            SharedOptions::new()
+                .node("b")
+                .listener(b_listener)
+                .peer(a_addr)
+                .conflict(ConflictPolicy::Report)
+                .on_conflict(conflict_s),
+        )
+        .await?;
+
+        a.modify(|counter| counter.n = 1).await?;
+        tokio::time::timeout(Duration::from_secs(5), b.wait_data()).await??;
+        assert_eq!(b.n, 1);
+
+        // 双方基于同一版本提交,产生冲突
+        a.n = 2;
+        b.n = 3;
+        a.commit_data().await?;
+        b.commit_data().await?;
+        let conflict = tokio::time::timeout(Duration::from_secs(5), conflict_r.recv()).await?;
+        assert!(conflict.is_some());
+        Ok(())
+    }
+}
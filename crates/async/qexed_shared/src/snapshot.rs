@@ -0,0 +1,265 @@
+// 快照持久化与跨节点同步共用的数据结构
+// 每次提交都会生成新版本,开启快照时写入磁盘,开启同步时发送给其他节点
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// 带版本信息的数据,同时是快照文件与同步消息的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub version: u64,
+    /// 修改所基于的版本
+    pub base: u64,
+    /// 提交时间(毫秒)
+    pub timestamp: i64,
+    /// 提交修改的节点
+    pub node: String,
+    pub data: T,
+}
+
+/// 当前数据的版本信息
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Stamp {
+    pub(crate) version: u64,
+    pub(crate) timestamp: i64,
+    pub(crate) node: String,
+}
+
+impl Stamp {
+    /// 本节点提交一次修改
+    pub(crate) fn next(&mut self, node: &str) -> u64 {
+        let base = self.version;
+        self.version += 1;
+        self.timestamp = chrono::Utc::now().timestamp_millis();
+        self.node = node.to_string();
+        base
+    }
+
+    pub(crate) fn versioned<T>(&self, base: u64, data: T) -> Versioned<T> {
+        Versioned {
+            version: self.version,
+            base,
+            timestamp: self.timestamp,
+            node: self.node.clone(),
+            data,
+        }
+    }
+}
+
+/// 收到其他节点的修改时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// 按提交时间取较新的一方,时间相同时比较节点名
+    #[default]
+    LastWriterWins,
+    /// 只接受基于本地当前版本的修改,否则保留本地数据并报告冲突
+    Report,
+}
+
+/// 版本冲突报告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// 另一方数据的最后写入节点
+    pub node: String,
+    pub local: u64,
+    pub remote: u64,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "共享数据版本冲突: 本地版本 {},节点 {} 的版本 {}",
+            self.local, self.node, self.remote
+        )
+    }
+}
+
+/// Shared::open 的选项
+#[derive(Debug, Clone, Default)]
+pub struct SharedOptions {
+    pub(crate) snapshot: Option<PathBuf>,
+    pub(crate) node: Option<String>,
+    pub(crate) conflict: ConflictPolicy,
+    pub(crate) on_conflict: Option<UnboundedSender<Conflict>>,
+    #[cfg(feature = "distributed")]
+    pub(crate) listen: Option<qexed_task::remote::Endpoint>,
+    #[cfg(feature = "distributed")]
+    pub(crate) peers: Vec<qexed_task::remote::Endpoint>,
+    #[cfg(feature = "distributed")]
+    pub(crate) token: String,
+    // 已绑定的监听,由第一次 open 取走
+    #[cfg(feature = "distributed")]
+    pub(crate) listener: Option<std::sync::Arc<std::sync::Mutex<Option<qexed_task::remote::Listener>>>>,
+}
+
+impl SharedOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 每次提交后写入快照文件,启动时从快照恢复
+    pub fn snapshot(mut self, path: impl Into<PathBuf>) -> Self {
+        self.snapshot = Some(path.into());
+        self
+    }
+
+    /// 节点名,默认随机生成
+    pub fn node(mut self, node: impl Into<String>) -> Self {
+        self.node = Some(node.into());
+        self
+    }
+
+    pub fn conflict(mut self, policy: ConflictPolicy) -> Self {
+        self.conflict = policy;
+        self
+    }
+
+    /// 发生版本冲突时发送报告
+    pub fn on_conflict(mut self, send: UnboundedSender<Conflict>) -> Self {
+        self.on_conflict = Some(send);
+        self
+    }
+
+    /// 接收其他节点同步的地址
+    #[cfg(feature = "distributed")]
+    pub fn listen(mut self, endpoint: qexed_task::remote::Endpoint) -> Self {
+        self.listen = Some(endpoint);
+        self
+    }
+
+    /// 使用已绑定的监听接收其他节点同步,优先于 listen
+    #[cfg(all(test, feature = "distributed"))]
+    pub(crate) fn listener(mut self, listener: qexed_task::remote::Listener) -> Self {
+        self.listener = Some(std::sync::Arc::new(std::sync::Mutex::new(Some(listener))));
+        self
+    }
+
+    /// 节点之间同步时校验的令牌,各节点需一致;为空时只能监听本机地址
+    #[cfg(feature = "distributed")]
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = token.into();
+        self
+    }
+
+    /// 提交后同步到的节点,各节点需互相列出
+    #[cfg(feature = "distributed")]
+    pub fn peer(mut self, endpoint: qexed_task::remote::Endpoint) -> Self {
+        self.peers.push(endpoint);
+        self
+    }
+}
+
+/// 读取快照,文件不存在时返回 None
+pub(crate) async fn load<T: DeserializeOwned>(path: &Path) -> anyhow::Result<Option<Versioned<T>>> {
+    if !tokio::fs::try_exists(path).await? {
+        return Ok(None);
+    }
+    let json = tokio::fs::read_to_string(path).await?;
+    Ok(Some(serde_json::from_str(&json)?))
+}
+
+/// 写入快照,先写临时文件再替换,避免中途退出留下损坏的快照
+async fn save(path: &Path, json: &[u8]) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, json).await?;
+    tokio::fs::rename(tmp, path).await?;
+    Ok(())
+}
+
+/// 创建快照写入任务,管理器只负责序列化,文件读写不占用管理器
+/// 写入跟不上提交时只写最新的版本,管理器关闭后仍会写完最后一份
+pub(crate) fn writer<T: Serialize>(path: PathBuf) -> Persist<T> {
+    let (send, mut recv) = tokio::sync::watch::channel::<Option<(u64, Vec<u8>)>>(None);
+    tokio::spawn(async move {
+        while recv.changed().await.is_ok() {
+            let Some((version, json)) = recv.borrow_and_update().clone() else {
+                continue;
+            };
+            if let Err(e) = save(&path, &json).await {
+                log::error!("共享数据快照写入失败(版本 {}): {}", version, e);
+            }
+        }
+    });
+    Box::new(move |versioned| {
+        let json = serde_json::to_vec_pretty(versioned)?;
+        send.send_replace(Some((versioned.version, json)));
+        Ok(())
+    })
+}
+
+pub(crate) type Persist<T> = Box<dyn Fn(&Versioned<T>) -> anyhow::Result<()> + Send + Sync>;
+pub(crate) type Replicate<T> = Box<dyn Fn(Versioned<T>) + Send + Sync>;
+
+/// 管理器提交后执行的持久化与同步,由 Shared::open 按数据类型创建
+pub(crate) struct Hooks<T> {
+    pub(crate) node: String,
+    pub(crate) persist: Option<Persist<T>>,
+    pub(crate) replicate: Option<Replicate<T>>,
+    pub(crate) conflict: ConflictPolicy,
+    pub(crate) on_conflict: Option<UnboundedSender<Conflict>>,
+}
+
+impl<T> Default for Hooks<T> {
+    fn default() -> Self {
+        Self {
+            node: String::new(),
+            persist: None,
+            replicate: None,
+            conflict: ConflictPolicy::default(),
+            on_conflict: None,
+        }
+    }
+}
+
+impl<T> Debug for Hooks<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hooks")
+            .field("node", &self.node)
+            .field("persist", &self.persist.is_some())
+            .field("replicate", &self.replicate.is_some())
+            .field("conflict", &self.conflict)
+            .finish()
+    }
+}
+
+impl<T> Hooks<T> {
+    pub(crate) fn enabled(&self) -> bool {
+        self.persist.is_some() || self.replicate.is_some()
+    }
+
+    pub(crate) fn persist(&self, versioned: &Versioned<T>) {
+        if let Some(persist) = &self.persist
+            && let Err(e) = persist(versioned)
+        {
+            log::error!("共享数据快照写入失败(版本 {}): {}", versioned.version, e);
+        }
+    }
+}
+
+pub(crate) fn report(on_conflict: &Option<UnboundedSender<Conflict>>, conflict: Conflict) {
+    log::warn!("{}", conflict);
+    if let Some(send) = on_conflict {
+        let _ = send.send(conflict);
+    }
+}
+
+/// 节点间同步的消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Replica<T> {
+    /// 提交的修改
+    Update(Versioned<T>),
+    /// 修改已被接受,附带对方的新版本
+    Accepted(u64),
+    /// 修改未被接受,附带对方当前的数据
+    Rejected(Versioned<T>),
+}
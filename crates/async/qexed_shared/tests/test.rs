@@ -83,4 +83,29 @@ async fn test_concurrent_behavior() -> Result<()> {
     println!("\n[观察者] 最终值: b = {}", observer.b);
     
     Ok(())
-}
\ No newline at end of file
+}
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct Counter {
+    n: i32,
+}
+
+#[tokio::test]
+async fn test_snapshot_restore() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("counter.json");
+    let options = qexed_shared::SharedOptions::new().snapshot(&path);
+
+    let mut c = Shared::open(Counter { n: 0 }, options.clone()).await?;
+    c.modify(|counter| counter.n = 7).await?;
+    c.modify(|counter| counter.n = 8).await?;
+    sleep(Duration::from_millis(50)).await;
+    c.close_manager().await?;
+
+    // 重新打开时忽略初始值,从快照恢复
+    let restored = Shared::open(Counter { n: 0 }, options).await?;
+    assert_eq!(restored.n, 8);
+    let json = std::fs::read_to_string(&path)?;
+    let snapshot: qexed_shared::Versioned<Counter> = serde_json::from_str(&json)?;
+    assert_eq!(snapshot.version, 2);
+    Ok(())
+}
@@ -11,6 +11,10 @@ pub trait TaskEvent<MessageType,ManageMessageType> {
         manage_api: &MessageSender<ManageMessageType>,
         data: MessageType,
     ) -> anyhow::Result<bool>;
+    // 监督策略决定重启时调用,默认保留现有状态继续运行
+    async fn restart(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
 
 
@@ -22,4 +26,8 @@ pub trait TaskEasyEvent<MessageType> {
         api: &MessageSender<MessageType>,
         data: MessageType,
     ) -> anyhow::Result<bool>;
+    // 监督策略决定重启时调用,默认保留现有状态继续运行
+    async fn restart(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
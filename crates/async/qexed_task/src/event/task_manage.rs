@@ -11,4 +11,8 @@ pub trait TaskManageEvent<ID,MessageType,SubMessageType> {
         task_map:&DashMap<ID,MessageSender<SubMessageType>>,
         data: MessageType,
     ) -> anyhow::Result<bool>;
-}
\ No newline at end of file
+    // 监督策略决定重启时调用,默认保留现有状态继续运行
+    async fn restart(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
+pub mod mailbox;
 pub mod message;
 pub mod task;
-pub mod event;
\ No newline at end of file
+pub mod event;
+pub mod supervisor;
+#[cfg(feature = "distributed")]
+pub mod remote;
\ No newline at end of file
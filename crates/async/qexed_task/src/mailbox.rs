@@ -0,0 +1,448 @@
+// 有界邮箱
+// 与 unbounded_channel 用法相同,但队列长度有上限,满时按溢出策略处理
+// 处理缓慢的接收方(负载过高的区块任务、卡住的客户端写任务)因此不会无限占用内存
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{
+        Arc, Mutex, Weak,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+use tokio::sync::Notify;
+
+/// 队列已满时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// 等待接收方腾出空间;同步发送(try_send)时等同于 Reject
+    Wait,
+    /// 丢弃最早的一条消息后放入
+    DropOldest,
+    /// 拒绝并返回错误
+    Reject,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MailboxConfig {
+    /// 队列长度上限,至少为 1
+    pub capacity: usize,
+    pub overflow: Overflow,
+}
+
+impl MailboxConfig {
+    pub fn new(capacity: usize, overflow: Overflow) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            overflow,
+        }
+    }
+
+    /// 服务管理器的外部邮箱,满时发送方等待
+    /// 子任务通过管理器自身的无界通道上报,不会因此阻塞
+    pub fn manager() -> Self {
+        Self::new(1024, Overflow::Wait)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MailboxError<T> {
+    #[error("邮箱已满")]
+    Full(T),
+    #[error("邮箱已关闭")]
+    Closed(T),
+}
+
+impl<T> MailboxError<T> {
+    /// 取回未送达的消息
+    pub fn into_inner(self) -> T {
+        match self {
+            MailboxError::Full(value) | MailboxError::Closed(value) => value,
+        }
+    }
+}
+
+/// 邮箱的运行统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MailboxStats {
+    /// 当前排队的消息数
+    pub depth: usize,
+    pub capacity: usize,
+    /// 队列曾经达到的最大长度
+    pub peak: usize,
+    /// DropOldest 策略丢弃的消息数
+    pub dropped: u64,
+    /// 因队列已满被拒绝的消息数
+    pub rejected: u64,
+}
+
+impl std::ops::Add for MailboxStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            depth: self.depth + other.depth,
+            capacity: self.capacity + other.capacity,
+            peak: self.peak.max(other.peak),
+            dropped: self.dropped + other.dropped,
+            rejected: self.rejected + other.rejected,
+        }
+    }
+}
+
+// 与消息类型无关的计数,可以放进全局登记表
+#[derive(Debug, Default)]
+struct Counters {
+    capacity: usize,
+    depth: AtomicUsize,
+    peak: AtomicUsize,
+    dropped: AtomicU64,
+    rejected: AtomicU64,
+    // 具名邮箱所属名称的累计值
+    totals: Option<Arc<Totals>>,
+}
+
+// 同名邮箱共享的累计计数,邮箱关闭后仍然保留,导出的计数因此不会回落
+#[derive(Debug, Default)]
+struct Totals {
+    peak: AtomicUsize,
+    dropped: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl Counters {
+    fn record_depth(&self, depth: usize) {
+        self.depth.store(depth, Ordering::Relaxed);
+        self.peak.fetch_max(depth, Ordering::Relaxed);
+        if let Some(totals) = &self.totals {
+            totals.peak.fetch_max(depth, Ordering::Relaxed);
+        }
+    }
+
+    fn record_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        if let Some(totals) = &self.totals {
+            totals.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn record_rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+        if let Some(totals) = &self.totals {
+            totals.rejected.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn stats(&self) -> MailboxStats {
+        MailboxStats {
+            depth: self.depth.load(Ordering::Relaxed),
+            capacity: self.capacity,
+            peak: self.peak.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+struct Shared<T> {
+    queue: Mutex<VecDeque<T>>,
+    overflow: Overflow,
+    counters: Arc<Counters>,
+    // 有新消息或发送端全部关闭
+    readable: Notify,
+    // 有空位或接收端关闭
+    writable: Notify,
+    senders: AtomicUsize,
+    closed: AtomicBool,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<T>> {
+        // 持锁期间只做队列操作,不会在中途 panic
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 放入一条消息,队列已满且不能丢弃时原样返回
+    fn push(&self, value: T) -> Result<(), MailboxError<T>> {
+        if self.closed.load(Ordering::Acquire) {
+            return Err(MailboxError::Closed(value));
+        }
+        let mut queue = self.lock();
+        if queue.len() >= self.counters.capacity {
+            if self.overflow != Overflow::DropOldest {
+                return Err(MailboxError::Full(value));
+            }
+            queue.pop_front();
+            self.counters.record_dropped();
+        }
+        queue.push_back(value);
+        let depth = queue.len();
+        drop(queue);
+        self.counters.record_depth(depth);
+        self.readable.notify_one();
+        Ok(())
+    }
+
+    fn pop(&self) -> Option<T> {
+        let mut queue = self.lock();
+        let value = queue.pop_front()?;
+        let depth = queue.len();
+        drop(queue);
+        self.counters.depth.store(depth, Ordering::Relaxed);
+        self.writable.notify_one();
+        Some(value)
+    }
+}
+
+pub struct MailboxSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct MailboxReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// 创建匿名邮箱
+pub fn channel<T>(config: MailboxConfig) -> (MailboxSender<T>, MailboxReceiver<T>) {
+    with_counters(config, None)
+}
+
+fn with_counters<T>(
+    config: MailboxConfig,
+    totals: Option<Arc<Totals>>,
+) -> (MailboxSender<T>, MailboxReceiver<T>) {
+    let counters = Arc::new(Counters {
+        capacity: config.capacity.max(1),
+        totals,
+        ..Default::default()
+    });
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::new()),
+        overflow: config.overflow,
+        counters,
+        readable: Notify::new(),
+        writable: Notify::new(),
+        senders: AtomicUsize::new(1),
+        closed: AtomicBool::new(false),
+    });
+    (
+        MailboxSender {
+            shared: shared.clone(),
+        },
+        MailboxReceiver { shared },
+    )
+}
+
+/// 创建邮箱并以 name 登记,统计可通过 [`stats`] 查询,同名邮箱的统计会合并
+pub fn named<T>(name: &str, config: MailboxConfig) -> (MailboxSender<T>, MailboxReceiver<T>) {
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    let (totals, live) = registry.entry(name.to_string()).or_default();
+    let (sender, receiver) = with_counters(config, Some(totals.clone()));
+    live.retain(|counters| counters.strong_count() > 0);
+    live.push(Arc::downgrade(&sender.shared.counters));
+    (sender, receiver)
+}
+
+type Registry = BTreeMap<String, (Arc<Totals>, Vec<Weak<Counters>>)>;
+
+static REGISTRY: Mutex<Registry> = Mutex::new(BTreeMap::new());
+
+/// 所有具名邮箱的统计,按名称合并并排序
+/// depth 与 capacity 只计仍在使用的邮箱,peak、dropped 与 rejected 是进程启动以来的累计值
+pub fn stats() -> Vec<(String, MailboxStats)> {
+    let registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    registry
+        .iter()
+        .map(|(name, (totals, live))| {
+            let mut merged = MailboxStats {
+                peak: totals.peak.load(Ordering::Relaxed),
+                dropped: totals.dropped.load(Ordering::Relaxed),
+                rejected: totals.rejected.load(Ordering::Relaxed),
+                ..Default::default()
+            };
+            for counters in live.iter().filter_map(Weak::upgrade) {
+                merged.depth += counters.depth.load(Ordering::Relaxed);
+                merged.capacity += counters.capacity;
+            }
+            (name.clone(), merged)
+        })
+        .collect()
+}
+
+impl<T> MailboxSender<T> {
+    /// 发送消息,Wait 策略下队列已满时等待
+    pub async fn send(&self, mut value: T) -> Result<(), MailboxError<T>> {
+        loop {
+            // 先登记等待再尝试,避免错过两者之间的唤醒
+            let writable = self.shared.writable.notified();
+            tokio::pin!(writable);
+            writable.as_mut().enable();
+            match self.shared.push(value) {
+                Err(MailboxError::Full(returned)) if self.shared.overflow == Overflow::Wait => {
+                    value = returned;
+                    writable.await;
+                }
+                result => return self.count(result),
+            }
+        }
+    }
+
+    /// 不等待地发送,队列已满时按策略丢弃旧消息或返回 Full
+    pub fn try_send(&self, value: T) -> Result<(), MailboxError<T>> {
+        self.count(self.shared.push(value))
+    }
+
+    fn count(&self, result: Result<(), MailboxError<T>>) -> Result<(), MailboxError<T>> {
+        if let Err(MailboxError::Full(_)) = result {
+            self.shared.counters.record_rejected();
+        }
+        result
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::Acquire)
+    }
+
+    /// 关闭邮箱,不再接收新消息
+    /// 接收方取完已排队的消息后收到 None,连接写任务借此在发出断开数据包后关闭连接
+    pub fn close(&self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.readable.notify_one();
+        self.shared.writable.notify_waiters();
+    }
+
+    pub fn same_channel(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+
+    pub fn stats(&self) -> MailboxStats {
+        self.shared.counters.stats()
+    }
+}
+
+impl<T> Clone for MailboxSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for MailboxSender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // 最后一个发送端关闭,唤醒接收方结束
+            self.shared.readable.notify_one();
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for MailboxSender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MailboxSender")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl<T> MailboxReceiver<T> {
+    /// 接收消息,队列为空且发送端全部关闭时返回 None
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            let readable = self.shared.readable.notified();
+            tokio::pin!(readable);
+            readable.as_mut().enable();
+            if let Some(value) = self.shared.pop() {
+                return Some(value);
+            }
+            if self.shared.senders.load(Ordering::Acquire) == 0 || self.is_closed() {
+                return None;
+            }
+            readable.await;
+        }
+    }
+
+    /// 拒绝新消息,已排队的消息仍可取出
+    pub fn close(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.writable.notify_waiters();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::Acquire)
+    }
+
+    pub fn stats(&self) -> MailboxStats {
+        self.shared.counters.stats()
+    }
+}
+
+impl<T> Drop for MailboxReceiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<T> std::fmt::Debug for MailboxReceiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MailboxReceiver")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn overflow_policies() {
+        let (tx, mut rx) = channel(MailboxConfig::new(2, Overflow::DropOldest));
+        for i in 0..3 {
+            tx.try_send(i).unwrap();
+        }
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(tx.stats().dropped, 1);
+
+        let (tx, mut rx) = channel(MailboxConfig::new(1, Overflow::Reject));
+        tx.try_send(1).unwrap();
+        assert!(matches!(tx.send(2).await, Err(MailboxError::Full(2))));
+        assert_eq!(tx.stats().rejected, 1);
+        assert_eq!(tx.stats().depth, 1);
+        drop(tx);
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, None);
+
+        // Wait 策略在接收方取走消息后继续发送
+        let (tx, mut rx) = named("test", MailboxConfig::new(1, Overflow::Wait));
+        tx.send(1).await.unwrap();
+        let waiting = tokio::spawn({
+            let tx = tx.clone();
+            async move { tx.send(2).await }
+        });
+        tokio::task::yield_now().await;
+        assert_eq!(stats(), vec![("test".to_string(), tx.stats())]);
+        assert_eq!(rx.recv().await, Some(1));
+        waiting.await.unwrap().unwrap();
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(tx.stats().peak, 1);
+        rx.close();
+        assert!(matches!(tx.try_send(3), Err(MailboxError::Closed(3))));
+
+        // 邮箱关闭后,同名的累计计数不会回落
+        let (other, _other_rx) = named("test", MailboxConfig::new(1, Overflow::DropOldest));
+        other.try_send(1).unwrap();
+        other.try_send(2).unwrap();
+        drop((tx, rx, other, _other_rx));
+        let merged = stats().into_iter().find(|(name, _)| name == "test").unwrap().1;
+        assert_eq!((merged.depth, merged.dropped, merged.peak), (0, 1, 1));
+
+        // 发送端关闭后,已排队的消息仍会送达
+        let (tx, mut rx) = channel(MailboxConfig::new(2, Overflow::Reject));
+        tx.try_send(1).unwrap();
+        tx.clone().close();
+        assert!(matches!(tx.try_send(2), Err(MailboxError::Closed(2))));
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, None);
+    }
+}
@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
+use crate::mailbox::{MailboxReceiver, MailboxSender};
+
 pub mod return_message;
 pub mod unreturn_message;
 pub type MessageSender<MessageType> = UnboundedSender<MessageType>;
@@ -14,7 +16,81 @@ where
     S: Send + 'static,
 {
     fn build(data: T) -> Self;
-    async fn post(self, send: &MessageSender<Self>) -> anyhow::Result<R>;
+    async fn post<O: Outbox<Self> + ?Sized>(self, send: &O) -> anyhow::Result<R>;
     async fn get_return_send(&mut self) -> anyhow::Result<S>;
 
-}
\ No newline at end of file
+}
+
+// 消息的投递目标,无界通道与有界邮箱均可
+#[async_trait]
+pub trait Outbox<T: Send + 'static>: Sync {
+    async fn deliver(&self, message: T) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl<T: Send + std::fmt::Debug + 'static> Outbox<T> for UnboundedSender<T> {
+    async fn deliver(&self, message: T) -> anyhow::Result<()> {
+        self.send(message)
+            .map_err(|e| anyhow::anyhow!("Failed to send message: {:?}", e))
+    }
+}
+
+#[async_trait]
+impl<T: Send + std::fmt::Debug + 'static> Outbox<T> for MailboxSender<T> {
+    async fn deliver(&self, message: T) -> anyhow::Result<()> {
+        self.send(message)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send message: {}", e))
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static, O: Outbox<T> + ?Sized> Outbox<T> for &O {
+    async fn deliver(&self, message: T) -> anyhow::Result<()> {
+        (**self).deliver(message).await
+    }
+}
+
+// 直接使用任务表中取出的引用
+#[async_trait]
+impl<K, V, T> Outbox<T> for dashmap::mapref::one::Ref<'_, K, V>
+where
+    K: Eq + std::hash::Hash + Sync,
+    V: Outbox<T>,
+    T: Send + 'static,
+{
+    async fn deliver(&self, message: T) -> anyhow::Result<()> {
+        self.value().deliver(message).await
+    }
+}
+
+// 任务的收件箱:自身的无界通道,以及可选的有界邮箱
+// 自身通道优先,任务发给自己的消息不受邮箱容量限制
+pub(crate) struct Inbox<T> {
+    receiver: MessageReceiver<T>,
+    mailbox: Option<MailboxReceiver<T>>,
+}
+
+impl<T> Inbox<T> {
+    pub(crate) fn new(receiver: MessageReceiver<T>, mailbox: Option<MailboxReceiver<T>>) -> Self {
+        Self { receiver, mailbox }
+    }
+
+    pub(crate) async fn recv(&mut self) -> Option<T> {
+        match &mut self.mailbox {
+            None => self.receiver.recv().await,
+            Some(mailbox) => tokio::select! {
+                biased;
+                Some(data) = self.receiver.recv() => Some(data),
+                data = mailbox.recv() => data,
+            },
+        }
+    }
+
+    pub(crate) fn close(&mut self) {
+        self.receiver.close();
+        if let Some(mailbox) = &mut self.mailbox {
+            mailbox.close();
+        }
+    }
+}
@@ -1,10 +1,65 @@
+use std::{
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
 use async_trait::async_trait;
 use tokio::sync::oneshot;
-use crate::message::{MessageSender, MessageType};
+use crate::message::{MessageType, Outbox};
+
+// 调试模式:记录未得到回复的请求及其处理者
+static DEBUG: AtomicBool = AtomicBool::new(false);
+// 调试模式下,没有期限的请求等待超过此时间后输出一次警告
+const DEBUG_WARN_AFTER: Duration = Duration::from_secs(5);
+
+tokio::task_local! {
+    // 当前正在处理消息的任务类型,由任务的监听循环设置
+    pub(crate) static ACTOR: &'static str;
+}
+
+pub fn set_debug(enabled: bool) {
+    DEBUG.store(enabled, Ordering::Relaxed);
+}
+
+pub fn debug_enabled() -> bool {
+    DEBUG.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RequestError {
+    #[error("请求 {0} 发送失败")]
+    Send(String),
+    #[error("请求 {message} 超过 {timeout:?} 未得到回复")]
+    Timeout { message: String, timeout: Duration },
+    #[error("请求 {0} 的回复通道已被丢弃,处理者没有回复")]
+    Dropped(String),
+}
+
+// 记录处理请求的任务,调试模式下才会创建
+#[derive(Debug, Default)]
+struct Handler(Arc<OnceLock<&'static str>>);
+
+impl Handler {
+    fn record(&self) {
+        let _ = ACTOR.try_with(|actor| self.0.set(actor));
+    }
+}
+
+impl Drop for Handler {
+    // 消息在处理者内被丢弃时也能记下是谁
+    fn drop(&mut self) {
+        self.record();
+    }
+}
+
 #[derive(Debug)]
 pub struct ReturnMessage<T> {
     pub data: T,
     sand: Option<oneshot::Sender<T>>,
+    handler: Option<Handler>,
 }
 
 #[async_trait]
@@ -13,41 +68,141 @@ where
     T: Send + 'static + Sync + std::fmt::Debug + Unpin,
 {
     fn build(data: T) -> Self {
-        Self { data, sand: None }
+        Self {
+            data,
+            sand: None,
+            handler: None,
+        }
     }
 
-    async fn post(
-        mut self,
-        send: &MessageSender<Self>,
+    async fn post<O: Outbox<Self> + ?Sized>(
+        self,
+        send: &O,
     ) -> anyhow::Result<oneshot::Receiver<T>> {
-        let (s, r) = oneshot::channel();
-        self.sand = Some(s);
+        Ok(self.request(send).await?.receiver)
+    }
 
-        // 发送消息
-        send.send(self)
-            .map_err(|e| anyhow::anyhow!("Failed to send message: {:?}", e))?;
+    async fn get_return_send(&mut self) -> anyhow::Result<Option<oneshot::Sender<T>>> {
+        Ok(self.take_return_send())
+    }
+
+
+}
+
+// 已发出、等待回复的请求
+struct Pending<T> {
+    receiver: oneshot::Receiver<T>,
+    // 调试模式下记录消息变体与处理者
+    label: Option<String>,
+    handler: Option<Arc<OnceLock<&'static str>>>,
+}
 
-        Ok(r)
+impl<T> Pending<T> {
+    fn describe(&self) -> String {
+        let message = match &self.label {
+            Some(label) => label.as_str(),
+            None => std::any::type_name::<T>(),
+        };
+        match self.handler.as_ref().map(|handler| handler.get()) {
+            Some(Some(actor)) => format!("{}(处理者 {})", message, actor),
+            Some(None) => format!("{}(处理者未知)", message),
+            None => message.to_string(),
+        }
     }
 
-    async fn get_return_send(&mut self) -> anyhow::Result<Option<oneshot::Sender<T>>> {
-        // 取出发送器，避免多次调用
-        Ok(self.sand.take())
+    async fn wait(mut self, deadline: Option<Duration>) -> Result<T, RequestError> {
+        let result = match deadline {
+            Some(timeout) => match tokio::time::timeout(timeout, &mut self.receiver).await {
+                Ok(result) => result,
+                Err(_) => {
+                    return Err(self.failed(RequestError::Timeout {
+                        message: self.describe(),
+                        timeout,
+                    }));
+                }
+            },
+            None if self.label.is_some() => {
+                match tokio::time::timeout(DEBUG_WARN_AFTER, &mut self.receiver).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        log::warn!("请求 {} 已等待 {:?} 仍未得到回复", self.describe(), DEBUG_WARN_AFTER);
+                        (&mut self.receiver).await
+                    }
+                }
+            }
+            None => (&mut self.receiver).await,
+        };
+        result.map_err(|_| self.failed(RequestError::Dropped(self.describe())))
     }
 
+    fn failed(&self, error: RequestError) -> RequestError {
+        if self.label.is_some() {
+            log::warn!("{}", error);
+        }
+        error
+    }
+}
 
+// 取枚举变体名,如 TaskMessage::LoginCheck
+fn label<T: std::fmt::Debug>(data: &T) -> String {
+    let text = format!("{:?}", data);
+    let end = text.find(['(', '{', ' ']).unwrap_or(text.len());
+    let type_name = std::any::type_name::<T>();
+    let type_name = type_name.rsplit("::").next().unwrap_or(type_name);
+    format!("{}::{}", type_name, &text[..end])
 }
 
 impl<T> ReturnMessage<T>
 where
     T: Send + 'static + Sync + std::fmt::Debug + Unpin,
 {
-    pub async fn get(
+    async fn request<O: Outbox<Self> + ?Sized>(
+        mut self,
+        send: &O,
+    ) -> Result<Pending<T>, RequestError> {
+        let (s, r) = oneshot::channel();
+        self.sand = Some(s);
+        let mut pending = Pending {
+            receiver: r,
+            label: None,
+            handler: None,
+        };
+        if debug_enabled() {
+            let handler = Handler::default();
+            pending.label = Some(label(&self.data));
+            pending.handler = Some(handler.0.clone());
+            self.handler = Some(handler);
+        }
+        // 发送消息
+        match send.deliver(self).await {
+            Ok(()) => Ok(pending),
+            Err(_) => Err(pending.failed(RequestError::Send(pending.describe()))),
+        }
+    }
+    pub async fn get<O: Outbox<Self> + ?Sized>(
         self,
-        send: &MessageSender<Self>,
+        send: &O,
     ) -> anyhow::Result<T> {
-        let return_data = self.post(&send).await?;
-        return Ok(ReturnMessage::get_return_data(return_data).await?)
+        Ok(self.request(send).await?.wait(None).await?)
+    }
+    /// 带期限的请求,超时或处理者丢弃回复通道时返回对应错误
+    pub async fn get_timeout<O: Outbox<Self> + ?Sized>(
+        self,
+        send: &O,
+        timeout: Duration,
+    ) -> Result<T, RequestError> {
+        self.request(send).await?.wait(Some(timeout)).await
+    }
+    // 取出发送器，避免多次调用
+    pub(crate) fn take_return_send(&mut self) -> Option<oneshot::Sender<T>> {
+        if let Some(handler) = &self.handler {
+            handler.record();
+        }
+        self.sand.take()
+    }
+    /// 请求方已超时或放弃等待,处理者可据此跳过耗时操作
+    pub fn is_cancelled(&self) -> bool {
+        self.sand.as_ref().is_some_and(|send| send.is_closed())
     }
     pub async fn get_return_data(result: oneshot::Receiver<T>) -> anyhow::Result<T> {
         match result.await {
@@ -64,4 +219,57 @@ where
         self.sand = Some(s);
         r
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::MessageSender;
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    enum Ask {
+        Ping(u8),
+    }
+
+    // 调试开关是全局的,测试结束或断言失败时都要关闭
+    struct DebugGuard;
+
+    impl DebugGuard {
+        fn enable() -> Self {
+            set_debug(true);
+            Self
+        }
+    }
+
+    impl Drop for DebugGuard {
+        fn drop(&mut self) {
+            set_debug(false);
+        }
+    }
+
+    #[tokio::test]
+    async fn timeout_and_dropped() {
+        let _debug = DebugGuard::enable();
+        let (send, mut recv): (MessageSender<ReturnMessage<Ask>>, _) =
+            tokio::sync::mpsc::unbounded_channel();
+        let waiting = tokio::spawn(async move {
+            ReturnMessage::build(Ask::Ping(1))
+                .get_timeout(&send, Duration::from_millis(20))
+                .await
+        });
+        let message = recv.recv().await.unwrap();
+        let error = waiting.await.unwrap().unwrap_err();
+        assert!(matches!(error, RequestError::Timeout { ref message, .. } if message.starts_with("Ask::Ping")));
+        assert!(message.is_cancelled());
+
+        let (send, mut recv) = tokio::sync::mpsc::unbounded_channel();
+        let waiting = tokio::spawn(async move { ReturnMessage::build(Ask::Ping(2)).get(&send).await });
+        ACTOR.scope("Test", async { drop(recv.recv().await) }).await;
+        let error = waiting.await.unwrap().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "请求 Ask::Ping(处理者 Test) 的回复通道已被丢弃,处理者没有回复"
+        );
+    }
+}
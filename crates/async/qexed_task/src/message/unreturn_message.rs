@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 
-use crate::message::{MessageSender, MessageType};
+use crate::message::{MessageType, Outbox};
 #[derive(Debug)]
 pub struct UnReturnMessage<T> {
     pub data: T,
@@ -15,11 +15,9 @@ where
         Self { data }
     }
 
-    async fn post(self, send: &MessageSender<Self>) -> anyhow::Result<()> {
+    async fn post<O: Outbox<Self> + ?Sized>(self, send: &O) -> anyhow::Result<()> {
         // 发送消息
-        send.send(self)
-            .map_err(|e| anyhow::anyhow!("Failed to send message: {:?}", e))?;
-        Ok(())
+        send.deliver(self).await
     }
 
     // 无返回时用不到
@@ -0,0 +1,456 @@
+// 跨进程的任务通信
+// 消息序列化为 JSON,以 4 字节长度前缀分帧,经 TCP 或 Unix 套接字传输
+// connect 返回普通的 MessageSender,调用方无需区分服务在本进程还是远程进程
+// 连接建立后客户端先发送令牌,令牌不一致的连接直接断开
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    sync::{mpsc, oneshot},
+};
+
+use crate::message::{
+    MessageSender, MessageType, return_message::ReturnMessage, unreturn_message::UnReturnMessage,
+};
+
+// 单帧上限,防止错误的长度前缀耗尽内存
+const MAX_FRAME: usize = 16 * 1024 * 1024;
+const RECONNECT_MIN: Duration = Duration::from_millis(100);
+const RECONNECT_MAX: Duration = Duration::from_secs(5);
+
+/// 服务地址,形如 tcp://127.0.0.1:25600 或 unix:///run/qexed/player_list.sock
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl FromStr for Endpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(addr) = s.strip_prefix("tcp://") {
+            return Ok(Endpoint::Tcp(addr.to_string()));
+        }
+        if let Some(path) = s.strip_prefix("unix://") {
+            #[cfg(unix)]
+            return Ok(Endpoint::Unix(path.into()));
+            #[cfg(not(unix))]
+            anyhow::bail!("当前平台不支持 Unix 套接字: {}", path);
+        }
+        if s.contains("://") {
+            anyhow::bail!("未知的服务地址: {}", s);
+        }
+        Ok(Endpoint::Tcp(s.to_string()))
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endpoint::Tcp(addr) => write!(f, "tcp://{}", addr),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+/// 可以跨进程传输的消息
+pub trait Wire: Sized + Send + 'static {
+    type Data: Serialize + DeserializeOwned + Send + 'static;
+
+    /// 发送方:拆出消息内容与回复通道
+    fn split(self) -> (Self::Data, Option<oneshot::Sender<Self::Data>>);
+    /// 接收方:用收到的内容重建消息,需要回复时同时返回等待回复的通道
+    fn join(data: Self::Data, reply: bool) -> (Self, Option<oneshot::Receiver<Self::Data>>);
+}
+
+impl<T> Wire for ReturnMessage<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + std::fmt::Debug + Unpin + 'static,
+{
+    type Data = T;
+
+    fn split(mut self) -> (T, Option<oneshot::Sender<T>>) {
+        let reply = self.take_return_send();
+        (self.data, reply)
+    }
+
+    fn join(data: T, reply: bool) -> (Self, Option<oneshot::Receiver<T>>) {
+        let mut message = ReturnMessage::build(data);
+        let receiver = reply.then(|| message.manual());
+        (message, receiver)
+    }
+}
+
+impl<T> Wire for UnReturnMessage<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + std::fmt::Debug + Unpin + 'static,
+{
+    type Data = T;
+
+    fn split(self) -> (T, Option<oneshot::Sender<T>>) {
+        (self.data, None)
+    }
+
+    fn join(data: T, _reply: bool) -> (Self, Option<oneshot::Receiver<T>>) {
+        (UnReturnMessage::build(data), None)
+    }
+}
+
+// 帧,id 为 0 表示不需要回复
+#[derive(Debug, Serialize, Deserialize)]
+enum Frame {
+    Hello { token: String },
+    Request { id: u64, body: serde_json::Value },
+    Reply { id: u64, body: serde_json::Value },
+    Failed { id: u64, reason: String },
+}
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> Stream for S {}
+
+async fn open(endpoint: &Endpoint) -> std::io::Result<Box<dyn Stream>> {
+    Ok(match endpoint {
+        Endpoint::Tcp(addr) => {
+            let stream = tokio::net::TcpStream::connect(addr).await?;
+            stream.set_nodelay(true)?;
+            Box::new(stream)
+        }
+        #[cfg(unix)]
+        Endpoint::Unix(path) => Box::new(tokio::net::UnixStream::connect(path).await?),
+    })
+}
+
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> anyhow::Result<Frame> {
+    let len = reader.read_u32().await? as usize;
+    if len > MAX_FRAME {
+        anyhow::bail!("帧长度 {} 超过上限", len);
+    }
+    let mut buf = vec![0; len];
+    reader.read_exact(&mut buf).await?;
+    Ok(serde_json::from_slice(&buf)?)
+}
+
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, frame: &Frame) -> anyhow::Result<()> {
+    let buf = serde_json::to_vec(frame)?;
+    if buf.len() > MAX_FRAME {
+        anyhow::bail!("帧长度 {} 超过上限", buf.len());
+    }
+    writer.write_u32(buf.len() as u32).await?;
+    writer.write_all(&buf).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// 逐字节比较全部内容,耗时与不匹配的位置无关
+fn token_eq(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// 是否只有本机可以连接,未配置令牌时只允许监听这类地址
+fn is_local(endpoint: &Endpoint) -> bool {
+    match endpoint {
+        Endpoint::Tcp(addr) => addr
+            .parse::<std::net::SocketAddr>()
+            .is_ok_and(|addr| addr.ip().is_loopback()),
+        #[cfg(unix)]
+        Endpoint::Unix(_) => true,
+    }
+}
+
+/// 连接远程服务,返回的发送端与本地任务的 api 用法相同
+/// 连接断开后自动重连,断开期间的消息排队等待,已发出未回复的请求会失败
+pub fn connect<M: Wire>(endpoint: Endpoint, token: &str) -> MessageSender<M> {
+    let (send, recv) = mpsc::unbounded_channel();
+    tokio::spawn(client(endpoint, token.to_string(), recv));
+    send
+}
+
+async fn client<M: Wire>(endpoint: Endpoint, token: String, mut recv: mpsc::UnboundedReceiver<M>) {
+    let mut backoff = RECONNECT_MIN;
+    loop {
+        let stream = match open(&endpoint).await {
+            Ok(stream) => {
+                log::info!("[远程服务] 已连接 {}", endpoint);
+                backoff = RECONNECT_MIN;
+                stream
+            }
+            Err(e) => {
+                log::warn!("[远程服务] 连接 {} 失败,{:?} 后重试: {}", endpoint, backoff, e);
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(RECONNECT_MAX);
+                if recv.is_closed() {
+                    return;
+                }
+                continue;
+            }
+        };
+        let (mut reader, mut writer) = tokio::io::split(stream);
+        if let Err(e) = write_frame(&mut writer, &Frame::Hello { token: token.clone() }).await {
+            log::warn!("[远程服务] 向 {} 发送令牌失败: {}", endpoint, e);
+            continue;
+        }
+        // 读帧不能在 select 中被打断,放到单独的任务里
+        let (frame_send, mut frames) = mpsc::unbounded_channel();
+        let read = tokio::spawn(async move {
+            loop {
+                let frame = read_frame(&mut reader).await;
+                let failed = frame.is_err();
+                if frame_send.send(frame).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        let mut pending: HashMap<u64, oneshot::Sender<M::Data>> = HashMap::new();
+        let mut next_id = 0u64;
+        let result: anyhow::Result<()> = async {
+            loop {
+                tokio::select! {
+                    message = recv.recv() => {
+                        // 所有发送端都已关闭
+                        let Some(message) = message else { return Ok(()) };
+                        let (data, reply) = message.split();
+                        let body = match serde_json::to_value(data) {
+                            Ok(body) => body,
+                            Err(e) => {
+                                log::warn!("[远程服务] 消息无法序列化,已丢弃: {}", e);
+                                continue;
+                            }
+                        };
+                        let id = match reply {
+                            Some(reply) => {
+                                next_id += 1;
+                                pending.insert(next_id, reply);
+                                next_id
+                            }
+                            None => 0,
+                        };
+                        write_frame(&mut writer, &Frame::Request { id, body }).await?;
+                    }
+                    frame = frames.recv() => match frame.unwrap_or_else(|| Err(anyhow::anyhow!("连接已关闭")))? {
+                        Frame::Reply { id, body } => {
+                            if let Some(reply) = pending.remove(&id) {
+                                match serde_json::from_value(body) {
+                                    Ok(data) => { let _ = reply.send(data); }
+                                    Err(e) => log::warn!("[远程服务] 无法解析回复: {}", e),
+                                }
+                            }
+                        }
+                        Frame::Failed { id, reason } => {
+                            // 丢弃回复通道,调用方会收到回复通道已丢弃的错误
+                            pending.remove(&id);
+                            log::warn!("[远程服务] {} 处理请求失败: {}", endpoint, reason);
+                        }
+                        Frame::Request { .. } | Frame::Hello { .. } => anyhow::bail!("服务端发送了请求帧"),
+                    },
+                }
+            }
+        }
+        .await;
+        read.abort();
+        match result {
+            Ok(()) => return,
+            Err(e) => log::warn!(
+                "[远程服务] 与 {} 的连接断开,{} 个请求未得到回复: {}",
+                endpoint,
+                pending.len(),
+                e
+            ),
+        }
+    }
+}
+
+/// 已绑定的服务端
+#[derive(Debug)]
+pub enum Listener {
+    Tcp(tokio::net::TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, std::path::PathBuf),
+}
+
+impl Listener {
+    pub async fn bind(endpoint: &Endpoint) -> anyhow::Result<Self> {
+        Ok(match endpoint {
+            Endpoint::Tcp(addr) => Listener::Tcp(tokio::net::TcpListener::bind(addr).await?),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => {
+                // 上次异常退出遗留的套接字文件
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+                Listener::Unix(tokio::net::UnixListener::bind(path)?, path.clone())
+            }
+        })
+    }
+
+    /// 实际监听的地址,绑定 0 端口时用于获取分配到的端口
+    pub fn endpoint(&self) -> anyhow::Result<Endpoint> {
+        Ok(match self {
+            Listener::Tcp(listener) => Endpoint::Tcp(listener.local_addr()?.to_string()),
+            #[cfg(unix)]
+            Listener::Unix(_, path) => Endpoint::Unix(path.clone()),
+        })
+    }
+
+    async fn accept(&self) -> std::io::Result<Box<dyn Stream>> {
+        Ok(match self {
+            Listener::Tcp(listener) => {
+                let (stream, _) = listener.accept().await?;
+                stream.set_nodelay(true)?;
+                Box::new(stream)
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener, _) => Box::new(listener.accept().await?.0),
+        })
+    }
+
+    /// 接受连接并把收到的消息转发给本地任务,客户端需先发送与 token 相同的令牌
+    /// token 为空时只允许监听本机地址
+    pub async fn serve<M: Wire>(self, token: &str, local: MessageSender<M>) -> anyhow::Result<()> {
+        let endpoint = self.endpoint()?;
+        if token.is_empty() && !is_local(&endpoint) {
+            anyhow::bail!("监听非本机地址 {} 时必须配置令牌", endpoint);
+        }
+        let token: std::sync::Arc<str> = token.into();
+        loop {
+            let stream = self.accept().await?;
+            tokio::spawn(serve_connection(stream, token.clone(), local.clone()));
+        }
+    }
+}
+
+/// 在 endpoint 上对外提供本地任务
+pub async fn serve<M: Wire>(endpoint: &Endpoint, token: &str, local: MessageSender<M>) -> anyhow::Result<()> {
+    let listener = Listener::bind(endpoint).await?;
+    log::info!("[远程服务] 正在监听 {}", endpoint);
+    listener.serve(token, local).await
+}
+
+async fn serve_connection<M: Wire>(stream: Box<dyn Stream>, token: std::sync::Arc<str>, local: MessageSender<M>) {
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    match read_frame(&mut reader).await {
+        Ok(Frame::Hello { token: given }) if token_eq(&given, &token) => {}
+        _ => {
+            log::warn!("[远程服务] 客户端令牌错误,断开连接");
+            return;
+        }
+    }
+    // 回复可能乱序完成,统一交给写任务
+    let (frames, mut frame_recv) = mpsc::unbounded_channel::<Frame>();
+    let write = tokio::spawn(async move {
+        while let Some(frame) = frame_recv.recv().await {
+            if let Err(e) = write_frame(&mut writer, &frame).await {
+                log::warn!("[远程服务] 写入回复失败: {}", e);
+                break;
+            }
+        }
+    });
+    loop {
+        let (id, body) = match read_frame(&mut reader).await {
+            Ok(Frame::Request { id, body }) => (id, body),
+            Ok(_) => {
+                log::warn!("[远程服务] 客户端发送了非请求帧,断开连接");
+                break;
+            }
+            // 客户端断开
+            Err(_) => break,
+        };
+        let data = match serde_json::from_value(body) {
+            Ok(data) => data,
+            Err(e) => {
+                let _ = frames.send(Frame::Failed {
+                    id,
+                    reason: format!("无法解析请求: {}", e),
+                });
+                continue;
+            }
+        };
+        let (message, reply) = M::join(data, id != 0);
+        if local.send(message).is_err() {
+            let _ = frames.send(Frame::Failed {
+                id,
+                reason: "服务已关闭".to_string(),
+            });
+            continue;
+        }
+        if let Some(reply) = reply {
+            let frames = frames.clone();
+            tokio::spawn(async move {
+                let frame = match reply.await {
+                    Ok(data) => match serde_json::to_value(data) {
+                        Ok(body) => Frame::Reply { id, body },
+                        Err(e) => Frame::Failed {
+                            id,
+                            reason: format!("回复无法序列化: {}", e),
+                        },
+                    },
+                    Err(_) => Frame::Failed {
+                        id,
+                        reason: "服务没有回复".to_string(),
+                    },
+                };
+                let _ = frames.send(frame);
+            });
+        }
+    }
+    drop(frames);
+    let _ = write.await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    enum Counter {
+        Add(i32, i32),
+    }
+
+    #[tokio::test]
+    async fn request_over_tcp() {
+        let (local, mut recv) = mpsc::unbounded_channel::<ReturnMessage<Counter>>();
+        tokio::spawn(async move {
+            while let Some(mut message) = recv.recv().await {
+                let Counter::Add(a, b) = message.data;
+                if let Some(send) = message.get_return_send().await.unwrap() {
+                    let _ = send.send(Counter::Add(a + b, 0));
+                }
+            }
+        });
+        let listener = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let endpoint = listener.endpoint().unwrap();
+        tokio::spawn(async move { listener.serve("secret", local).await });
+
+        let remote = connect::<ReturnMessage<Counter>>(endpoint.clone(), "secret");
+        let Counter::Add(sum, _) = ReturnMessage::build(Counter::Add(2, 3))
+            .get(&remote)
+            .await
+            .unwrap();
+        assert_eq!(sum, 5);
+        assert!("ws://x".parse::<Endpoint>().is_err());
+
+        // 令牌错误的连接被断开,请求得不到回复
+        let intruder = connect::<ReturnMessage<Counter>>(endpoint, "guess");
+        let result = ReturnMessage::build(Counter::Add(2, 3))
+            .get_timeout(&intruder, Duration::from_millis(500))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn empty_token_only_on_loopback() {
+        let (local, _recv) = mpsc::unbounded_channel::<ReturnMessage<Counter>>();
+        let listener = Listener::bind(&"0.0.0.0:0".parse().unwrap()).await.unwrap();
+        assert!(listener.serve("", local).await.is_err());
+        assert!(is_local(&"127.0.0.1:25601".parse().unwrap()));
+        assert!(!is_local(&"0.0.0.0:25601".parse().unwrap()));
+    }
+}
@@ -0,0 +1,284 @@
+// 任务监督
+// 处理消息时返回错误或 panic 视为任务失败,按监督策略重启、停止或上报给管理器
+// 重启在原任务内进行,发送端与邮箱保持不变,已保存在任务表中的 api 不会失效
+use std::{
+    any::Any,
+    collections::VecDeque,
+    future::Future,
+    panic::AssertUnwindSafe,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use futures::FutureExt;
+
+use crate::message::{
+    MessageSender, MessageType, return_message::ReturnMessage, unreturn_message::UnReturnMessage,
+};
+
+/// 任务失败后的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Directive {
+    /// 调用 restart 钩子后继续处理后续消息
+    Restart,
+    /// 停止任务
+    Stop,
+    /// 停止任务并交由管理器决定后续处理
+    Escalate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SupervisorPolicy {
+    pub directive: Directive,
+    /// 时间窗口内最多重启的次数,超过后改为上报
+    pub max_restarts: u32,
+    pub within: Duration,
+}
+
+impl SupervisorPolicy {
+    pub fn new(directive: Directive, max_restarts: u32, within: Duration) -> Self {
+        Self {
+            directive,
+            max_restarts,
+            within,
+        }
+    }
+
+    /// 失败即停止,未设置监督的任务使用此策略
+    pub fn stop() -> Self {
+        Self::new(Directive::Stop, 0, Duration::ZERO)
+    }
+}
+
+impl Default for SupervisorPolicy {
+    fn default() -> Self {
+        Self::new(Directive::Restart, 3, Duration::from_secs(60))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// 事件处理返回了错误
+    Error,
+    Panic,
+}
+
+/// 任务失败的报告,由子任务发送给管理器
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub task: String,
+    pub kind: FailureKind,
+    pub reason: String,
+    /// 实际采取的处理,超过重启次数上限时为 Escalate
+    pub directive: Directive,
+    /// 时间窗口内已重启的次数
+    pub restarts: u32,
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            FailureKind::Error => "错误",
+            FailureKind::Panic => "panic",
+        };
+        write!(f, "任务 {} 发生{}: {}", self.task, kind, self.reason)
+    }
+}
+
+pub(crate) type Report = Box<dyn Fn(Failure) + Send + Sync>;
+
+pub(crate) struct Supervisor {
+    name: String,
+    policy: SupervisorPolicy,
+    restarts: VecDeque<Instant>,
+    report: Option<Report>,
+}
+
+impl Supervisor {
+    pub(crate) fn new(name: String, policy: SupervisorPolicy, report: Option<Report>) -> Self {
+        Self {
+            name,
+            policy,
+            restarts: VecDeque::new(),
+            report,
+        }
+    }
+
+    /// 修改名称与策略,保留已设置的上报方式
+    pub(crate) fn reconfigure(&mut self, name: String, policy: SupervisorPolicy) {
+        self.name = name;
+        self.policy = policy;
+        self.restarts.clear();
+    }
+
+    pub(crate) fn report_with(&mut self, report: Report) {
+        self.report = Some(report);
+    }
+
+    /// 未设置监督的任务失败即停止,只记录日志
+    pub(crate) fn unsupervised<T>() -> Self {
+        Self::new(std::any::type_name::<T>().to_string(), SupervisorPolicy::stop(), None)
+    }
+
+    /// 按策略与重启频率决定如何处理这次失败
+    pub(crate) fn failed(&mut self, kind: FailureKind, reason: String) -> Failure {
+        let mut directive = self.policy.directive;
+        if directive == Directive::Restart {
+            let now = Instant::now();
+            while self
+                .restarts
+                .front()
+                .is_some_and(|at| now.duration_since(*at) > self.policy.within)
+            {
+                self.restarts.pop_front();
+            }
+            if self.restarts.len() as u32 >= self.policy.max_restarts {
+                directive = Directive::Escalate;
+            } else {
+                self.restarts.push_back(now);
+            }
+        }
+        let failure = Failure {
+            task: self.name.clone(),
+            kind,
+            reason,
+            directive,
+            restarts: self.restarts.len() as u32,
+        };
+        match directive {
+            Directive::Restart => log::warn!("{},第 {} 次重启", failure, failure.restarts),
+            Directive::Stop => log::error!("{},任务已停止", failure),
+            Directive::Escalate => log::error!("{},任务已停止并上报", failure),
+        }
+        failure
+    }
+
+    /// 处理一次失败,需要重启时执行 restart,重启失败则停止任务
+    pub(crate) async fn recover<F>(&mut self, kind: FailureKind, reason: String, restart: F) -> Failure
+    where
+        F: Future<Output = anyhow::Result<()>>,
+    {
+        let mut failure = self.failed(kind, reason);
+        if failure.directive == Directive::Restart
+            && let Err((_, reason)) = guard(async { restart.await.map(|_| false) }).await
+        {
+            log::error!("任务 {} 重启失败,任务已停止: {}", self.name, reason);
+            failure.directive = Directive::Stop;
+        }
+        failure
+    }
+
+    /// 通知管理器,没有管理器时只记录日志
+    pub(crate) fn report(&self, failure: Failure) {
+        if let Some(report) = &self.report {
+            report(failure);
+        }
+    }
+}
+
+/// 执行一次事件处理,把返回的错误与 panic 都转换为失败原因
+pub(crate) async fn guard<F>(event: F) -> Result<bool, (FailureKind, String)>
+where
+    F: Future<Output = anyhow::Result<bool>>,
+{
+    match AssertUnwindSafe(event).catch_unwind().await {
+        Ok(Ok(close)) => Ok(close),
+        Ok(Err(e)) => Err((FailureKind::Error, format!("{:#}", e))),
+        Err(panic) => Err((FailureKind::Panic, panic_message(panic))),
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "未知 panic".to_string()
+    }
+}
+
+// 管理器的消息类型实现 From<Failure> 后即可接收子任务的失败报告
+impl<T> From<Failure> for ReturnMessage<T>
+where
+    T: From<Failure> + Send + Sync + std::fmt::Debug + Unpin + 'static,
+{
+    fn from(failure: Failure) -> Self {
+        ReturnMessage::build(T::from(failure))
+    }
+}
+
+impl<T> From<Failure> for UnReturnMessage<T>
+where
+    T: From<Failure> + Send + Sync + std::fmt::Debug + Unpin + 'static,
+{
+    fn from(failure: Failure) -> Self {
+        UnReturnMessage::build(T::from(failure))
+    }
+}
+
+/// 移除任务表中已停止的任务,返回被移除的 ID
+pub fn prune<ID, T>(task_map: &DashMap<ID, MessageSender<T>>) -> Vec<ID>
+where
+    ID: std::hash::Hash + Eq + Clone,
+{
+    let mut removed = Vec::new();
+    task_map.retain(|id, api| {
+        let closed = api.is_closed();
+        if closed {
+            removed.push(id.clone());
+        }
+        !closed
+    });
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn restart_intensity() {
+        let mut supervisor = Supervisor::new(
+            "test".to_string(),
+            SupervisorPolicy::new(Directive::Restart, 2, Duration::from_secs(60)),
+            None,
+        );
+        let failure = guard(async { panic!("boom") }).await.unwrap_err();
+        assert_eq!(failure, (FailureKind::Panic, "boom".to_string()));
+        let directives: Vec<_> = (0..3)
+            .map(|_| supervisor.failed(FailureKind::Error, String::new()).directive)
+            .collect();
+        assert_eq!(
+            directives,
+            [Directive::Restart, Directive::Restart, Directive::Escalate]
+        );
+    }
+
+    #[derive(Debug)]
+    struct Failing;
+
+    #[async_trait::async_trait]
+    impl crate::event::task::TaskEasyEvent<UnReturnMessage<()>> for Failing {
+        async fn event(
+            &mut self,
+            _api: &MessageSender<UnReturnMessage<()>>,
+            _data: UnReturnMessage<()>,
+        ) -> anyhow::Result<bool> {
+            anyhow::bail!("boom")
+        }
+    }
+
+    #[tokio::test]
+    async fn easy_task_reports_to_parent() {
+        // 未设置策略的任务失败即停止,并把失败报告给上级
+        let (parent, mut failures) = tokio::sync::mpsc::unbounded_channel::<Failure>();
+        let (task, api) = crate::task::task::TaskEasy::new(Failing);
+        task.report_to(parent).run().await.unwrap();
+        let _ = api.send(UnReturnMessage::build(()));
+        let failure = failures.recv().await.unwrap();
+        assert_eq!(failure.directive, Directive::Stop);
+        assert_eq!(failure.reason, "boom");
+        assert!(api.is_closed());
+    }
+}
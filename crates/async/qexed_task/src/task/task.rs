@@ -1,6 +1,11 @@
 use tokio::sync::mpsc::UnboundedReceiver;
 
-use crate::{event::task::{TaskEasyEvent, TaskEvent}, message::MessageSender};
+use crate::{
+    event::task::{TaskEasyEvent, TaskEvent},
+    mailbox::{self, MailboxConfig, MailboxReceiver, MailboxSender},
+    message::{Inbox, MessageSender, return_message::ACTOR},
+    supervisor::{self, Directive, Failure, Supervisor, SupervisorPolicy},
+};
 
 
 
@@ -10,6 +15,8 @@ pub struct Task<MessageType,ManageMessageType, Task> {
     manage_api: MessageSender<ManageMessageType>,
     other: Task,
     receiver: Option<UnboundedReceiver<MessageType>>,
+    mailbox: Option<MailboxReceiver<MessageType>>,
+    supervisor: Supervisor,
 }
 impl<MessageType,ManageMessageType, TaskData> Task<MessageType,ManageMessageType,TaskData>
 where
@@ -29,26 +36,68 @@ where
                 manage_api: manage_api,
                 other: data,
                 receiver: Some(r),
+                mailbox: None,
+                supervisor: Supervisor::unsupervised::<TaskData>(),
             },
             w,
         )
     }
+    // 使用有界邮箱接收外部消息,任务收到的 api 仍是自身的无界通道
+    pub fn with_mailbox(
+        manage_api: MessageSender<ManageMessageType>,
+        data: TaskData,
+        name: &str,
+        config: MailboxConfig,
+    ) -> (Self, MailboxSender<MessageType>) {
+        let (mut task, _) = Self::new(manage_api, data);
+        let (w, r) = mailbox::named(name, config);
+        task.mailbox = Some(r);
+        (task, w)
+    }
+    // 设置监督策略,失败报告以 ManageMessageType 发送给管理器
+    pub fn supervise(mut self, name: impl Into<String>, policy: SupervisorPolicy) -> Self
+    where
+        ManageMessageType: From<Failure>,
+    {
+        let manage_api = self.manage_api.clone();
+        self.supervisor = Supervisor::new(
+            name.into(),
+            policy,
+            Some(Box::new(move |failure: Failure| {
+                let _ = manage_api.send(failure.into());
+            })),
+        );
+        self
+    }
     // 请注意:下面的所有权转移并不是失误,是刻意的设计
     pub async fn run(self) -> anyhow::Result<()> {
         tokio::spawn(self.listen());
         Ok(())
     }
     async fn listen(mut self) -> anyhow::Result<()> {
-        let mut receiver = self
+        let receiver = self
             .receiver
             .take()
             .ok_or_else(|| anyhow::anyhow!("接收管道不存在"))?;
+        let mut receiver = Inbox::new(receiver, self.mailbox.take());
+        let actor = std::any::type_name::<TaskData>();
         let api = self.api;
         let manage_api = self.manage_api;
         while let Some(data) = receiver.recv().await {
-            // 这里我们后面修改来实现具体业务逻辑
-            if self.other.event(&api, &manage_api, data).await? {
-                receiver.close();
+            match supervisor::guard(ACTOR.scope(actor, self.other.event(&api, &manage_api, data))).await {
+                Ok(true) => receiver.close(),
+                Ok(false) => {}
+                Err((kind, reason)) => {
+                    let failure = self.supervisor.recover(kind, reason, self.other.restart()).await;
+                    let stopped = failure.directive != Directive::Restart;
+                    if stopped {
+                        receiver.close();
+                    }
+                    self.supervisor.report(failure);
+                    if stopped {
+                        break;
+                    }
+                }
             }
         }
         Ok(())
@@ -60,6 +109,8 @@ pub struct TaskEasy<MessageType, Task> {
     api: MessageSender<MessageType>,
     other: Task,
     receiver: Option<UnboundedReceiver<MessageType>>,
+    mailbox: Option<MailboxReceiver<MessageType>>,
+    supervisor: Supervisor,
 }
 impl<MessageType, TaskData> TaskEasy<MessageType, TaskData>
 where
@@ -75,27 +126,68 @@ where
                 api: w.clone(),
                 other: data,
                 receiver: Some(r),
+                mailbox: None,
+                supervisor: Supervisor::unsupervised::<TaskData>(),
             },
             w,
         )
     }
+    // 使用有界邮箱接收外部消息,任务收到的 api 仍是自身的无界通道
+    pub fn with_mailbox(
+        data: TaskData,
+        name: &str,
+        config: MailboxConfig,
+    ) -> (Self, MailboxSender<MessageType>) {
+        let (mut task, _) = Self::new(data);
+        let (w, r) = mailbox::named(name, config);
+        task.mailbox = Some(r);
+        (task, w)
+    }
+    // 设置监督策略,没有通过 report_to 设置上级时 Escalate 等同于 Stop
+    pub fn supervise(mut self, name: impl Into<String>, policy: SupervisorPolicy) -> Self {
+        self.supervisor.reconfigure(name.into(), policy);
+        self
+    }
+    // 失败报告以 ParentMessageType 发送给上级管理器
+    pub fn report_to<ParentMessageType>(mut self, parent: MessageSender<ParentMessageType>) -> Self
+    where
+        ParentMessageType: From<Failure> + Send + 'static,
+    {
+        self.supervisor.report_with(Box::new(move |failure: Failure| {
+            let _ = parent.send(failure.into());
+        }));
+        self
+    }
     // 请注意:下面的所有权转移并不是失误,是刻意的设计
     pub async fn run(self) -> anyhow::Result<()> {
         tokio::spawn(self.listen());
         Ok(())
     }
     async fn listen(mut self) -> anyhow::Result<()> {
-        let mut receiver = self
+        let receiver = self
             .receiver
             .take()
             .ok_or_else(|| anyhow::anyhow!("接收管道不存在"))?;
+        let mut receiver = Inbox::new(receiver, self.mailbox.take());
+        let actor = std::any::type_name::<TaskData>();
         let api = self.api;
         while let Some(data) = receiver.recv().await {
-            // 这里我们后面修改来实现具体业务逻辑
-            if self.other.event(&api, data).await? {
-                receiver.close();
+            match supervisor::guard(ACTOR.scope(actor, self.other.event(&api, data))).await {
+                Ok(true) => receiver.close(),
+                Ok(false) => {}
+                Err((kind, reason)) => {
+                    let failure = self.supervisor.recover(kind, reason, self.other.restart()).await;
+                    let stopped = failure.directive != Directive::Restart;
+                    if stopped {
+                        receiver.close();
+                    }
+                    self.supervisor.report(failure);
+                    if stopped {
+                        break;
+                    }
+                }
             }
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
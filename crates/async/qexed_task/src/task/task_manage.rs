@@ -1,7 +1,12 @@
 use dashmap::DashMap;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
-use crate::{event::task_manage::TaskManageEvent, message::MessageSender};
+use crate::{
+    event::task_manage::TaskManageEvent,
+    mailbox::{self, MailboxConfig, MailboxReceiver, MailboxSender},
+    message::{Inbox, MessageSender, return_message::ACTOR},
+    supervisor::{self, Directive, Failure, Supervisor, SupervisorPolicy},
+};
 
 // 任务管理器
 // 创建与回收均由此管理
@@ -10,7 +15,9 @@ use crate::{event::task_manage::TaskManageEvent, message::MessageSender};
 pub struct TaskManage<ID,Task,MessageType,SubMessageType> {
     task_map:DashMap<ID,MessageSender<SubMessageType>>, // 你问我为什么不保存任务而是保存api接口？
     receiver: Option<UnboundedReceiver<MessageType>>,
+    mailbox: Option<MailboxReceiver<MessageType>>,
     api: UnboundedSender<MessageType>,
+    supervisor: Supervisor,
     other: Task,// 任务管理器本质也是任务,只是他是基于任务实现的
 }
 impl <ID,Task,MessageType,SubMessageType> TaskManage<ID,Task,MessageType,SubMessageType> 
@@ -30,27 +37,68 @@ where
                 api: w.clone(),
                 other: data,
                 receiver: Some(r),
+                mailbox: None,
+                supervisor: Supervisor::unsupervised::<Task>(),
                 task_map:DashMap::<ID, MessageSender<SubMessageType>>::new(),
             },
             w,
         )
     }
+    // 使用有界邮箱接收外部消息,管理器收到的 api 仍是自身的无界通道
+    pub fn with_mailbox(
+        data: Task,
+        name: &str,
+        config: MailboxConfig,
+    ) -> (Self, MailboxSender<MessageType>) {
+        let (mut task, _) = Self::new(data);
+        let (w, r) = mailbox::named(name, config);
+        task.mailbox = Some(r);
+        (task, w)
+    }
+    // 设置监督策略,没有通过 report_to 设置上级时 Escalate 等同于 Stop
+    pub fn supervise(mut self, name: impl Into<String>, policy: SupervisorPolicy) -> Self {
+        self.supervisor.reconfigure(name.into(), policy);
+        self
+    }
+    // 失败报告以 ParentMessageType 发送给上级管理器
+    pub fn report_to<ParentMessageType>(mut self, parent: MessageSender<ParentMessageType>) -> Self
+    where
+        ParentMessageType: From<Failure> + Send + 'static,
+    {
+        self.supervisor.report_with(Box::new(move |failure: Failure| {
+            let _ = parent.send(failure.into());
+        }));
+        self
+    }
     // 请注意:下面的所有权转移并不是失误,是刻意的设计
     pub async fn run(self) -> anyhow::Result<()> {
         tokio::spawn(self.listen());
         Ok(())
     }
     async fn listen(mut self) -> anyhow::Result<()> {
-        let mut receiver = self
+        let receiver = self
             .receiver
             .take()
             .ok_or_else(|| anyhow::anyhow!("接收管道不存在"))?;
+        let mut receiver = Inbox::new(receiver, self.mailbox.take());
+        let actor = std::any::type_name::<Task>();
         let api = self.api;
         let task_map = self.task_map;
         while let Some(data) = receiver.recv().await {
-            // 这里我们后面修改来实现具体业务逻辑
-            if self.other.event(&api, &task_map, data).await? {
-                receiver.close();
+            match supervisor::guard(ACTOR.scope(actor, self.other.event(&api, &task_map, data))).await {
+                Ok(true) => receiver.close(),
+                Ok(false) => {}
+                Err((kind, reason)) => {
+                    let failure = self.supervisor.recover(kind, reason, self.other.restart()).await;
+                    let stopped = failure.directive != Directive::Restart;
+                    if stopped {
+                        receiver.close();
+                    }
+                    self.supervisor.report(failure);
+                    if stopped {
+                        break;
+                    }
+                }
             }
         }
         Ok(())
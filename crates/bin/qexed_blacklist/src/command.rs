@@ -0,0 +1,75 @@
+use qexed_task::message::{MessageType, return_message::ReturnMessage};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::Message;
+
+pub async fn register_pardon_command(
+    command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+    api2: UnboundedSender<ReturnMessage<Message>>,
+) -> anyhow::Result<()> {
+    // 克隆 api2 用于闭包
+    let api2_for_closure = api2.clone();
+
+    qexed_command::register::register_command(
+        "pardon",
+        "将玩家移出黑名单",
+        "qexed.pardon",
+        vec![qexed_command::message::CommandParameter {
+            name: "player".to_string(),
+            description: "封禁时的玩家名或 UUID".to_string(),
+            required: true,
+            param_type: qexed_command::message::ParameterType::String {
+                behavior: qexed_command::message::StringBehavior::SingleWord,
+            },
+            suggestions: None,
+        }],
+        vec![],
+        command_api,
+        move |mut cmd_rx| {
+            let api2 = api2_for_closure.clone();
+            async move {
+                while let Some(cmd) = cmd_rx.recv().await {
+                    // 指令系统不检查权限字符串,解封只允许控制台执行
+                    if !cmd.is_cmd {
+                        cmd.send_chat_message("§c该指令只能在控制台使用").await?;
+                        continue;
+                    }
+                    let args = cmd.parse_args();
+                    let Some(player) = args.get(1) else {
+                        cmd.send_chat_message("用法: /pardon <玩家>").await?;
+                        continue;
+                    };
+                    let Some(uuid) = find(&api2, player).await? else {
+                        cmd.send_chat_message(&format!("§c{} 不在黑名单中", player)).await?;
+                        continue;
+                    };
+                    ReturnMessage::build(Message::Pardon(uuid, false)).get(&api2).await?;
+                    log::info!("[黑名单] 解除封禁 {}", player);
+                    cmd.send_chat_message(&format!("已将 {} 移出黑名单", player)).await?;
+                }
+                Ok(())
+            }
+        },
+    )
+    .await?;
+    qexed_command::register::register_suggestion("pardon", crate::ban_list_provider(api2), command_api)
+        .await
+}
+
+/// 按 UUID 或封禁时记录的玩家名(忽略大小写)查找黑名单条目
+async fn find(
+    api: &UnboundedSender<ReturnMessage<Message>>,
+    player: &str,
+) -> anyhow::Result<Option<uuid::Uuid>> {
+    let Message::GetBanEntries(entries) = ReturnMessage::build(Message::GetBanEntries(vec![])).get(api).await?
+    else {
+        return Ok(None);
+    };
+    let uuid = player.parse::<uuid::Uuid>().ok();
+    Ok(entries
+        .into_iter()
+        .find(|(entry, name)| {
+            Some(*entry) == uuid || name.as_deref().is_some_and(|name| name.eq_ignore_ascii_case(player))
+        })
+        .map(|(entry, _)| entry))
+}
@@ -1,20 +1,40 @@
 use anyhow::Ok;
 use async_trait::async_trait;
-use qexed_config::{app::{ qexed_blacklist::BlackList}, public::storage_engine::StorageEngine};
+use qexed_config::{app::qexed_blacklist::{BlackList, Simple}, public::storage_engine::StorageEngine, tool::Persist};
 use qexed_task::{event::task::{TaskEasyEvent}, message::{MessageSender, MessageType, return_message::ReturnMessage}};
+use qexed_command::suggestion::{self, Suggestion, SuggestionProvider};
 use tokio::sync::mpsc::UnboundedSender;
+pub mod command;
 #[derive(Debug, Clone)]
 pub enum Message {
-    CheckPlayerBan(uuid::Uuid,Option<String>)
+    CheckPlayerBan(uuid::Uuid,Option<String>),
+    GetBanList(Vec<uuid::Uuid>),
+    GetBanEntries(Vec<(uuid::Uuid, Option<String>)>), // 黑名单及封禁时记录的玩家名
+    Ban(uuid::Uuid, Option<String>, bool), // 加入黑名单并记录玩家名,返回名单是否变化
+    Pardon(uuid::Uuid, bool), // 移出黑名单,返回名单是否变化
+    Save(bool),               // 写回名单,返回是否成功
 }
 
-#[derive(Debug)]
 pub struct Task{
-    pub config:BlackList
+    pub config:BlackList,
+    persist:Persist<Simple>,
 }
 impl Task {
-    pub fn new(config:BlackList)->Self{
-        Self { config }
+    pub fn new(config:BlackList,persist:Persist<Simple>)->Self{
+        Self { config, persist }
+    }
+    // 名单在本任务内修改并写回,多个修改请求不会互相覆盖
+    fn save(&self) -> bool {
+        if let Err(e) = (self.persist)(&self.config.simple) {
+            log::error!("[黑名单] 保存名单失败: {}", e);
+            return false;
+        }
+        true
+    }
+}
+impl std::fmt::Debug for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Task").field("config", &self.config).finish_non_exhaustive()
     }
 }
 
@@ -42,6 +62,46 @@ impl TaskEasyEvent<ReturnMessage<Message>> for Task {
                     
                 }
             },
+            Message::GetBanList(ref mut list) => {
+                if self.config.storage_engine == StorageEngine::Simple {
+                    *list = self.config.simple.player_list.clone();
+                }
+            }
+            Message::GetBanEntries(ref mut entries) => {
+                let simple = &self.config.simple;
+                *entries = simple
+                    .player_list
+                    .iter()
+                    .map(|uuid| (*uuid, simple.names.get(uuid).cloned()))
+                    .collect();
+            }
+            Message::Ban(uuid, ref name, ref mut changed) => {
+                *changed = !self.config.simple.player_list.contains(&uuid);
+                if *changed {
+                    self.config.simple.player_list.push(uuid);
+                }
+                if let Some(name) = name
+                    && self.config.simple.names.get(&uuid) != Some(name)
+                {
+                    self.config.simple.names.insert(uuid, name.clone());
+                    *changed = true;
+                }
+                if *changed {
+                    self.save();
+                }
+            }
+            Message::Pardon(uuid, ref mut changed) => {
+                let before = self.config.simple.player_list.len();
+                self.config.simple.player_list.retain(|player| player != &uuid);
+                self.config.simple.names.remove(&uuid);
+                *changed = self.config.simple.player_list.len() != before;
+                if *changed {
+                    self.save();
+                }
+            }
+            Message::Save(ref mut saved) => {
+                *saved = self.save();
+            }
         };
         if let Some(send) = data.get_return_send().await? {
             let _ = send.send(data.data);
@@ -49,12 +109,29 @@ impl TaskEasyEvent<ReturnMessage<Message>> for Task {
         Ok(false)
     }
 }
-pub async fn run(config:BlackList)->anyhow::Result<UnboundedSender<ReturnMessage<Message>>>{
+/// 黑名单条目补全,候选值为封禁时记录的玩家名,没有记录名字的条目使用 UUID
+pub fn ban_list_provider(api: UnboundedSender<ReturnMessage<Message>>) -> SuggestionProvider {
+    SuggestionProvider::new(move |request| {
+        let api = api.clone();
+        async move {
+            let entries = match ReturnMessage::build(Message::GetBanEntries(vec![])).get(&api).await? {
+                Message::GetBanEntries(entries) => entries,
+                _ => vec![],
+            };
+            let entries = entries.into_iter().map(|(uuid, name)| match name {
+                Some(name) => Suggestion::with_tooltip(name, uuid.to_string()),
+                None => Suggestion::with_tooltip(uuid.to_string(), "黑名单玩家"),
+            });
+            Ok(suggestion::filter_prefix(entries, request.current()))
+        }
+    })
+}
+pub async fn run(config:BlackList,persist:Persist<Simple>)->anyhow::Result<UnboundedSender<ReturnMessage<Message>>>{
     if config.storage_engine!=StorageEngine::Simple{
         return Err(anyhow::anyhow!("暂未支持此引擎"))
     }
     // 假设创建任务服务端
-    let task_data = Task::new(config);
+    let task_data = Task::new(config, persist);
     let (task,task_send) = qexed_task::task::task::TaskEasy::new(task_data);
     task.run().await?;
     log::info!("[服务] 黑名单 已启用");
@@ -0,0 +1,56 @@
+use qexed_task::message::return_message::ReturnMessage;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{get_block_states_by_name, get_default_state_id, suggestion::block_id_provider};
+
+pub async fn register_blockinfo_command(
+    command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+) -> anyhow::Result<()> {
+    qexed_command::register::register_command(
+        "blockinfo",
+        "查询方块的默认状态ID与状态数",
+        "qexed.blockinfo",
+        vec![qexed_command::message::CommandParameter {
+            name: "block".to_string(),
+            description: "方块ID,可省略 minecraft: 命名空间".to_string(),
+            required: true,
+            param_type: qexed_command::message::ParameterType::String {
+                behavior: qexed_command::message::StringBehavior::SingleWord,
+            },
+            suggestions: None,
+        }],
+        vec![],
+        command_api,
+        move |mut cmd_rx| async move {
+            while let Some(cmd) = cmd_rx.recv().await {
+                let message = match cmd.parse_args().get(1) {
+                    Some(block) => describe(block),
+                    None => "用法: /blockinfo <方块>".to_string(),
+                };
+                cmd.send_chat_message(&message).await?;
+            }
+            Ok(())
+        },
+    )
+    .await?;
+    qexed_command::register::register_suggestion("blockinfo", block_id_provider(), command_api).await
+}
+
+fn describe(block: &str) -> String {
+    let block = block.to_lowercase();
+    let name = match block.contains(':') {
+        true => block,
+        false => format!("minecraft:{}", block),
+    };
+    match (get_default_state_id(&name), get_block_states_by_name(&name)) {
+        (Some(default), Some(states)) => format!(
+            "{} 默认状态ID: {},共 {} 个状态 ({} - {})",
+            name,
+            default,
+            states.len(),
+            states.first().unwrap_or(&default),
+            states.last().unwrap_or(&default)
+        ),
+        _ => format!("§c未知的方块 {}", name),
+    }
+}
@@ -6,6 +6,9 @@ use once_cell::sync::Lazy;
 // 包含生成的代码
 include!(concat!(env!("OUT_DIR"), "/block_registry_generated.rs"));
 
+pub mod command;
+pub mod suggestion;
+
 /// 方块注册表管理器
 #[derive(Debug, Clone)]
 pub struct BlockRegistry {
@@ -0,0 +1,20 @@
+use qexed_command::suggestion::{Suggestion, SuggestionProvider};
+
+use crate::all_block_names;
+
+/// 方块ID补全,输入时可省略 minecraft: 命名空间
+pub fn block_id_provider() -> SuggestionProvider {
+    SuggestionProvider::new(|request| async move {
+        let current = request.current().to_lowercase();
+        Ok(all_block_names()
+            .iter()
+            .filter(|name| {
+                name.starts_with(&current)
+                    || name
+                        .strip_prefix("minecraft:")
+                        .is_some_and(|short| short.starts_with(&current))
+            })
+            .map(|name| Suggestion::new(*name))
+            .collect())
+    })
+}
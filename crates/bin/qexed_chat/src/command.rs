@@ -1,12 +1,15 @@
 use qexed_command::message::CommandData;
-use qexed_task::message::{MessageType, return_message::ReturnMessage};
+use qexed_task::{
+    mailbox::MailboxSender,
+    message::{MessageType, return_message::ReturnMessage},
+};
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::message::ManagerMessage;
 
 pub async fn register_tell_command(
     command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
-    api2: UnboundedSender<ReturnMessage<ManagerMessage>>,
+    api2: MailboxSender<ReturnMessage<ManagerMessage>>,
 ) -> anyhow::Result<()> {
     // 克隆 api2 用于闭包
     let api2_for_closure = api2.clone();
@@ -21,9 +24,7 @@ pub async fn register_tell_command(
                 name: "player".to_string(),
                 description: "要私聊的玩家".to_string(),
                 required: true,
-                param_type: qexed_command::message::ParameterType::String {
-                    behavior: qexed_command::message::StringBehavior::Greedy,
-                },
+                param_type: qexed_command::message::ParameterType::Player,
                 suggestions: None,
             },
             // 第二个参数：消息内容
@@ -57,7 +58,7 @@ pub async fn register_tell_command(
 
 pub async fn register_me_command(
     command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
-    api2: UnboundedSender<ReturnMessage<ManagerMessage>>,
+    api2: MailboxSender<ReturnMessage<ManagerMessage>>,
 ) -> anyhow::Result<()> {
     // 克隆 api2 用于闭包
     let api2_for_closure = api2.clone();
@@ -97,7 +98,7 @@ pub async fn register_me_command(
 
 pub async fn register_say_command(
     command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
-    api2: UnboundedSender<ReturnMessage<ManagerMessage>>,
+    api2: MailboxSender<ReturnMessage<ManagerMessage>>,
 ) -> anyhow::Result<()> {
     // 克隆 api2 用于闭包
     let api2_for_closure = api2.clone();
@@ -134,3 +135,174 @@ pub async fn register_say_command(
     )
     .await
 }
+
+pub async fn register_reply_command(
+    command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+    api2: MailboxSender<ReturnMessage<ManagerMessage>>,
+) -> anyhow::Result<()> {
+    // 克隆 api2 用于闭包
+    let api2_for_closure = api2.clone();
+
+    qexed_command::register::register_command(
+        "r",
+        "回复最近一次私聊你的玩家",
+        "qexed.tell",
+        vec![
+            qexed_command::message::CommandParameter {
+                name: "message".to_string(),
+                description: "私聊消息内容".to_string(),
+                required: true,
+                param_type: qexed_command::message::ParameterType::String {
+                    behavior: qexed_command::message::StringBehavior::Greedy,
+                },
+                suggestions: None,
+            },
+        ],
+        vec!["reply"], // 可以添加多个别名
+        command_api,
+        move |mut cmd_rx| {
+            let api2 = api2_for_closure.clone();
+            async move {
+                while let Some(cmd) = cmd_rx.recv().await {
+                    ReturnMessage::build(ManagerMessage::CommandReply(cmd))
+                    .get(&api2)
+                    .await?;
+                }
+                Ok(())
+            }
+        },
+    )
+    .await
+}
+
+pub async fn register_mute_command(
+    command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+    api2: MailboxSender<ReturnMessage<ManagerMessage>>,
+) -> anyhow::Result<()> {
+    // 克隆 api2 用于闭包
+    let api2_for_closure = api2.clone();
+
+    qexed_command::register::register_command(
+        "mute",
+        "禁言玩家,不填时长为永久禁言",
+        "qexed.mute",
+        vec![
+            qexed_command::message::CommandParameter {
+                name: "player".to_string(),
+                description: "要禁言的玩家".to_string(),
+                required: true,
+                param_type: qexed_command::message::ParameterType::Player,
+                suggestions: None,
+            },
+            qexed_command::message::CommandParameter {
+                name: "duration".to_string(),
+                description: "禁言时长,如 30s、10m、2h、1d".to_string(),
+                required: false,
+                param_type: qexed_command::message::ParameterType::String {
+                    behavior: qexed_command::message::StringBehavior::SingleWord,
+                },
+                suggestions: Some(vec!["10m".to_string(), "1h".to_string(), "1d".to_string()]),
+            },
+            qexed_command::message::CommandParameter {
+                name: "reason".to_string(),
+                description: "禁言原因".to_string(),
+                required: false,
+                param_type: qexed_command::message::ParameterType::String {
+                    behavior: qexed_command::message::StringBehavior::Greedy,
+                },
+                suggestions: None,
+            },
+        ],
+        vec![], // 可以添加多个别名
+        command_api,
+        move |mut cmd_rx| {
+            let api2 = api2_for_closure.clone();
+            async move {
+                while let Some(cmd) = cmd_rx.recv().await {
+                    ReturnMessage::build(ManagerMessage::CommandMute(cmd))
+                    .get(&api2)
+                    .await?;
+                }
+                Ok(())
+            }
+        },
+    )
+    .await
+}
+
+pub async fn register_unmute_command(
+    command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+    api2: MailboxSender<ReturnMessage<ManagerMessage>>,
+) -> anyhow::Result<()> {
+    // 克隆 api2 用于闭包
+    let api2_for_closure = api2.clone();
+
+    qexed_command::register::register_command(
+        "unmute",
+        "解除玩家禁言",
+        "qexed.mute",
+        vec![
+            qexed_command::message::CommandParameter {
+                name: "player".to_string(),
+                description: "要解除禁言的玩家".to_string(),
+                required: true,
+                param_type: qexed_command::message::ParameterType::Player,
+                suggestions: None,
+            },
+        ],
+        vec![], // 可以添加多个别名
+        command_api,
+        move |mut cmd_rx| {
+            let api2 = api2_for_closure.clone();
+            async move {
+                while let Some(cmd) = cmd_rx.recv().await {
+                    ReturnMessage::build(ManagerMessage::CommandUnmute(cmd))
+                    .get(&api2)
+                    .await?;
+                }
+                Ok(())
+            }
+        },
+    )
+    .await
+}
+
+pub async fn register_chatlog_command(
+    command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+    api2: MailboxSender<ReturnMessage<ManagerMessage>>,
+) -> anyhow::Result<()> {
+    // 克隆 api2 用于闭包
+    let api2_for_closure = api2.clone();
+
+    qexed_command::register::register_command(
+        "chatlog",
+        "查看最近被过滤或拦截的聊天消息",
+        "qexed.chat.log",
+        vec![
+            qexed_command::message::CommandParameter {
+                name: "count".to_string(),
+                description: "显示条数,默认 10".to_string(),
+                required: false,
+                param_type: qexed_command::message::ParameterType::Integer {
+                    min: Some(1),
+                    max: None,
+                },
+                suggestions: None,
+            },
+        ],
+        vec![],
+        command_api,
+        move |mut cmd_rx| {
+            let api2 = api2_for_closure.clone();
+            async move {
+                while let Some(cmd) = cmd_rx.recv().await {
+                    ReturnMessage::build(ManagerMessage::CommandChatLog(cmd))
+                    .get(&api2)
+                    .await?;
+                }
+                Ok(())
+            }
+        },
+    )
+    .await
+}
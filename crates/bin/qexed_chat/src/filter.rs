@@ -0,0 +1,133 @@
+use qexed_config::app::qexed_chat::{FilterAction, FilterConfig};
+use regex::Regex;
+use uuid::Uuid;
+
+/// 过滤结果
+#[derive(Debug, PartialEq)]
+pub enum FilterOutcome {
+    /// 未命中任何规则
+    Pass,
+    /// 命中替换规则,附带替换后的文本
+    Replaced(String),
+    /// 命中拦截规则
+    Blocked,
+}
+
+/// 过滤/刷屏记录,管理员可通过 /chatlog 查看
+#[derive(Debug, Clone)]
+pub struct FilterRecord {
+    /// 记录时间(Unix 秒)
+    pub time: i64,
+    pub uuid: Uuid,
+    pub name: String,
+    /// 处理原因,如 "已替换"、"已拦截"、"发言过于频繁"
+    pub reason: String,
+    /// 原始消息
+    pub message: String,
+}
+
+impl FilterRecord {
+    pub fn describe(&self) -> String {
+        format!("{}: {} ({})", self.name, self.message, self.reason)
+    }
+}
+
+/// 按配置的词语/正则规则过滤消息
+#[derive(Debug, Clone, Default)]
+pub struct ChatFilter {
+    rules: Vec<(Regex, FilterAction)>,
+    replacement: String,
+}
+
+impl ChatFilter {
+    pub fn new(config: &FilterConfig) -> Self {
+        if !config.enabled {
+            return Self::default();
+        }
+        let rules = config
+            .rules
+            .iter()
+            .filter_map(|rule| {
+                let pattern = if rule.regex {
+                    rule.pattern.clone()
+                } else {
+                    format!("(?i){}", regex::escape(&rule.pattern))
+                };
+                match Regex::new(&pattern) {
+                    Ok(regex) => Some((regex, rule.action)),
+                    Err(e) => {
+                        log::warn!("[聊天] 过滤规则 {} 无效,已忽略: {}", rule.pattern, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self {
+            rules,
+            replacement: config.replacement.clone(),
+        }
+    }
+
+    pub fn apply(&self, message: &str) -> FilterOutcome {
+        if self
+            .rules
+            .iter()
+            .any(|(regex, action)| *action == FilterAction::Block && regex.is_match(message))
+        {
+            return FilterOutcome::Blocked;
+        }
+        let mut text = message.to_string();
+        for (regex, _) in &self.rules {
+            if regex.is_match(&text) {
+                text = regex
+                    .replace_all(&text, |caps: &regex::Captures| {
+                        self.replacement.repeat(caps[0].chars().count())
+                    })
+                    .into_owned();
+            }
+        }
+        if text == message {
+            FilterOutcome::Pass
+        } else {
+            FilterOutcome::Replaced(text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qexed_config::app::qexed_chat::FilterRule;
+
+    use super::*;
+
+    #[test]
+    fn replace_and_block() {
+        let config = FilterConfig {
+            rules: vec![
+                FilterRule {
+                    pattern: "Bad".to_string(),
+                    regex: false,
+                    action: FilterAction::Replace,
+                },
+                FilterRule {
+                    pattern: r"discord\.gg/\w+".to_string(),
+                    regex: true,
+                    action: FilterAction::Block,
+                },
+                FilterRule {
+                    pattern: "(".to_string(),
+                    regex: true,
+                    action: FilterAction::Block,
+                },
+            ],
+            ..Default::default()
+        };
+        let filter = ChatFilter::new(&config);
+        assert_eq!(filter.apply("hello"), FilterOutcome::Pass);
+        assert_eq!(
+            filter.apply("so bad, BAD"),
+            FilterOutcome::Replaced("so ***, ***".to_string())
+        );
+        assert_eq!(filter.apply("join discord.gg/abc"), FilterOutcome::Blocked);
+    }
+}
@@ -0,0 +1,303 @@
+use std::{collections::HashMap, sync::Arc};
+
+use qexed_config::app::qexed_chat::{ChatConfig, ChatFormat, ChatGroup};
+use qexed_nbt::{ListHeader, Tag, tag_id};
+use uuid::Uuid;
+
+/// 模板中的片段
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Text(String),
+    Player,
+}
+
+/// 按配置模板生成聊天文本组件
+#[derive(Debug, Clone)]
+pub struct ChatFormatter {
+    pub format: ChatFormat,
+    default_group: String,
+    groups: Vec<(String, ChatGroup)>,
+}
+
+/// 渲染时使用的玩家信息
+#[derive(Debug, Clone, Copy)]
+pub struct Sender<'a> {
+    pub uuid: Option<Uuid>,
+    pub name: &'a str,
+}
+
+impl ChatFormatter {
+    pub fn new(config: &ChatConfig) -> Self {
+        Self {
+            format: config.format.clone(),
+            default_group: config.default_group.clone(),
+            groups: config
+                .groups
+                .iter()
+                .map(|(name, group)| (name.clone(), group.clone()))
+                .collect(),
+        }
+    }
+
+    /// 玩家所在的分组,按分组名顺序取第一个匹配项,未匹配时使用默认分组
+    /// 只用于显示前后缀,成员可以按玩家名匹配
+    pub fn group_of(&self, sender: Sender) -> Option<(&str, &ChatGroup)> {
+        let uuid = sender.uuid.map(|u| u.to_string());
+        self.find_group(|member| {
+            member.eq_ignore_ascii_case(sender.name) || Some(member) == uuid.as_deref()
+        })
+    }
+
+    /// 权限只按 UUID 匹配成员,离线模式下玩家名可以被任意冒用
+    pub fn has_permission(&self, sender: Sender, permission: &str) -> bool {
+        let uuid = sender.uuid.map(|u| u.to_string());
+        self.find_group(|member| Some(member) == uuid.as_deref())
+            .is_some_and(|(_, group)| group.permissions.iter().any(|p| p == permission || p == "*"))
+    }
+
+    fn find_group(&self, is_member: impl Fn(&str) -> bool) -> Option<(&str, &ChatGroup)> {
+        self.groups
+            .iter()
+            .find(|(_, group)| group.members.iter().any(|member| is_member(member)))
+            .or_else(|| self.groups.iter().find(|(name, _)| *name == self.default_group))
+            .map(|(name, group)| (name.as_str(), group))
+    }
+
+    /// 渲染模板,{player} 处附带悬停与点击事件
+    pub fn render(&self, template: &str, sender: Sender, target: &str, message: &str) -> Tag {
+        let (group, prefix, suffix) = match self.group_of(sender) {
+            Some((name, group)) => (name, group.prefix.as_str(), group.suffix.as_str()),
+            None => ("", "", ""),
+        };
+        // 玩家输入的内容不允许携带格式代码
+        let message = message.replace('§', "");
+        let segments = parse_template(template, |key| match key {
+            "prefix" => Some(prefix.to_string()),
+            "suffix" => Some(suffix.to_string()),
+            "group" => Some(group.to_string()),
+            "target" => Some(target.to_string()),
+            "message" => Some(message.clone()),
+            _ => None,
+        });
+
+        let mut extra = Vec::with_capacity(segments.len());
+        // 旧式格式代码只在单个组件内生效,需要延续到后续组件
+        let mut active = String::new();
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => {
+                    extra.push(text_component(format!("{}{}", active, text)));
+                    active = trailing_format_codes(&active, &text);
+                }
+                Segment::Player => {
+                    let mut component = HashMap::new();
+                    component.insert(
+                        "text".to_string(),
+                        Tag::String(format!("{}{}", active, sender.name).into()),
+                    );
+                    if !self.format.name_hover.is_empty() {
+                        let hover = self.format.name_hover.replace("{player}", sender.name);
+                        component.insert(
+                            "hover_event".to_string(),
+                            compound(vec![
+                                ("action", Tag::String("show_text".into())),
+                                ("value", text_component(hover)),
+                            ]),
+                        );
+                    }
+                    if !self.format.name_click.is_empty() {
+                        let click = self.format.name_click.replace("{player}", sender.name);
+                        component.insert(
+                            "click_event".to_string(),
+                            compound(vec![
+                                ("action", Tag::String("suggest_command".into())),
+                                ("command", Tag::String(click.into())),
+                            ]),
+                        );
+                    }
+                    extra.push(Tag::Compound(Arc::new(component)));
+                }
+            }
+        }
+        if extra.is_empty() {
+            return text_component(String::new());
+        }
+        let mut root = HashMap::new();
+        root.insert("text".to_string(), Tag::String("".into()));
+        root.insert(
+            "extra".to_string(),
+            Tag::List(
+                ListHeader {
+                    tag_id: tag_id::COMPOUND,
+                    length: extra.len() as i32,
+                },
+                extra.into(),
+            ),
+        );
+        Tag::Compound(Arc::new(root))
+    }
+
+    /// 渲染为纯文本(控制台日志使用)
+    pub fn render_plain(&self, template: &str, sender: Sender, target: &str, message: &str) -> String {
+        let (group, prefix, suffix) = match self.group_of(sender) {
+            Some((name, group)) => (name, group.prefix.as_str(), group.suffix.as_str()),
+            None => ("", "", ""),
+        };
+        let segments = parse_template(template, |key| match key {
+            "prefix" => Some(prefix.to_string()),
+            "suffix" => Some(suffix.to_string()),
+            "group" => Some(group.to_string()),
+            "target" => Some(target.to_string()),
+            "message" => Some(message.to_string()),
+            _ => None,
+        });
+        let text: String = segments
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text,
+                Segment::Player => sender.name.to_string(),
+            })
+            .collect();
+        strip_format_codes(&text)
+    }
+}
+
+/// 切分模板,未知占位符原样保留
+fn parse_template(template: &str, mut resolve: impl FnMut(&str) -> Option<String>) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            text.push('{');
+            rest = after;
+            continue;
+        };
+        let key = &after[..end];
+        if key == "player" {
+            if !text.is_empty() {
+                segments.push(Segment::Text(std::mem::take(&mut text)));
+            }
+            segments.push(Segment::Player);
+        } else if let Some(value) = resolve(key) {
+            text.push_str(&value);
+        } else {
+            text.push('{');
+            text.push_str(key);
+            text.push('}');
+        }
+        rest = &after[end + 1..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    segments
+}
+
+/// 计算一段文本结束时仍生效的格式代码
+fn trailing_format_codes(active: &str, text: &str) -> String {
+    let mut codes = active.to_string();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '§' {
+            continue;
+        }
+        let Some(code) = chars.next() else {
+            break;
+        };
+        match code.to_ascii_lowercase() {
+            // 颜色代码与重置会清除之前的格式
+            '0'..='9' | 'a'..='f' => codes = format!("§{}", code),
+            'r' => codes.clear(),
+            'k'..='o' => {
+                codes.push('§');
+                codes.push(code);
+            }
+            _ => {}
+        }
+    }
+    codes
+}
+
+pub fn strip_format_codes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+pub fn text_component(text: String) -> Tag {
+    compound(vec![("text", Tag::String(text.into()))])
+}
+
+fn compound(entries: Vec<(&str, Tag)>) -> Tag {
+    Tag::Compound(Arc::new(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_splits_at_player() {
+        let segments = parse_template("{prefix}<{player}> {message} {unknown}", |key| match key {
+            "prefix" => Some("§c[管理]".to_string()),
+            "message" => Some("{player}".to_string()),
+            _ => None,
+        });
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Text("§c[管理]<".to_string()),
+                Segment::Player,
+                Segment::Text("> {player} {unknown}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn format_codes_carry_over() {
+        assert_eq!(trailing_format_codes("", "§c[管理] §l"), "§c§l");
+        assert_eq!(trailing_format_codes("§c§l", "abc§r"), "");
+        assert_eq!(trailing_format_codes("§l", "§a"), "§a");
+    }
+
+    #[test]
+    fn members_select_group() {
+        let config = ChatConfig::default();
+        let mut formatter = ChatFormatter::new(&config);
+        let uuid = Uuid::new_v4();
+        for (name, group) in formatter.groups.iter_mut() {
+            if name == "admin" {
+                group.members.push("Steve".to_string());
+                group.members.push(uuid.to_string());
+            }
+        }
+        let steve = Sender { uuid: None, name: "steve" };
+        let alex = Sender { uuid: None, name: "Alex" };
+        assert_eq!(formatter.group_of(steve).map(|g| g.0), Some("admin"));
+        assert_eq!(formatter.group_of(alex).map(|g| g.0), Some("default"));
+        // 按玩家名匹配只影响显示,不授予权限
+        assert!(!formatter.has_permission(steve, "qexed.mute"));
+        assert!(!formatter.has_permission(alex, "qexed.mute"));
+        assert!(formatter.has_permission(Sender { uuid: Some(uuid), name: "Alex" }, "qexed.mute"));
+        assert_eq!(
+            formatter.render_plain(&config.format.chat, steve, "", "hi"),
+            "[管理] <steve> hi"
+        );
+    }
+}
@@ -1,22 +1,51 @@
+use std::sync::Arc;
+
+use qexed_config::tool::AppConfigTrait;
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::return_message::ReturnMessage;
 use tokio::sync::mpsc::UnboundedSender;
 
-use crate::{manager::ChatManagerActor, message::ManagerMessage};
+use crate::{manager::ChatManagerActor, message::ManagerMessage, mute::MuteList, secure::MojangKeys};
 pub mod command;
+pub mod filter;
+pub mod format;
 pub mod manager;
 pub mod message;
+pub mod mute;
+pub mod secure;
+pub mod spam;
 pub mod task;
 pub async fn run(
     config: qexed_config::app::qexed_chat::ChatConfig,
-    player_list_api:UnboundedSender<ReturnMessage<qexed_player_list::Message>>
-
-) -> anyhow::Result<UnboundedSender<ReturnMessage<ManagerMessage>>> {
+    player_list_api:UnboundedSender<ReturnMessage<qexed_player_list::Message>>,
+    events: qexed_event::EventBus,
+) -> anyhow::Result<MailboxSender<ReturnMessage<ManagerMessage>>> {
+    // 开启强制安全聊天时拿不到公钥就拒绝启动,不降级为无签名模式
+    let mojang_keys = if config.enforce_secure_profile {
+        let keys = MojangKeys::fetch()
+            .await
+            .map_err(|e| anyhow::anyhow!("[聊天] 获取 Mojang 聊天公钥失败: {}", e))?;
+        if keys.is_empty() {
+            anyhow::bail!("[聊天] 未获取到 Mojang 聊天公钥");
+        }
+        Some(Arc::new(keys))
+    } else {
+        None
+    };
+    let mutes = MuteList::load_or_create_default()?;
     let manager_actor = ChatManagerActor::new(
         config,
-        player_list_api
+        player_list_api,
+        mojang_keys,
+        mutes,
+        events,
     );
     let (manager_task, manager_sender) =
-        qexed_task::task::task_manage::TaskManage::new(manager_actor);
+        qexed_task::task::task_manage::TaskManage::with_mailbox(
+            manager_actor,
+            "chat",
+            qexed_task::mailbox::MailboxConfig::manager(),
+        );
     manager_task.run().await?;
     log::info!("[服务] 聊天 已启用");
     Ok(manager_sender)
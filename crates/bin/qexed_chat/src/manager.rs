@@ -1,22 +1,37 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use dashmap::DashMap;
+use qexed_command::message::CommandData;
+use qexed_config::tool::AppConfigTrait;
 use qexed_nbt::Tag;
-use qexed_protocol::to_client::play::system_chat::{self, SystemChat};
+use qexed_protocol::to_client::play::{
+    player_info_update::{
+        ADD_PLAYER, INITIALIZE_CHAT, PlayerInfoEntry, PlayerInfoUpdate, UPDATE_LISTED,
+    },
+    system_chat::SystemChat,
+};
 use qexed_task::{
     event::task_manage::TaskManageEvent,
     message::{
         MessageSender, MessageType, return_message::ReturnMessage,
         unreturn_message::UnReturnMessage,
     },
+    supervisor::{self, Directive, SupervisorPolicy},
     task::task::Task,
 };
 use tokio::sync::mpsc::UnboundedSender;
 use uuid::Uuid;
 
 use crate::{
+    filter::{ChatFilter, FilterOutcome, FilterRecord},
+    format::{ChatFormatter, Sender, text_component},
     message::{ManagerMessage, NewPlayerConnectError, TaskMessage},
+    mute::{MuteEntry, MuteList, format_duration, now_secs, parse_duration},
+    secure::MojangKeys,
     task::ChatActor,
 };
 
@@ -24,15 +39,194 @@ use crate::{
 pub struct ChatManagerActor {
     config: qexed_config::app::qexed_chat::ChatConfig,
     player_list_api: UnboundedSender<ReturnMessage<qexed_player_list::Message>>,
+    // 为空时以无签名模式运行
+    mojang_keys: Option<Arc<MojangKeys>>,
+    // 已初始化聊天会话的玩家,新玩家进服时下发
+    sessions: HashMap<Uuid, PlayerInfoEntry>,
+    formatter: ChatFormatter,
+    mutes: MuteList,
+    // 私聊回复对象: 玩家 -> (对方, 对方名字)
+    last_private: HashMap<Uuid, (Uuid, String)>,
+    // 所有玩家共用同一份编译好的规则
+    filter: Arc<ChatFilter>,
+    // 最近的过滤/刷屏记录
+    filter_log: VecDeque<FilterRecord>,
+    events: qexed_event::EventBus,
 }
 impl ChatManagerActor {
     pub fn new(
         config: qexed_config::app::qexed_chat::ChatConfig,
         player_list_api: UnboundedSender<ReturnMessage<qexed_player_list::Message>>,
+        mojang_keys: Option<Arc<MojangKeys>>,
+        mutes: MuteList,
+        events: qexed_event::EventBus,
     ) -> Self {
         Self {
+            formatter: ChatFormatter::new(&config),
+            mutes,
+            last_private: HashMap::new(),
+            filter: Arc::new(ChatFilter::new(&config.filter)),
+            filter_log: VecDeque::new(),
             config: config,
             player_list_api: player_list_api,
+            mojang_keys,
+            sessions: HashMap::new(),
+            events,
+        }
+    }
+    async fn find_online(&self, name: &str) -> anyhow::Result<Option<Uuid>> {
+        if let qexed_player_list::Message::GetPlayerIsOnline {
+            name: _,
+            is_true: true,
+            player_uuid,
+        } = ReturnMessage::build(qexed_player_list::Message::GetPlayerIsOnline {
+            name: name.to_string(),
+            is_true: false,
+            player_uuid: uuid::Uuid::nil(),
+        })
+        .get(&self.player_list_api)
+        .await?
+        {
+            return Ok(Some(player_uuid));
+        }
+        Ok(None)
+    }
+    /// 发送者被禁言时提示并返回 true
+    async fn reject_muted(&mut self, cmd: &CommandData) -> anyhow::Result<bool> {
+        let Some(uuid) = cmd.player_uuid else {
+            return Ok(false);
+        };
+        match self.mutes.check(&uuid) {
+            Some(mute) => {
+                cmd.send_chat_message(&format!("§c{}", mute.describe())).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+    /// 过滤指令中的消息,被拦截时提示发送者并返回 None
+    async fn filter_command_message(
+        &mut self,
+        cmd: &CommandData,
+        message: &str,
+        task_map: &DashMap<Uuid, MessageSender<UnReturnMessage<TaskMessage>>>,
+    ) -> anyhow::Result<Option<String>> {
+        let (reason, result) = match self.filter.apply(message) {
+            FilterOutcome::Pass => return Ok(Some(message.to_string())),
+            FilterOutcome::Replaced(text) => ("已替换", Some(text)),
+            FilterOutcome::Blocked => ("已拦截", None),
+        };
+        self.record_filtered(
+            FilterRecord {
+                time: now_secs(),
+                uuid: cmd.player_uuid.unwrap_or(Uuid::nil()),
+                name: sender_of(cmd).name.to_string(),
+                reason: reason.to_string(),
+                message: message.to_string(),
+            },
+            task_map,
+        );
+        if result.is_none() {
+            cmd.send_chat_message("§c消息包含违禁内容,未发送").await?;
+        }
+        Ok(result)
+    }
+    /// 保存过滤记录并提醒在线管理员
+    fn record_filtered(
+        &mut self,
+        record: FilterRecord,
+        task_map: &DashMap<Uuid, MessageSender<UnReturnMessage<TaskMessage>>>,
+    ) {
+        log::warn!("[聊天过滤] {}", record.describe());
+        for task in task_map {
+            let _ = task.send(UnReturnMessage::build(TaskMessage::FilterNotice(
+                record.clone(),
+            )));
+        }
+        self.filter_log.push_back(record);
+        while self.filter_log.len() > self.config.filter.log_size {
+            self.filter_log.pop_front();
+        }
+    }
+    fn broadcast_template(
+        &self,
+        cmd: &CommandData,
+        template: &str,
+        message: &str,
+        task_map: &DashMap<Uuid, MessageSender<UnReturnMessage<TaskMessage>>>,
+    ) {
+        let sender = sender_of(cmd);
+        log::info!("{}", self.formatter.render_plain(template, sender, "", message));
+        let content = self.formatter.render(template, sender, "", message);
+        for task in task_map {
+            let _ = task.send(UnReturnMessage::build(TaskMessage::SendMessage(SystemChat {
+                content: content.clone(),
+                overlay: false,
+            })));
+        }
+    }
+    /// 发送私聊并记录双方的回复对象,控制台以 nil UUID 记录
+    async fn send_private(
+        &mut self,
+        cmd: &CommandData,
+        target_uuid: Uuid,
+        target_name: &str,
+        message: &str,
+        task_map: &DashMap<Uuid, MessageSender<UnReturnMessage<TaskMessage>>>,
+    ) -> anyhow::Result<()> {
+        if self.reject_muted(cmd).await? {
+            return Ok(());
+        }
+        let Some(message) = self.filter_command_message(cmd, message, task_map).await? else {
+            return Ok(());
+        };
+        let message = message.as_str();
+        let sender = sender_of(cmd);
+        let format = &self.formatter.format;
+        if target_uuid.is_nil() {
+            log::info!(
+                "{}",
+                self.formatter.render_plain(&format.msg_incoming, sender, "控制台", message)
+            );
+        } else {
+            let incoming = self.formatter.render(&format.msg_incoming, sender, target_name, message);
+            if !send_to(task_map, &target_uuid, incoming) {
+                cmd.send_chat_message(&format!("§c玩家 {} 不在线", target_name))
+                    .await?;
+                return Ok(());
+            }
+        }
+        match cmd.player_uuid {
+            Some(uuid) if !cmd.is_cmd => {
+                let outgoing = self.formatter.render(&format.msg_outgoing, sender, target_name, message);
+                send_to(task_map, &uuid, outgoing);
+            }
+            _ => {
+                cmd.send_chat_message(
+                    &self.formatter.render_plain(&format.msg_outgoing, sender, target_name, message),
+                )
+                .await?;
+            }
+        }
+        let sender_key = cmd.player_uuid.unwrap_or(Uuid::nil());
+        let sender_name = sender.name.to_string();
+        self.last_private
+            .insert(target_uuid, (sender_key, sender_name));
+        self.last_private
+            .insert(sender_key, (target_uuid, target_name.to_string()));
+        Ok(())
+    }
+    fn remove_session(
+        &mut self,
+        uuid: &Uuid,
+        task_map: &DashMap<Uuid, MessageSender<UnReturnMessage<TaskMessage>>>,
+    ) {
+        if self.sessions.remove(uuid).is_some() {
+            for task in task_map {
+                let _ = task.send(UnReturnMessage::build(TaskMessage::PlayerInfoRemove(vec![
+                    *uuid,
+                ])));
+            }
         }
     }
 }
@@ -46,6 +240,15 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
         task_map: &DashMap<Uuid, MessageSender<UnReturnMessage<TaskMessage>>>,
         mut data: ReturnMessage<ManagerMessage>,
     ) -> anyhow::Result<bool> {
+        // 失败报告不需要回复
+        if let ManagerMessage::ChildFailed(failure) = &data.data {
+            if failure.directive != Directive::Restart {
+                for uuid in supervisor::prune(task_map) {
+                    self.remove_session(&uuid, task_map);
+                }
+            }
+            return Ok(false);
+        }
         let send = match data.get_return_send().await? {
             Some(send) => send,
             None => return Ok(false),
@@ -63,14 +266,28 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
                     return Ok(false);
                 }
                 let (task, task_sand) =
-                    Task::new(api.clone(), ChatActor::new(uuid, self.config.clone()));
-                task.run().await?;
+                    Task::new(api.clone(), ChatActor::new(
+                        uuid,
+                        self.config.clone(),
+                        self.mojang_keys.clone(),
+                        self.filter.clone(),
+                        self.events.clone(),
+                    ));
+                // 单个玩家的聊天任务出错时原地重启,不影响该玩家本次会话后续的聊天
+                task.supervise(format!("chat-{}", uuid), SupervisorPolicy::default())
+                    .run()
+                    .await?;
                 task_map.insert(uuid, task_sand.clone());
                 *task_api = Some(task_sand);
                 *is_true = true;
                 let _ = send.send(data.data);
                 return Ok(false);
             }
+            ManagerMessage::SendTo(uuid, ref content, ref mut delivered) => {
+                *delivered = send_to(task_map, &uuid, content.clone());
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
             ManagerMessage::BroadCastEvent(_uuid, system_chat) => {
                 for task in task_map {
                     let _ = task.send(UnReturnMessage::build(TaskMessage::SendMessage(
@@ -80,6 +297,92 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
                 let _ = send.send(ManagerMessage::BroadCastEvent(_uuid, system_chat));
                 return Ok(false);
             }
+            ManagerMessage::BroadCastPlayerChat(ref signed_chat) => {
+                for task in task_map {
+                    let _ = task.send(UnReturnMessage::build(TaskMessage::PlayerChat(
+                        signed_chat.clone(),
+                    )));
+                }
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::EnforcesSecureChat(ref mut is_true) => {
+                *is_true = self.mojang_keys.is_some();
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::SessionInitialized(uuid, ref name, ref chat_session) => {
+                let entry = PlayerInfoEntry {
+                    uuid,
+                    name: name.clone(),
+                    chat_session: Some(chat_session.clone()),
+                    listed: true,
+                    ..Default::default()
+                };
+                self.sessions.insert(uuid, entry.clone());
+                let player_info = PlayerInfoUpdate {
+                    actions: ADD_PLAYER | INITIALIZE_CHAT | UPDATE_LISTED,
+                    players: vec![entry],
+                };
+                for task in task_map {
+                    let _ = task.send(UnReturnMessage::build(TaskMessage::PlayerInfo(
+                        player_info.clone(),
+                    )));
+                }
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::GetChatSessions(ref mut entries) => {
+                *entries = self.sessions.values().cloned().collect();
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::CheckMute(uuid, ref mut mute) => {
+                *mute = self.mutes.check(&uuid);
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::Mute(ref entry) => {
+                log::info!("[聊天] {} 因 {} 被临时禁言", entry.name, entry.reason);
+                self.mutes.mute(entry.clone());
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::Filtered(ref record) => {
+                self.record_filtered(record.clone(), task_map);
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::CommandChatLog(ref cmd) => {
+                let sender = sender_of(cmd);
+                if !cmd.is_cmd && !self.formatter.has_permission(sender, "qexed.chat.log") {
+                    cmd.send_chat_message("§c你没有权限使用此命令").await?;
+                    let _ = send.send(data.data);
+                    return Ok(false);
+                }
+                let args = cmd.parse_args();
+                let count = args
+                    .get(1)
+                    .and_then(|a| a.parse::<usize>().ok())
+                    .unwrap_or(10);
+                if self.filter_log.is_empty() {
+                    cmd.send_chat_message("§7暂无过滤记录").await?;
+                } else {
+                    let skip = self.filter_log.len().saturating_sub(count);
+                    cmd.send_chat_message(&format!(
+                        "§6最近 {} 条过滤记录:",
+                        self.filter_log.len() - skip
+                    ))
+                    .await?;
+                    for record in self.filter_log.iter().skip(skip) {
+                        let ago = format_duration((now_secs() - record.time).max(0) as u64);
+                        cmd.send_chat_message(&format!("§7[{}前] §f{}", ago, record.describe()))
+                            .await?;
+                    }
+                }
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
             ManagerMessage::Command(ref cmd) => {
                 let args = cmd.parse_args();
                 let help_args: Vec<String> = args.into_iter().skip(1).collect();
@@ -88,61 +391,47 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
                     let _ = send.send(data.data);
                     return Ok(false);
                 }
-                let player_name = match &cmd.player_name {
-                    Some(name) => name,
-                    None => "系统",
-                };
                 let target_player = &help_args[0];
                 let message = &help_args[1..].join(" ");
-                if let qexed_player_list::Message::GetPlayerIsOnline {
-                    name: _,
-                    is_true,
-                    player_uuid,
-                } = ReturnMessage::build(qexed_player_list::Message::GetPlayerIsOnline {
-                    name: target_player.clone(),
-                    is_true: false,
-                    player_uuid: uuid::Uuid::nil(),
-                })
-                .get(&self.player_list_api)
-                .await?
-                {
-                    if is_true == false {
-                        cmd.send_chat_message(&format!("§c玩家 {} 不在线", target_player))
+                match self.find_online(target_player).await? {
+                    Some(target_uuid) => {
+                        self.send_private(cmd, target_uuid, target_player, message, task_map)
                             .await?;
-                        let _ = send.send(data.data);
-                        return Ok(false);
                     }
-                    if let Some(other_player_api) = task_map.get(&player_uuid) {
-                        // build_chat_packet(message:String)
-                        other_player_api.send(UnReturnMessage::build(
-                            TaskMessage::SendMessage(build_chat_packet(format!(
-                                "§5[私聊] §r[{}§r->{}§r] §r{}",
-                                player_name,
-                                target_player.clone(),
-                                message.clone()
-                            ))),
-                        ))?;
-                        cmd.send_chat_message(&format!(
-                            "§5[私聊] §r[{}§r->{}§r] §r{}",
-                            player_name,
-                            target_player.clone(),
-                            message
-                        ))
-                        .await?;
-                        let _ = send.send(data.data);
-                        return Ok(false);
+                    None => {
+                        cmd.send_chat_message(&format!("§c玩家 {} 不在线", target_player))
+                            .await?;
                     }
-
-                    cmd.send_chat_message(&format!("§c玩家 {} 不在线", target_player))
-                        .await?;
+                }
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::CommandReply(ref cmd) => {
+                let args = cmd.parse_args();
+                let help_args: Vec<String> = args.into_iter().skip(1).collect();
+                if help_args.is_empty() {
+                    cmd.send_chat_message("§c用法: /r <消息>").await?;
                     let _ = send.send(data.data);
                     return Ok(false);
-                } else {
-                    cmd.send_chat_message(&format!("§c玩家 {} 不在线", target_player))
+                }
+                let sender_key = cmd.player_uuid.unwrap_or(Uuid::nil());
+                match self.last_private.get(&sender_key).cloned() {
+                    Some((target_uuid, target_name)) => {
+                        self.send_private(
+                            cmd,
+                            target_uuid,
+                            &target_name,
+                            &help_args.join(" "),
+                            task_map,
+                        )
                         .await?;
-                    let _ = send.send(data.data);
-                    return Ok(false);
+                    }
+                    None => {
+                        cmd.send_chat_message("§c没有可以回复的私聊").await?;
+                    }
                 }
+                let _ = send.send(data.data);
+                return Ok(false);
             }
             ManagerMessage::CommandMe(ref cmd) => {
                 let args = cmd.parse_args();
@@ -152,20 +441,13 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
                     let _ = send.send(data.data);
                     return Ok(false);
                 }
-                let player_name = match &cmd.player_name {
-                    Some(name) => name,
-                    None => "系统",
-                };
-                let message = &help_args.join(" ");
-                let system_chat = build_chat_packet(format!(
-                                "* §r{}§r §r{}",
-                                player_name,
-                                message.clone()
-                            ));
-                for task in task_map {
-                    let _ = task.send(UnReturnMessage::build(TaskMessage::SendMessage(
-                        system_chat.clone(),
-                    )));
+                if !self.reject_muted(cmd).await?
+                    && let Some(message) = self
+                        .filter_command_message(cmd, &help_args.join(" "), task_map)
+                        .await?
+                {
+                    let template = self.formatter.format.me.clone();
+                    self.broadcast_template(cmd, &template, &message, task_map);
                 }
                 let _ = send.send(data.data);
                 return Ok(false);
@@ -178,20 +460,97 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
                     let _ = send.send(data.data);
                     return Ok(false);
                 }
-                let player_name = match &cmd.player_name {
-                    Some(name) => name,
-                    None => "系统",
+                if !self.reject_muted(cmd).await?
+                    && let Some(message) = self
+                        .filter_command_message(cmd, &help_args.join(" "), task_map)
+                        .await?
+                {
+                    let template = self.formatter.format.say.clone();
+                    self.broadcast_template(cmd, &template, &message, task_map);
+                }
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::CommandMute(ref cmd) => {
+                let sender = sender_of(cmd);
+                if !cmd.is_cmd && !self.formatter.has_permission(sender, "qexed.mute") {
+                    cmd.send_chat_message("§c你没有权限使用此命令").await?;
+                    let _ = send.send(data.data);
+                    return Ok(false);
+                }
+                let args = cmd.parse_args();
+                let help_args: Vec<String> = args.into_iter().skip(1).collect();
+                if help_args.is_empty() {
+                    cmd.send_chat_message("§c用法: /mute <玩家> [时长] [原因]").await?;
+                    let _ = send.send(data.data);
+                    return Ok(false);
+                }
+                let target_player = &help_args[0];
+                let Some(target_uuid) = self.find_online(target_player).await? else {
+                    cmd.send_chat_message(&format!("§c玩家 {} 不在线", target_player))
+                        .await?;
+                    let _ = send.send(data.data);
+                    return Ok(false);
                 };
-                let message = &help_args.join(" ");
-                let system_chat = build_chat_packet(format!(
-                                "[§r{}§r] §r{}",
-                                player_name,
-                                message.clone()
-                            ));
-                for task in task_map {
-                    let _ = task.send(UnReturnMessage::build(TaskMessage::SendMessage(
-                        system_chat.clone(),
-                    )));
+                // 第二个参数不是时长时视为原因的一部分,禁言为永久
+                let (duration, reason_start) = match help_args.get(1).and_then(|a| parse_duration(a)) {
+                    Some(duration) => (Some(duration), 2),
+                    None => (None, 1),
+                };
+                let entry = MuteEntry {
+                    uuid: target_uuid,
+                    name: target_player.clone(),
+                    until: duration.map(|d| now_secs() + d.as_secs() as i64),
+                    reason: help_args[reason_start..].join(" "),
+                    by: sender.name.to_string(),
+                };
+                let time = match duration {
+                    Some(d) => format_duration(d.as_secs()),
+                    None => "永久".to_string(),
+                };
+                send_to(task_map, &target_uuid, text_component(format!("§c{}", entry.describe())));
+                cmd.send_chat_message(&format!("§a已禁言 {} ({})", target_player, time))
+                    .await?;
+                log::info!("[聊天] {} 禁言了 {} ({}) {}", entry.by, entry.name, time, entry.reason);
+                self.mutes.mute(entry);
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::CommandUnmute(ref cmd) => {
+                let sender = sender_of(cmd);
+                if !cmd.is_cmd && !self.formatter.has_permission(sender, "qexed.mute") {
+                    cmd.send_chat_message("§c你没有权限使用此命令").await?;
+                    let _ = send.send(data.data);
+                    return Ok(false);
+                }
+                let args = cmd.parse_args();
+                let help_args: Vec<String> = args.into_iter().skip(1).collect();
+                if help_args.is_empty() {
+                    cmd.send_chat_message("§c用法: /unmute <玩家>").await?;
+                    let _ = send.send(data.data);
+                    return Ok(false);
+                }
+                let target_player = &help_args[0];
+                // 离线玩家按禁言记录中的名字查找
+                let target_uuid = match self
+                    .mutes
+                    .mutes
+                    .iter()
+                    .find(|m| m.name.eq_ignore_ascii_case(target_player))
+                {
+                    Some(entry) => Some(entry.uuid),
+                    None => self.find_online(target_player).await?,
+                };
+                if target_uuid.is_some_and(|uuid| self.mutes.unmute(&uuid)) {
+                    if let Some(uuid) = target_uuid {
+                        send_to(task_map, &uuid, text_component("§a你的禁言已被解除".to_string()));
+                    }
+                    cmd.send_chat_message(&format!("§a已解除 {} 的禁言", target_player))
+                        .await?;
+                    log::info!("[聊天] {} 解除了 {} 的禁言", sender.name, target_player);
+                } else {
+                    cmd.send_chat_message(&format!("§c玩家 {} 未被禁言", target_player))
+                        .await?;
                 }
                 let _ = send.send(data.data);
                 return Ok(false);
@@ -199,10 +558,13 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
             
             ManagerMessage::PlayerClose(uuid) => {
                 task_map.remove(&uuid);
+                self.remove_session(&uuid, task_map);
                 let _ = send.send(data.data);
 
                 return Ok(false);
             }
+            // 已在取回复通道前处理
+            ManagerMessage::ChildFailed(_) => Ok(false),
             ManagerMessage::ConnectClose(uuid) => {
                 if let Some(task_api) = task_map.get(&uuid) {
                     UnReturnMessage::build(TaskMessage::Close)
@@ -210,31 +572,45 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
                         .await?;
                 }
                 task_map.remove(&uuid);
+                self.remove_session(&uuid, task_map);
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::Shutdown => {
+                for entry in task_map.iter() {
+                    let _ = UnReturnMessage::build(TaskMessage::Close)
+                        .post(entry.value())
+                        .await;
+                }
+                if let Err(e) = self.mutes.save() {
+                    log::error!("[聊天] 禁言列表保存失败: {}", e);
+                }
                 let _ = send.send(data.data);
                 return Ok(false);
             }
         }
     }
 }
-// async fn config
-fn build_chat_packet(message: String) -> SystemChat {
-    // 1. 创建文本组件的 Compound
-    let mut chat_component = HashMap::new();
-    // Minecraft 文本组件的基础格式：{"text": "实际内容"}
-    chat_component.insert(
-        "text".to_string(),
-        Tag::String(message.into()), // 使用 `into()` 转为 Arc<str>
-    );
-
-    // 2. 可选：添加样式（例如颜色）
-    // chat_component.insert("color".to_string(), Tag::String("red".into()));
-
-    // 3. 将 HashMap 包装为 Tag::Compound
-    let content_nbt = Tag::Compound(Arc::new(chat_component));
-
-    // 4. 构建 SystemChat（overlay = false 表示显示在普通聊天框）
-    SystemChat {
-        content: content_nbt,
-        overlay: false,
+/// 指令发送者,控制台显示为"系统"
+fn sender_of(cmd: &CommandData) -> Sender<'_> {
+    Sender {
+        uuid: cmd.player_uuid,
+        name: cmd.player_name.as_deref().unwrap_or("系统"),
+    }
+}
+/// 向单个玩家发送系统消息,玩家不在线时返回 false
+fn send_to(
+    task_map: &DashMap<Uuid, MessageSender<UnReturnMessage<TaskMessage>>>,
+    uuid: &Uuid,
+    content: Tag,
+) -> bool {
+    match task_map.get(uuid) {
+        Some(task) => task
+            .send(UnReturnMessage::build(TaskMessage::SendMessage(SystemChat {
+                content,
+                overlay: false,
+            })))
+            .is_ok(),
+        None => false,
     }
 }
@@ -1,5 +1,6 @@
 use bytes::Bytes;
 use qexed_command::message::CommandData;
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::unreturn_message::UnReturnMessage;
 use thiserror::Error;
 use tokio::sync::mpsc::UnboundedSender;
@@ -15,19 +16,49 @@ pub enum ManagerMessage {
     Command(CommandData),// 指令事件
     CommandMe(CommandData),// 指令事件
     CommandSay(CommandData),// 指令事件
+    CommandReply(CommandData),// 指令事件
+    CommandMute(CommandData),// 指令事件
+    CommandUnmute(CommandData),// 指令事件
+    CommandChatLog(CommandData),// 指令事件
+    CheckMute(uuid::Uuid,Option<crate::mute::MuteEntry>),// 查询玩家禁言状态
+    Mute(crate::mute::MuteEntry),// 刷屏检测触发的临时禁言
+    Filtered(crate::filter::FilterRecord),// 消息被过滤或拦截
     BroadCastEvent(uuid::Uuid,qexed_protocol::to_client::play::system_chat::SystemChat),// 广播聊天数据包
+    SendTo(uuid::Uuid,qexed_nbt::Tag,bool),// 向指定玩家发送系统消息,返回是否在线
+    BroadCastPlayerChat(crate::secure::SignedChat),// 广播签名聊天消息
+    EnforcesSecureChat(bool),// 查询是否强制安全聊天(用于登录数据包)
+    SessionInitialized(uuid::Uuid,String,qexed_protocol::to_client::play::player_info_update::ChatSessionData),// 玩家聊天会话校验通过
+    GetChatSessions(Vec<qexed_protocol::to_client::play::player_info_update::PlayerInfoEntry>),// 获取已初始化聊天会话的玩家
     PlayerClose(uuid::Uuid),  // 游戏连接关闭
     ConnectClose(uuid::Uuid), // 连接关闭
+    ChildFailed(qexed_task::supervisor::Failure), // 玩家聊天任务失败
+    Shutdown, // 关服:关闭所有聊天任务并保存禁言列表
+}
+impl From<qexed_task::supervisor::Failure> for ManagerMessage {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        ManagerMessage::ChildFailed(failure)
+    }
 }
 #[derive(Debug)]
 pub enum TaskMessage {
     Start(
         String,
-        Option<UnboundedSender<Bytes>>, // 数据包发送器
+        Option<MailboxSender<Bytes>>, // 数据包发送器
     ), // 传递数据包收发器
     ChatEvent(qexed_protocol::to_server::play::chat_message::ChatMessage),// 数据包分割器传递聊天数据包
+    CommandEvent(
+        String,
+        UnboundedSender<UnReturnMessage<qexed_command::message::TaskCommand>>, // 检测通过后转发到指令服务
+    ),// 数据包分割器传递指令,用于指令刷屏检测
+    FilterNotice(crate::filter::FilterRecord),// 过滤提醒,仅发送给有权限的玩家
     SystemEvent(SystemEvent),
     SendMessage(qexed_protocol::to_client::play::system_chat::SystemChat),// 广播事件数据包
+    SessionUpdate(qexed_protocol::to_server::play::chat_session_update::ChatSessionUpdate),// 客户端提交聊天会话
+    ChatAck(i32),// 客户端确认已读消息
+    LastSeenUpdate(i32,[u8; 3],i8),// 签名指令携带的已读更新
+    PlayerChat(crate::secure::SignedChat),// 转发签名聊天消息
+    PlayerInfo(qexed_protocol::to_client::play::player_info_update::PlayerInfoUpdate),// 玩家聊天会话信息
+    PlayerInfoRemove(Vec<uuid::Uuid>),// 移除玩家聊天会话信息
     Close,// 连接关闭
 }
 
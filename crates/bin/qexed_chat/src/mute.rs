@@ -0,0 +1,147 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use qexed_config::tool::AppConfigTrait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 禁言记录
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MuteEntry {
+    pub uuid: Uuid,
+    pub name: String,
+    /// 解除时间(Unix 秒),为空表示永久
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<i64>,
+    pub reason: String,
+    /// 执行者
+    pub by: String,
+}
+
+impl MuteEntry {
+    pub fn is_expired(&self) -> bool {
+        self.until.is_some_and(|until| until <= now_secs())
+    }
+
+    /// 提示被禁言玩家的文本
+    pub fn describe(&self) -> String {
+        let time = match self.until {
+            Some(until) => format!("剩余 {}", format_duration((until - now_secs()).max(0) as u64)),
+            None => "永久".to_string(),
+        };
+        if self.reason.is_empty() {
+            format!("你已被禁言({})", time)
+        } else {
+            format!("你已被禁言({}): {}", time, self.reason)
+        }
+    }
+}
+
+/// 持久化的禁言列表,保存在聊天配置目录下
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MuteList {
+    pub mutes: Vec<MuteEntry>,
+}
+
+impl AppConfigTrait for MuteList {
+    const PATH: &'static str = "./config/qexed_chat/";
+    const NAME: &'static str = "mutes";
+}
+
+impl MuteList {
+    /// 查询禁言状态,顺带清理已过期的记录
+    pub fn check(&mut self, uuid: &Uuid) -> Option<MuteEntry> {
+        let before = self.mutes.len();
+        self.mutes.retain(|m| !m.is_expired());
+        if self.mutes.len() != before {
+            self.persist();
+        }
+        self.mutes.iter().find(|m| &m.uuid == uuid).cloned()
+    }
+
+    pub fn mute(&mut self, entry: MuteEntry) {
+        self.mutes.retain(|m| m.uuid != entry.uuid);
+        self.mutes.push(entry);
+        self.persist();
+    }
+
+    pub fn unmute(&mut self, uuid: &Uuid) -> bool {
+        let before = self.mutes.len();
+        self.mutes.retain(|m| &m.uuid != uuid);
+        let removed = self.mutes.len() != before;
+        if removed {
+            self.persist();
+        }
+        removed
+    }
+
+    fn persist(&self) {
+        if let Err(e) = self.save() {
+            log::error!("[聊天] 禁言列表保存失败: {}", e);
+        }
+    }
+}
+
+/// 解析时长,如 30s、10m、2h、1d、1h30m
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value: u64 = number.parse().ok()?;
+        number.clear();
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        total = total.checked_add(value.checked_mul(unit)?)?;
+    }
+    // 必须以单位结尾
+    if !number.is_empty() || total == 0 {
+        return None;
+    }
+    Some(Duration::from_secs(total))
+}
+
+pub fn format_duration(secs: u64) -> String {
+    let (d, h, m, s) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60, secs % 60);
+    let mut text = String::new();
+    for (value, unit) in [(d, "天"), (h, "小时"), (m, "分"), (s, "秒")] {
+        if value > 0 {
+            text.push_str(&format!("{}{}", value, unit));
+        }
+    }
+    if text.is_empty() {
+        text.push_str("0秒");
+    }
+    text
+}
+
+pub fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2D"), Some(Duration::from_secs(172800)));
+        assert_eq!(parse_duration("10"), None);
+        assert_eq!(parse_duration("刷屏"), None);
+        assert_eq!(format_duration(5400), "1小时30分");
+    }
+}
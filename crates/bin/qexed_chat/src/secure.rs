@@ -0,0 +1,354 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use qexed_protocol::{
+    to_client::play::player_info_update::ChatSessionData,
+    to_server::play::chat_session_update::ChatSessionUpdate, types::MessageSignature,
+};
+use rsa::{
+    RsaPublicKey,
+    pkcs1v15::{Signature, VerifyingKey},
+    pkcs8::DecodePublicKey,
+    sha2::Sha256,
+    signature::Verifier,
+};
+use sha1::Sha1;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// 客户端最多追踪的已读消息数
+pub const LAST_SEEN_COUNT: usize = 20;
+/// 等待客户端确认的消息上限,超过视为客户端异常
+pub const MAX_PENDING_MESSAGES: usize = 4096;
+/// chat_type 注册表中 minecraft:chat 的下标
+pub const CHAT_TYPE_CHAT: i32 = 0;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SecureChatError {
+    #[error("聊天公钥已过期")]
+    ExpiredPublicKey,
+    #[error("无效的聊天公钥")]
+    InvalidPublicKey,
+    #[error("聊天公钥签名无效")]
+    InvalidKeySignature,
+    #[error("未初始化聊天会话,服务器要求安全聊天")]
+    MissingSession,
+    #[error("消息缺少签名")]
+    MissingSignature,
+    #[error("消息签名无效")]
+    InvalidSignature,
+    #[error("消息时间戳早于上一条消息")]
+    OutOfOrder,
+    #[error("已读窗口前移 {0} 条,但最多只能前移 {1} 条")]
+    InvalidOffset(i32, usize),
+    #[error("已读更新包含过多消息")]
+    TooManyAcknowledged,
+    #[error("确认了未知或已忽略的消息(下标 {0})")]
+    UnknownAcknowledged(usize),
+    #[error("忽略了已确认的消息(下标 {0})")]
+    IgnoredAcknowledged(usize),
+    #[error("已读消息校验和不匹配")]
+    ChecksumMismatch,
+    #[error("待确认的消息过多")]
+    TooManyPending,
+}
+
+/// Mojang 用于签发玩家聊天公钥的证书公钥
+#[derive(Debug, Clone, Default)]
+pub struct MojangKeys(Vec<RsaPublicKey>);
+
+impl MojangKeys {
+    pub async fn fetch() -> anyhow::Result<Self> {
+        let keys = qexed_mojang_auth::MojangAuthClient::new(None)
+            .get_public_keys()
+            .await?;
+        let keys = keys
+            .player_certificate_keys
+            .iter()
+            .filter_map(|der| RsaPublicKey::from_public_key_der(der).ok())
+            .collect();
+        Ok(Self(keys))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> bool {
+        let Ok(signature) = Signature::try_from(signature) else {
+            return false;
+        };
+        self.0.iter().any(|key| {
+            VerifyingKey::<Sha1>::new(key.clone())
+                .verify(payload, &signature)
+                .is_ok()
+        })
+    }
+}
+
+/// 经过校验的玩家聊天会话
+#[derive(Debug, Clone)]
+pub struct ChatSession {
+    pub session_id: Uuid,
+    pub expires_at: i64,
+    verifying_key: VerifyingKey<Sha256>,
+    public_key: Vec<u8>,
+    key_signature: Vec<u8>,
+}
+
+impl ChatSession {
+    /// 校验玩家提交的聊天公钥: 未过期且由 Mojang 签发给该玩家
+    pub fn verify(
+        player: Uuid,
+        update: ChatSessionUpdate,
+        keys: &MojangKeys,
+    ) -> Result<Self, SecureChatError> {
+        if update.expires_at <= now_millis() {
+            return Err(SecureChatError::ExpiredPublicKey);
+        }
+        let public_key = RsaPublicKey::from_public_key_der(&update.public_key)
+            .map_err(|_| SecureChatError::InvalidPublicKey)?;
+        let mut payload = Vec::with_capacity(24 + update.public_key.len());
+        payload.extend_from_slice(player.as_bytes());
+        payload.extend_from_slice(&update.expires_at.to_be_bytes());
+        payload.extend_from_slice(&update.public_key);
+        if !keys.verify(&payload, &update.key_signature) {
+            return Err(SecureChatError::InvalidKeySignature);
+        }
+        Ok(Self {
+            session_id: update.session_id,
+            expires_at: update.expires_at,
+            verifying_key: VerifyingKey::new(public_key),
+            public_key: update.public_key,
+            key_signature: update.key_signature,
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= now_millis()
+    }
+
+    /// 下发给其他客户端的会话数据
+    pub fn to_data(&self) -> ChatSessionData {
+        ChatSessionData {
+            session_id: self.session_id,
+            expires_at: self.expires_at,
+            public_key: self.public_key.clone(),
+            key_signature: self.key_signature.clone(),
+        }
+    }
+
+    pub fn verify_message(
+        &self,
+        sender: Uuid,
+        index: i32,
+        body: &SignedBody,
+        signature: &MessageSignature,
+    ) -> bool {
+        let Ok(signature) = Signature::try_from(&signature.0[..]) else {
+            return false;
+        };
+        let payload = body.payload(sender, self.session_id, index);
+        self.verifying_key.verify(&payload, &signature).is_ok()
+    }
+}
+
+/// 参与签名的消息内容
+#[derive(Debug, Clone)]
+pub struct SignedBody<'a> {
+    pub message: &'a str,
+    /// 毫秒时间戳
+    pub timestamp: i64,
+    pub salt: i64,
+    pub last_seen: &'a [MessageSignature],
+}
+
+impl SignedBody<'_> {
+    /// 按客户端的顺序拼接签名数据: 版本、消息链、消息体、已读消息
+    fn payload(&self, sender: Uuid, session_id: Uuid, index: i32) -> Vec<u8> {
+        let message = self.message.as_bytes();
+        let mut payload =
+            Vec::with_capacity(64 + message.len() + self.last_seen.len() * 256);
+        payload.extend_from_slice(&1i32.to_be_bytes());
+        payload.extend_from_slice(sender.as_bytes());
+        payload.extend_from_slice(session_id.as_bytes());
+        payload.extend_from_slice(&index.to_be_bytes());
+        payload.extend_from_slice(&self.salt.to_be_bytes());
+        payload.extend_from_slice(&self.timestamp.div_euclid(1000).to_be_bytes());
+        payload.extend_from_slice(&(message.len() as i32).to_be_bytes());
+        payload.extend_from_slice(message);
+        payload.extend_from_slice(&(self.last_seen.len() as i32).to_be_bytes());
+        for signature in self.last_seen {
+            payload.extend_from_slice(&signature.0);
+        }
+        payload
+    }
+}
+
+/// 校验通过、待广播的签名消息
+#[derive(Debug, Clone)]
+pub struct SignedChat {
+    pub sender: Uuid,
+    pub sender_name: String,
+    pub index: i32,
+    pub signature: MessageSignature,
+    pub message: String,
+    pub timestamp: i64,
+    pub salt: i64,
+    pub last_seen: Vec<MessageSignature>,
+    /// 按聊天格式渲染后的内容,作为未签名内容下发
+    pub decorated: Option<qexed_nbt::Tag>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TrackedEntry {
+    signature: MessageSignature,
+    pending: bool,
+}
+
+/// 跟踪发给玩家的签名消息,校验客户端上报的已读窗口
+#[derive(Debug, Clone)]
+pub struct LastSeenMessagesValidator {
+    tracked: Vec<Option<TrackedEntry>>,
+    last_pending: Option<MessageSignature>,
+}
+
+impl Default for LastSeenMessagesValidator {
+    fn default() -> Self {
+        Self {
+            tracked: vec![None; LAST_SEEN_COUNT],
+            last_pending: None,
+        }
+    }
+}
+
+impl LastSeenMessagesValidator {
+    /// 记录一条已发给客户端的签名消息
+    pub fn add_pending(&mut self, signature: MessageSignature) -> Result<(), SecureChatError> {
+        if self.last_pending != Some(signature) {
+            self.tracked.push(Some(TrackedEntry {
+                signature,
+                pending: true,
+            }));
+            self.last_pending = Some(signature);
+        }
+        if self.tracked.len() > MAX_PENDING_MESSAGES {
+            return Err(SecureChatError::TooManyPending);
+        }
+        Ok(())
+    }
+
+    /// 已读窗口前移(chat_ack)
+    pub fn apply_offset(&mut self, offset: i32) -> Result<(), SecureChatError> {
+        let max = self.tracked.len() - LAST_SEEN_COUNT;
+        if offset < 0 || offset as usize > max {
+            return Err(SecureChatError::InvalidOffset(offset, max));
+        }
+        self.tracked.drain(..offset as usize);
+        Ok(())
+    }
+
+    /// 应用聊天消息携带的已读更新,返回该消息签名时已读的消息
+    pub fn apply_update(
+        &mut self,
+        offset: i32,
+        acknowledged: [u8; 3],
+        checksum: i8,
+    ) -> Result<Vec<MessageSignature>, SecureChatError> {
+        self.apply_offset(offset)?;
+        if acknowledged[2] >> (LAST_SEEN_COUNT - 16) != 0 {
+            return Err(SecureChatError::TooManyAcknowledged);
+        }
+        let mut last_seen = Vec::new();
+        for i in 0..LAST_SEEN_COUNT {
+            let is_acknowledged = acknowledged[i / 8] >> (i % 8) & 1 == 1;
+            let entry = &mut self.tracked[i];
+            if is_acknowledged {
+                let Some(tracked) = entry else {
+                    return Err(SecureChatError::UnknownAcknowledged(i));
+                };
+                tracked.pending = false;
+                last_seen.push(tracked.signature);
+            } else {
+                if matches!(entry, Some(tracked) if !tracked.pending) {
+                    return Err(SecureChatError::IgnoredAcknowledged(i));
+                }
+                *entry = None;
+            }
+        }
+        if checksum != 0 && checksum != last_seen_checksum(&last_seen) {
+            return Err(SecureChatError::ChecksumMismatch);
+        }
+        Ok(last_seen)
+    }
+}
+
+/// 与客户端一致的已读消息校验和(基于 Java 的 Arrays.hashCode)
+pub fn last_seen_checksum(signatures: &[MessageSignature]) -> i8 {
+    let mut hash: i32 = 1;
+    for signature in signatures {
+        let mut signature_hash: i32 = 1;
+        for b in signature.0 {
+            signature_hash = signature_hash.wrapping_mul(31).wrapping_add(b as i8 as i32);
+        }
+        hash = hash.wrapping_mul(31).wrapping_add(signature_hash);
+    }
+    match hash as i8 {
+        0 => 1,
+        b => b,
+    }
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(b: u8) -> MessageSignature {
+        MessageSignature([b; 256])
+    }
+
+    #[test]
+    fn last_seen_window_tracks_acknowledgements() {
+        let mut validator = LastSeenMessagesValidator::default();
+        validator.add_pending(signature(1)).unwrap();
+        validator.add_pending(signature(2)).unwrap();
+        // 客户端收到两条消息,窗口前移 2 并确认最后两位
+        let last_seen = validator
+            .apply_update(2, [0, 0, 0b1100], 0)
+            .unwrap();
+        assert_eq!(last_seen, vec![signature(1), signature(2)]);
+
+        // 再次忽略已确认的消息是非法的
+        let mut copy = validator.clone();
+        assert_eq!(
+            copy.apply_update(0, [0, 0, 0b0100], 0),
+            Err(SecureChatError::IgnoredAcknowledged(19))
+        );
+
+        // 窗口前移不能超过新消息数量
+        assert_eq!(
+            validator.apply_offset(1),
+            Err(SecureChatError::InvalidOffset(1, 0))
+        );
+    }
+
+    #[test]
+    fn checksum_is_verified_when_present() {
+        assert_eq!(last_seen_checksum(&[]), 1);
+        let mut validator = LastSeenMessagesValidator::default();
+        validator.add_pending(signature(7)).unwrap();
+        let expected = last_seen_checksum(&[signature(7)]);
+        let wrong = if expected == 1 { 2 } else { 1 };
+        assert_eq!(
+            validator.clone().apply_update(1, [0, 0, 0b1000], wrong),
+            Err(SecureChatError::ChecksumMismatch)
+        );
+        assert!(validator.apply_update(1, [0, 0, 0b1000], expected).is_ok());
+    }
+}
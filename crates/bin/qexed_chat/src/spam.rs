@@ -0,0 +1,187 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use qexed_config::app::qexed_chat::SpamConfig;
+
+/// 违规类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpamKind {
+    /// 聊天过于频繁
+    Rate,
+    /// 重复发送相同消息
+    Duplicate,
+    /// 指令过于频繁
+    Command,
+}
+
+impl SpamKind {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            SpamKind::Rate => "发言过于频繁",
+            SpamKind::Duplicate => "请勿重复发送相同的消息",
+            SpamKind::Command => "指令执行过于频繁",
+        }
+    }
+}
+
+/// 检测结果,除 Allow 外消息都会被拦截
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    Warn(SpamKind),
+    Mute(SpamKind, Duration),
+    Kick(SpamKind),
+}
+
+/// 单个玩家的刷屏检测状态
+#[derive(Debug)]
+pub struct SpamGuard {
+    config: SpamConfig,
+    messages: VecDeque<Instant>,
+    commands: VecDeque<Instant>,
+    recent: VecDeque<(Instant, String)>,
+    warnings: u32,
+    mutes: u32,
+    last_violation: Option<Instant>,
+}
+
+impl SpamGuard {
+    pub fn new(config: SpamConfig) -> Self {
+        Self {
+            config,
+            messages: VecDeque::new(),
+            commands: VecDeque::new(),
+            recent: VecDeque::new(),
+            warnings: 0,
+            mutes: 0,
+            last_violation: None,
+        }
+    }
+
+    pub fn check_message(&mut self, message: &str, now: Instant) -> Verdict {
+        if !self.config.enabled {
+            return Verdict::Allow;
+        }
+        let window = Duration::from_secs(self.config.window_seconds);
+        if push_limited(&mut self.messages, now, window, self.config.max_messages) {
+            return self.violate(SpamKind::Rate, now);
+        }
+        let duplicate_window = Duration::from_secs(self.config.duplicate_window_seconds);
+        while self
+            .recent
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > duplicate_window)
+        {
+            self.recent.pop_front();
+        }
+        let normalized: String = message
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let duplicates = self.recent.iter().filter(|(_, m)| *m == normalized).count();
+        if duplicates >= self.config.max_duplicates {
+            return self.violate(SpamKind::Duplicate, now);
+        }
+        self.recent.push_back((now, normalized));
+        Verdict::Allow
+    }
+
+    pub fn check_command(&mut self, now: Instant) -> Verdict {
+        if !self.config.enabled {
+            return Verdict::Allow;
+        }
+        let window = Duration::from_secs(self.config.window_seconds);
+        if push_limited(&mut self.commands, now, window, self.config.max_commands) {
+            return self.violate(SpamKind::Command, now);
+        }
+        Verdict::Allow
+    }
+
+    /// 记录一次违规并按 警告 -> 临时禁言 -> 踢出 升级
+    fn violate(&mut self, kind: SpamKind, now: Instant) -> Verdict {
+        let reset = Duration::from_secs(self.config.reset_seconds);
+        if self
+            .last_violation
+            .is_some_and(|last| now.duration_since(last) > reset)
+        {
+            self.warnings = 0;
+            self.mutes = 0;
+        }
+        self.last_violation = Some(now);
+        if self.warnings < self.config.warnings {
+            self.warnings += 1;
+            return Verdict::Warn(kind);
+        }
+        if self.mutes < self.config.mutes_before_kick {
+            self.mutes += 1;
+            // 禁言结束后重新从警告开始
+            self.warnings = 0;
+            return Verdict::Mute(kind, Duration::from_secs(self.config.mute_seconds));
+        }
+        Verdict::Kick(kind)
+    }
+}
+
+/// 清理窗口外的记录,未超出上限时记录本次并返回 false
+fn push_limited(times: &mut VecDeque<Instant>, now: Instant, window: Duration, max: usize) -> bool {
+    while times
+        .front()
+        .is_some_and(|time| now.duration_since(*time) > window)
+    {
+        times.pop_front();
+    }
+    if times.len() >= max {
+        return true;
+    }
+    times.push_back(now);
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escalates_to_kick() {
+        let config = SpamConfig {
+            max_messages: 2,
+            warnings: 1,
+            mutes_before_kick: 1,
+            ..Default::default()
+        };
+        let mut guard = SpamGuard::new(config);
+        let now = Instant::now();
+        assert_eq!(guard.check_message("a", now), Verdict::Allow);
+        assert_eq!(guard.check_message("b", now), Verdict::Allow);
+        assert_eq!(guard.check_message("c", now), Verdict::Warn(SpamKind::Rate));
+        assert!(matches!(
+            guard.check_message("d", now),
+            Verdict::Mute(SpamKind::Rate, _)
+        ));
+        assert_eq!(guard.check_message("e", now), Verdict::Warn(SpamKind::Rate));
+        assert_eq!(guard.check_message("f", now), Verdict::Kick(SpamKind::Rate));
+        // 窗口过后恢复
+        let later = now + Duration::from_secs(6);
+        assert_eq!(guard.check_message("g", later), Verdict::Allow);
+    }
+
+    #[test]
+    fn detects_duplicates() {
+        let mut guard = SpamGuard::new(SpamConfig::default());
+        let now = Instant::now();
+        let step = Duration::from_secs(2);
+        assert_eq!(guard.check_message("hello", now), Verdict::Allow);
+        assert_eq!(guard.check_message("HELLO ", now + step), Verdict::Allow);
+        assert_eq!(
+            guard.check_message("hello", now + step * 2),
+            Verdict::Warn(SpamKind::Duplicate)
+        );
+        assert_eq!(
+            guard.check_message("hello", now + Duration::from_secs(40)),
+            Verdict::Allow
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -6,9 +6,16 @@ use qexed_nbt::Tag;
 use qexed_packet::PacketCodec;
 use qexed_player::Player;
 use qexed_protocol::{
-    to_client::play::system_chat::SystemChat,
-    to_server::play::{keep_alive::KeepAlive, pong::Pong},
+    to_client::play::{
+        disconnect::Disconnect,
+        player_chat::{ChatDecoration, ChatTypeData, ChatTypeHolder, PlayerChat, PreviousMessage},
+        player_info_remove::PlayerInfoRemove,
+        player_info_update::{ADD_PLAYER, INITIALIZE_CHAT, PlayerInfoUpdate, UPDATE_LISTED},
+        system_chat::SystemChat,
+    },
+    to_server::play::chat_message::ChatMessage,
 };
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::{
     event::task::TaskEvent,
     message::{
@@ -17,26 +24,189 @@ use qexed_task::{
     },
 };
 use qexed_tcp_connect::PacketSend;
-use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use uuid::Uuid;
 
-use crate::message::{ManagerMessage, TaskMessage};
+use crate::{
+    filter::{ChatFilter, FilterOutcome, FilterRecord},
+    format::{ChatFormatter, Sender, text_component},
+    message::{ManagerMessage, TaskMessage},
+    mute::{MuteEntry, now_secs},
+    secure::{
+        CHAT_TYPE_CHAT, ChatSession, LastSeenMessagesValidator, MojangKeys, SecureChatError,
+        SignedBody, SignedChat,
+    },
+    spam::{SpamGuard, Verdict},
+};
 
 #[derive(Debug)]
 pub struct ChatActor {
     uuid: Uuid,
     name: String,
-    packet_write: Option<UnboundedSender<Bytes>>,
+    packet_write: Option<MailboxSender<Bytes>>,
     config: qexed_config::app::qexed_chat::ChatConfig,
+    // 为空时以无签名模式运行
+    mojang_keys: Option<Arc<MojangKeys>>,
+    session: Option<ChatSession>,
+    // 发送者消息链
+    next_index: i32,
+    last_timestamp: i64,
+    // 接收者已读窗口
+    last_seen: LastSeenMessagesValidator,
+    next_global_index: i32,
+    formatter: ChatFormatter,
+    filter: Arc<ChatFilter>,
+    spam: SpamGuard,
+    events: qexed_event::EventBus,
 }
 impl ChatActor {
-    pub fn new(uuid: Uuid, config: qexed_config::app::qexed_chat::ChatConfig) -> Self {
+    pub fn new(
+        uuid: Uuid,
+        config: qexed_config::app::qexed_chat::ChatConfig,
+        mojang_keys: Option<Arc<MojangKeys>>,
+        filter: Arc<ChatFilter>,
+        events: qexed_event::EventBus,
+    ) -> Self {
         Self {
             uuid,
             name: "".to_string(),
             packet_write: None,
+            formatter: ChatFormatter::new(&config),
+            filter,
+            spam: SpamGuard::new(config.spam.clone()),
             config: config,
+            mojang_keys,
+            session: None,
+            next_index: 0,
+            last_timestamp: i64::MIN,
+            last_seen: LastSeenMessagesValidator::default(),
+            next_global_index: 0,
+            events,
+        }
+    }
+    /// 校验签名消息并推进消息链
+    fn accept_signed(&mut self, chat_message: ChatMessage) -> Result<SignedChat, SecureChatError> {
+        let last_seen = self.last_seen.apply_update(
+            chat_message.offset.0,
+            chat_message.acknowledged,
+            chat_message.checksum,
+        )?;
+        let session = self.session.as_ref().ok_or(SecureChatError::MissingSession)?;
+        if session.is_expired() {
+            return Err(SecureChatError::ExpiredPublicKey);
+        }
+        let signature = chat_message
+            .signature
+            .ok_or(SecureChatError::MissingSignature)?;
+        if chat_message.timestamp < self.last_timestamp {
+            return Err(SecureChatError::OutOfOrder);
+        }
+        // 客户端发送即推进消息链,校验失败也不能回退
+        let index = self.next_index;
+        self.next_index += 1;
+        self.last_timestamp = chat_message.timestamp;
+        let body = SignedBody {
+            message: &chat_message.message,
+            timestamp: chat_message.timestamp,
+            salt: chat_message.salt,
+            last_seen: &last_seen,
+        };
+        if !session.verify_message(self.uuid, index, &body, &signature) {
+            return Err(SecureChatError::InvalidSignature);
+        }
+        Ok(SignedChat {
+            sender: self.uuid,
+            sender_name: self.name.clone(),
+            index,
+            signature,
+            message: chat_message.message,
+            timestamp: chat_message.timestamp,
+            salt: chat_message.salt,
+            last_seen,
+            decorated: None,
+        })
+    }
+    async fn check_mute(
+        &self,
+        manage_api: &MessageSender<ReturnMessage<ManagerMessage>>,
+    ) -> anyhow::Result<Option<MuteEntry>> {
+        match ReturnMessage::build(ManagerMessage::CheckMute(self.uuid, None))
+            .get(manage_api)
+            .await?
+        {
+            ManagerMessage::CheckMute(_, mute) => Ok(mute),
+            _ => Ok(None),
+        }
+    }
+    /// 按 警告 -> 临时禁言 -> 踢出 处理刷屏违规
+    async fn punish(
+        &mut self,
+        verdict: Verdict,
+        message: &str,
+        manage_api: &MessageSender<ReturnMessage<ManagerMessage>>,
+    ) -> anyhow::Result<()> {
+        let reason = match verdict {
+            Verdict::Allow => return Ok(()),
+            Verdict::Warn(kind) => {
+                self.send_system_message(format!("§c{},继续刷屏将被禁言", kind.describe()))
+                    .await?;
+                kind.describe().to_string()
+            }
+            Verdict::Mute(kind, duration) => {
+                let entry = MuteEntry {
+                    uuid: self.uuid,
+                    name: self.name.clone(),
+                    until: Some(now_secs() + duration.as_secs() as i64),
+                    reason: kind.describe().to_string(),
+                    by: "刷屏检测".to_string(),
+                };
+                self.send_system_message(format!("§c{}", entry.describe())).await?;
+                ReturnMessage::build(ManagerMessage::Mute(entry))
+                    .get(manage_api)
+                    .await?;
+                format!("{},已禁言", kind.describe())
+            }
+            Verdict::Kick(kind) => {
+                self.kick().await?;
+                format!("{},已踢出", kind.describe())
+            }
+        };
+        self.report_filtered(manage_api, reason, message).await
+    }
+    /// 发送断开数据包并关闭写邮箱,连接层发完排队的数据包后关闭连接
+    async fn kick(&self) -> anyhow::Result<()> {
+        let Some(packet_write) = &self.packet_write else {
+            return Ok(());
+        };
+        let packet = PacketSend::build_send_packet(Disconnect {
+            reason: text_component(self.config.spam.kick_message.clone()),
+        })
+        .await?;
+        let _ = packet_write.try_send(packet);
+        packet_write.close();
+        Ok(())
+    }
+    async fn report_filtered(
+        &self,
+        manage_api: &MessageSender<ReturnMessage<ManagerMessage>>,
+        reason: String,
+        message: &str,
+    ) -> anyhow::Result<()> {
+        ReturnMessage::build(ManagerMessage::Filtered(FilterRecord {
+            time: now_secs(),
+            uuid: self.uuid,
+            name: self.name.clone(),
+            reason,
+            message: message.to_string(),
+        }))
+        .get(manage_api)
+        .await?;
+        Ok(())
+    }
+    async fn send_system_message(&self, message: String) -> anyhow::Result<()> {
+        if let Some(packet_write) = &self.packet_write {
+            packet_write.try_send(PacketSend::build_send_packet(build_system_message(message)).await?)?;
         }
+        Ok(())
     }
 }
 #[async_trait]
@@ -52,30 +222,148 @@ impl TaskEvent<UnReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for
                 // 玩家进入了服务器
                 self.name = name;
                 self.packet_write = unbounded_sender.take();
-                let _packet_write = match self.packet_write.clone() {
+                let packet_write = match self.packet_write.clone() {
                     Some(p) => p,
                     None => {
                         return Ok(false);
                     }
                 };
+                if self.mojang_keys.is_some() {
+                    // 下发已有玩家的聊天会话,否则客户端无法校验他们的消息
+                    if let ManagerMessage::GetChatSessions(players) =
+                        ReturnMessage::build(ManagerMessage::GetChatSessions(vec![]))
+                            .get(manage_api)
+                            .await?
+                        && !players.is_empty()
+                    {
+                        packet_write.try_send(
+                            PacketSend::build_send_packet(PlayerInfoUpdate {
+                                actions: ADD_PLAYER | INITIALIZE_CHAT | UPDATE_LISTED,
+                                players,
+                            })
+                            .await?,
+                        )?;
+                    }
+                }
 
                 return Ok(false);
             }
             TaskMessage::ChatEvent(chat_message) => {
-                log::info!("<{}> {}", &self.name, chat_message.message);
-                ReturnMessage::build(ManagerMessage::BroadCastEvent(
-                    self.uuid,
-                    build_chat(&self.name, chat_message.message),
-                ))
-                .get(manage_api)
-                .await?;
+                // 安全模式下先推进消息链,被禁言的消息也要与客户端保持同步
+                let (message, signed_chat) = if self.mojang_keys.is_some() {
+                    match self.accept_signed(chat_message) {
+                        Ok(signed_chat) => (signed_chat.message.clone(), Some(signed_chat)),
+                        Err(e) => {
+                            log::warn!("[聊天] 拒绝 {} 的消息: {}", &self.name, e);
+                            self.send_system_message(format!("§c消息未发送: {}", e)).await?;
+                            return Ok(false);
+                        }
+                    }
+                } else {
+                    (chat_message.message, None)
+                };
+                if let Some(mute) = self.check_mute(manage_api).await? {
+                    self.send_system_message(format!("§c{}", mute.describe())).await?;
+                    return Ok(false);
+                }
+                let verdict = self.spam.check_message(&message, Instant::now());
+                if verdict != Verdict::Allow {
+                    self.punish(verdict, &message, manage_api).await?;
+                    return Ok(false);
+                }
+                let message = match self.filter.apply(&message) {
+                    FilterOutcome::Pass => message,
+                    FilterOutcome::Replaced(text) => {
+                        self.report_filtered(manage_api, "已替换".to_string(), &message)
+                            .await?;
+                        text
+                    }
+                    FilterOutcome::Blocked => {
+                        self.report_filtered(manage_api, "已拦截".to_string(), &message)
+                            .await?;
+                        self.send_system_message("§c消息包含违禁内容,未发送".to_string())
+                            .await?;
+                        return Ok(false);
+                    }
+                };
+                // 监听器可以修改或取消消息,签名消息被修改时只改变显示内容
+                let message = if self.events.has_listeners(qexed_event::EventKind::Chat) {
+                    let dispatch = self
+                        .events
+                        .publish(qexed_event::Event::Chat {
+                            uuid: self.uuid,
+                            name: self.name.clone(),
+                            message,
+                        })
+                        .await;
+                    match dispatch {
+                        qexed_event::Dispatch {
+                            cancelled: false,
+                            event: qexed_event::Event::Chat { message, .. },
+                        } => message,
+                        _ => return Ok(false),
+                    }
+                } else {
+                    message
+                };
+                let sender = Sender {
+                    uuid: Some(self.uuid),
+                    name: &self.name,
+                };
+                let template = &self.formatter.format.chat;
+                log::info!("{}", self.formatter.render_plain(template, sender, "", &message));
+                let content = self.formatter.render(template, sender, "", &message);
+                match signed_chat {
+                    Some(mut signed_chat) => {
+                        signed_chat.decorated = Some(content);
+                        ReturnMessage::build(ManagerMessage::BroadCastPlayerChat(signed_chat))
+                            .get(manage_api)
+                            .await?;
+                    }
+                    None => {
+                        ReturnMessage::build(ManagerMessage::BroadCastEvent(
+                            self.uuid,
+                            SystemChat {
+                                content,
+                                overlay: false,
+                            },
+                        ))
+                        .get(manage_api)
+                        .await?;
+                    }
+                }
+                return Ok(false);
+            }
+            TaskMessage::CommandEvent(command, command_api) => {
+                let verdict = self.spam.check_command(Instant::now());
+                if verdict != Verdict::Allow {
+                    self.punish(verdict, &format!("/{}", command), manage_api)
+                        .await?;
+                    return Ok(false);
+                }
+                let _ = UnReturnMessage::build(qexed_command::message::TaskCommand::Command(command))
+                    .post(&command_api)
+                    .await;
+                return Ok(false);
+            }
+            TaskMessage::FilterNotice(record) => {
+                let sender = Sender {
+                    uuid: Some(self.uuid),
+                    name: &self.name,
+                };
+                if record.uuid != self.uuid
+                    && self.formatter.has_permission(sender, "qexed.chat.notify")
+                {
+                    self.send_system_message(format!("§7[聊天过滤] {}", record.describe()))
+                        .await?;
+                }
                 return Ok(false);
             }
             TaskMessage::SendMessage(system_chat) => {
                 if let Some(packet_write) = &self.packet_write {
-                    packet_write.send(PacketSend::build_send_packet(system_chat).await?)?;
+                    packet_write.try_send(PacketSend::build_send_packet(system_chat).await?)?;
                     // Test 给泥土
-                    packet_write.send(PacketSend::build_send_packet(build_item()?).await?)?;
+                    packet_write.try_send(PacketSend::build_send_packet(build_item()?).await?)?;
                     // 初始化配方
                     // packet_write.send(
                     //     PacketSend::build_send_packet(build_dirt_from_4_stones_recipe())
@@ -88,6 +376,93 @@ impl TaskEvent<UnReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for
                 }
                 return Ok(false);
             }
+            TaskMessage::SessionUpdate(chat_session_update) => {
+                let Some(mojang_keys) = &self.mojang_keys else {
+                    return Ok(false);
+                };
+                match ChatSession::verify(self.uuid, chat_session_update, mojang_keys) {
+                    Ok(session) => {
+                        // 新会话重新开始消息链
+                        self.next_index = 0;
+                        let chat_session = session.to_data();
+                        self.session = Some(session);
+                        ReturnMessage::build(ManagerMessage::SessionInitialized(
+                            self.uuid,
+                            self.name.clone(),
+                            chat_session,
+                        ))
+                        .get(manage_api)
+                        .await?;
+                    }
+                    Err(e) => {
+                        log::warn!("[聊天] {} 的聊天会话校验失败: {}", &self.name, e);
+                        self.send_system_message(format!("§c聊天会话校验失败: {}", e))
+                            .await?;
+                    }
+                }
+                return Ok(false);
+            }
+            TaskMessage::ChatAck(offset) => {
+                if let Err(e) = self.last_seen.apply_offset(offset) {
+                    log::warn!("[聊天] {} 的已读确认无效: {}", &self.name, e);
+                }
+                return Ok(false);
+            }
+            TaskMessage::LastSeenUpdate(offset, acknowledged, checksum) => {
+                if self.mojang_keys.is_some()
+                    && let Err(e) = self.last_seen.apply_update(offset, acknowledged, checksum)
+                {
+                    log::warn!("[聊天] {} 的已读更新无效: {}", &self.name, e);
+                }
+                return Ok(false);
+            }
+            TaskMessage::PlayerChat(signed_chat) => {
+                let Some(packet_write) = &self.packet_write else {
+                    return Ok(false);
+                };
+                if let Err(e) = self.last_seen.add_pending(signed_chat.signature) {
+                    log::warn!("[聊天] {} 的已读窗口异常: {}", &self.name, e);
+                }
+                let player_chat = PlayerChat {
+                    global_index: qexed_packet::net_types::VarInt(self.next_global_index),
+                    sender: signed_chat.sender,
+                    index: qexed_packet::net_types::VarInt(signed_chat.index),
+                    signature: Some(signed_chat.signature),
+                    message: signed_chat.message,
+                    timestamp: signed_chat.timestamp,
+                    salt: signed_chat.salt,
+                    previous_messages: signed_chat
+                        .last_seen
+                        .into_iter()
+                        .map(PreviousMessage::full)
+                        .collect(),
+                    // 有自定义格式时以未签名内容展示,聊天类型只保留内容本身
+                    chat_type: match signed_chat.decorated {
+                        Some(_) => ChatTypeHolder::Inline(content_only_chat_type()),
+                        None => ChatTypeHolder::Registry(CHAT_TYPE_CHAT),
+                    },
+                    unsigned_content: signed_chat.decorated,
+                    filter: Default::default(),
+                    sender_name: text_component(signed_chat.sender_name),
+                    target_name: None,
+                };
+                self.next_global_index += 1;
+                packet_write.try_send(PacketSend::build_send_packet(player_chat).await?)?;
+                return Ok(false);
+            }
+            TaskMessage::PlayerInfo(player_info) => {
+                if let Some(packet_write) = &self.packet_write {
+                    packet_write.try_send(PacketSend::build_send_packet(player_info).await?)?;
+                }
+                return Ok(false);
+            }
+            TaskMessage::PlayerInfoRemove(uuids) => {
+                if let Some(packet_write) = &self.packet_write {
+                    packet_write
+                        .try_send(PacketSend::build_send_packet(PlayerInfoRemove { uuids }).await?)?;
+                }
+                return Ok(false);
+            }
             TaskMessage::SystemEvent(system_event) => match system_event {
                 crate::message::SystemEvent::PlayerJoin => {
                     log::info!("{} 进入了服务器", &self.name);
@@ -121,25 +496,22 @@ impl TaskEvent<UnReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for
     }
 }
 // async fn config
-fn build_chat(player: &str, message: String) -> SystemChat {
-    // 1. 创建文本组件的 Compound
-    let mut chat_component = HashMap::new();
-    // Minecraft 文本组件的基础格式：{"text": "实际内容"}
-    chat_component.insert(
-        "text".to_string(),
-        Tag::String(format!("<{}> {}", player.clone(), message).into()), // 使用 `into()` 转为 Arc<str>
-    );
-
-    // 2. 可选：添加样式（例如颜色）
-    // chat_component.insert("color".to_string(), Tag::String("red".into()));
-
-    // 3. 将 HashMap 包装为 Tag::Compound
-    let content_nbt = Tag::Compound(Arc::new(chat_component));
-
-    // 4. 构建 SystemChat（overlay = false 表示显示在普通聊天框）
-    SystemChat {
-        content: content_nbt,
-        overlay: false,
+/// 只显示消息内容的聊天类型,旁白仍播报发送者
+fn content_only_chat_type() -> ChatTypeData {
+    ChatTypeData {
+        chat: ChatDecoration {
+            translation_key: "%s".to_string(),
+            parameters: vec![qexed_packet::net_types::VarInt(2)],
+            style: Tag::default(),
+        },
+        narration: ChatDecoration {
+            translation_key: "chat.type.text.narrate".to_string(),
+            parameters: vec![
+                qexed_packet::net_types::VarInt(0),
+                qexed_packet::net_types::VarInt(2),
+            ],
+            style: Tag::default(),
+        },
     }
 }
 fn build_system_message(message: String) -> SystemChat {
@@ -282,3 +654,30 @@ fn create_slot(
         components_to_remove: Some(vec![]),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use qexed_task::mailbox::{MailboxConfig, Overflow};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn kick_closes_writer() {
+        let mut actor = ChatActor::new(
+            Uuid::new_v4(),
+            Default::default(),
+            None,
+            Arc::new(ChatFilter::new(&Default::default())),
+            qexed_event::EventBus::new(&Default::default()),
+        );
+        // 邮箱已满时也要关闭写端
+        let (write, mut read) =
+            qexed_task::mailbox::channel::<Bytes>(MailboxConfig::new(1, Overflow::Reject));
+        write.try_send(Bytes::new()).unwrap();
+        actor.packet_write = Some(write.clone());
+        actor.kick().await.unwrap();
+        assert!(write.is_closed());
+        assert!(read.recv().await.is_some());
+        assert!(read.recv().await.is_none());
+    }
+}
@@ -1 +1,2 @@
-pub mod seed;
\ No newline at end of file
+pub mod seed;
+pub mod suggestion;
\ No newline at end of file
@@ -14,7 +14,15 @@ pub async fn register_seed_command(
         "seed",
         "显示世界种子。",
         "qexed.seed",
-        vec![],
+        vec![qexed_command::message::CommandParameter {
+            name: "world".to_string(),
+            description: "世界名,默认为主世界".to_string(),
+            required: false,
+            param_type: qexed_command::message::ParameterType::String {
+                behavior: qexed_command::message::StringBehavior::SingleWord,
+            },
+            suggestions: None,
+        }],
         vec![], // 可以添加多个别名
         command_api,
         move |mut cmd_rx| {
@@ -30,5 +38,7 @@ pub async fn register_seed_command(
             }
         },
     )
-    .await
+    .await?;
+    qexed_command::register::register_suggestion("seed", super::suggestion::world_name_provider(api2), command_api)
+        .await
 }
@@ -0,0 +1,23 @@
+use qexed_command::suggestion::{self, Suggestion, SuggestionProvider};
+use qexed_task::message::{MessageType, unreturn_message::UnReturnMessage};
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
+
+use crate::message::world::WorldCommand;
+
+/// 世界名补全,候选值取自当前已加载的世界
+pub fn world_name_provider(api: UnboundedSender<UnReturnMessage<WorldCommand>>) -> SuggestionProvider {
+    SuggestionProvider::new(move |request| {
+        let api = api.clone();
+        async move {
+            let (result, wait) = oneshot::channel();
+            UnReturnMessage::build(WorldCommand::GetWorldsStatus { result })
+                .post(&api)
+                .await?;
+            let worlds = wait
+                .await?
+                .into_iter()
+                .map(|world| Suggestion::with_tooltip(world.name, world.uuid.to_string()));
+            Ok(suggestion::filter_prefix(worlds, request.current()))
+        }
+    })
+}
@@ -4,7 +4,10 @@ use std::{
 };
 
 use dashmap::DashMap;
-use qexed_task::message::{MessageSender, MessageType, unreturn_message::UnReturnMessage};
+use qexed_task::{
+    message::{MessageSender, MessageType, unreturn_message::UnReturnMessage},
+    supervisor::SupervisorPolicy,
+};
 use uuid::Uuid;
 
 use crate::{
@@ -102,7 +105,10 @@ impl RegionManage {
                 ),
             );
 
-            chunk_task.run().await?;
+            chunk_task
+                .supervise(format!("chunk-{}-{}", i[0], i[1]), SupervisorPolicy::stop())
+                .run()
+                .await?;
             chunk_sender.send(UnReturnMessage::build(ChunkCommand::Init))?;
             task_map.insert(i, chunk_sender);
         }
@@ -128,7 +134,10 @@ impl RegionManage {
                     ),
                 );
 
-                chunk_task.run().await?;
+                chunk_task
+                    .supervise(format!("chunk-{}-{}", i[0], i[1]), SupervisorPolicy::stop())
+                    .run()
+                    .await?;
                 chunk_sender.send(UnReturnMessage::build(ChunkCommand::Init))?;
                 task_map.insert(i, chunk_sender);
             }
@@ -68,7 +68,8 @@ impl WorldManage {
                     api.clone(),
                 ));
 
-            manager_task.run().await?;
+            // 区域失败时报告给世界
+            manager_task.report_to(api.clone()).run().await?;
             manager_sender.send(UnReturnMessage::build(RegionCommand::Init))?;
             task_map.insert(pos, manager_sender);
         }
@@ -98,7 +98,7 @@ impl TaskEvent<UnReturnMessage<ChunkCommand>, UnReturnMessage<RegionCommand>> fo
                         light: create_light_data_for_all_sections(),
                     }
                 };
-                packet_send.send(PacketSend::build_send_packet(p_q).await?)?;
+                packet_send.try_send(PacketSend::build_send_packet(p_q).await?)?;
             }
             ChunkCommand::CloseCommand { result } => {
                 // 暂时没写数据读写
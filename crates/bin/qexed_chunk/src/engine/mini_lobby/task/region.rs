@@ -4,6 +4,7 @@ use futures::future::try_join_all;
 use qexed_task::{
     event::task_manage::TaskManageEvent,
     message::{MessageSender, MessageType, unreturn_message::UnReturnMessage},
+    supervisor::{self, Directive},
 };
 use tokio::sync::oneshot;
 
@@ -28,6 +29,13 @@ impl TaskManageEvent<[i64; 2], UnReturnMessage<RegionCommand>, UnReturnMessage<C
         data: UnReturnMessage<RegionCommand>,
     ) -> anyhow::Result<bool> {
         match data.data {
+            RegionCommand::ChildFailed(failure) => {
+                // 停止的任务从任务表中移除
+                if failure.directive != Directive::Restart {
+                    supervisor::prune(task_map);
+                }
+                return Ok(false);
+            }
             RegionCommand::Init => {
                 self.init(api, task_map).await?;
                 Ok(false)
@@ -422,6 +430,10 @@ impl TaskManageEvent<[i64; 2], UnReturnMessage<RegionCommand>, UnReturnMessage<C
                 result.send(());
                 return Ok(true);
             }
+            RegionCommand::GetChunkCount { result } => {
+                let _ = result.send(task_map.len());
+                Ok(false)
+            }
         }
     }
     // 计算区块是否是相邻区块，如果是则判定是否有自己的相邻区块
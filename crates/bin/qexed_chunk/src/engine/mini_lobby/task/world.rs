@@ -3,6 +3,7 @@ use dashmap::DashMap;
 use qexed_task::{
     event::task_manage::TaskManageEvent,
     message::{MessageSender, MessageType, unreturn_message::UnReturnMessage},
+    supervisor::{self, Directive},
 };
 use qexed_tcp_connect::PacketSend;
 use tokio::sync::oneshot;
@@ -11,7 +12,7 @@ use crate::{
     engine::mini_lobby::event::world::WorldManage,
     message::{
         region::{RegionCommand, RegionCommandResult},
-        world::WorldCommand,
+        world::{WorldCommand, WorldStatus},
     },
 };
 
@@ -26,6 +27,13 @@ impl TaskManageEvent<[i64; 2], UnReturnMessage<WorldCommand>, UnReturnMessage<Re
         data: UnReturnMessage<WorldCommand>,
     ) -> anyhow::Result<bool> {
         match data.data {
+            WorldCommand::ChildFailed(failure) => {
+                // 停止的任务从任务表中移除
+                if failure.directive != Directive::Restart {
+                    supervisor::prune(task_map);
+                }
+                return Ok(false);
+            }
             WorldCommand::Init=>{
                 self.init(api,task_map).await?;
             }
@@ -34,7 +42,7 @@ impl TaskManageEvent<[i64; 2], UnReturnMessage<WorldCommand>, UnReturnMessage<Re
                 // 所有区块均已加载，直接发送即可、
                 let join_pos_chunk = self.player_pos_to_chunk_pos([pos[0] as i32,pos[1] as i32,pos[2] as i32]);
                 // 构建 SetChunkCacheCenter 数据包
-                packet_send.send(PacketSend::build_send_packet(qexed_protocol::to_client::play::update_view_position::UpdateViewPosition{
+                packet_send.try_send(PacketSend::build_send_packet(qexed_protocol::to_client::play::update_view_position::UpdateViewPosition{
                     chunk_x:qexed_packet::net_types::VarInt(join_pos_chunk[0]),
                     chunk_z:qexed_packet::net_types::VarInt(join_pos_chunk[1]),
                 }).await?)?;
@@ -179,7 +187,44 @@ impl TaskManageEvent<[i64; 2], UnReturnMessage<WorldCommand>, UnReturnMessage<Re
                 result.send(());
                 return Ok(true); // 世界关闭完成
             }
-            WorldCommand::CommandSeed(command_data) => {}
+            WorldCommand::GetWorldsStatus { result } => {
+                // 小游戏大厅只有一个世界
+                let mut counts = Vec::with_capacity(task_map.len());
+                for entry in task_map.iter() {
+                    let (tx, rx) = oneshot::channel();
+                    if entry
+                        .value()
+                        .send(UnReturnMessage::build(RegionCommand::GetChunkCount { result: tx }))
+                        .is_ok()
+                    {
+                        counts.push(rx);
+                    }
+                }
+                let mut status = WorldStatus {
+                    uuid: self.world_uuid,
+                    name: self.config.name.clone(),
+                    regions: task_map.len(),
+                    chunks: 0,
+                };
+                // 在独立任务中等待区域回复,不阻塞世界的消息处理
+                tokio::spawn(async move {
+                    for rx in counts {
+                        status.chunks += rx.await.unwrap_or_default();
+                    }
+                    let _ = result.send(vec![status]);
+                });
+            }
+            WorldCommand::CommandSeed(command_data) => {
+                // 小游戏大厅从地图文件加载,不由种子生成
+                let message = match command_data.parse_args().get(1) {
+                    Some(world) if world != &self.config.name => format!("§c未找到世界 {}", world),
+                    _ => format!("世界 {} 由地图文件加载,没有种子", self.config.name),
+                };
+                // 回复失败不能影响世界的运行
+                if let Err(e) = command_data.send_chat_message(&message).await {
+                    log::debug!("[区块] seed 指令回复失败: {}", e);
+                }
+            }
         }
 
         Ok(false)
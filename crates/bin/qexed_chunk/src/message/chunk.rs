@@ -1,4 +1,4 @@
-use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use tokio::sync::oneshot;
 use uuid::Uuid;
 
 #[derive(Debug)]
@@ -7,7 +7,7 @@ pub enum ChunkCommand{
     PlayerJoin{
         uuid:Uuid,
         pos:[i64;3],
-        packet_send:UnboundedSender<bytes::Bytes>
+        packet_send:qexed_task::mailbox::MailboxSender<bytes::Bytes>
     },
     // 区块强制关闭命令(要求同步区块数据)
     CloseCommand{
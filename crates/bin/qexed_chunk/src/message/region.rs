@@ -1,5 +1,5 @@
 use qexed_task::message::{MessageSender, unreturn_message::UnReturnMessage};
-use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use tokio::sync::oneshot;
 use uuid::Uuid;
 
 use crate::message::chunk::ChunkCommand;
@@ -10,7 +10,7 @@ pub enum RegionCommand {
     PlayerJoin{
         uuid:Uuid,
         pos:[i64;3],
-        packet_send:UnboundedSender<bytes::Bytes>
+        packet_send:qexed_task::mailbox::MailboxSender<bytes::Bytes>
     },
     // 获取ChunkApi(非创建)
     GetChunkApi {
@@ -83,9 +83,18 @@ pub enum RegionCommand {
     // 这不是请求，而是命令
     RegionCloseCommand{
         result:oneshot::Sender<()>,
+    },
+    // 查询已加载的区块数量
+    GetChunkCount{
+        result:oneshot::Sender<usize>,
+    },
+    // 区块任务失败
+    ChildFailed(qexed_task::supervisor::Failure),
+}
+impl From<qexed_task::supervisor::Failure> for RegionCommand {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        RegionCommand::ChildFailed(failure)
     }
-    
-
 }
 #[derive(Debug)]
 pub enum RegionCommandResult {
@@ -1,6 +1,6 @@
 use qexed_command::message::CommandData;
 use qexed_task::message::{MessageSender, unreturn_message::UnReturnMessage};
-use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use tokio::sync::oneshot;
 use uuid::Uuid;
 
 use crate::message::{chunk::ChunkCommand, region::{RegionCommand, RegionCommandResult}};
@@ -13,7 +13,7 @@ pub enum WorldCommand {
     PlayerJoin{
         uuid:Uuid,
         pos:[i64;3],
-        packet_send:UnboundedSender<bytes::Bytes>
+        packet_send:qexed_task::mailbox::MailboxSender<bytes::Bytes>
     },
     // 区域管理
     GetRegionApi {
@@ -86,7 +86,29 @@ pub enum WorldCommand {
     WorldCloseCommand{
         result:oneshot::Sender<()>,
     },
+    // 系统状态
+    GetWorldsStatus{
+        result:oneshot::Sender<Vec<WorldStatus>>,
+    },
 
     // 指令:seed
     CommandSeed(CommandData),// 指令事件
-}
\ No newline at end of file
+    // 区域任务失败
+    ChildFailed(qexed_task::supervisor::Failure),
+}
+impl From<qexed_task::supervisor::Failure> for WorldCommand {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        WorldCommand::ChildFailed(failure)
+    }
+}
+
+/// 已加载世界的状态
+#[derive(Debug, Clone)]
+pub struct WorldStatus {
+    pub uuid: Uuid,
+    pub name: String,
+    /// 已加载的区域数
+    pub regions: usize,
+    /// 已加载的区块数
+    pub chunks: usize,
+}
@@ -6,11 +6,14 @@ pub mod message;
 pub mod task;
 pub mod tool;
 pub mod register;
+pub mod suggestion;
+pub mod tree;
 
 pub async fn run(
     config: qexed_config::app::qexed_command::CommandConfig,
+    events: qexed_event::EventBus,
 ) -> anyhow::Result<UnboundedSender<ReturnMessage<message::ManagerCommand>>> {
-    let manager_actor = manager::CommandManagerActor::new(config);
+    let manager_actor = manager::CommandManagerActor::new(config, events);
     let (manager_task, manager_sender) =
         qexed_task::task::task_manage::TaskManage::new(manager_actor);
     
@@ -3,8 +3,9 @@ use std::{collections::HashMap, fs::Permissions};
 use async_trait::async_trait;
 use dashmap::DashMap;
 use qexed_packet::net_types::VarInt;
-use qexed_protocol::to_client::play::commands::{Commands, Node, Varies};
+use qexed_protocol::to_client::play::commands::Commands;
 use qexed_task::{
+    supervisor::{self, Directive, SupervisorPolicy},
     event::task_manage::TaskManageEvent,
     message::{
         MessageSender, MessageType, return_message::ReturnMessage,
@@ -21,209 +22,117 @@ use crate::{
         StringBehavior, TaskCommand,
     },
     task::CommandTask,
+    suggestion::{self, Suggestion, SuggestionProvider, SuggestionRequest},
+    tree::{CommandGraph, CommandNode, NodeKind},
 };
 
 #[derive(Debug)]
 pub struct CommandManagerActor {
     config: qexed_config::app::qexed_command::CommandConfig,
     cmd: HashMap<String, crate::message::RegisterCommand>,
-    tab_completions: HashMap<String, Vec<String>>,
+    // 命令名 -> 补全提供者
+    suggestion_providers: HashMap<String, SuggestionProvider>,
+    // 在线玩家,用于玩家参数的补全
+    players: HashMap<Uuid, String>,
+    events: qexed_event::EventBus,
 }
 
 impl CommandManagerActor {
-    pub fn new(config: qexed_config::app::qexed_command::CommandConfig) -> Self {
+    pub fn new(
+        config: qexed_config::app::qexed_command::CommandConfig,
+        events: qexed_event::EventBus,
+    ) -> Self {
         Self {
             config,
             cmd: Default::default(),
-            tab_completions: Default::default(),
+            suggestion_providers: Default::default(),
+            players: Default::default(),
+            events,
         }
     }
     pub fn build_commands_from_list(&self, player_uuid: Uuid) -> anyhow::Result<Commands> {
-        let mut nodes = Vec::new();
-        let mut root_children = Vec::new();
-
-        // 为每个命令创建节点
-        for (i, (cmd_name, cmd_info)) in self.cmd.iter().enumerate() {
-            // 命令节点索引
-            let cmd_node_index = 1 + nodes.len() as i32;
-
-            // 1. 创建命令字面量节点
-            let mut cmd_flags = 0x01; // 字面量
-
-            // 如果没有参数，命令本身可执行
-            if cmd_info.parameters.is_empty() {
-                cmd_flags |= 0x04; // 可执行
-            }
-
-            let cmd_node = Node {
-                flags: cmd_flags,
-                children: if cmd_info.parameters.is_empty() {
-                    vec![]
-                } else {
-                    vec![VarInt(cmd_node_index + 1)]
-                },
-                redirect_node: None,
-                name: Some(cmd_name.clone()),
-                parser_id: None,
-                properties: None,
-                suggestions_type: None,
-            };
-
-            nodes.push(cmd_node);
-            root_children.push(VarInt(cmd_node_index));
-
-            // 2. 为每个参数创建节点
-            let mut current_parent = cmd_node_index;
-            for (param_idx, param) in cmd_info.parameters.iter().enumerate() {
-                let is_last = param_idx == cmd_info.parameters.len() - 1;
-                let param_node_index = cmd_node_index + 1 + param_idx as i32;
-
-                // 创建参数节点
-                let mut param_flags = 0x02; // 参数节点
-
-                if !param.required {
-                    param_flags |= 0x10; // 可选
-                }
-
-                if is_last {
-                    param_flags |= 0x04; // 可执行
-                }
-
-                // 将参数类型转换为网络格式
-                let (parser_id, properties) = self.parameter_to_network(&param.param_type);
-
-                let param_node = Node {
-                    flags: param_flags,
-                    children: if is_last {
-                        vec![]
-                    } else {
-                        vec![VarInt(param_node_index + 1)]
-                    },
-                    redirect_node: None,
-                    name: Some(param.name.clone()),
-                    parser_id: Some(VarInt(parser_id)),
-                    properties,
-                    suggestions_type: if param.suggestions.is_some() {
-                        Some("minecraft:ask_server".to_string())
-                    } else {
-                        None
-                    },
-                };
-
-                nodes.push(param_node);
-                current_parent = param_node_index;
-            }
-
-            // 3. 为命令别名创建节点
+        let mut graph = CommandGraph::new();
+
+        // 别名在 cmd 中同样占有条目，这里只取主命令，避免重复节点
+        let mut commands: Vec<&RegisterCommand> = self
+            .cmd
+            .iter()
+            .filter(|(key, cmd_info)| **key == cmd_info.name)
+            .map(|(_, cmd_info)| cmd_info)
+            .collect();
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for cmd_info in &commands {
+            let ask_server = self.suggestion_providers.contains_key(&cmd_info.name);
+            graph.add_command(&cmd_info.tree, ask_server);
+        }
+        // 别名统一作为重定向节点，放在所有命令之后以保证目标已存在
+        for cmd_info in &commands {
             for alias in &cmd_info.aliases {
-                let alias_node_index = 1 + nodes.len() as i32;
-
-                let alias_node = Node {
-                    flags: 0x09, // 字面量(0x01) + 可执行(0x04) + 重定向(0x08)
-                    children: vec![],
-                    redirect_node: Some(VarInt(cmd_node_index)),
-                    name: Some(alias.clone()),
-                    parser_id: None,
-                    properties: None,
-                    suggestions_type: None,
-                };
-
-                nodes.push(alias_node);
-                root_children.push(VarInt(alias_node_index));
+                if *alias != cmd_info.name {
+                    graph.add_alias(alias, &cmd_info.name);
+                }
             }
         }
 
-        // 创建根节点
-        let root_node = Node {
-            flags: 0x00,
-            children: root_children,
-            redirect_node: None,
-            name: None,
-            parser_id: None,
-            properties: None,
-            suggestions_type: None,
-        };
-
-        // 将根节点插入到开头
-        nodes.insert(0, root_node);
-
         Ok(Commands {
-            nodes,
+            nodes: graph.build(),
             root_index: VarInt(0),
         })
     }
 
-    /// 将参数类型转换为网络格式
-    fn parameter_to_network(&self, param_type: &ParameterType) -> (i32, Option<Varies>) {
-        use qexed_protocol::to_client::play::commands::{Brigadier, BrigadierString};
-
-        match param_type {
-            ParameterType::Literal(literal) => {
-                // 字面量参数实际上是字符串的一种
-                (
-                    5,
-                    Some(Varies::BrigadierString(BrigadierString {
-                        behavior: VarInt(0), // 单个单词
-                    })),
-                )
-            }
-
-            ParameterType::String { behavior } => {
-                let behavior_id = match behavior {
-                    StringBehavior::SingleWord => 0,
-                    StringBehavior::Quotable => 1,
-                    StringBehavior::Greedy => 2,
-                };
-
-                (
-                    5,
-                    Some(Varies::BrigadierString(BrigadierString {
-                        behavior: VarInt(behavior_id),
-                    })),
-                )
+    /// 让在线玩家重新获取命令树
+    async fn refresh_command_trees(
+        &self,
+        task_map: &DashMap<uuid::Uuid, MessageSender<UnReturnMessage<TaskCommand>>>,
+    ) {
+        let tasks: Vec<_> = task_map
+            .iter()
+            .map(|task| (*task.key(), task.value().clone()))
+            .collect();
+        for (uuid, task) in tasks {
+            if let Err(e) = UnReturnMessage::build(TaskCommand::InitCommandPacket)
+                .post(&task)
+                .await
+            {
+                log::warn!("刷新玩家 {} 的命令树失败: {}", uuid, e);
             }
+        }
+    }
 
-            ParameterType::Integer { min, max } => {
-                let mut flags = 0;
-                let mut brigadier = Brigadier {
-                    flags: 0,
-                    min: None,
-                    max: None,
-                };
-
-                if min.is_some() {
-                    flags |= 0x01;
-                    brigadier.min = *min;
-                }
+    /// 根据命令树与静态候选值生成补全,返回该命令注册的补全提供者
+    fn complete(
+        &self,
+        request: &mut SuggestionRequest,
+        suggestions: &mut Vec<Suggestion>,
+    ) -> Option<SuggestionProvider> {
+        let current = request.current().to_string();
+        if request.index() == 0 {
+            // 补全命令名(含别名)
+            let mut names: Vec<Suggestion> = self
+                .cmd
+                .iter()
+                .map(|(key, cmd_info)| Suggestion::with_tooltip(key.clone(), cmd_info.doc.clone()))
+                .collect();
+            names.sort_by(|a, b| a.text.cmp(&b.text));
+            suggestions.extend(suggestion::filter_prefix(names, &current));
+            return None;
+        }
 
-                if max.is_some() {
-                    flags |= 0x02;
-                    brigadier.max = *max;
+        let cmd_info = self.cmd.get(&request.args[0])?;
+        let online_players: Vec<String> = self.players.values().cloned().collect();
+        let mut candidates = Vec::new();
+        for node in suggestion::candidate_nodes(&cmd_info.tree, &request.args[1..]) {
+            match &node.kind {
+                NodeKind::Literal(name) => candidates.push(Suggestion::new(name.clone())),
+                NodeKind::Argument(param) => {
+                    request.node.get_or_insert_with(|| param.name.clone());
+                    candidates.extend(suggestion::static_suggestions(param, &online_players));
                 }
-
-                brigadier.flags = flags;
-
-                (3, Some(Varies::BrigadierInteger(brigadier)))
-            }
-
-            ParameterType::Boolean => {
-                (0, None) // brigadier:bool
-            }
-
-            ParameterType::Player => {
-                (6, None) // minecraft:entity
-            }
-
-            _ => {
-                // 默认使用字符串类型
-                (
-                    5,
-                    Some(Varies::BrigadierString(BrigadierString {
-                        behavior: VarInt(0),
-                    })),
-                )
             }
         }
+        suggestions.extend(suggestion::filter_prefix(candidates, &current));
+        self.suggestion_providers.get(&cmd_info.name).cloned()
     }
 }
 #[async_trait]
@@ -236,6 +145,15 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
         task_map: &DashMap<uuid::Uuid, MessageSender<UnReturnMessage<TaskCommand>>>,
         mut data: ReturnMessage<ManagerCommand>,
     ) -> anyhow::Result<bool> {
+        // 失败报告不需要回复,停止的任务从任务表中移除
+        if let ManagerCommand::ChildFailed(failure) = &data.data {
+            if failure.directive != Directive::Restart {
+                for uuid in supervisor::prune(task_map) {
+                    self.players.remove(&uuid);
+                }
+            }
+            return Ok(false);
+        }
         let send = match data.get_return_send().await? {
             Some(send) => send,
             None => return Ok(false),
@@ -277,6 +195,7 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                 permission,
                 parameters,
                 aliases,
+                tree,
                 api,
                 success,
             } => {
@@ -291,6 +210,7 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                         permission: "".to_string(),
                         parameters: vec![],
                         aliases: vec![],
+                        tree: None,
                         api: None,
                         success: false,
                     });
@@ -298,6 +218,7 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                 }
 
                 if let Some(api) = api {
+                    let tree = tree.unwrap_or_else(|| CommandNode::from_parameters(&name, &parameters));
                     self.cmd.insert(
                         name.clone(),
                         RegisterCommand {
@@ -307,6 +228,7 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                             permission: permission.clone(),
                             parameters: parameters.clone(),
                             aliases: aliases.clone(),
+                            tree: tree.clone(),
                         },
                     );
                     for i in aliases.clone(){
@@ -319,6 +241,7 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                                 permission: permission.clone(),
                                 parameters: parameters.clone(),
                                 aliases: aliases.clone(),
+                                tree: tree.clone(),
                             },
                         ); 
                     }
@@ -329,11 +252,14 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                         permission: "".to_string(),
                         parameters: vec![],
                         aliases: vec![],
+                        tree: None,
                         api: None,
                         success: true,
                     });
 
                     log::info!("注册命令: {}", name);
+                    // 服务运行中注册的命令(如插件命令)需要同步到在线玩家
+                    self.refresh_command_trees(task_map).await;
                 } else {
                     let _ = send.send(ManagerCommand::RegisterCommand {
                         name:name.clone(),
@@ -341,6 +267,7 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                         permission: "".to_string(),
                         parameters: vec![],
                         aliases: vec![],
+                        tree: None,
                         api: None,
                         success: false,
                     });
@@ -388,7 +315,7 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                 }
 
                 // 获取数据包发送通道
-                let packet_send: tokio::sync::mpsc::UnboundedSender<bytes::Bytes> =
+                let packet_send: qexed_task::mailbox::MailboxSender<bytes::Bytes> =
                     match packet_send.take() {
                         Some(pk) => pk,
                         None => {
@@ -410,12 +337,17 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                     uuid.clone(),
                     username.clone(),
                     packet_send,
+                    self.events.clone(),
                 );
                 let (task, task_sand) = Task::new(api.clone(), t);
-                task.run().await?;
+                // 玩家任务失败即停止并报告给管理器
+                task.supervise(format!("command-{}", uuid), SupervisorPolicy::stop())
+                    .run()
+                    .await?;
 
                 // 保存任务通道
                 task_map.insert(*uuid, task_sand.clone());
+                self.players.insert(*uuid, username.clone());
 
                 // 返回成功
                 let _ = send.send(ManagerCommand::NewPlayerConnect(
@@ -498,18 +430,54 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
             ManagerCommand::PlayerClose(uuid) => {
                 // 移除心跳任务
                 task_map.remove(&uuid.clone());
+                self.players.remove(&uuid);
                 let _ = send.send(ManagerCommand::PlayerClose(uuid));
                 Ok(false)
             }
             ManagerCommand::TabComplete {
-                ref player_uuid,
-                ref command_line,
-                ref cursor,
-                ref suggestions,
+                mut request,
+                mut suggestions,
+                ..
             } => {
-                let _ = send.send(data.data);
+                let provider = self.complete(&mut request, &mut suggestions);
+                let _ = send.send(ManagerCommand::TabComplete {
+                    request,
+                    suggestions,
+                    provider,
+                });
                 Ok(false)
             }
+            ManagerCommand::UnregisterCommand { name, .. } => {
+                // 只能按主命令名注销,别名随主命令一起移除
+                let success = self.cmd.get(&name).is_some_and(|c| c.name == name);
+                if success {
+                    self.cmd.retain(|_, cmd_info| cmd_info.name != name);
+                    self.suggestion_providers.remove(&name);
+                    log::info!("注销命令: {}", name);
+                    self.refresh_command_trees(task_map).await;
+                }
+                let _ = send.send(ManagerCommand::UnregisterCommand { name, success });
+                Ok(false)
+            }
+            ManagerCommand::RegisterSuggestion { name, provider, .. } => {
+                // 别名统一归到主命令名下
+                let main_name = self.cmd.get(&name).map(|c| c.name.clone());
+                let success = match (main_name, provider) {
+                    (Some(main_name), Some(provider)) => {
+                        log::debug!("注册命令补全: {}", main_name);
+                        self.suggestion_providers.insert(main_name, provider);
+                        true
+                    }
+                    _ => false,
+                };
+                let _ = send.send(ManagerCommand::RegisterSuggestion {
+                    name,
+                    provider: None,
+                    success,
+                });
+                Ok(false)
+            }
+            ManagerCommand::ChildFailed(_) => Ok(false),
         }
     }
 }
@@ -696,8 +664,15 @@ fn generate_command_help(cmd_name: &str,
             
             // 用法示例
             help_lines.push("§6用法示例:".to_string());
-            let example = generate_command_example(&meta);
-            help_lines.push(format!("  §e{}", example));
+            if meta.parameters.is_empty() && !meta.tree.children.is_empty() {
+                // 以命令树注册的命令：逐条列出子命令用法
+                for usage in meta.tree.usages() {
+                    help_lines.push(format!("  §e{}", usage));
+                }
+            } else {
+                let example = generate_command_example(meta);
+                help_lines.push(format!("  §e{}", example));
+            }
             
             // 如果是别名，显示原始命令
             if meta.name != cmd_name {
@@ -732,6 +707,15 @@ fn format_parameter_type(param_type: &ParameterType) -> String {
             };
             format!("整数({})", range_desc)
         }
+        ParameterType::Float { min, max } => {
+            let range_desc = match (min, max) {
+                (Some(min_val), Some(max_val)) => format!("{}到{}", min_val, max_val),
+                (Some(min_val), None) => format!("≥{}", min_val),
+                (None, Some(max_val)) => format!("≤{}", max_val),
+                (None, None) => "任意小数".to_string(),
+            };
+            format!("小数({})", range_desc)
+        }
         ParameterType::Boolean => "布尔值".to_string(),
         ParameterType::Player => "玩家名".to_string(),
     }
@@ -759,6 +743,7 @@ fn generate_command_example(cmd: &RegisterCommand) -> String {
                     "123"
                 }.to_string()
             }
+            ParameterType::Float { .. } => "1.5".to_string(),
             ParameterType::Boolean => "true".to_string(),
             ParameterType::Player => "<玩家名>".to_string(),
         };
@@ -1,10 +1,13 @@
 use async_trait::async_trait;
 use bytes::Bytes;
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::unreturn_message::UnReturnMessage;
 use qexed_tcp_connect::PacketSend;
 use thiserror::Error;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
+use crate::suggestion::{Suggestion, SuggestionProvider, SuggestionRequest};
+
 #[derive(Debug)]
 pub enum ManagerCommand {
     NewPlayerConnect(
@@ -13,7 +16,7 @@ pub enum ManagerCommand {
         bool,
         Option<NewPlayerConnectError>,
         Option<UnboundedSender<UnReturnMessage<TaskCommand>>>,
-        Option<UnboundedSender<Bytes>>,
+        Option<MailboxSender<Bytes>>,
     ),
     Command(String),
     RegisterCommand {
@@ -22,6 +25,7 @@ pub enum ManagerCommand {
         permission: String,
         parameters: Vec<CommandParameter>, // 新增参数定义
         aliases: Vec<String>,              // 新增别名
+        tree: Option<crate::tree::CommandNode>, // 子命令树，为空时由 parameters 生成
         api: Option<UnboundedSender<CommandData>>,
         success: bool,
     },
@@ -29,17 +33,35 @@ pub enum ManagerCommand {
     GetCommand(String, Option<UnboundedSender<CommandData>>),
     PlayerClose(uuid::Uuid),
     // 新增：Tab补全请求
+    // 管理器填入命令树与静态候选值,并返回该命令的补全提供者(如果有)
     TabComplete {
-        player_uuid: uuid::Uuid,
-        command_line: String,
-        cursor: usize,
-        suggestions: Vec<String>,
+        request: SuggestionRequest,
+        suggestions: Vec<Suggestion>,
+        provider: Option<SuggestionProvider>,
+    },
+    // 为已注册的命令(或其别名)设置补全提供者
+    RegisterSuggestion {
+        name: String,
+        provider: Option<SuggestionProvider>,
+        success: bool,
     },
     CommandHelp(CommandData),
+    // 注销命令及其别名、补全提供者,并刷新在线玩家的命令树
+    UnregisterCommand {
+        name: String,
+        success: bool,
+    },
+    ChildFailed(qexed_task::supervisor::Failure), // 玩家指令任务失败
+}
+impl From<qexed_task::supervisor::Failure> for ManagerCommand {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        ManagerCommand::ChildFailed(failure)
+    }
 }
 #[derive(Debug)]
 pub enum TaskCommand {
     Command(String),
+    Suggestion(i32, String), // 补全请求:事务ID,光标前的文本
     InitCommandPacket,
     Close,
 }
@@ -49,7 +71,7 @@ pub struct CommandData {
     pub player_name: Option<String>,
     pub command_line: String,
     pub is_cmd: bool,
-    pub packet_sender: Option<UnboundedSender<Bytes>>,
+    pub packet_sender: Option<MailboxSender<Bytes>>,
 }
 impl CommandData {
     pub fn new(
@@ -57,7 +79,7 @@ impl CommandData {
         player_name: Option<String>,
         command_line: String,
         is_cmd: bool,
-        packet_sender: Option<UnboundedSender<Bytes>>,
+        packet_sender: Option<MailboxSender<Bytes>>,
     ) -> Self {
         Self {
             player_uuid,
@@ -91,7 +113,7 @@ impl CommandData {
             if let Some(packet_sender) = &self.packet_sender {
                 // 发送数据包
                 packet_sender
-                    .send(
+                    .try_send(
                         PacketSend::build_send_packet(
                             qexed_protocol::to_client::play::system_chat::SystemChat {
                                 content: content_nbt,
@@ -142,6 +164,7 @@ pub struct RegisterCommand {
     pub permission: String,
     pub parameters: Vec<CommandParameter>, // 新增：参数定义
     pub aliases: Vec<String>,              // 新增：命令别名
+    pub tree: crate::tree::CommandNode,    // 命令树
 }
 // 新增参数类型定义
 #[derive(Debug, Clone)]
@@ -149,6 +172,7 @@ pub enum ParameterType {
     Literal(&'static str), // 字面量，如"about", "version"
     String { behavior: StringBehavior },
     Integer { min: Option<i32>, max: Option<i32> },
+    Float { min: Option<f32>, max: Option<f32> },
     Boolean,
     Player,
     // 可以添加更多类型
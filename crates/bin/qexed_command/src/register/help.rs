@@ -18,6 +18,7 @@ pub async fn register(api: &UnboundedSender<ReturnMessage<ManagerCommand>>) -> a
                 suggestions: None, // 可以动态填充建议
             }],
             aliases: vec!["帮助".to_string(), "?".to_string(), "helpme".to_string()],
+            tree: None,
             api: Some(cmd_tx),
             success: false,
         })
@@ -1,6 +1,8 @@
 use qexed_task::message::{MessageType, return_message::ReturnMessage};
 use tokio::{process::Command, sync::mpsc::UnboundedSender};
 use crate::message::{CommandData, CommandParameter, ManagerCommand};
+use crate::suggestion::SuggestionProvider;
+use crate::tree::{CommandNode, NodeKind};
 pub mod help;
 
 pub async fn register(api:&UnboundedSender<ReturnMessage<ManagerCommand>>)->anyhow::Result<()>{
@@ -15,6 +17,45 @@ pub async fn register_command<F, Fut>(
     parameters: Vec<CommandParameter>,
     aliases: Vec<&str>,
     command_api: &UnboundedSender<ReturnMessage<ManagerCommand>>,
+    handler: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut(tokio::sync::mpsc::UnboundedReceiver<CommandData>) -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    send_register(name, doc, permission, parameters, None, aliases, command_api, handler).await
+}
+
+// 以命令树注册命令，适用于 /ban add|remove|list 这类带子命令的情况
+// 树的根节点必须是与命令同名的字面量
+pub async fn register_command_tree<F, Fut>(
+    tree: CommandNode,
+    doc: &str,
+    permission: &str,
+    aliases: Vec<&str>,
+    command_api: &UnboundedSender<ReturnMessage<ManagerCommand>>,
+    handler: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut(tokio::sync::mpsc::UnboundedReceiver<CommandData>) -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    let name = match &tree.kind {
+        NodeKind::Literal(name) => name.clone(),
+        NodeKind::Argument(_) => anyhow::bail!("命令树的根节点必须是字面量"),
+    };
+    send_register(&name, doc, permission, vec![], Some(tree), aliases, command_api, handler).await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn send_register<F, Fut>(
+    name: &str,
+    doc: &str,
+    permission: &str,
+    parameters: Vec<CommandParameter>,
+    tree: Option<CommandNode>,
+    aliases: Vec<&str>,
+    command_api: &UnboundedSender<ReturnMessage<ManagerCommand>>,
     mut handler: F,
 ) -> anyhow::Result<()>
 where
@@ -22,7 +63,7 @@ where
     Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
 {
     // 创建命令通道
-    let (cmd_tx, mut cmd_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (cmd_tx, cmd_rx) = tokio::sync::mpsc::unbounded_channel();
     
     // 构建注册消息
     let register_msg = ManagerCommand::RegisterCommand {
@@ -31,6 +72,7 @@ where
         permission: permission.to_string(),
         parameters,
         aliases: aliases.into_iter().map(|s| s.to_string()).collect(),
+        tree,
         api: Some(cmd_tx),
         success: false,
     };
@@ -57,4 +99,38 @@ where
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+// 注销命令，命令的处理任务在通道关闭后自行结束
+pub async fn unregister_command(
+    name: &str,
+    command_api: &UnboundedSender<ReturnMessage<ManagerCommand>>,
+) -> anyhow::Result<()> {
+    let response = ReturnMessage::build(ManagerCommand::UnregisterCommand {
+        name: name.to_string(),
+        success: false,
+    })
+    .get(command_api)
+    .await?;
+    match response {
+        ManagerCommand::UnregisterCommand { success: true, .. } => Ok(()),
+        _ => anyhow::bail!("命令 '{}' 注销失败", name),
+    }
+}
+// 为已注册的命令设置补全提供者
+pub async fn register_suggestion(
+    name: &str,
+    provider: SuggestionProvider,
+    command_api: &UnboundedSender<ReturnMessage<ManagerCommand>>,
+) -> anyhow::Result<()> {
+    let response = ReturnMessage::build(ManagerCommand::RegisterSuggestion {
+        name: name.to_string(),
+        provider: Some(provider),
+        success: false,
+    })
+    .get(command_api)
+    .await?;
+    match response {
+        ManagerCommand::RegisterSuggestion { success: true, .. } => Ok(()),
+        _ => anyhow::bail!("命令 '{}' 补全注册失败", name),
+    }
+}
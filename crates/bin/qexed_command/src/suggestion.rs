@@ -0,0 +1,196 @@
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
+
+use crate::{
+    message::{CommandParameter, ParameterType},
+    tree::{CommandNode, NodeKind},
+};
+
+/// 一条补全建议
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub text: String,
+    pub tooltip: Option<String>,
+}
+
+impl Suggestion {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            tooltip: None,
+        }
+    }
+
+    pub fn with_tooltip(text: impl Into<String>, tooltip: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            tooltip: Some(tooltip.into()),
+        }
+    }
+}
+
+/// 提交给补全提供者的请求
+#[derive(Debug, Clone)]
+pub struct SuggestionRequest {
+    pub player_uuid: Option<uuid::Uuid>,
+    pub player_name: Option<String>,
+    /// 不含开头 / 的命令行,截止到光标处
+    pub command_line: String,
+    /// 按空格切分后的参数,第一个为命令名,最后一个为正在输入的部分(可能为空)
+    pub args: Vec<String>,
+    /// 正在补全的参数节点名,无法匹配命令树时为空
+    pub node: Option<String>,
+}
+
+impl SuggestionRequest {
+    pub fn new(
+        player_uuid: Option<uuid::Uuid>,
+        player_name: Option<String>,
+        command_line: String,
+    ) -> Self {
+        let mut args: Vec<String> = command_line.split(' ').map(|s| s.to_string()).collect();
+        if args.is_empty() {
+            args.push(String::new());
+        }
+        Self {
+            player_uuid,
+            player_name,
+            command_line,
+            args,
+            node: None,
+        }
+    }
+
+    /// 正在输入的部分
+    pub fn current(&self) -> &str {
+        self.args.last().map(|s| s.as_str()).unwrap_or("")
+    }
+
+    /// 正在输入的参数下标(0 为命令名)
+    pub fn index(&self) -> usize {
+        self.args.len().saturating_sub(1)
+    }
+
+    /// 正在输入部分在原始文本(含开头 /)中的范围,单位为 UTF-16 码元
+    pub fn range(&self) -> (usize, usize) {
+        let current = self.current();
+        let prefix = &self.command_line[..self.command_line.len() - current.len()];
+        (
+            1 + prefix.encode_utf16().count(),
+            current.encode_utf16().count(),
+        )
+    }
+}
+
+pub type SuggestionFuture = Pin<Box<dyn Future<Output = anyhow::Result<Vec<Suggestion>>> + Send>>;
+
+/// 按命令注册的异步补全提供者
+#[derive(Clone)]
+pub struct SuggestionProvider(Arc<dyn Fn(SuggestionRequest) -> SuggestionFuture + Send + Sync>);
+
+impl SuggestionProvider {
+    pub fn new<F, Fut>(f: F) -> Self
+    where
+        F: Fn(SuggestionRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<Vec<Suggestion>>> + Send + 'static,
+    {
+        Self(Arc::new(move |request| Box::pin(f(request))))
+    }
+
+    pub async fn suggest(&self, request: SuggestionRequest) -> anyhow::Result<Vec<Suggestion>> {
+        (self.0)(request).await
+    }
+}
+
+impl fmt::Debug for SuggestionProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SuggestionProvider")
+    }
+}
+
+/// 按前缀过滤候选值(忽略大小写)
+pub fn filter_prefix<I>(candidates: I, prefix: &str) -> Vec<Suggestion>
+where
+    I: IntoIterator<Item = Suggestion>,
+{
+    let prefix = prefix.to_lowercase();
+    candidates
+        .into_iter()
+        .filter(|s| s.text.to_lowercase().starts_with(&prefix))
+        .collect()
+}
+
+/// 沿命令树找到正在补全位置的候选子节点
+/// args 不包含命令名,最后一项为正在输入的部分
+pub fn candidate_nodes<'a>(tree: &'a CommandNode, args: &[String]) -> Vec<&'a CommandNode> {
+    let Some((_, walked)) = args.split_last() else {
+        return vec![];
+    };
+    let mut node = tree;
+    for arg in walked {
+        let next = node
+            .children
+            .iter()
+            .find(|c| matches!(&c.kind, NodeKind::Literal(l) if l == arg))
+            .or_else(|| {
+                node.children
+                    .iter()
+                    .find(|c| matches!(c.kind, NodeKind::Argument(_)))
+            });
+        match next {
+            Some(next) => node = next,
+            None => return vec![],
+        }
+    }
+    node.children.iter().collect()
+}
+
+/// 参数自带的静态补全
+pub fn static_suggestions(param: &CommandParameter, online_players: &[String]) -> Vec<Suggestion> {
+    let mut result: Vec<Suggestion> = param
+        .suggestions
+        .iter()
+        .flatten()
+        .map(|s| Suggestion::with_tooltip(s.clone(), param.description.clone()))
+        .collect();
+    match param.param_type {
+        ParameterType::Player => {
+            result.extend(online_players.iter().map(|name| Suggestion::new(name.clone())));
+        }
+        ParameterType::Boolean => {
+            result.push(Suggestion::new("true"));
+            result.push(Suggestion::new("false"));
+        }
+        _ => {}
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_is_measured_in_utf16_after_slash() {
+        let request = SuggestionRequest::new(None, None, "tell 玩家 hel".to_string());
+        assert_eq!(request.current(), "hel");
+        assert_eq!(request.index(), 2);
+        // "/tell 玩家 " 共 9 个码元
+        assert_eq!(request.range(), (9, 3));
+
+        let request = SuggestionRequest::new(None, None, "ban ".to_string());
+        assert_eq!(request.current(), "");
+        assert_eq!(request.range(), (5, 0));
+    }
+
+    #[test]
+    fn candidates_follow_literals() {
+        let tree = CommandNode::literal("ban")
+            .then(CommandNode::literal("add"))
+            .then(CommandNode::literal("list").then(CommandNode::literal("all")));
+        let args = vec!["list".to_string(), "a".to_string()];
+        let names: Vec<&str> = candidate_nodes(&tree, &args).iter().map(|n| n.name()).collect();
+        assert_eq!(names, vec!["all"]);
+        let args = vec!["".to_string()];
+        assert_eq!(candidate_nodes(&tree, &args).len(), 2);
+    }
+}
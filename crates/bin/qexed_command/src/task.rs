@@ -2,7 +2,10 @@ use std::cell::RefCell;
 
 use bytes::Bytes;
 use dashmap::DashMap;
+use qexed_packet::net_types::VarInt;
+use qexed_protocol::to_client::play::command_suggestions::{CommandSuggestions, Matches};
 use qexed_protocol::to_client::play::commands::{Commands, Node};
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::event::task::TaskEvent;
 use qexed_task::message::MessageType;
 use qexed_task::message::return_message::ReturnMessage;
@@ -12,15 +15,22 @@ use tokio::sync::mpsc::UnboundedSender;
 use uuid::Uuid;
 
 use crate::message::{CommandData, ManagerCommand, TaskCommand};
+use crate::suggestion::{Suggestion, SuggestionProvider, SuggestionRequest};
+
+// 补全提供者的最长等待时间,超时后只返回静态候选值
+const SUGGESTION_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+// 向管理器发出的请求超过此时间未回复则放弃,避免管理器卡住时玩家任务一直等待
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
 
 #[derive(Debug)]
 pub struct CommandTask {
     config: qexed_config::app::qexed_command::CommandConfig,
     player_uuid: Uuid,
     player_name: String,
-    packet_send: UnboundedSender<Bytes>,
+    packet_send: MailboxSender<Bytes>,
     // 命令发送器缓存：命令名 -> 发送器 (使用 RefCell 实现内部可变性)
     cmd_cache: DashMap<String, UnboundedSender<CommandData>>,
+    events: qexed_event::EventBus,
 }
 
 impl CommandTask {
@@ -28,9 +38,11 @@ impl CommandTask {
         config: qexed_config::app::qexed_command::CommandConfig,
         player_uuid: Uuid,
         player_name: String,
-        packet_send: UnboundedSender<Bytes>,
+        packet_send: MailboxSender<Bytes>,
+        events: qexed_event::EventBus,
     ) -> Self {
         Self {
+            events,
             player_uuid,
             packet_send,
             player_name,
@@ -53,7 +65,7 @@ impl CommandTask {
             }
         }
         let result = ReturnMessage::build(ManagerCommand::GetCommand(cmd_name.to_string(), None))
-            .get(manage_api)
+            .get_timeout(manage_api, REQUEST_TIMEOUT)
             .await?;
 
         // 3. 处理管理器响应
@@ -92,14 +104,81 @@ impl TaskEvent<UnReturnMessage<TaskCommand>, ReturnMessage<ManagerCommand>> for
         match data.data {
             TaskCommand::InitCommandPacket => {
                 if let Ok(ManagerCommand::GetCommandPacket(_,packet)) = ReturnMessage::build(ManagerCommand::GetCommandPacket(self.player_uuid,None))
-                    .get(manage_api)
+                    .get_timeout(manage_api, REQUEST_TIMEOUT)
                     .await{
                     if let Some(packet) = packet{
-                        self.packet_send.send(packet)?;
+                        self.packet_send.try_send(packet)?;
+                    }
+                };
+            }
+            TaskCommand::Suggestion(transaction_id, text) => {
+                if !self.config.tab {
+                    return Ok(false);
+                }
+                let command_line = text.strip_prefix('/').unwrap_or(&text).to_string();
+                let request = SuggestionRequest::new(
+                    Some(self.player_uuid),
+                    Some(self.player_name.clone()),
+                    command_line,
+                );
+                let result = match ReturnMessage::build(ManagerCommand::TabComplete {
+                    request,
+                    suggestions: vec![],
+                    provider: None,
+                })
+                .get_timeout(manage_api, REQUEST_TIMEOUT)
+                .await
+                {
+                    Ok(result) => result,
+                    Err(e) => {
+                        log::warn!("获取命令补全失败: {}", e);
+                        return Ok(false);
                     }
                 };
+                if let ManagerCommand::TabComplete {
+                    request,
+                    suggestions,
+                    provider,
+                } = result
+                {
+                    // 提供者可能查询其他服务,放到单独的任务里避免阻塞指令执行
+                    let packet_send = self.packet_send.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = send_suggestions(
+                            transaction_id,
+                            request,
+                            suggestions,
+                            provider,
+                            packet_send,
+                        )
+                        .await
+                        {
+                            log::warn!("发送命令补全失败: {}", e);
+                        }
+                    });
+                }
             }
             TaskCommand::Command(full_cmd) => {
+                // 监听器可以改写或取消指令
+                let full_cmd = if self.events.has_listeners(qexed_event::EventKind::CommandPreprocess) {
+                    let dispatch = self
+                        .events
+                        .publish(qexed_event::Event::CommandPreprocess {
+                            uuid: self.player_uuid,
+                            name: self.player_name.clone(),
+                            command: full_cmd,
+                        })
+                        .await;
+                    match dispatch {
+                        qexed_event::Dispatch {
+                            cancelled: false,
+                            event: qexed_event::Event::CommandPreprocess { command, .. },
+                        } => command,
+                        _ => return Ok(false),
+                    }
+                } else {
+                    full_cmd
+                };
                 log::info!(
                     "玩家 {}[{}] 执行指令: {}",
                     self.player_name,
@@ -157,7 +236,7 @@ impl TaskEvent<UnReturnMessage<TaskCommand>, ReturnMessage<ManagerCommand>> for
             TaskCommand::Close => {
                 // 通知管理器玩家连接关闭
                 let _ = ReturnMessage::build(ManagerCommand::PlayerClose(self.player_uuid))
-                    .get(manage_api)
+                    .get_timeout(manage_api, REQUEST_TIMEOUT)
                     .await;
 
                 // 清理所有缓存
@@ -184,7 +263,7 @@ impl CommandTask {
             qexed_nbt::Tag::String(message.into()), // 使用 `into()` 转为 Arc<str>
         );
         let content_nbt = qexed_nbt::Tag::Compound(std::sync::Arc::new(chat_component));
-        self.packet_send.send(
+        self.packet_send.try_send(
             PacketSend::build_send_packet(
                 qexed_protocol::to_client::play::system_chat::SystemChat {
                     content: content_nbt,
@@ -201,3 +280,48 @@ impl CommandTask {
         Ok(false)
     }
 }
+
+/// 调用补全提供者并发送 command_suggestions 数据包
+async fn send_suggestions(
+    transaction_id: i32,
+    request: SuggestionRequest,
+    mut suggestions: Vec<Suggestion>,
+    provider: Option<SuggestionProvider>,
+    packet_send: MailboxSender<Bytes>,
+) -> anyhow::Result<()> {
+    if let Some(provider) = provider {
+        match tokio::time::timeout(SUGGESTION_TIMEOUT, provider.suggest(request.clone())).await {
+            Ok(Ok(extra)) => {
+                for s in extra {
+                    if !suggestions.iter().any(|e| e.text == s.text) {
+                        suggestions.push(s);
+                    }
+                }
+            }
+            Ok(Err(e)) => log::warn!("命令 {} 的补全提供者出错: {}", request.args[0], e),
+            Err(_) => log::warn!("命令 {} 的补全提供者超时", request.args[0]),
+        }
+    }
+    let (start, length) = request.range();
+    let matches = suggestions
+        .into_iter()
+        .map(|s| Matches {
+            r#match: s.text,
+            tooltip: s.tooltip.map(|tooltip| {
+                let mut component = std::collections::HashMap::new();
+                component.insert("text".to_string(), qexed_nbt::Tag::String(tooltip.into()));
+                qexed_nbt::Tag::Compound(std::sync::Arc::new(component))
+            }),
+        })
+        .collect();
+    packet_send.try_send(
+        PacketSend::build_send_packet(CommandSuggestions {
+            id: VarInt(transaction_id),
+            start: VarInt(start as i32),
+            length: VarInt(length as i32),
+            matches,
+        })
+        .await?,
+    )?;
+    Ok(())
+}
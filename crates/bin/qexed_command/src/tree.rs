@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+
+use qexed_packet::net_types::VarInt;
+use qexed_protocol::to_client::play::commands::{
+    Brigadier, BrigadierString, MinecraftEntity, Node, Varies,
+};
+
+use crate::message::{CommandParameter, ParameterType, StringBehavior};
+
+// 节点标志位（与 commands 数据包保持一致）
+const NODE_ROOT: u8 = 0x00;
+const NODE_LITERAL: u8 = 0x01;
+const NODE_ARGUMENT: u8 = 0x02;
+const NODE_EXECUTABLE: u8 = 0x04;
+const NODE_REDIRECT: u8 = 0x08;
+const NODE_SUGGESTIONS: u8 = 0x10;
+
+/// 命令树节点的类型
+#[derive(Debug, Clone)]
+pub enum NodeKind {
+    /// 字面量，如 /ban add 中的 "add"
+    Literal(String),
+    /// 参数节点
+    Argument(CommandParameter),
+}
+
+/// 重定向目标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Redirect {
+    /// 重定向到根节点（/execute ... run 的写法）
+    Root,
+    /// 重定向到某个字面量路径，如 ["execute"]
+    Path(Vec<String>),
+}
+
+/// 命令树节点
+///
+/// 用于注册带分支的子命令：
+/// ```ignore
+/// CommandNode::literal("ban")
+///     .then(CommandNode::literal("add").then(CommandNode::argument(player).executes()))
+///     .then(CommandNode::literal("remove").then(CommandNode::argument(player).executes()))
+///     .then(CommandNode::literal("list").executes())
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandNode {
+    pub kind: NodeKind,
+    pub executable: bool,
+    pub children: Vec<CommandNode>,
+    pub redirect: Option<Redirect>,
+}
+
+impl CommandNode {
+    pub fn literal(name: &str) -> Self {
+        Self {
+            kind: NodeKind::Literal(name.to_string()),
+            executable: false,
+            children: vec![],
+            redirect: None,
+        }
+    }
+
+    pub fn argument(param: CommandParameter) -> Self {
+        Self {
+            kind: NodeKind::Argument(param),
+            executable: false,
+            children: vec![],
+            redirect: None,
+        }
+    }
+
+    /// 添加子节点
+    pub fn then(mut self, child: CommandNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// 添加可选分支：当前节点本身可执行，子节点可以省略
+    pub fn optional(mut self, child: CommandNode) -> Self {
+        self.executable = true;
+        self.children.push(child);
+        self
+    }
+
+    /// 标记当前节点可执行（中间节点同样适用）
+    pub fn executes(mut self) -> Self {
+        self.executable = true;
+        self
+    }
+
+    /// 重定向到指定字面量路径
+    pub fn redirect(mut self, path: &[&str]) -> Self {
+        self.redirect = Some(Redirect::Path(path.iter().map(|s| s.to_string()).collect()));
+        self
+    }
+
+    /// 重定向到根节点
+    pub fn redirect_root(mut self) -> Self {
+        self.redirect = Some(Redirect::Root);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        match &self.kind {
+            NodeKind::Literal(name) => name,
+            NodeKind::Argument(param) => &param.name,
+        }
+    }
+
+    /// 将旧式的线性参数列表转换为命令树
+    /// 某个节点之后的参数全部可选时，该节点可执行
+    pub fn from_parameters(name: &str, parameters: &[CommandParameter]) -> Self {
+        let mut tail: Option<CommandNode> = None;
+        let mut tail_optional = true;
+        for param in parameters.iter().rev() {
+            let mut node = match &param.param_type {
+                ParameterType::Literal(literal) => CommandNode::literal(literal),
+                _ => CommandNode::argument(param.clone()),
+            };
+            node.executable = tail_optional;
+            if let Some(child) = tail.take() {
+                node.children.push(child);
+            }
+            tail_optional = tail_optional && !param.required;
+            tail = Some(node);
+        }
+        let mut root = CommandNode::literal(name);
+        root.executable = tail_optional;
+        if let Some(child) = tail {
+            root.children.push(child);
+        }
+        root
+    }
+
+    /// 列出所有可执行路径的用法，如 "/ban add <player>"
+    pub fn usages(&self) -> Vec<String> {
+        let mut result = Vec::new();
+        self.collect_usages(String::from("/"), &mut result);
+        result
+    }
+
+    fn collect_usages(&self, prefix: String, result: &mut Vec<String>) {
+        let part = match &self.kind {
+            NodeKind::Literal(name) => name.clone(),
+            NodeKind::Argument(param) => format!("<{}>", param.name),
+        };
+        let line = if prefix.ends_with('/') {
+            format!("{}{}", prefix, part)
+        } else {
+            format!("{} {}", prefix, part)
+        };
+        if self.executable {
+            result.push(line.clone());
+        }
+        if self.redirect.is_some() {
+            result.push(format!("{} ...", line));
+        }
+        for child in &self.children {
+            child.collect_usages(line.clone(), result);
+        }
+    }
+}
+
+/// 把若干命令树展开为 commands 数据包所需的扁平节点列表
+#[derive(Debug, Default)]
+pub struct CommandGraph {
+    nodes: Vec<Node>,
+    root_children: Vec<VarInt>,
+    // 字面量路径 -> 节点索引，用于解析重定向
+    paths: HashMap<Vec<String>, i32>,
+    pending_redirects: Vec<(usize, Redirect)>,
+}
+
+impl CommandGraph {
+    pub fn new() -> Self {
+        Self {
+            // 索引 0 预留给根节点
+            nodes: vec![Node::default()],
+            ..Default::default()
+        }
+    }
+
+    /// 添加一条根命令，返回其节点索引
+    /// ask_server 为 true 时所有参数节点都向服务端请求补全
+    pub fn add_command(&mut self, tree: &CommandNode, ask_server: bool) -> i32 {
+        let index = self.push(tree, &[], ask_server);
+        self.root_children.push(VarInt(index));
+        index
+    }
+
+    /// 添加别名：一个重定向到原命令的字面量节点
+    /// 目标需先添加，别名沿用其可执行标记，否则客户端认为不带参数的别名不完整
+    pub fn add_alias(&mut self, alias: &str, target: &str) {
+        let mut node = CommandNode::literal(alias).redirect(&[target]);
+        node.executable = self
+            .paths
+            .get(&vec![target.to_string()])
+            .is_some_and(|&index| self.nodes[index as usize].flags & NODE_EXECUTABLE != 0);
+        self.add_command(&node, false);
+    }
+
+    fn push(&mut self, tree: &CommandNode, parent_path: &[String], ask_server: bool) -> i32 {
+        let index = self.nodes.len();
+        self.nodes.push(Node::default());
+
+        let mut path = parent_path.to_vec();
+        path.push(tree.name().to_string());
+        self.paths.entry(path.clone()).or_insert(index as i32);
+
+        let mut node = match &tree.kind {
+            NodeKind::Literal(name) => Node {
+                flags: NODE_LITERAL,
+                name: Some(name.clone()),
+                ..Default::default()
+            },
+            NodeKind::Argument(param) => {
+                let (parser_id, properties) = parameter_to_network(&param.param_type);
+                Node {
+                    flags: NODE_ARGUMENT,
+                    name: Some(param.name.clone()),
+                    parser_id: Some(VarInt(parser_id)),
+                    properties,
+                    suggestions_type: (ask_server
+                        || param.suggestions.is_some()
+                        || matches!(param.param_type, ParameterType::Player))
+                    .then(|| "minecraft:ask_server".to_string()),
+                    ..Default::default()
+                }
+            }
+        };
+        if tree.executable {
+            node.flags |= NODE_EXECUTABLE;
+        }
+        if node.suggestions_type.is_some() {
+            node.flags |= NODE_SUGGESTIONS;
+        }
+        if let Some(redirect) = &tree.redirect {
+            node.flags |= NODE_REDIRECT;
+            self.pending_redirects.push((index, redirect.clone()));
+        }
+        node.children = tree
+            .children
+            .iter()
+            .map(|child| VarInt(self.push(child, &path, ask_server)))
+            .collect();
+
+        self.nodes[index] = node;
+        index as i32
+    }
+
+    /// 生成扁平节点列表，根节点位于索引 0
+    pub fn build(mut self) -> Vec<Node> {
+        for (index, redirect) in std::mem::take(&mut self.pending_redirects) {
+            let target = match &redirect {
+                Redirect::Root => Some(0),
+                Redirect::Path(path) => self.paths.get(path).copied(),
+            };
+            match target {
+                Some(target) => self.nodes[index].redirect_node = Some(VarInt(target)),
+                None => {
+                    log::warn!("命令树重定向目标不存在: {:?}", redirect);
+                    self.nodes[index].flags &= !NODE_REDIRECT;
+                }
+            }
+        }
+        self.nodes[0] = Node {
+            flags: NODE_ROOT,
+            children: self.root_children,
+            ..Default::default()
+        };
+        self.nodes
+    }
+}
+
+/// 将参数类型转换为网络格式
+pub fn parameter_to_network(param_type: &ParameterType) -> (i32, Option<Varies>) {
+    match param_type {
+        // 字面量参数被当作参数节点使用时按单个单词处理
+        ParameterType::Literal(_) => (
+            5,
+            Some(Varies::BrigadierString(BrigadierString {
+                behavior: VarInt(0),
+            })),
+        ),
+        ParameterType::String { behavior } => {
+            let behavior_id = match behavior {
+                StringBehavior::SingleWord => 0,
+                StringBehavior::Quotable => 1,
+                StringBehavior::Greedy => 2,
+            };
+            (
+                5,
+                Some(Varies::BrigadierString(BrigadierString {
+                    behavior: VarInt(behavior_id),
+                })),
+            )
+        }
+        ParameterType::Integer { min, max } => {
+            let mut flags = 0;
+            if min.is_some() {
+                flags |= 0x01;
+            }
+            if max.is_some() {
+                flags |= 0x02;
+            }
+            (
+                3,
+                Some(Varies::BrigadierInteger(Brigadier {
+                    flags,
+                    min: *min,
+                    max: *max,
+                })),
+            )
+        }
+        ParameterType::Float { min, max } => {
+            let mut flags = 0;
+            if min.is_some() {
+                flags |= 0x01;
+            }
+            if max.is_some() {
+                flags |= 0x02;
+            }
+            (
+                1,
+                Some(Varies::BrigadierFloat(Brigadier {
+                    flags,
+                    min: *min,
+                    max: *max,
+                })),
+            )
+        }
+        // brigadier:bool
+        ParameterType::Boolean => (0, None),
+        // minecraft:entity，仅限单个玩家
+        ParameterType::Player => (6, Some(Varies::MinecraftEntity(MinecraftEntity { flags: 0x03 }))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player() -> CommandParameter {
+        CommandParameter {
+            name: "player".to_string(),
+            description: "玩家".to_string(),
+            required: true,
+            param_type: ParameterType::Player,
+            suggestions: None,
+        }
+    }
+
+    #[test]
+    fn branching_tree_layout() {
+        let ban = CommandNode::literal("ban")
+            .then(CommandNode::literal("add").then(CommandNode::argument(player()).executes()))
+            .then(CommandNode::literal("remove").then(CommandNode::argument(player()).executes()))
+            .then(CommandNode::literal("list").executes());
+        let mut graph = CommandGraph::new();
+        let ban_index = graph.add_command(&ban, false);
+        graph.add_alias("b", "ban");
+        let nodes = graph.build();
+
+        assert_eq!(nodes[0].flags, NODE_ROOT);
+        assert_eq!(nodes[0].children.len(), 2);
+        let ban_node = &nodes[ban_index as usize];
+        assert_eq!(ban_node.children.len(), 3);
+        assert_eq!(ban_node.flags & NODE_EXECUTABLE, 0);
+
+        let alias = nodes.iter().find(|n| n.name.as_deref() == Some("b")).unwrap();
+        assert_eq!(alias.flags, NODE_LITERAL | NODE_REDIRECT);
+        assert_eq!(alias.redirect_node, Some(VarInt(ban_index)));
+    }
+
+    #[test]
+    fn alias_copies_executable_flag() {
+        let tp = CommandNode::literal("teleport")
+            .executes()
+            .then(CommandNode::argument(player()).executes());
+        let mut graph = CommandGraph::new();
+        let tp_index = graph.add_command(&tp, false);
+        graph.add_alias("tp", "teleport");
+        let nodes = graph.build();
+
+        let alias = nodes.iter().find(|n| n.name.as_deref() == Some("tp")).unwrap();
+        assert_eq!(alias.flags, NODE_LITERAL | NODE_EXECUTABLE | NODE_REDIRECT);
+        assert_eq!(alias.redirect_node, Some(VarInt(tp_index)));
+    }
+
+    #[test]
+    fn linear_parameters_mark_optional_tail_executable() {
+        let page = CommandParameter {
+            name: "page".to_string(),
+            description: "页码".to_string(),
+            required: false,
+            param_type: ParameterType::Integer { min: Some(1), max: None },
+            suggestions: None,
+        };
+        let tree = CommandNode::from_parameters("list", &[page]);
+        assert!(tree.executable);
+        assert!(tree.children[0].executable);
+
+        let tree = CommandNode::from_parameters("kick", &[player()]);
+        assert!(!tree.executable);
+        assert!(tree.children[0].executable);
+    }
+
+    #[test]
+    fn redirect_to_root() {
+        let execute = CommandNode::literal("execute")
+            .then(CommandNode::literal("as").then(CommandNode::argument(player()).redirect(&["execute"])))
+            .then(CommandNode::literal("run").redirect_root());
+        let mut graph = CommandGraph::new();
+        let execute_index = graph.add_command(&execute, false);
+        let nodes = graph.build();
+        let run = nodes.iter().find(|n| n.name.as_deref() == Some("run")).unwrap();
+        assert_eq!(run.redirect_node, Some(VarInt(0)));
+        let as_player = nodes.iter().find(|n| n.name.as_deref() == Some("player")).unwrap();
+        assert_eq!(as_player.redirect_node, Some(VarInt(execute_index)));
+    }
+}
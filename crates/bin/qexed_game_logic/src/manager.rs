@@ -2,6 +2,8 @@ use async_trait::async_trait;
 use dashmap::DashMap;
 use qexed_config::app::qexed_game_logic::GameLogicConfig;
 use qexed_task::{
+    mailbox::MailboxSender,
+    supervisor::{self, Directive},
     event::task_manage::TaskManageEvent,
     message::{MessageSender, MessageType, return_message::ReturnMessage, unreturn_message::UnReturnMessage},
 };
@@ -18,28 +20,30 @@ pub struct GameLogicManagerActor {
     config: GameLogicConfig,
     registry_data: Vec<qexed_protocol::to_client::configuration::registry_data::RegistryData>,
     tags: qexed_protocol::to_client::configuration::tags::Tags,
-    qexed_ping_api:UnboundedSender<ReturnMessage<qexed_ping::message::ManagerCommand>>,
+    qexed_ping_api:MailboxSender<ReturnMessage<qexed_ping::message::ManagerCommand>>,
     qexed_heartbeat_api:UnboundedSender<ReturnMessage<qexed_heartbeat::message::ManagerCommand>>,
-    qexed_packet_split_api:UnboundedSender<ReturnMessage<qexed_packet_split::message::ManagerMessage>>,
-    qexed_chat_api:UnboundedSender<ReturnMessage<qexed_chat::message::ManagerMessage>>,
+    qexed_packet_split_api:MailboxSender<ReturnMessage<qexed_packet_split::message::ManagerMessage>>,
+    qexed_chat_api:MailboxSender<ReturnMessage<qexed_chat::message::ManagerMessage>>,
     qexed_command_api:UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
     qexed_player_list_api:UnboundedSender<ReturnMessage<qexed_player_list::Message>>,
     qexed_chunk_api:UnboundedSender<UnReturnMessage<qexed_chunk::message::world::WorldCommand>>,
-    qexed_title_api:UnboundedSender<ReturnMessage<qexed_title::message::ManagerMessage>>,
+    qexed_title_api:MailboxSender<ReturnMessage<qexed_title::message::ManagerMessage>>,
+    events: qexed_event::EventBus,
 }
 impl GameLogicManagerActor {
     pub fn new(
         config: GameLogicConfig,
         registry_data: Vec<qexed_protocol::to_client::configuration::registry_data::RegistryData>,
         tags: qexed_protocol::to_client::configuration::tags::Tags,
-        qexed_ping_api:UnboundedSender<ReturnMessage<qexed_ping::message::ManagerCommand>>,
+        qexed_ping_api:MailboxSender<ReturnMessage<qexed_ping::message::ManagerCommand>>,
         qexed_heartbeat_api:UnboundedSender<ReturnMessage<qexed_heartbeat::message::ManagerCommand>>,
-        qexed_packet_split_api:UnboundedSender<ReturnMessage<qexed_packet_split::message::ManagerMessage>>,
-        qexed_chat_api:UnboundedSender<ReturnMessage<qexed_chat::message::ManagerMessage>>,
+        qexed_packet_split_api:MailboxSender<ReturnMessage<qexed_packet_split::message::ManagerMessage>>,
+        qexed_chat_api:MailboxSender<ReturnMessage<qexed_chat::message::ManagerMessage>>,
         qexed_command_api:UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
         qexed_player_list_api:UnboundedSender<ReturnMessage<qexed_player_list::Message>>,
         qexed_chunk_api:UnboundedSender<UnReturnMessage<qexed_chunk::message::world::WorldCommand>>,
-        qexed_title_api:UnboundedSender<ReturnMessage<qexed_title::message::ManagerMessage>>,
+        qexed_title_api:MailboxSender<ReturnMessage<qexed_title::message::ManagerMessage>>,
+        events: qexed_event::EventBus,
     ) -> Self {
         Self {
             config,
@@ -53,6 +57,7 @@ impl GameLogicManagerActor {
             qexed_player_list_api,
             qexed_chunk_api,
             qexed_title_api,
+            events,
         }
     }
 
@@ -67,6 +72,13 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, ReturnMessage<TaskMess
         task_map: &DashMap<Uuid, MessageSender<ReturnMessage<TaskMessage>>>,
         mut data: ReturnMessage<ManagerMessage>,
     ) -> anyhow::Result<bool> {
+        // 失败报告不需要回复,停止的任务从任务表中移除
+        if let ManagerMessage::ChildFailed(failure) = &data.data {
+            if failure.directive != Directive::Restart {
+                supervisor::prune(task_map);
+            }
+            return Ok(false);
+        }
         let send = match data.get_return_send().await? {
             Some(send) => send,
             None => return Ok(false),
@@ -85,7 +97,7 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, ReturnMessage<TaskMess
                     let _ = send.send(data.data);
                     return Ok(false);
                 }
-                let (task, task_sand) = crate::task::Task::new(api.clone(), GameLogicActor::new(uuid));
+                let (task, task_sand) = crate::task::Task::new(api.clone(), GameLogicActor::new(uuid, self.events.clone()));
                 task.run().await?;
                 task_map.insert(uuid, task_sand.clone());
                 *task_api = Some(task_sand);
@@ -192,6 +204,43 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, ReturnMessage<TaskMess
                 let _ = send.send(data);
                 return Ok(false);
             }
+            ManagerMessage::Shutdown(ref reason) => {
+                for entry in task_map.iter() {
+                    let _ = ReturnMessage::build(TaskMessage::Kick(reason.clone()))
+                        .post(entry.value())
+                        .await;
+                }
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::CloseAll => {
+                // 任务关闭时会回调 PlayerClose,这里不能等待回复
+                for entry in task_map.iter() {
+                    let _ = ReturnMessage::build(TaskMessage::Close)
+                        .post(entry.value())
+                        .await;
+                }
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::Online(ref mut count) => {
+                *count = task_map.len();
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::Kick(uuid, ref reason, ref mut found) => {
+                // 与 Shutdown 相同,只投递不等待回复
+                let task = task_map.get(&uuid).map(|task| task.value().clone());
+                *found = task.is_some();
+                if let Some(task) = task {
+                    let _ = ReturnMessage::build(TaskMessage::Kick(reason.clone()))
+                        .post(&task)
+                        .await;
+                }
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerMessage::ChildFailed(_) => Ok(false),
         }
     }
 }
@@ -1,5 +1,6 @@
 use bytes::Bytes;
 use qexed_player::Player;
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::{return_message::ReturnMessage, unreturn_message::UnReturnMessage};
 use thiserror::Error;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
@@ -29,16 +30,27 @@ pub enum ManagerMessage {
     
     PlayerClose(uuid::Uuid),  // 游戏连接关闭
     ConnectClose(uuid::Uuid), // 连接关闭
+    Shutdown(String),         // 关服:以给定原因断开所有玩家
+    CloseAll,                 // 关服:强制关闭剩余的玩家任务
+    Online(usize),            // 查询剩余的玩家任务数
+    Kick(uuid::Uuid, String, bool), // 以给定原因断开玩家,返回玩家是否在线
+    ChildFailed(qexed_task::supervisor::Failure), // 玩家游戏逻辑任务失败
+}
+impl From<qexed_task::supervisor::Failure> for ManagerMessage {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        ManagerMessage::ChildFailed(failure)
+    }
 }
 #[derive(Debug)]
 pub enum TaskMessage {
     Start(
         Player,
         Option<UnboundedReceiver<Vec<u8>>>,
-        Option<UnboundedSender<Bytes>>,
+        Option<MailboxSender<Bytes>>,
     ), // 传递数据包收发器
     Configuration(bool),
     Play,  // 游戏阶段
+    Kick(String), // 断开连接并显示原因
     Close, // 连接关闭
 }
 
@@ -11,12 +11,14 @@ use qexed_protocol::{
     },
     types::KnownPacks,
 };
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::{
     event::task::TaskEvent,
     message::{
         MessageSender, MessageType, return_message::ReturnMessage,
         unreturn_message::UnReturnMessage,
     },
+    supervisor::{Directive, Failure, FailureKind},
 };
 use qexed_tcp_connect::PacketSend;
 use rsa::pkcs8::der::asn1::Null;
@@ -30,7 +32,7 @@ pub struct GameLogicActor {
     uuid: Uuid,
     player: Option<Player>,
     packet_read: Option<UnboundedReceiver<Vec<u8>>>,
-    packet_write: Option<UnboundedSender<Bytes>>,
+    packet_write: Option<MailboxSender<Bytes>>,
     qexed_ping_api: Option<UnboundedSender<UnReturnMessage<qexed_ping::message::TaskCommand>>>,
     qexed_heartbeat_api:
         Option<UnboundedSender<UnReturnMessage<qexed_heartbeat::message::TaskCommand>>>,
@@ -40,10 +42,18 @@ pub struct GameLogicActor {
     qexed_command_api:Option<UnboundedSender<UnReturnMessage<qexed_command::message::TaskCommand>>>,
     qexed_player_list_api:Option<UnboundedSender<ReturnMessage<qexed_player_list::Message>>>,
     qexed_title_api:Option<UnboundedSender<UnReturnMessage<qexed_title::message::TaskMessage>>>,
+    events: qexed_event::EventBus,
+    // 已发布 PlayerJoin 且未被取消
+    joined: bool,
+    // 配置阶段已结束,客户端处于 Play 状态
+    in_play: bool,
 }
 impl GameLogicActor {
-    pub fn new(uuid: Uuid) -> Self {
+    pub fn new(uuid: Uuid, events: qexed_event::EventBus) -> Self {
         Self {
+            events,
+            joined: false,
+            in_play: false,
             uuid,
             player: None,
             packet_read: None,
@@ -58,6 +68,28 @@ impl GameLogicActor {
         }
     }
 }
+impl GameLogicActor {
+    /// 发送断开数据包并关闭写邮箱,连接层发完排队的数据包后关闭连接,随后发送 Close
+    async fn disconnect(&self, reason: &str) -> anyhow::Result<()> {
+        let Some(packet_write) = &self.packet_write else {
+            return Ok(());
+        };
+        let packet = if self.in_play {
+            PacketSend::build_send_packet(qexed_protocol::to_client::play::disconnect::Disconnect {
+                reason: create_text_nbt(reason),
+            })
+            .await?
+        } else {
+            PacketSend::build_send_packet(qexed_protocol::to_client::configuration::disconnect::Disconnect {
+                reason: create_text_nbt(reason),
+            })
+            .await?
+        };
+        let _ = packet_write.try_send(packet);
+        packet_write.close();
+        Ok(())
+    }
+}
 #[async_trait]
 impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for GameLogicActor {
     async fn event(
@@ -204,7 +236,7 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                             >(&mut reader)?;
                             locale = pk.locale;
                             view_distance = pk.view_distance;
-                            packet_write.send(
+                            packet_write.try_send(
                                 PacketSend::build_send_packet(qexed_protocol::to_client::configuration::select_known_packs::SelectKnownPacks {
                                     known_packs: vec![KnownPacks {
                                         namespace: "minecraft".to_string(),
@@ -228,6 +260,7 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                                 &mut reader,
                             )?;
                             *is_true = true;
+                            self.in_play = true;
                             // if let Some(api_ping) =&self.qexed_ping_api{
                             //     let (w,r) = tokio::sync::oneshot::channel();
                             //     UnReturnMessage::build(qexed_ping::message::TaskCommand::Await(w)).post(&api_ping).await?;
@@ -265,14 +298,14 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                                 if let Some(d1) = d1 {
                                     for p in d1 {
                                         packet_write
-                                            .send(PacketSend::build_send_packet(p).await?)?;
+                                            .try_send(PacketSend::build_send_packet(p).await?)?;
                                     }
                                 }
                                 if let Some(d2) = d2 {
-                                    packet_write.send(PacketSend::build_send_packet(d2).await?)?;
+                                    packet_write.try_send(PacketSend::build_send_packet(d2).await?)?;
                                 }
                             }
-                            packet_write.send(
+                            packet_write.try_send(
                                 PacketSend::build_send_packet(FinishConfiguration {}).await?,
                             )?;
                         }
@@ -318,7 +351,19 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                         return Ok(false);
                     }
                 };
-                packet_write.send(
+                // 是否强制安全聊天由聊天服务决定
+                let enforces_secure_chat = match ReturnMessage::build(ManagerMessage::GetPlayerChat(Some(
+                    qexed_chat::message::ManagerMessage::EnforcesSecureChat(false),
+                )))
+                .get(manage_api)
+                .await?
+                {
+                    ManagerMessage::GetPlayerChat(Some(
+                        qexed_chat::message::ManagerMessage::EnforcesSecureChat(is_true),
+                    )) => is_true,
+                    _ => false,
+                };
+                packet_write.try_send(
                     PacketSend::build_send_packet(qexed_protocol::to_client::play::login::Login {
                         entity_id: 1,
                         is_hardcore: false,
@@ -345,12 +390,12 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                         death_position: None,
                         portal_cooldown: qexed_packet::net_types::VarInt(0),
                         sea_level: qexed_packet::net_types::VarInt(63),
-                        enforces_secure_chat: false,
+                        enforces_secure_chat,
                     })
                     .await?,
                 )?;
                 // 初始化成就
-                packet_write.send(
+                packet_write.try_send(
                     PacketSend::build_send_packet(create_multiple_advancements_packet())
                     .await?,
                 )?;
@@ -492,7 +537,7 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                     .await?;
                 ReturnMessage::build(qexed_player_list::Message::PlayerJoin(player.uuid.clone(),player.username.clone())).get(&player_list_api).await?;
                 // 区块初始化:
-                packet_write.send(
+                packet_write.try_send(
                     PacketSend::build_send_packet(qexed_protocol::to_client::play::game_state_change::GameStateChange{reason:13,game_mode:0.0}).await?)?;
                 // 发送玩家附近区块
                 ReturnMessage::build(ManagerMessage::GetWorld(Some(qexed_chunk::message::world::WorldCommand::PlayerJoin {
@@ -548,6 +593,22 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                         return Ok(false);
                     }
                 };
+                let dispatch = self
+                    .events
+                    .publish(qexed_event::Event::PlayerJoin {
+                        uuid: player.uuid,
+                        name: player.username.clone(),
+                    })
+                    .await;
+                if dispatch.cancelled {
+                    log::info!("玩家 {} 的进服事件被取消,断开连接", player.username);
+                    self.disconnect("你无法加入此服务器").await?;
+                    if let Some(send) = data.get_return_send().await? {
+                        let _ = send.send(data.data);
+                    }
+                    return Ok(false);
+                }
+                self.joined = true;
                     let _ = UnReturnMessage::build(qexed_chat::message::TaskMessage::SystemEvent(qexed_chat::message::SystemEvent::PlayerJoin))
                         .post(&chat_api)
                         .await;                
@@ -568,7 +629,22 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
 
                 return Ok(false);
             }
+            TaskMessage::Kick(ref reason) => {
+                self.disconnect(reason).await?;
+                if let Some(send) = data.get_return_send().await? {
+                    let _ = send.send(data.data);
+                }
+                return Ok(false);
+            }
             TaskMessage::Close => {
+                if let (true, Some(player)) = (self.joined, &self.player) {
+                    self.events
+                        .publish(qexed_event::Event::PlayerQuit {
+                            uuid: player.uuid,
+                            name: player.username.clone(),
+                        })
+                        .await;
+                }
                 if let Some(api_ping) = &self.qexed_ping_api {
                     let _ = UnReturnMessage::build(qexed_ping::message::TaskCommand::Close)
                         .post(&api_ping)
@@ -731,7 +807,7 @@ pub struct Task<ManageMessageType> {
 }
 impl<ManageMessageType> Task<ManageMessageType>
 where
-    ManageMessageType: Send + 'static + std::fmt::Debug + Unpin,
+    ManageMessageType: Send + 'static + std::fmt::Debug + Unpin + From<Failure>,
     GameLogicActor: Send + 'static + std::fmt::Debug + Unpin + TaskEvent<ReturnMessage<TaskMessage>,ManageMessageType>, // 添加 Send
     
 {
@@ -771,8 +847,19 @@ where
                     }
                 }
                 Err(e)=>{
+                    let reason = format!("{:#}", e);
                     self.other.event(&api, &manage_api, ReturnMessage::build(TaskMessage::Close)).await?;
                     receiver.close();
+                    // 出错即停止,报告给管理器后由管理器从任务表中移除
+                    let failure = Failure {
+                        task: format!("game-logic-{}", self.other.uuid),
+                        kind: FailureKind::Error,
+                        reason,
+                        directive: Directive::Stop,
+                        restarts: 0,
+                    };
+                    log::error!("{},任务已停止", failure);
+                    let _ = manage_api.send(failure.into());
                 }
             }
         }
@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use qexed_task::message::return_message::ReturnMessage;
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -5,6 +7,25 @@ pub mod manager;
 pub mod message;
 pub mod task;
 
+// 所有玩家累计的心跳丢失与超时断开次数,供监控读取
+static MISSES: AtomicU64 = AtomicU64::new(0);
+static TIMEOUTS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeartbeatStats {
+    /// 未按时收到响应的心跳数
+    pub misses: u64,
+    /// 连续丢失过多而断开的玩家数
+    pub timeouts: u64,
+}
+
+pub fn stats() -> HeartbeatStats {
+    HeartbeatStats {
+        misses: MISSES.load(Ordering::Relaxed),
+        timeouts: TIMEOUTS.load(Ordering::Relaxed),
+    }
+}
+
 pub async fn run(
     config: qexed_config::app::qexed_heartbeat::HeartbeatConfig,
 ) -> anyhow::Result<UnboundedSender<ReturnMessage<message::ManagerCommand>>> {
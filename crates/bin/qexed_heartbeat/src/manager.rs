@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use dashmap::DashMap;
 use qexed_config::app::qexed_heartbeat::HeartbeatConfig;
 use qexed_task::{
+    supervisor::{self, Directive, SupervisorPolicy},
     event::task_manage::TaskManageEvent,
     message::{
         MessageSender, MessageType, return_message::ReturnMessage,
@@ -36,6 +37,13 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
         task_map: &DashMap<uuid::Uuid, MessageSender<UnReturnMessage<TaskCommand>>>,
         mut data: ReturnMessage<ManagerCommand>,
     ) -> anyhow::Result<bool> {
+        // 失败报告不需要回复,停止的任务从任务表中移除
+        if let ManagerCommand::ChildFailed(failure) = &data.data {
+            if failure.directive != Directive::Restart {
+                supervisor::prune(task_map);
+            }
+            return Ok(false);
+        }
         let send = match data.get_return_send().await? {
             Some(send) => send,
             None => return Ok(false),
@@ -62,7 +70,7 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                 }
                 
                 // 获取数据包发送通道
-                let packet_send: tokio::sync::mpsc::UnboundedSender<bytes::Bytes> =
+                let packet_send: qexed_task::mailbox::MailboxSender<bytes::Bytes> =
                     match packet_send.take() {
                         Some(pk) => pk,
                         None => {
@@ -76,7 +84,10 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                 // 创建心跳任务
                 let t = HeartbeatTask::new(self.config.clone(), uuid.clone(), packet_send);
                 let (task, task_sand) = Task::new(api.clone(), t);
-                task.run().await?;
+                // 玩家任务失败即停止并报告给管理器
+                task.supervise(format!("heartbeat-{}", uuid), SupervisorPolicy::stop())
+                    .run()
+                    .await?;
                 
                 // 保存任务通道
                 task_map.insert(*uuid, task_sand.clone());
@@ -99,6 +110,17 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                 let _ = send.send(data.data);
                 Ok(false)
             }
+
+            ManagerCommand::Reload(ref reload) => {
+                self.config = reload.config.clone();
+                for entry in task_map.iter() {
+                    let _ = UnReturnMessage::build(TaskCommand::Reload(self.config.clone()))
+                        .post(entry.value())
+                        .await;
+                }
+                let _ = send.send(data.data);
+                Ok(false)
+            }
             
             ManagerCommand::HeartbeatStatus(uuid, status) => {
                 match status {
@@ -113,6 +135,7 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                 let _ = send.send(ManagerCommand::HeartbeatStatus(uuid, status));
                 Ok(false)
             }
+            ManagerCommand::ChildFailed(_) => Ok(false),
         }
     }
 }
\ No newline at end of file
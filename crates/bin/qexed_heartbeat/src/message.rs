@@ -1,4 +1,6 @@
 use bytes::Bytes;
+use qexed_config::{app::qexed_heartbeat::HeartbeatConfig, tool::Reload};
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::unreturn_message::UnReturnMessage;
 use thiserror::Error;
 use tokio::sync::mpsc::UnboundedSender;
@@ -10,10 +12,17 @@ pub enum ManagerCommand {
         bool,
         Option<NewPlayerConnectError>,
         Option<UnboundedSender<UnReturnMessage<TaskCommand>>>,
-        Option<UnboundedSender<Bytes>>,
+        Option<MailboxSender<Bytes>>,
     ),
     PlayerClose(uuid::Uuid),
     HeartbeatStatus(uuid::Uuid, HeartbeatStatus),
+    Reload(Reload<HeartbeatConfig>), // 热重载,同步到所有玩家的心跳任务
+    ChildFailed(qexed_task::supervisor::Failure), // 玩家心跳任务失败
+}
+impl From<qexed_task::supervisor::Failure> for ManagerCommand {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        ManagerCommand::ChildFailed(failure)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +54,7 @@ pub enum InternalMessage {
     HeartbeatReceived(i64),
     StateChange(StateChange),
     PhaseChange(HeartbeatPhase),  // 修改：使用 HeartbeatPhase 枚举
+    Reload(HeartbeatConfig),
     Shutdown,
 }
 
@@ -60,6 +70,7 @@ pub enum TaskCommand {
     Stop,
     Heartbeat(i64),          // 心跳信号
     Part(bool),// False:配置阶段,True:游戏阶段
+    Reload(HeartbeatConfig),// 更新配置
     // CheckTimeout,      // 检查超时
     Close,
 }
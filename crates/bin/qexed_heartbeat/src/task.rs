@@ -5,12 +5,13 @@ use qexed_tcp_connect::PacketSend;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::{
-    mpsc::{Receiver, Sender, UnboundedSender, channel},
+    mpsc::{Receiver, Sender, channel},
     Mutex,
 };
 use uuid::Uuid;
 
 use crate::message::{HeartbeatPhase, InternalMessage, ManagerCommand, StateChange, TaskCommand};
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::{
     event::task::TaskEvent,
     message::{
@@ -144,6 +145,7 @@ impl HeartbeatState {
     
     fn increment_miss_count(&mut self) {
         self.consecutive_misses += 1;
+        crate::MISSES.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     }
     
     fn reset_miss_count(&mut self) {
@@ -159,7 +161,7 @@ impl HeartbeatState {
 pub struct HeartbeatTask {
     config: HeartbeatConfig,
     player_uuid: Uuid,
-    packet_send: UnboundedSender<Bytes>,
+    packet_send: MailboxSender<Bytes>,
     internal_sender: Sender<InternalMessage>,
     task_handle: Option<tokio::task::JoinHandle<()>>,
 }
@@ -168,7 +170,7 @@ impl HeartbeatTask {
     pub fn new(
         config: HeartbeatConfig,
         player_uuid: Uuid,
-        packet_send: UnboundedSender<Bytes>,
+        packet_send: MailboxSender<Bytes>,
     ) -> Self {
         let (internal_sender, _) = channel(100);
         
@@ -189,7 +191,7 @@ impl HeartbeatTask {
     }
     
     async fn send_heartbeat(
-        packet_send: &UnboundedSender<Bytes>,
+        packet_send: &MailboxSender<Bytes>,
         heartbeat_id: i64,
         player_uuid: Uuid,
         phase: HeartbeatPhase,
@@ -206,7 +208,7 @@ impl HeartbeatTask {
         };
         
         packet_send
-            .send(heartbeat_data)
+            .try_send(heartbeat_data)
             .map_err(|e| anyhow::anyhow!("Failed to send heartbeat packet: {e}"))
     }
     
@@ -227,6 +229,7 @@ impl HeartbeatTask {
         phase: HeartbeatPhase,
     ) {
         log::warn!("Heartbeat timeout for player {player_uuid} in {phase:?} phase");
+        crate::TIMEOUTS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         
         // 通知管理器心跳超时
         Self::notify_heartbeat_status(
@@ -284,7 +287,7 @@ impl HeartbeatTask {
     async fn handle_internal_message(
         state: &mut HeartbeatState,
         msg: InternalMessage,
-        packet_send: &UnboundedSender<Bytes>,
+        packet_send: &MailboxSender<Bytes>,
         player_uuid: Uuid,
         api: &MessageSender<UnReturnMessage<TaskCommand>>,
         manage_api: &MessageSender<ReturnMessage<ManagerCommand>>,
@@ -323,6 +326,10 @@ impl HeartbeatTask {
                 log::debug!("Player {player_uuid} heartbeat phase changed to {phase:?}");
             }
             
+            InternalMessage::Reload(config) => {
+                state.config = config;
+            }
+
             InternalMessage::Shutdown => {
                 return Err(anyhow::anyhow!("Shutdown requested"));
             }
@@ -333,7 +340,7 @@ impl HeartbeatTask {
     
     async fn handle_heartbeat_tick(
         state: &mut HeartbeatState,
-        packet_send: &UnboundedSender<Bytes>,
+        packet_send: &MailboxSender<Bytes>,
         player_uuid: Uuid,
         api: &MessageSender<UnReturnMessage<TaskCommand>>,
         manage_api: &MessageSender<ReturnMessage<ManagerCommand>>,
@@ -421,6 +428,12 @@ impl TaskEvent<UnReturnMessage<TaskCommand>, ReturnMessage<ManagerCommand>> for
                 log::info!("Player {} heartbeat phase changed to {:?}", self.player_uuid, phase);
             }
             
+            TaskCommand::Reload(config) => {
+                self.config = config.clone();
+                // 后台任务未启动时没有接收方
+                let _ = self.send_internal(InternalMessage::Reload(config)).await;
+            }
+
             TaskCommand::Close => {
                 // 发送关闭信号
                 let _ = self.send_internal(InternalMessage::Shutdown).await;
@@ -1,4 +1,5 @@
-use qexed_config::app::qexed_one::One;
+use qexed_config::{app::qexed_one::One, tool::AppConfigTrait};
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::{return_message::ReturnMessage, unreturn_message::UnReturnMessage};
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -15,17 +16,17 @@ pub struct Api {
     pub tcp_connect:
         UnboundedSender<ReturnMessage<qexed_tcp_connect_app::messages::ManagerCommand>>,
     /// 玩家Ping维持服务
-    pub ping: UnboundedSender<ReturnMessage<qexed_ping::message::ManagerCommand>>,
+    pub ping: MailboxSender<ReturnMessage<qexed_ping::message::ManagerCommand>>,
     /// 玩家核心逻辑服务
-    pub game_logic: UnboundedSender<ReturnMessage<qexed_game_logic::message::ManagerMessage>>,
+    pub game_logic: MailboxSender<ReturnMessage<qexed_game_logic::message::ManagerMessage>>,
     /// 实体id分配器服务
     pub entity_id_allocator: UnboundedSender<ReturnMessage<qexed_entity_id_allocator::Message>>,
     /// 数据包分流服务
-    pub packet_split: UnboundedSender<ReturnMessage<qexed_packet_split::message::ManagerMessage>>,
+    pub packet_split: MailboxSender<ReturnMessage<qexed_packet_split::message::ManagerMessage>>,
     /// 心跳服务
     pub heartbeat: UnboundedSender<ReturnMessage<qexed_heartbeat::message::ManagerCommand>>,
     /// 聊天服务
-    pub chat: UnboundedSender<ReturnMessage<qexed_chat::message::ManagerMessage>>,
+    pub chat: MailboxSender<ReturnMessage<qexed_chat::message::ManagerMessage>>,
     /// 指令服务
     pub command: UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
     /// 规则服务
@@ -33,20 +34,46 @@ pub struct Api {
     /// 区块服务
     pub chunk: UnboundedSender<UnReturnMessage<qexed_chunk::message::world::WorldCommand>>,
     /// Title指令服务
-    pub title:UnboundedSender<ReturnMessage<qexed_title::message::ManagerMessage>>,
+    pub title:MailboxSender<ReturnMessage<qexed_title::message::ManagerMessage>>,
+    /// 插件管理服务
+    pub plugin:UnboundedSender<ReturnMessage<qexed_plugin_manage::message::ManagerMessage>>,
+    /// 事件总线
+    pub events: qexed_event::EventBus,
+    /// 数据包钩子
+    pub hooks: qexed_event::PacketHooks,
 }
 impl Api {
     pub async fn init(config: One) -> anyhow::Result<Self> {
-        let command = qexed_command::run(config.command).await?;
+        let events = qexed_event::EventBus::new(&config.event);
+        let hooks = qexed_event::PacketHooks::new(&config.event);
+        let command = qexed_command::run(config.command, events.clone()).await?;
+        #[cfg(feature = "distributed")]
+        let player_list = match config.remote_services.get("player_list") {
+            Some(endpoint) => {
+                log::info!("[服务] 玩家列表 使用远程服务 {}", endpoint);
+                qexed_task::remote::connect(endpoint.parse()?, &config.remote_token)
+            }
+            None => qexed_player_list::run(config.player_list).await?,
+        };
+        #[cfg(not(feature = "distributed"))]
         let player_list = qexed_player_list::run(config.player_list).await?;
-        let black_list = qexed_blacklist::run(config.black_list).await?;
-        let white_list = qexed_whitelist::run(config.white_list).await?;
+        // 名单由各自的服务修改并写回 qexed.toml 中对应的配置节
+        let black_list = qexed_blacklist::run(
+            config.black_list,
+            Box::new(|list| One::update("black_list.simple", list)),
+        )
+        .await?;
+        let white_list = qexed_whitelist::run(
+            config.white_list,
+            Box::new(|list| One::update("white_list.simple", list)),
+        )
+        .await?;
         let server_status = qexed_status::run(config.server_status, player_list.clone()).await?;
         let ping = qexed_ping::run(config.ping).await?;
         let heartbeat = qexed_heartbeat::run(config.heartbeat).await?;
-        let chat = qexed_chat::run(config.chat,player_list.clone()).await?;
+        let chat = qexed_chat::run(config.chat,player_list.clone(), events.clone()).await?;
         let title = qexed_title::run(config.title, player_list.clone()).await?;
-        let packet_split = qexed_packet_split::run(config.packet_split).await?;
+        let packet_split = qexed_packet_split::run(config.packet_split, events.clone(), hooks.clone()).await?;
         let chunk = qexed_chunk::run(config.chunk).await?;
         let game_logic = qexed_game_logic::run(
             config.game_logic,
@@ -58,6 +85,7 @@ impl Api {
             player_list.clone(),
             chunk.clone(),
             title.clone(),
+            events.clone(),
         )
         .await?;
         let tcp_connect = qexed_tcp_connect_app::run(
@@ -67,11 +95,33 @@ impl Api {
             black_list.clone(),
             white_list.clone(),
             game_logic.clone(),
+            hooks.clone(),
         )
         .await?;
         let entity_id_allocator =
             qexed_entity_id_allocator::run(config.entity_id_allocator).await?;
         let rule = qexed_rule::run(config.rule).await?;
+        let plugin = qexed_plugin_manage::run(
+            config.plugin,
+            chat.clone(),
+            player_list.clone(),
+            events.clone(),
+            hooks.clone(),
+            command.clone(),
+        )
+        .await?;
+        qexed_web::run(
+            config.web,
+            qexed_web::Services {
+                player_list: player_list.clone(),
+                chunk: chunk.clone(),
+                game_logic: game_logic.clone(),
+                white_list: white_list.clone(),
+                black_list: black_list.clone(),
+                command: command.clone(),
+            },
+        )
+        .await?;
         
         Ok(Self {
             player_list,
@@ -89,6 +139,9 @@ impl Api {
             rule: rule,
             chunk: chunk,
             title:title,
+            plugin,
+            events,
+            hooks,
         })
     }
     pub async fn _listen() -> anyhow::Result<()> {
@@ -99,8 +152,16 @@ impl Api {
         qexed_chat::command::register_tell_command(&self.command, self.chat.clone()).await?;
         qexed_chat::command::register_me_command(&self.command, self.chat.clone()).await?;
         qexed_chat::command::register_say_command(&self.command, self.chat.clone()).await?;
+        qexed_chat::command::register_reply_command(&self.command, self.chat.clone()).await?;
+        qexed_chat::command::register_mute_command(&self.command, self.chat.clone()).await?;
+        qexed_chat::command::register_unmute_command(&self.command, self.chat.clone()).await?;
+        qexed_chat::command::register_chatlog_command(&self.command, self.chat.clone()).await?;
         qexed_chunk::command::seed::register_seed_command(&self.command, self.chunk.clone()).await?;
+        qexed_blacklist::command::register_pardon_command(&self.command, self.black_list.clone()).await?;
+        qexed_block::command::register_blockinfo_command(&self.command).await?;
         qexed_title::command::register_title_command_full(&self.command, self.title.clone()).await?;
+        qexed_plugin_manage::command::register_plugins_command(&self.command, self.plugin.clone()).await?;
+        qexed_plugin_manage::command::register_plugin_command(&self.command, self.plugin.clone()).await?;
         Ok(())
     }
 }
@@ -1,12 +1,21 @@
 use log;
-use qexed_config::{app::qexed_one::One, tool::AppConfigTrait};
+use qexed_config::{app::qexed_one::One, tool::{AppConfigTrait, overrides}};
 use qexed_task::message::{MessageType, return_message::ReturnMessage};
 use tklog::{ASYNC_LOG, Format, MODE};
 mod api;
+mod reload;
 mod server;
+mod shutdown;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let args = overrides::Args::parse(std::env::args().skip(1))?;
+    args.install();
+    if args.print_config {
+        let config = One::load_or_default()?;
+        println!("{}", toml::to_string_pretty(&config)?);
+        return Ok(());
+    }
     // 创建日志通道
     let (log_tx, log_rx): (tokio::sync::mpsc::Sender<String>, _) = tokio::sync::mpsc::channel(1000);
 
@@ -40,14 +49,35 @@ async fn main() -> anyhow::Result<()> {
     // 启动服务器
     log::info!("读取配置文件中");
     let config: One = One::load_or_create_default()?;
+    for key in overrides::unmatched(&toml::Table::try_from(&config)?) {
+        log::warn!("命令行覆盖的配置项 {} 不存在", key);
+    }
     log::info!("读取配置文件完成");
+    qexed_task::message::return_message::set_debug(config.task_debug);
+    let shutdown_config = config.shutdown.clone();
+    let watch_config = config.watch_config;
 
     log::info!("服务初始化");
     let server = server::Server::init(config).await?;
     log::info!("服务初始化完成");
     log::info!("指令注册中");
     server.register().await?;
+    let reloader = std::sync::Arc::new(tokio::sync::Mutex::new(reload::Reloader::new(&server.api)?));
+    reload::register_reload_command(&server.api.command, reloader.clone()).await?;
+    if watch_config {
+        reload::watch(reloader);
+    }
     log::info!("指令注册完成");
+    // SIGINT/SIGTERM 与 /stop 走同一关服流程
+    let signal_tx = log_tx.clone();
+    tokio::spawn(async move {
+        if let Err(e) = shutdown::signal().await {
+            log::error!("监听关闭信号失败: {}", e);
+            return;
+        }
+        log::info!("收到关闭信号,正在关闭服务器...");
+        let _ = signal_tx.send("::qexed_cli::close".to_string()).await;
+    });
     // 注册命令 /stop
         // name: String,
         // doc: String,
@@ -63,6 +93,7 @@ async fn main() -> anyhow::Result<()> {
             permission:"qexed.console.stop".to_string(),
             parameters: vec![],
             aliases:vec![],
+            tree:None,
             api:Some(cmd_tx),
             success:false,
     })
@@ -115,12 +146,13 @@ async fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
+    let command_api = server.api.command.clone();
     // 启动命令处理任务
     let command_handle = tokio::spawn(async move {
         while let Some(cmd) = command_rx.recv().await {
             // log::info!("收到命令: {}", cmd);
             let _ = ReturnMessage::build(qexed_command::message::ManagerCommand::Command(cmd))
-                .get(&server.api.command)
+                .get(&command_api)
                 .await;
             // // 处理服务器命令
             // match cmd.as_str() {
@@ -157,6 +189,7 @@ async fn main() -> anyhow::Result<()> {
 
     // 等待命令处理任务完成
     command_handle.abort(); // 如果CLI退出，就停止命令处理
+    shutdown::Shutdown::new(&server.api, shutdown_config).run().await;
 
     match cli_result {
         Ok(_) => {
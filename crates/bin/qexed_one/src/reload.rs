@@ -0,0 +1,194 @@
+// 配置热重载
+// 重新读取并校验配置文件,把可以在线修改的配置推送给运行中的服务,玩家连接不受影响
+// 其余发生变化的配置项只做提示,需要重启服务器才能生效
+use std::{sync::Arc, time::{Duration, SystemTime}};
+
+use qexed_command::message::ManagerCommand as CommandManagerCommand;
+use qexed_config::{
+    app::qexed_one::One,
+    tool::{AppConfigTrait, Reload, changed_fields},
+};
+use qexed_task::mailbox::MailboxSender;
+use qexed_task::message::{MessageType, return_message::ReturnMessage};
+use tokio::sync::{Mutex, mpsc::UnboundedSender};
+
+use crate::api::Api;
+
+// 支持在线修改的服务,与配置文件中的配置项同名
+const SERVICES: [&str; 4] = ["server_status", "white_list", "ping", "heartbeat"];
+
+// 检查配置文件修改时间的间隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct Reloader {
+    server_status: UnboundedSender<ReturnMessage<qexed_status::Message>>,
+    white_list: UnboundedSender<ReturnMessage<qexed_whitelist::Message>>,
+    ping: MailboxSender<ReturnMessage<qexed_ping::message::ManagerCommand>>,
+    heartbeat: UnboundedSender<ReturnMessage<qexed_heartbeat::message::ManagerCommand>>,
+    /// 运行中的配置
+    current: One,
+}
+
+impl Reloader {
+    pub fn new(api: &Api) -> anyhow::Result<Self> {
+        Ok(Self {
+            server_status: api.server_status.clone(),
+            white_list: api.white_list.clone(),
+            ping: api.ping.clone(),
+            heartbeat: api.heartbeat.clone(),
+            current: One::load_or_create_default()?,
+        })
+    }
+
+    /// 重载全部或指定服务的配置,返回每一项的处理结果
+    pub async fn reload(&mut self, service: Option<&str>) -> anyhow::Result<Vec<String>> {
+        if let Some(service) = service
+            && !SERVICES.contains(&service)
+        {
+            anyhow::bail!("未知的服务 {},可重载: {}", service, SERVICES.join(", "));
+        }
+        let new = One::load()?;
+        let mut report = Vec::new();
+        for field in changed_fields(&self.current, &new)? {
+            if service.is_some_and(|service| service != field) {
+                continue;
+            }
+            let restart = match field.as_str() {
+                "server_status" => {
+                    let reload = Reload::new(new.server_status.clone());
+                    let message = ReturnMessage::build(qexed_status::Message {
+                        data: None,
+                        reload: Some(reload),
+                    })
+                    .get(&self.server_status)
+                    .await?;
+                    self.current.server_status = new.server_status.clone();
+                    message.reload.map(|reload| reload.restart).unwrap_or_default()
+                }
+                "white_list" => {
+                    let reload = Reload::new(new.white_list.clone());
+                    let message = ReturnMessage::build(qexed_whitelist::Message::Reload(reload))
+                        .get(&self.white_list)
+                        .await?;
+                    self.current.white_list = new.white_list.clone();
+                    match message {
+                        qexed_whitelist::Message::Reload(reload) => reload.restart,
+                        _ => Vec::new(),
+                    }
+                }
+                "ping" => {
+                    let reload = Reload::new(new.ping.clone());
+                    let message = ReturnMessage::build(qexed_ping::message::ManagerCommand::Reload(reload))
+                        .get(&self.ping)
+                        .await?;
+                    self.current.ping = new.ping.clone();
+                    match message {
+                        qexed_ping::message::ManagerCommand::Reload(reload) => reload.restart,
+                        _ => Vec::new(),
+                    }
+                }
+                "heartbeat" => {
+                    let reload = Reload::new(new.heartbeat.clone());
+                    let message =
+                        ReturnMessage::build(qexed_heartbeat::message::ManagerCommand::Reload(reload))
+                            .get(&self.heartbeat)
+                            .await?;
+                    self.current.heartbeat = new.heartbeat.clone();
+                    match message {
+                        qexed_heartbeat::message::ManagerCommand::Reload(reload) => reload.restart,
+                        _ => Vec::new(),
+                    }
+                }
+                _ => {
+                    report.push(format!("{} 已修改,需要重启才能生效", field));
+                    continue;
+                }
+            };
+            report.push(format!("{} 已重载", field));
+            for field in restart {
+                report.push(format!("{} 已修改,需要重启才能生效", field));
+            }
+        }
+        if report.is_empty() {
+            report.push("配置没有变化".to_string());
+        }
+        Ok(report)
+    }
+}
+
+pub async fn register_reload_command(
+    command_api: &UnboundedSender<ReturnMessage<CommandManagerCommand>>,
+    reloader: Arc<Mutex<Reloader>>,
+) -> anyhow::Result<()> {
+    qexed_command::register::register_command(
+        "reload",
+        "重新加载配置文件",
+        "qexed.console.reload",
+        vec![qexed_command::message::CommandParameter {
+            name: "service".to_string(),
+            description: "只重载指定服务的配置".to_string(),
+            required: false,
+            param_type: qexed_command::message::ParameterType::String {
+                behavior: qexed_command::message::StringBehavior::SingleWord,
+            },
+            suggestions: Some(SERVICES.iter().map(|s| s.to_string()).collect()),
+        }],
+        vec![],
+        command_api,
+        move |mut cmd_rx| {
+            let reloader = reloader.clone();
+            async move {
+                while let Some(cmd) = cmd_rx.recv().await {
+                    // 与 /stop 相同,只允许控制台重载配置
+                    if !cmd.is_cmd {
+                        cmd.send_chat_message("§c该指令只能在控制台使用").await?;
+                        continue;
+                    }
+                    let service = cmd.command_line.split_whitespace().nth(1).map(str::to_string);
+                    let result = reloader.lock().await.reload(service.as_deref()).await;
+                    match result {
+                        Ok(report) => {
+                            for line in report {
+                                cmd.send_chat_message(&format!("§a[重载] §f{}", line)).await?;
+                            }
+                        }
+                        Err(e) => {
+                            cmd.send_chat_message(&format!("§c[重载] 配置未应用: {}", e)).await?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+        },
+    )
+    .await
+}
+
+fn modified() -> Option<SystemTime> {
+    std::fs::metadata(One::path()).and_then(|meta| meta.modified()).ok()
+}
+
+/// 配置文件修改后自动重载全部配置
+pub fn watch(reloader: Arc<Mutex<Reloader>>) {
+    tokio::spawn(async move {
+        let mut last = modified();
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let now = modified();
+            if now == last {
+                continue;
+            }
+            last = now;
+            log::info!("[重载] 检测到配置文件修改");
+            match reloader.lock().await.reload(None).await {
+                Ok(report) => {
+                    for line in report {
+                        log::info!("[重载] {}", line);
+                    }
+                }
+                Err(e) => log::error!("[重载] 配置未应用: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,116 @@
+// 关服流程
+// 先停止接入并断开玩家,再按依赖顺序关闭服务: 聊天 -> 游戏逻辑 -> 区块保存 -> 存储
+// 每个阶段都有时间上限,超时后记录警告并继续下一阶段,保证进程总能退出
+use std::{future::Future, time::Duration};
+
+use qexed_config::app::qexed_shutdown::ShutdownConfig;
+use qexed_task::message::{MessageType, return_message::ReturnMessage, unreturn_message::UnReturnMessage};
+
+use crate::api::Api;
+
+// 等待玩家任务退出时的查询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct Shutdown<'a> {
+    api: &'a Api,
+    config: ShutdownConfig,
+}
+
+impl<'a> Shutdown<'a> {
+    pub fn new(api: &'a Api, config: ShutdownConfig) -> Self {
+        Self { api, config }
+    }
+
+    pub async fn run(&self) {
+        log::info!("[关服] 开始关闭服务器");
+        let reason = self.config.kick_message.clone();
+        self.stage("停止接入", async {
+            ReturnMessage::build(qexed_tcp_connect_app::messages::ManagerCommand::Shutdown(reason.clone()))
+                .get(&self.api.tcp_connect)
+                .await?;
+            Ok(())
+        })
+        .await;
+        self.stage("断开玩家", async {
+            ReturnMessage::build(qexed_game_logic::message::ManagerMessage::Shutdown(reason.clone()))
+                .get(&self.api.game_logic)
+                .await?;
+            self.wait_players().await
+        })
+        .await;
+        self.stage("聊天", async {
+            ReturnMessage::build(qexed_chat::message::ManagerMessage::Shutdown)
+                .get(&self.api.chat)
+                .await?;
+            Ok(())
+        })
+        .await;
+        self.stage("游戏逻辑", async {
+            ReturnMessage::build(qexed_game_logic::message::ManagerMessage::CloseAll)
+                .get(&self.api.game_logic)
+                .await?;
+            self.wait_players().await
+        })
+        .await;
+        self.stage("区块保存", async {
+            let (result, wait) = tokio::sync::oneshot::channel();
+            UnReturnMessage::build(qexed_chunk::message::world::WorldCommand::WorldCloseCommand { result })
+                .post(&self.api.chunk)
+                .await?;
+            wait.await?;
+            Ok(())
+        })
+        .await;
+        // 停用所有插件,让插件有机会调用 on_disable 并写入插件存储
+        self.stage("存储", async {
+            ReturnMessage::build(qexed_plugin_manage::message::ManagerMessage::Close)
+                .get(&self.api.plugin)
+                .await?;
+            Ok(())
+        })
+        .await;
+        log::info!("[关服] 所有服务已关闭");
+    }
+
+    async fn stage<F>(&self, name: &str, stage: F)
+    where
+        F: Future<Output = anyhow::Result<()>>,
+    {
+        let timeout = Duration::from_secs(self.config.stage_timeout_secs);
+        log::info!("[关服] {}", name);
+        match tokio::time::timeout(timeout, stage).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => log::error!("[关服] {} 阶段出错: {}", name, e),
+            Err(_) => log::warn!("[关服] {} 阶段超过 {:?},跳过", name, timeout),
+        }
+    }
+
+    // 玩家断开后连接层会关闭对应的游戏逻辑任务
+    async fn wait_players(&self) -> anyhow::Result<()> {
+        loop {
+            if let qexed_game_logic::message::ManagerMessage::Online(0) =
+                ReturnMessage::build(qexed_game_logic::message::ManagerMessage::Online(0))
+                    .get(&self.api.game_logic)
+                    .await?
+            {
+                return Ok(());
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// 收到 SIGINT 或 SIGTERM 时返回
+pub async fn signal() -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut term = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result?,
+            _ = term.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
@@ -1,5 +1,5 @@
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::return_message::ReturnMessage;
-use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{manager::PacketSplitManagerActor, message::ManagerMessage};
 pub mod manager;
@@ -7,12 +7,20 @@ pub mod message;
 pub mod task;
 pub async fn run(
     config: qexed_config::app::qexed_packet_split::PacketSplitConfig,
-) -> anyhow::Result<UnboundedSender<ReturnMessage<ManagerMessage>>> {
+    events: qexed_event::EventBus,
+    hooks: qexed_event::PacketHooks,
+) -> anyhow::Result<MailboxSender<ReturnMessage<ManagerMessage>>> {
     let manager_actor = PacketSplitManagerActor::new( 
         config,
+        events,
+        hooks,
     );
     let (manager_task, manager_sender) =
-        qexed_task::task::task_manage::TaskManage::new(manager_actor);
+        qexed_task::task::task_manage::TaskManage::with_mailbox(
+            manager_actor,
+            "packet_split",
+            qexed_task::mailbox::MailboxConfig::manager(),
+        );
     manager_task.run().await?;
     log::info!("[服务] 数据包分流 已启用");
     Ok(manager_sender)
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use dashmap::DashMap;
 use qexed_task::{
+    supervisor::{self, Directive, SupervisorPolicy},
     event::task_manage::TaskManageEvent,
     message::{MessageSender, MessageType, return_message::ReturnMessage},
     task::task::Task,
@@ -15,13 +16,19 @@ use crate::{
 #[derive(Debug)]
 pub struct PacketSplitManagerActor {
     _config: qexed_config::app::qexed_packet_split::PacketSplitConfig,
+    events: qexed_event::EventBus,
+    hooks: qexed_event::PacketHooks,
 }
 impl PacketSplitManagerActor {
     pub fn new(
         config: qexed_config::app::qexed_packet_split::PacketSplitConfig,
+        events: qexed_event::EventBus,
+        hooks: qexed_event::PacketHooks,
     ) -> Self {
         Self {
             _config:config,
+            events,
+            hooks,
         }
     }
 }
@@ -35,6 +42,13 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, ReturnMessage<TaskMess
         task_map: &DashMap<Uuid, MessageSender<ReturnMessage<TaskMessage>>>,
         mut data: ReturnMessage<ManagerMessage>,
     ) -> anyhow::Result<bool> {
+        // 失败报告不需要回复,停止的任务从任务表中移除
+        if let ManagerMessage::ChildFailed(failure) = &data.data {
+            if failure.directive != Directive::Restart {
+                supervisor::prune(task_map);
+            }
+            return Ok(false);
+        }
         
         let send = match data.get_return_send().await? {
             Some(send) => send,
@@ -52,8 +66,11 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, ReturnMessage<TaskMess
                     let _ = send.send(data.data);
                     return Ok(false);
                 }
-                let (task, task_sand) = Task::new(api.clone(), QexedPacketSplitActor::new(uuid));
-                task.run().await?;
+                let (task, task_sand) = Task::new(api.clone(), QexedPacketSplitActor::new(uuid, self.events.clone(), self.hooks.clone()));
+                // 玩家任务失败即停止并报告给管理器
+                task.supervise(format!("packet-split-{}", uuid), SupervisorPolicy::stop())
+                    .run()
+                    .await?;
                 task_map.insert(uuid, task_sand.clone());
                 *task_api = Some(task_sand);
                 *is_true = true;
@@ -75,6 +92,7 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, ReturnMessage<TaskMess
                 return Ok(false);
 
             }
+            ManagerMessage::ChildFailed(_) => Ok(false),
         }
     }
 }
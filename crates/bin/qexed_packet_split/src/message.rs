@@ -1,4 +1,5 @@
 use bytes::Bytes;
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::{return_message::ReturnMessage, unreturn_message::UnReturnMessage};
 use thiserror::Error;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
@@ -15,13 +16,19 @@ pub enum ManagerMessage {
     // PlayerPlayPart(uuid::Uuid),// 玩家进入游戏阶段
     PlayerClose(uuid::Uuid),  // 游戏连接关闭
     ConnectClose(uuid::Uuid), // 连接关闭
+    ChildFailed(qexed_task::supervisor::Failure), // 玩家数据包分发任务失败
+}
+impl From<qexed_task::supervisor::Failure> for ManagerMessage {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        ManagerMessage::ChildFailed(failure)
+    }
 }
 #[derive(Debug)]
 pub enum TaskMessage {
     Start(
         qexed_player::Player,
         Option<UnboundedReceiver<Vec<u8>>>, // 数据包接收器
-        Option<UnboundedSender<Bytes>>, // 数据包发送器
+        Option<MailboxSender<Bytes>>, // 数据包发送器
         // Option<UnboundedSender<UnReturnMessage<qexed_ping::message::TaskCommand>>>,// Ping服务:由上层服务 qexed_game_logic 提供
         Option<UnboundedSender<UnReturnMessage<qexed_heartbeat::message::TaskCommand>>>,// 心跳服务
         Option<UnboundedSender<UnReturnMessage<qexed_chat::message::TaskMessage>>>,// 聊天服务
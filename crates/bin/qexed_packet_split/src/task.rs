@@ -3,6 +3,8 @@ use bytes::Bytes;
 use qexed_packet::PacketCodec;
 use qexed_player::Player;
 use qexed_protocol::to_server::play::{keep_alive::KeepAlive, pong::Pong};
+use qexed_tcp_connect::PacketSend;
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::{
     event::task::TaskEvent,
     message::{
@@ -20,16 +22,20 @@ pub struct QexedPacketSplitActor {
     uuid: Uuid,
     player: Option<Player>,
     packet_read: Option<UnboundedReceiver<Vec<u8>>>,
-    packet_write: Option<UnboundedSender<Bytes>>,
+    packet_write: Option<MailboxSender<Bytes>>,
     qexed_ping_api: Option<UnboundedSender<UnReturnMessage<qexed_ping::message::TaskCommand>>>,
     qexed_heartbeat_api:
         Option<UnboundedSender<UnReturnMessage<qexed_heartbeat::message::TaskCommand>>>,
     qexed_chat_api:Option<UnboundedSender<UnReturnMessage<qexed_chat::message::TaskMessage>>>,
     qexed_command_api:Option<UnboundedSender<UnReturnMessage<qexed_command::message::TaskCommand>>>,
+    events: qexed_event::EventBus,
+    hooks: qexed_event::PacketHooks,
 }
 impl QexedPacketSplitActor {
-    pub fn new(uuid: Uuid) -> Self {
+    pub fn new(uuid: Uuid, events: qexed_event::EventBus, hooks: qexed_event::PacketHooks) -> Self {
         Self {
+            events,
+            hooks,
             uuid,
             player: None,
             packet_read: None,
@@ -144,6 +150,25 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>>
                 // UnReturnMessage::build(qexed_ping::message::TaskCommand::UpdatePart(qexed_ping::message::Part::Play)).post(&qexed_ping_api).await?;
                 // UnReturnMessage::build(qexed_ping::message::TaskCommand::Start).post(&qexed_ping_api).await?;
                 while let Some(raw_data) = packet_read.recv().await {
+                    // 没有插件钩子时直接分发,不复制数据包
+                    let raw_data = if self.hooks.is_active(qexed_event::packet::HookPoint::Split) {
+                        match self
+                            .hooks
+                            .apply(
+                                self.uuid,
+                                qexed_event::packet::HookPoint::Split,
+                                qexed_event::packet::Direction::Serverbound,
+                                qexed_event::packet::State::Play,
+                                raw_data,
+                            )
+                            .await
+                        {
+                            Some(raw_data) => raw_data,
+                            None => continue,
+                        }
+                    } else {
+                        raw_data
+                    };
                     let mut buf: bytes::BytesMut = bytes::BytesMut::new();
                     buf.extend_from_slice(&raw_data);
                     let mut reader = qexed_packet::PacketReader::new(Box::new(&mut buf));
@@ -154,8 +179,34 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>>
                             let pk = qexed_tcp_connect::decode_packet::<
                                 qexed_protocol::to_server::play::chat_command::ChatCommand,
                             >(&mut reader)?;
-                            let _ = UnReturnMessage::build(qexed_command::message::TaskCommand::Command(pk.command)).post(&qexed_command_api).await;
-                            
+                            // 指令先经过聊天服务的刷屏检测再转发到指令服务
+                            let _ = UnReturnMessage::build(qexed_chat::message::TaskMessage::CommandEvent(pk.command, qexed_command_api.clone())).post(&qexed_chat_api).await;
+                        }
+                        0x07 => {
+                            let pk = qexed_tcp_connect::decode_packet::<
+                                qexed_protocol::to_server::play::chat_command_signed::ChatCommandSigned,
+                            >(&mut reader)?;
+                            // 签名指令同样携带已读更新,需要同步给聊天服务
+                            let _ = UnReturnMessage::build(qexed_chat::message::TaskMessage::LastSeenUpdate(pk.offset.0, pk.acknowledged, pk.checksum)).post(&qexed_chat_api).await;
+                            let _ = UnReturnMessage::build(qexed_chat::message::TaskMessage::CommandEvent(pk.command, qexed_command_api.clone())).post(&qexed_chat_api).await;
+                        }
+                        0x05 => {
+                            let pk = qexed_tcp_connect::decode_packet::<
+                                qexed_protocol::to_server::play::chat_ack::ChatAck,
+                            >(&mut reader)?;
+                            let _ = UnReturnMessage::build(qexed_chat::message::TaskMessage::ChatAck(pk.offset.0)).post(&qexed_chat_api).await;
+                        }
+                        0x09 => {
+                            let pk = qexed_tcp_connect::decode_packet::<
+                                qexed_protocol::to_server::play::chat_session_update::ChatSessionUpdate,
+                            >(&mut reader)?;
+                            let _ = UnReturnMessage::build(qexed_chat::message::TaskMessage::SessionUpdate(pk)).post(&qexed_chat_api).await;
+                        }
+                        0x0E => {
+                            let pk = qexed_tcp_connect::decode_packet::<
+                                qexed_protocol::to_server::play::command_suggestion::CommandSuggestion,
+                            >(&mut reader)?;
+                            let _ = UnReturnMessage::build(qexed_command::message::TaskCommand::Suggestion(pk.transaction_id.0, pk.text)).post(&qexed_command_api).await;
                         }
                         0x08 => {
                             let pk = qexed_tcp_connect::decode_packet::<
@@ -163,6 +214,38 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>>
                             >(&mut reader)?;
                             let _ = UnReturnMessage::build(qexed_chat::message::TaskMessage::ChatEvent(pk)).post(&qexed_chat_api).await;
                         }
+                        0x28 => {
+                            let pk = qexed_tcp_connect::decode_packet::<
+                                qexed_protocol::to_server::play::player_action::PlayerAction,
+                            >(&mut reader)?;
+                            // 方块状态尚未由服务端维护,放行时沿用客户端的预测,只处理完成挖掘
+                            if pk.status.0 == 2
+                                && self.events.has_listeners(qexed_event::EventKind::BlockBreak)
+                                && let Some(player) = &self.player
+                            {
+                                let dispatch = self
+                                    .events
+                                    .publish(qexed_event::Event::BlockBreak {
+                                        uuid: player.uuid,
+                                        name: player.username.clone(),
+                                        x: pk.location.x,
+                                        y: pk.location.y,
+                                        z: pk.location.z,
+                                    })
+                                    .await;
+                                if dispatch.cancelled {
+                                    // 确认序号后客户端会恢复被破坏的方块
+                                    _packet_write.try_send(
+                                        PacketSend::build_send_packet(
+                                            qexed_protocol::to_client::play::block_changed_ack::BlockChangedAck {
+                                                sequence: pk.sequence,
+                                            },
+                                        )
+                                        .await?,
+                                    )?;
+                                }
+                            }
+                        }
                         0x1b => {
                             let pk = qexed_tcp_connect::decode_packet::<KeepAlive>(&mut reader)?;
                             let _ = UnReturnMessage::build(
@@ -1,17 +1,42 @@
+use std::{sync::LazyLock, time::Duration};
+
+use dashmap::DashMap;
+use uuid::Uuid;
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::return_message::ReturnMessage;
-use tokio::sync::mpsc::UnboundedSender;
 use crate::{manager::PingManagerActor, message::ManagerCommand};
 pub mod manager;
 pub mod message;
 pub mod task;
+
+// 各玩家最近一次测得的延迟,供监控读取
+static LATENCY: LazyLock<DashMap<Uuid, Duration>> = LazyLock::new(DashMap::new);
+
+/// 在线玩家最近一次测得的延迟
+pub fn latency() -> Vec<(Uuid, Duration)> {
+    LATENCY.iter().map(|entry| (*entry.key(), *entry.value())).collect()
+}
+
+pub(crate) fn record_latency(uuid: Uuid, latency: Duration) {
+    LATENCY.insert(uuid, latency);
+}
+
+pub(crate) fn forget_latency(uuid: &Uuid) {
+    LATENCY.remove(uuid);
+}
+
 pub async fn run(
     config: qexed_config::app::qexed_ping::PingConfig,
-) -> anyhow::Result<UnboundedSender<ReturnMessage<ManagerCommand>>> {
+) -> anyhow::Result<MailboxSender<ReturnMessage<ManagerCommand>>> {
     let manager_actor = PingManagerActor::new( 
         config,
     );
     let (manager_task, manager_sender) =
-        qexed_task::task::task_manage::TaskManage::new(manager_actor);
+        qexed_task::task::task_manage::TaskManage::with_mailbox(
+            manager_actor,
+            "ping",
+            qexed_task::mailbox::MailboxConfig::manager(),
+        );
     manager_task.run().await?;
     log::info!("[服务] Ping 已启用");
     Ok(manager_sender)
@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use dashmap::DashMap;
 use qexed_config::app::qexed_ping::PingConfig;
 use qexed_task::{
+    supervisor::{self, Directive, SupervisorPolicy},
     event::task_manage::TaskManageEvent,
     message::{
         MessageSender, MessageType, return_message::ReturnMessage,
@@ -34,6 +35,15 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
         task_map: &DashMap<uuid::Uuid, MessageSender<UnReturnMessage<TaskCommand>>>,
         mut data: ReturnMessage<ManagerCommand>,
     ) -> anyhow::Result<bool> {
+        // 失败报告不需要回复,停止的任务从任务表中移除
+        if let ManagerCommand::ChildFailed(failure) = &data.data {
+            if failure.directive != Directive::Restart {
+                for uuid in supervisor::prune(task_map) {
+                    crate::forget_latency(&uuid);
+                }
+            }
+            return Ok(false);
+        }
         let send = match data.get_return_send().await? {
             Some(send) => send,
             None => return Ok(false),
@@ -57,7 +67,7 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                     ));
                     return Ok(false);
                 }
-                let packet_send: tokio::sync::mpsc::UnboundedSender<bytes::Bytes> =
+                let packet_send: qexed_task::mailbox::MailboxSender<bytes::Bytes> =
                     match packet_send.take() {
                         Some(pk) => pk,
                         None => {
@@ -70,7 +80,10 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
                     };
                 let t = PingTask::new(self.config.clone(), uuid.clone(), packet_send);
                 let (task, task_sand) = Task::new(api.clone(), t);
-                task.run().await?;
+                // 玩家任务失败即停止并报告给管理器
+                task.supervise(format!("ping-{}", uuid), SupervisorPolicy::stop())
+                    .run()
+                    .await?;
                 task_map.insert(*uuid, task_sand.clone());
 
                 let _ = send.send(ManagerCommand::NewPlayerConnect(
@@ -85,11 +98,23 @@ impl TaskManageEvent<uuid::Uuid, ReturnMessage<ManagerCommand>, UnReturnMessage<
             }
             ManagerCommand::PlayerClose(uuid) => {
                 task_map.remove(&uuid);
+                crate::forget_latency(&uuid);
 
                 let _ = send.send(data.data);
 
                 return Ok(false);
             }
+            ManagerCommand::Reload(ref reload) => {
+                self.config = reload.config.clone();
+                for entry in task_map.iter() {
+                    let _ = UnReturnMessage::build(TaskCommand::Reload(self.config.clone()))
+                        .post(entry.value())
+                        .await;
+                }
+                let _ = send.send(data.data);
+                return Ok(false);
+            }
+            ManagerCommand::ChildFailed(_) => Ok(false),
         }
     }
 }
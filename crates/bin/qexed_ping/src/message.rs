@@ -1,12 +1,21 @@
 use bytes::Bytes;
+use qexed_config::{app::qexed_ping::PingConfig, tool::Reload};
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::{ unreturn_message::UnReturnMessage};
 use thiserror::Error;
 use tokio::sync::{mpsc::{ UnboundedSender}, oneshot};
 
 #[derive(Debug,Clone)]
 pub enum ManagerCommand{
-    NewPlayerConnect(uuid::Uuid,bool,Option<NewPlayerConnectError>,Option<UnboundedSender<UnReturnMessage<TaskCommand>>>,Option<UnboundedSender<Bytes>>),
+    NewPlayerConnect(uuid::Uuid,bool,Option<NewPlayerConnectError>,Option<UnboundedSender<UnReturnMessage<TaskCommand>>>,Option<MailboxSender<Bytes>>),
     PlayerClose(uuid::Uuid),// 游戏连接关闭
+    Reload(Reload<PingConfig>),// 热重载,同步到所有玩家的 Ping 任务
+    ChildFailed(qexed_task::supervisor::Failure), // 玩家 Ping 任务失败
+}
+impl From<qexed_task::supervisor::Failure> for ManagerCommand {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        ManagerCommand::ChildFailed(failure)
+    }
 }
 #[derive(Debug)]
 pub enum TaskCommand{
@@ -16,6 +25,7 @@ pub enum TaskCommand{
     UpdatePart(Part),
     Await(oneshot::Sender<bool>),// 或者保证在下一场执行Ping之前拦截
     Pong(i32),// Ping的返回结果
+    Reload(PingConfig),// 更新配置
     Close,// 关闭服务
 }
 #[derive(Debug,Clone)]
@@ -3,6 +3,7 @@ use bytes::Bytes;
 use qexed_config::app::qexed_ping::PingConfig;
 
 use crate::message::{ManagerCommand, Part, TaskCommand};
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::{
     event::task::TaskEvent,
     message::{
@@ -13,7 +14,7 @@ use qexed_task::{
 use qexed_tcp_connect::PacketSend;
 use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender, channel};
+use tokio::sync::mpsc::{Receiver, Sender, channel};
 use tokio::sync::oneshot;
 
 // 内部消息类型
@@ -24,6 +25,7 @@ enum InternalMessage {
     TimeoutCheck,
     AwaitBarrier(oneshot::Sender<bool>),
     StateChange(StateChange),
+    Reload(PingConfig),
 }
 
 #[derive(Debug)]
@@ -278,7 +280,7 @@ impl PingState {
 #[derive(Debug)]
 pub struct PingTask {
     player_uuid: uuid::Uuid,
-    packet_send: UnboundedSender<Bytes>,
+    packet_send: MailboxSender<Bytes>,
 
     // 内部通信通道
     internal_sender: Sender<InternalMessage>,
@@ -295,7 +297,7 @@ impl PingTask {
     pub fn new(
         config: PingConfig,
         player_uuid: uuid::Uuid,
-        packet_send: UnboundedSender<Bytes>,
+        packet_send: MailboxSender<Bytes>,
     ) -> Self {
         let (internal_sender, _) = channel(100);
         let state = PingState::new(config);
@@ -364,6 +366,13 @@ impl PingTask {
                                     StateChange::PartChanged(part) => self.state.set_part(part),
                                 }
                             }
+                            InternalMessage::Reload(config) => {
+                                if config.interval != self.state.config.interval {
+                                    ping_timer = tokio::time::interval(Duration::from_secs(config.interval as u64));
+                                    ping_timer.tick().await;
+                                }
+                                self.state.config = config;
+                            }
                         }
                     }
 
@@ -401,7 +410,7 @@ impl PingTask {
                 // 构建并发送 Ping 包
                 let ping_data = self.build_ping_data(ping_id).await?;
                 self.packet_send
-                    .send(ping_data)
+                    .try_send(ping_data)
                     .map_err(|e| anyhow::anyhow!("Failed to send ping packet: {}", e))?;
 
                 log::debug!("Sent ping {} to player {}", ping_id, self.player_uuid);
@@ -428,6 +437,7 @@ impl PingTask {
                     self.player_uuid,
                     latency
                 );
+                crate::record_latency(self.player_uuid, latency);
 
                 // 检查延迟限制
                 if self.state.config.enable_latency_limit {
@@ -571,6 +581,12 @@ impl TaskEvent<UnReturnMessage<TaskCommand>, ReturnMessage<ManagerCommand>> for
                     .await?;
             }
 
+            TaskCommand::Reload(config) => {
+                self.state.config = config.clone();
+                // 后台任务未启动时没有接收方
+                let _ = self.send_internal(InternalMessage::Reload(config)).await;
+            }
+
             TaskCommand::Pong(timestamp) => {
                 let now = Instant::now();
                 self.send_internal(InternalMessage::PongReceived(timestamp, now))
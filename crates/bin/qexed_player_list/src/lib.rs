@@ -1,6 +1,5 @@
 use async_trait::async_trait;
 use dashmap::DashMap;
-use qexed_command::message::CommandData;
 use qexed_config::app::qexed_player_list::PlayerList;
 use qexed_task::{
     event::task::TaskEasyEvent,
@@ -10,13 +9,16 @@ use tokio::sync::mpsc::UnboundedSender;
 use uuid::Uuid;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "distributed", derive(serde::Serialize, serde::Deserialize))]
 pub enum Message {
     PlayerJoin(uuid::Uuid, String),
     PlayerLeft(uuid::Uuid),
     CheckPlayeIsInList(uuid::Uuid, bool),
     LoadData(i32, i32),
-    Command(CommandData),
+    // 生成 /list 指定页的消息,指令在本地处理,玩家列表服务可以部署在其他进程
+    ListPage(usize, String),
     GetPlayerIsOnline{name:String,is_true:bool,player_uuid:uuid::Uuid},
+    GetPlayers(Vec<(uuid::Uuid, String)>),// 获取全部在线玩家
 }
 
 #[derive(Debug)]
@@ -171,34 +173,19 @@ impl TaskEasyEvent<ReturnMessage<Message>> for Task {
                 }
                 return Ok(false);
             }
-            Message::Command(ref cmd) => {
-                // 解析页码参数
-                let args = cmd.parse_args();
-                let help_args: Vec<String> = args.into_iter().skip(1).collect();
-                let page = match help_args.len() {
-                    0 => 1,
-                    1 => {
-                        // 一个参数：可能是页码或命令名
-                        match help_args[0].parse::<usize>() {
-                            Ok(page) => page,
-                            Err(_) => 1,
-                        }
-                    }
-                    _ => {
-                        // 多个参数：错误用法
-                        cmd.send_chat_message("§c用法: /list [页码]\n§7例如: /list 或 /list 1").await?;
-                        if let Some(send) = data.get_return_send().await? {
-                            let _ = send.send(data.data);
-                        }
-                        return Ok(false);
-                    }
-                };
-
-                // 生成玩家列表消息
-                let message = self.format_player_list(page);
-
-                // 发送消息
-                cmd.send_chat_message(&message).await?;
+            Message::GetPlayers(ref mut players) => {
+                *players = self
+                    .player_map
+                    .iter()
+                    .map(|entry| (*entry.key(), entry.value().clone()))
+                    .collect();
+                if let Some(send) = data.get_return_send().await? {
+                    let _ = send.send(data.data);
+                }
+                return Ok(false);
+            }
+            Message::ListPage(page, ref mut message) => {
+                *message = self.format_player_list(page);
                 if let Some(send) = data.get_return_send().await? {
                     let _ = send.send(data.data);
                 }
@@ -236,9 +223,31 @@ pub async fn register_list_command(
             async move {
                 // 处理命令，直到通道关闭
                 while let Some(cmd) = cmd_rx.recv().await {
-                    ReturnMessage::build(Message::Command(cmd))
+                    // 解析页码参数
+                    let args = cmd.parse_args();
+                    let page = match args.len() {
+                        1 => 1,
+                        // 一个参数：页码
+                        2 => args[1].parse::<usize>().unwrap_or(1),
+                        _ => {
+                            // 多个参数：错误用法
+                            cmd.send_chat_message("§c用法: /list [页码]\n§7例如: /list 或 /list 1").await?;
+                            continue;
+                        }
+                    };
+                    // 玩家列表服务出错只影响本次指令
+                    let message = match ReturnMessage::build(Message::ListPage(page, String::new()))
                         .get(&api2) // 使用闭包内部的 api2
-                        .await?;
+                        .await
+                    {
+                        Ok(Message::ListPage(_, message)) => message,
+                        Ok(_) => "§c玩家列表服务返回了意外的消息".to_string(),
+                        Err(e) => {
+                            log::warn!("[指令] [list] 查询玩家列表失败: {}", e);
+                            "§c暂时无法获取玩家列表".to_string()
+                        }
+                    };
+                    cmd.send_chat_message(&message).await?;
                 }
 
                 // 通道关闭，正常结束
@@ -1,2 +1,23 @@
-pub fn main(){
-}
\ No newline at end of file
+// 独立部署的玩家列表服务,qexed_one 通过 remote_services 连接
+#[cfg(feature = "distributed")]
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    use qexed_config::{app::qexed_player_list::PlayerList, tool::AppConfigTrait};
+    use tklog::{ASYNC_LOG, Format, MODE};
+
+    ASYNC_LOG
+        .set_cutmode_by_time("./log/player_list.log", MODE::DAY, 30, true)
+        .await
+        .set_format(Format::LevelFlag | Format::Time | Format::ShortFileName)
+        .uselog();
+    let config = PlayerList::load_or_create_default()?;
+    let endpoint = config.ip.parse()?;
+    let token = config.token.clone();
+    let api = qexed_player_list::run(config).await?;
+    qexed_task::remote::serve(&endpoint, &token, api).await
+}
+
+#[cfg(not(feature = "distributed"))]
+pub fn main() {
+    eprintln!("独立运行玩家列表服务需要启用 distributed 特性");
+}
@@ -20,7 +20,7 @@
 // pub enum TaskMessage {
 //     Start(
 //         String,
-//         Option<UnboundedSender<Bytes>>, // 数据包发送器
+//         Option<MailboxSender<Bytes>>, // 数据包发送器
 //     ), // 传递数据包收发器
 //     SendTitleMessage(qexed_protocol::to_client::play::set_title_text::SetTitleText),// 广播事件数据包
 //     Close,// 连接关闭
@@ -18,7 +18,7 @@
 // pub struct TitleActor {
 //     uuid: Uuid,
 //     name: String,
-//     packet_write: Option<UnboundedSender<Bytes>>,
+//     packet_write: Option<MailboxSender<Bytes>>,
 //     config: qexed_config::app::qexed_title::TitleConfig,
 // }
 // impl TitleActor {
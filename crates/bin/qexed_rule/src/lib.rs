@@ -1,7 +1,11 @@
+/// 规则快照,运行中修改的规则在重启后从这里恢复
+const SNAPSHOT: &str = "./config/qexed_rule/snapshot.json";
+
 pub async fn run(
     config: qexed_config::app::qexed_rule::RuleConfig,
 ) -> anyhow::Result<qexed_shared::Shared<qexed_config::app::qexed_rule::RuleConfig>> {
-    let app = qexed_shared::Shared::new(config).await?;
+    let options = qexed_shared::SharedOptions::new().snapshot(SNAPSHOT);
+    let app = qexed_shared::Shared::open(config, options).await?;
     log::info!("[服务] 规则管理 已启用");
     Ok(app)
-}
\ No newline at end of file
+}
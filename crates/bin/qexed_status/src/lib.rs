@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use qexed_config::app::{qexed_status::StatusConfig};
+use qexed_config::{app::qexed_status::StatusConfig, tool::Reload};
 use qexed_task::{
     event::task::{TaskEasyEvent},
     message::{MessageSender, MessageType, return_message::ReturnMessage},
@@ -14,6 +14,8 @@ use tokio::{
 #[derive(Debug, Clone,Default)]
 pub struct Message {
     pub data: Option<Bytes>,
+    /// 热重载配置,设置时只应用配置不构建状态
+    pub reload: Option<Reload<StatusConfig>>,
 }
 
 #[derive(Debug)]
@@ -110,6 +112,14 @@ impl Task {
         }).await?)
     }
 
+    /// 应用新配置,MOTD 与图标在下次查询时生效
+    fn reload(&mut self, config: &StatusConfig) {
+        self.cache_time = config.cache;
+        self.motd = config.motd.clone();
+        self.favicon = config.favicon.clone();
+        self.cache = None;
+    }
+
     /// 更新缓存
     async fn update_cache(&mut self) -> anyhow::Result<()> {
         let status_json = self.build_status_json().await?;
@@ -126,6 +136,13 @@ impl TaskEasyEvent<ReturnMessage<Message>> for Task {
         _api: &MessageSender<ReturnMessage<Message>>,
         mut data: ReturnMessage<Message>,
     ) -> anyhow::Result<bool> {
+        if let Some(reload) = &data.data.reload {
+            self.reload(&reload.config);
+            if let Some(send) = data.get_return_send().await? {
+                let _ = send.send(data.data);
+            }
+            return Ok(false);
+        }
         let status_json = if self.is_cache_valid() {
             // 缓存有效，直接使用缓存
             self.cache.clone().unwrap()
@@ -1,3 +1,4 @@
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::{MessageType, return_message::ReturnMessage};
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -11,7 +12,8 @@ pub async fn run(
     qexed_player_list_api: UnboundedSender<ReturnMessage<qexed_player_list::Message>>,
     qexed_black_list_api: UnboundedSender<ReturnMessage<qexed_blacklist::Message>>,
     qexed_white_list_api: UnboundedSender<ReturnMessage<qexed_whitelist::Message>>,
-    qexed_game_logic:UnboundedSender<ReturnMessage<qexed_game_logic::message::ManagerMessage>>,
+    qexed_game_logic:MailboxSender<ReturnMessage<qexed_game_logic::message::ManagerMessage>>,
+    hooks: qexed_event::PacketHooks,
 ) -> anyhow::Result<UnboundedSender<ReturnMessage<ManagerCommand>>> {
     let manager_actor = TcpConnectManagerActor::new(
         config,
@@ -20,6 +22,7 @@ pub async fn run(
         qexed_black_list_api,
         qexed_white_list_api,
         qexed_game_logic,
+        hooks,
     )
     .await;
     let (manager_task, manager_sender) =
@@ -1,11 +1,14 @@
 use std::{collections::HashMap, sync::{Arc, atomic::{AtomicBool, Ordering}}, time::Duration};
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use chrono::Local;
+use qexed_event::packet::{Direction, HookPoint, State};
 use qexed_packet::{PacketCodec, PacketWriter, net_types::VarInt};
 use qexed_protocol::to_server::status::{ping::Ping, ping_start::PingStart};
 use qexed_task::{
     event::task::TaskEvent,
+    mailbox::{MailboxConfig, Overflow},
     message::{MessageSender, MessageType, return_message::ReturnMessage},
 };
 use rand::Rng;
@@ -16,6 +19,11 @@ use tokio_util::sync::CancellationToken;
 
 use crate::messages::{ManagerCommand, TaskCommand};
 
+// 登录检查需要依次询问名单服务,超过此时间未回复则放弃本次登录
+const LOGIN_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+// 登录路径上的其他请求超过此时间未回复则放弃,管理器转发查询时也使用此期限
+pub(crate) const REQUEST_TIMEOUT: Duration = Duration::from_secs(4);
+
 #[derive(Debug)]
 pub struct TcpConnectActor {
     socket: Option<TcpStream>,
@@ -26,6 +34,8 @@ pub struct TcpConnectActor {
     public_key: RsaPublicKey,
     public_key_der: Vec<u8>,
     status_timeout_secs:i32,
+    write_queue_capacity: usize,
+    hooks: qexed_event::PacketHooks,
 }
 impl TcpConnectActor {
     pub fn new(
@@ -37,6 +47,8 @@ impl TcpConnectActor {
         public_key: RsaPublicKey,
         public_key_der: Vec<u8>,
         status_timeout_secs:i32,
+        write_queue_capacity: usize,
+        hooks: qexed_event::PacketHooks,
     ) -> Self {
         Self {
             socket: Some(socket),
@@ -47,6 +59,8 @@ impl TcpConnectActor {
             public_key,
             public_key_der,
             status_timeout_secs,
+            write_queue_capacity,
+            hooks,
         }
     }
 }
@@ -70,6 +84,8 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                 let public_key: RsaPublicKey = self.public_key.clone();
                 let public_key_der: Vec<u8> = self.public_key_der.clone();
                 let status_timeout_secs = self.status_timeout_secs.clone();
+                let write_queue_capacity = self.write_queue_capacity;
+                let hooks = self.hooks.clone();
                 // let 
                 tokio::spawn(async move {
                     if let Some(socket) = socket {
@@ -81,6 +97,7 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                             qexed_tcp_connect::PacketListener::new(rs, ws, compression_threshold);
                         let (mut packet_read, mut packet_write) = packet_socket.split();
                         let mut qexed_logic_api = None;
+                        let mut injected = None;
                         let _: anyhow::Result<()> = async {
                             let set_protocol = qexed_tcp_connect::read_one_packet::<
                                 qexed_protocol::to_server::handshaking::set_protocol::SetProtocol,
@@ -132,16 +149,45 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                             // pass
                             // 读数据包流:
                             let (rpw, rpr) = unbounded_channel();
-                            // 写数据包流
-                            let (wpw, mut wpr) = unbounded_channel();
+                            // 写数据包流,队列写满说明客户端处理过慢,拒绝后由写任务断开连接
+                            let (wpw, mut wpr) = qexed_task::mailbox::named::<Bytes>(
+                                "connection_write",
+                                MailboxConfig::new(write_queue_capacity, Overflow::Reject),
+                            );
+                            // 插件注入数据包的通道,注入的数据包不经过连接层钩子,队列写满时注入失败
+                            let uuid = player.uuid;
+                            let (inject_write, mut inject_read) = qexed_task::mailbox::named::<Bytes>(
+                                "connection_inject",
+                                MailboxConfig::new(write_queue_capacity, Overflow::Reject),
+                            );
+                            // 注入到服务端的数据包使用同样的有界邮箱,由转发任务送入读数据包流
+                            let (inject_server, mut inject_server_read) = qexed_task::mailbox::named::<Vec<u8>>(
+                                "connection_inject",
+                                MailboxConfig::new(write_queue_capacity, Overflow::Reject),
+                            );
+                            tokio::spawn({
+                                let rpw = rpw.clone();
+                                async move {
+                                    // 连接关闭时注销注入通道,邮箱随之关闭,转发任务结束
+                                    while let Some(pk) = inject_server_read.recv().await {
+                                        if rpw.send(pk).is_err() {
+                                            break;
+                                        }
+                                    }
+                                }
+                            });
+                            hooks.attach(uuid, inject_write.clone(), inject_server);
+                            injected = Some((uuid, inject_write));
                             // 创建协调器
                             let shutdown = Arc::new(ConnectionShutdown::new());
                                                     
                             // 分离的写任务
                             let mut write_handle = {
                                 let shutdown = Arc::clone(&shutdown);
+                                let hooks = hooks.clone();
                                 tokio::spawn(async move {
                                     let mut packet_write = packet_write;
+                                    let mut state = State::Configuration;
                                     loop {
                                         tokio::select! {
                                             _ = shutdown.cancel_token.cancelled() => {
@@ -156,7 +202,24 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                                 log::debug!("读任务已停止，写任务退出");
                                 break;
                             }
+                            if wpr.stats().rejected > 0 {
+                                log::warn!("客户端处理过慢,发送队列已满,断开连接");
+                                break;
+                            }
                             
+                            // 服务端发出结束配置后进入游玩阶段
+                            let current = state;
+                            if state == State::Configuration && pk.first() == Some(&0x03) {
+                                state = State::Play;
+                            }
+                            let pk = if hooks.is_active(HookPoint::Connection) {
+                                match hooks.apply(uuid, HookPoint::Connection, Direction::Clientbound, current, pk.to_vec()).await {
+                                    Some(pk) => Bytes::from(pk),
+                                    None => continue,
+                                }
+                            } else {
+                                pk
+                            };
                             if let Err(e) = packet_write.send_raw(pk).await {
                                 log::error!("写入数据包出错: {}", e);
                                 break;
@@ -168,6 +231,12 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                                                     }
                                                 }
                                             }
+                                            Some(pk) = inject_read.recv() => {
+                                                if let Err(e) = packet_write.send_raw(pk).await {
+                                                    log::error!("写入注入的数据包出错: {}", e);
+                                                    break;
+                                                }
+                                            }
                                         }
                                     }
                                     
@@ -183,7 +252,9 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                             // 主循环处理读取
                             let mut read_handle = {
                                 let shutdown = Arc::clone(&shutdown);
+                                let hooks = hooks.clone();
                                 tokio::spawn(async move {
+                                    let mut state = State::Configuration;
                                     let read_result = async {
                                         loop {
                                             tokio::select! {
@@ -200,6 +271,19 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                                                     
                                                     match result {
                                                         Ok(pk) => {
+                                                            // 客户端确认结束配置后进入游玩阶段
+                                                            let current = state;
+                                                            if state == State::Configuration && pk.first() == Some(&0x03) {
+                                                                state = State::Play;
+                                                            }
+                                                            let pk = if hooks.is_active(HookPoint::Connection) {
+                                                                match hooks.apply(uuid, HookPoint::Connection, Direction::Serverbound, current, pk).await {
+                                                                    Some(pk) => pk,
+                                                                    None => continue,
+                                                                }
+                                                            } else {
+                                                                pk
+                                                            };
                                                             if rpw.is_closed() {
                                                                 break Ok(());
                                                             }
@@ -234,7 +318,7 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                                     Ok(_v)=>{},
                                     Err(_v)=>{
                                     let _ = ReturnMessage::build(qexed_game_logic::message::TaskMessage::Close)
-                                        .get(&logic_api)
+                                        .get_timeout(&logic_api, REQUEST_TIMEOUT)
                                         .await;
                                     }
                                 };
@@ -243,7 +327,7 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                                     Ok(_v)=>{},
                                     Err(_v)=>{
                                     let _ = ReturnMessage::build(qexed_game_logic::message::TaskMessage::Close)
-                                        .get(&logic_api)
+                                        .get_timeout(&logic_api, REQUEST_TIMEOUT)
                                         .await;
                                     }
                                 };
@@ -289,17 +373,20 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                             Ok(())
                         }
                         .await;
+                        if let Some((uuid, inject_write)) = injected {
+                            hooks.detach(uuid, &inject_write);
+                        }
                         if let Some(logic_api) = qexed_logic_api{
                             
                             let _ = ReturnMessage::build(qexed_game_logic::message::TaskMessage::Close)
-                                .get(&logic_api)
+                                .get_timeout(&logic_api, REQUEST_TIMEOUT)
                                 .await;
                         }
 
                         
                         // 告知自己关闭自己
                         let _ = ReturnMessage::build(TaskCommand::ConnClose(addr))
-                            .get(&api_clone)
+                            .get_timeout(&api_clone, REQUEST_TIMEOUT)
                             .await;
                         anyhow::Ok(())
                     } else {
@@ -313,7 +400,7 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
             TaskCommand::ConnClose(addr) => {
                 // 向父级关闭告知自己
                 ReturnMessage::build(ManagerCommand::ConnClose(addr))
-                    .get(&manage_api)
+                    .get_timeout(&manage_api, REQUEST_TIMEOUT)
                     .await?;
                 if let Some(send) = data.get_return_send().await? {
                     let _ = send.send(data.data);
@@ -400,7 +487,7 @@ async fn part_status(
                 qexed_tcp_connect::decode_packet::<PingStart>(&mut reader)?;
                 let return_data: ManagerCommand =
                     ReturnMessage::build(ManagerCommand::GetStatusPackageBytes(Default::default()))
-                        .get(&manage_api)
+                        .get_timeout(&manage_api, REQUEST_TIMEOUT)
                         .await?;
                 
                 match return_data {
@@ -452,7 +539,7 @@ async fn login_status(
                 player.username = pk.username;
                 player.uuid = pk.player_uuid;
                 // 检查有没有被拉黑
-                if let ManagerCommand::LoginCheck(uuid,_ip,is_login,reason) = ReturnMessage::build(ManagerCommand::LoginCheck(player.uuid.clone(),Some(addr.ip()), false,None)).get(&manage_api).await?{
+                if let ManagerCommand::LoginCheck(uuid,_ip,is_login,reason) = ReturnMessage::build(ManagerCommand::LoginCheck(player.uuid,Some(addr.ip()), false,None)).get_timeout(&manage_api, LOGIN_CHECK_TIMEOUT).await?{
                     if uuid!=player.uuid.clone(){
                         let server_info = qexed_protocol::to_client::login::disconnect::Disconnect {
                             reason: serde_json::json!({
@@ -487,7 +574,7 @@ async fn login_status(
                     }
                 }
                 // 检查玩家是否在线
-                if let ManagerCommand::CheckPlayeIsInList(uuid,is_login) = ReturnMessage::build(ManagerCommand::CheckPlayeIsInList(player.uuid.clone(), true)).get(&manage_api).await?{
+                if let ManagerCommand::CheckPlayeIsInList(uuid,is_login) = ReturnMessage::build(ManagerCommand::CheckPlayeIsInList(player.uuid, true)).get_timeout(&manage_api, REQUEST_TIMEOUT).await?{
                     if uuid!=player.uuid.clone(){
                         let server_info = qexed_protocol::to_client::login::disconnect::Disconnect {
                             reason: serde_json::json!({
@@ -536,7 +623,7 @@ async fn login_status(
                         qexed_game_logic::message::ManagerMessage::NewPlayerConnect(
                             player.uuid.clone(), false, None,None)
                         )
-                    ).get(&manage_api).await?{
+                    ).get_timeout(&manage_api, REQUEST_TIMEOUT).await?{
                         if !is_true{
                             let server_info =
                                 qexed_protocol::to_client::login::disconnect::Disconnect {
@@ -712,7 +799,7 @@ async fn login_status(
                         qexed_game_logic::message::ManagerMessage::NewPlayerConnect(
                             player.uuid.clone(), false, None,None)
                         )
-                    ).get(&manage_api).await?{
+                    ).get_timeout(&manage_api, REQUEST_TIMEOUT).await?{
                         if !is_true{
                             let server_info =
                                 qexed_protocol::to_client::login::disconnect::Disconnect {
@@ -1,11 +1,14 @@
-use qexed_task::message::{MessageType, return_message::ReturnMessage};
+use qexed_task::{
+    mailbox::MailboxSender,
+    message::{MessageType, return_message::ReturnMessage},
+};
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::message::ManagerMessage;
 
 pub async fn register_title_command_full(
     command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
-    api2: UnboundedSender<ReturnMessage<ManagerMessage>>,
+    api2: MailboxSender<ReturnMessage<ManagerMessage>>,
 ) -> anyhow::Result<()> {
     let api2_for_closure = api2.clone();
 
@@ -1,3 +1,4 @@
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::return_message::ReturnMessage;
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -13,13 +14,17 @@ pub async fn run(
     config: qexed_config::app::qexed_title::TitleConfig,
     player_list_api:UnboundedSender<ReturnMessage<qexed_player_list::Message>>
 
-) -> anyhow::Result<UnboundedSender<ReturnMessage<ManagerMessage>>> {
+) -> anyhow::Result<MailboxSender<ReturnMessage<ManagerMessage>>> {
     let manager_actor = TitleManagerActor::new(
         config,
         player_list_api
     );
     let (manager_task, manager_sender) =
-        qexed_task::task::task_manage::TaskManage::new(manager_actor);
+        qexed_task::task::task_manage::TaskManage::with_mailbox(
+            manager_actor,
+            "title",
+            qexed_task::mailbox::MailboxConfig::manager(),
+        );
     manager_task.run().await?;
     log::info!("[服务] Title 已启用");
     Ok(manager_sender)
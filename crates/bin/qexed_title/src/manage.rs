@@ -5,6 +5,7 @@ use dashmap::DashMap;
 use qexed_nbt::Tag;
 use qexed_protocol::to_client::play::{set_title_text::SetTitleText, system_chat::SystemChat};
 use qexed_task::{
+    supervisor::{self, Directive, SupervisorPolicy},
     event::task_manage::TaskManageEvent,
     message::{
         MessageSender, MessageType, return_message::ReturnMessage,
@@ -46,6 +47,13 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
         task_map: &DashMap<Uuid, MessageSender<UnReturnMessage<TaskMessage>>>,
         mut data: ReturnMessage<ManagerMessage>,
     ) -> anyhow::Result<bool> {
+        // 失败报告不需要回复,停止的任务从任务表中移除
+        if let ManagerMessage::ChildFailed(failure) = &data.data {
+            if failure.directive != Directive::Restart {
+                supervisor::prune(task_map);
+            }
+            return Ok(false);
+        }
         let send = match data.get_return_send().await? {
             Some(send) => send,
             None => return Ok(false),
@@ -64,7 +72,10 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
                 }
                 let (task, task_sand) =
                     Task::new(api.clone(), TitleActor::new(uuid, self.config.clone()));
-                task.run().await?;
+                // 玩家任务失败即停止并报告给管理器
+                task.supervise(format!("title-{}", uuid), SupervisorPolicy::stop())
+                    .run()
+                    .await?;
                 task_map.insert(uuid, task_sand.clone());
                 *task_api = Some(task_sand);
                 *is_true = true;
@@ -116,6 +127,7 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, UnReturnMessage<TaskMe
                 let _ = send.send(data.data);
                 return Ok(false);
             }
+            ManagerMessage::ChildFailed(_) => Ok(false),
         }
     }
 }
@@ -1,5 +1,6 @@
 use bytes::Bytes;
 use qexed_command::message::CommandData;
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::unreturn_message::UnReturnMessage;
 use thiserror::Error;
 use tokio::sync::mpsc::UnboundedSender;
@@ -15,12 +16,18 @@ pub enum ManagerMessage {
     Command(CommandData),// 指令事件
     PlayerClose(uuid::Uuid),  // 游戏连接关闭
     ConnectClose(uuid::Uuid), // 连接关闭
+    ChildFailed(qexed_task::supervisor::Failure), // 玩家标题任务失败
+}
+impl From<qexed_task::supervisor::Failure> for ManagerMessage {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        ManagerMessage::ChildFailed(failure)
+    }
 }
 #[derive(Debug)]
 pub enum TaskMessage {
     Start(
         String,
-        Option<UnboundedSender<Bytes>>, // 数据包发送器
+        Option<MailboxSender<Bytes>>, // 数据包发送器
     ), // 传递数据包收发器
     SendTitleMessage(qexed_protocol::to_client::play::set_title_text::SetTitleText),// 广播事件数据包
     Close,// 连接关闭
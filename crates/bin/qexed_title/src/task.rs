@@ -4,12 +4,12 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use qexed_nbt::Tag;
 use qexed_protocol::to_client::{ play::system_chat::SystemChat};
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::{
     event::task::TaskEvent,
     message::{MessageSender, MessageType, return_message::ReturnMessage, unreturn_message::UnReturnMessage},
 };
 use qexed_tcp_connect::PacketSend;
-use tokio::sync::mpsc::UnboundedSender;
 use uuid::Uuid;
 
 use crate::message::{ManagerMessage, TaskMessage};
@@ -18,7 +18,7 @@ use crate::message::{ManagerMessage, TaskMessage};
 pub struct TitleActor {
     uuid: Uuid,
     name: String,
-    packet_write: Option<UnboundedSender<Bytes>>,
+    packet_write: Option<MailboxSender<Bytes>>,
     config: qexed_config::app::qexed_title::TitleConfig,
 }
 impl TitleActor {
@@ -57,7 +57,7 @@ impl TaskEvent<UnReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for
             }
             TaskMessage::SendTitleMessage(system_chat) => {
                 if let Some(packet_write) = &self.packet_write{
-                    packet_write.send(PacketSend::build_send_packet(system_chat).await?)?;
+                    packet_write.try_send(PacketSend::build_send_packet(system_chat).await?)?;
                 }
                 return Ok(false);
             },
@@ -1,6 +1,10 @@
 use anyhow::Ok;
 use async_trait::async_trait;
-use qexed_config::{app::qexed_whitelist::WhiteList, public::storage_engine::StorageEngine};
+use qexed_config::{
+    app::qexed_whitelist::{Simple, WhiteList},
+    public::storage_engine::StorageEngine,
+    tool::{Persist, Reload},
+};
 use qexed_task::{
     event::task::TaskEasyEvent,
     message::{MessageSender, MessageType, return_message::ReturnMessage},
@@ -9,15 +13,33 @@ use tokio::sync::mpsc::UnboundedSender;
 #[derive(Debug, Clone)]
 pub enum Message {
     CheckPlayerCanJoinServer(uuid::Uuid, Option<String>),
+    Reload(Reload<WhiteList>), // 热重载名单与踢出消息
+    GetList(Vec<uuid::Uuid>),
+    Add(uuid::Uuid, bool),    // 加入白名单,返回名单是否变化
+    Remove(uuid::Uuid, bool), // 移出白名单,返回名单是否变化
+    Save(bool),               // 写回名单,返回是否成功
 }
 
-#[derive(Debug)]
 pub struct Task {
     pub config: WhiteList,
+    persist: Persist<Simple>,
 }
 impl Task {
-    pub fn new(config: WhiteList) -> Self {
-        Self { config }
+    pub fn new(config: WhiteList, persist: Persist<Simple>) -> Self {
+        Self { config, persist }
+    }
+    // 名单在本任务内修改并写回,多个修改请求不会互相覆盖
+    fn save(&self) -> bool {
+        if let Err(e) = (self.persist)(&self.config.simple) {
+            log::error!("[白名单] 保存名单失败: {}", e);
+            return false;
+        }
+        true
+    }
+}
+impl std::fmt::Debug for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Task").field("config", &self.config).finish_non_exhaustive()
     }
 }
 
@@ -44,6 +66,34 @@ impl TaskEasyEvent<ReturnMessage<Message>> for Task {
                     }
                 }
             }
+            Message::Reload(ref mut reload) => {
+                // 存储引擎在启动时确定
+                let engine = self.config.storage_engine.clone();
+                reload.restart_if("white_list.storage_engine", reload.config.storage_engine != engine);
+                self.config = reload.config.clone();
+                self.config.storage_engine = engine;
+            }
+            Message::GetList(ref mut list) => {
+                *list = self.config.simple.player_list.clone();
+            }
+            Message::Add(uuid, ref mut changed) => {
+                *changed = !self.config.simple.player_list.contains(&uuid);
+                if *changed {
+                    self.config.simple.player_list.push(uuid);
+                    self.save();
+                }
+            }
+            Message::Remove(uuid, ref mut changed) => {
+                let before = self.config.simple.player_list.len();
+                self.config.simple.player_list.retain(|player| player != &uuid);
+                *changed = self.config.simple.player_list.len() != before;
+                if *changed {
+                    self.save();
+                }
+            }
+            Message::Save(ref mut saved) => {
+                *saved = self.save();
+            }
         };
         if let Some(send) = data.get_return_send().await? {
             let _ = send.send(data.data);
@@ -51,12 +101,15 @@ impl TaskEasyEvent<ReturnMessage<Message>> for Task {
         Ok(false)
     }
 }
-pub async fn run(config: WhiteList) -> anyhow::Result<UnboundedSender<ReturnMessage<Message>>> {
+pub async fn run(
+    config: WhiteList,
+    persist: Persist<Simple>,
+) -> anyhow::Result<UnboundedSender<ReturnMessage<Message>>> {
     if config.storage_engine != StorageEngine::Simple {
         return Err(anyhow::anyhow!("暂未支持此引擎"));
     }
     // 假设创建任务服务端
-    let task_data = Task::new(config);
+    let task_data = Task::new(config, persist);
     let (task, task_send) = qexed_task::task::task::TaskEasy::new(task_data);
     task.run().await?;
     log::info!("[服务] 白名单 已启用");
@@ -1,4 +1,4 @@
-use qexed_config::app::{qtunnel_one::One};
+use qexed_config::{app::qtunnel_one::One, tool::AppConfigTrait};
 use qexed_task::message::return_message::ReturnMessage;
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -20,10 +20,12 @@ pub struct Api {
 }
 impl Api {
     pub async fn init(config: One) -> anyhow::Result<Self> {
-        let command = qexed_command::run(config.command).await?;
+        // 隧道端没有插件,事件总线上不会有监听器
+        let events = qexed_event::EventBus::new(&Default::default());
+        let command = qexed_command::run(config.command, events).await?;
         let player_list= qexed_player_list::run(config.player_list).await?;
-        let black_list= qexed_blacklist::run(config.black_list).await?;
-        let white_list= qexed_whitelist::run(config.white_list).await?;
+        let black_list= qexed_blacklist::run(config.black_list,Box::new(|list| One::update("black_list.simple", list))).await?;
+        let white_list= qexed_whitelist::run(config.white_list,Box::new(|list| One::update("white_list.simple", list))).await?;
         let server_status = qexed_status::run(config.server_status, player_list.clone()).await?;
         let heartbeat = qexed_heartbeat::run(config.heartbeat).await?;
         let server_logic = qtunnel_server_logic::run(config.server_logic,heartbeat.clone(),command.clone(),player_list.clone()).await?;
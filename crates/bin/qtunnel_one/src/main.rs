@@ -41,6 +41,7 @@ async fn main() -> anyhow::Result<()> {
     log::info!("读取配置文件中");
     let config: One = One::load_or_create_default()?;
     log::info!("读取配置文件完成");
+    qexed_task::message::return_message::set_debug(config.task_debug);
 
     log::info!("服务初始化");
     let server = server::Server::init(config).await?;
@@ -63,6 +64,7 @@ async fn main() -> anyhow::Result<()> {
             permission:"qtunnel.console.stop".to_string(),
             parameters: vec![],
             aliases:vec![],
+            tree:None,
             api:Some(cmd_tx),
             success:false,
     })
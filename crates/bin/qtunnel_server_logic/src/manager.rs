@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use dashmap::DashMap;
 use qexed_config::app::qexed_game_logic::GameLogicConfig;
 use qexed_task::{
+    supervisor::{self, Directive, SupervisorPolicy},
     event::task_manage::TaskManageEvent,
     message::{MessageSender, MessageType, return_message::ReturnMessage},
     task::task::Task,
@@ -53,6 +54,13 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, ReturnMessage<TaskMess
         task_map: &DashMap<Uuid, MessageSender<ReturnMessage<TaskMessage>>>,
         mut data: ReturnMessage<ManagerMessage>,
     ) -> anyhow::Result<bool> {
+        // 失败报告不需要回复,停止的任务从任务表中移除
+        if let ManagerMessage::ChildFailed(failure) = &data.data {
+            if failure.directive != Directive::Restart {
+                supervisor::prune(task_map);
+            }
+            return Ok(false);
+        }
         let send = match data.get_return_send().await? {
             Some(send) => send,
             None => return Ok(false),
@@ -70,7 +78,10 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, ReturnMessage<TaskMess
                     return Ok(false);
                 }
                 let (task, task_sand) = Task::new(api.clone(), GameLogicActor::new(uuid));
-                task.run().await?;
+                // 玩家任务失败即停止并报告给管理器
+                task.supervise(format!("game-logic-{}", uuid), SupervisorPolicy::stop())
+                    .run()
+                    .await?;
                 task_map.insert(uuid, task_sand.clone());
                 *task_api = Some(task_sand);
                 *is_true = true;
@@ -123,6 +134,7 @@ impl TaskManageEvent<Uuid, ReturnMessage<ManagerMessage>, ReturnMessage<TaskMess
                 let _ = send.send(data);
                 return Ok(false);
             }
+            ManagerMessage::ChildFailed(_) => Ok(false),
         }
     }
 }
@@ -1,5 +1,6 @@
 use bytes::Bytes;
 use qexed_player::Player;
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::message::return_message::ReturnMessage;
 use thiserror::Error;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
@@ -23,13 +24,19 @@ pub enum ManagerMessage {
     GetPlayerListApi(Option<UnboundedSender<ReturnMessage<qexed_player_list::Message>>>),
     PlayerClose(uuid::Uuid),  // 游戏连接关闭
     ConnectClose(uuid::Uuid), // 连接关闭
+    ChildFailed(qexed_task::supervisor::Failure), // 玩家游戏逻辑任务失败
+}
+impl From<qexed_task::supervisor::Failure> for ManagerMessage {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        ManagerMessage::ChildFailed(failure)
+    }
 }
 #[derive(Debug)]
 pub enum TaskMessage {
     Start(
         Player,
         Option<UnboundedReceiver<Vec<u8>>>,
-        Option<UnboundedSender<Bytes>>,
+        Option<MailboxSender<Bytes>>,
     ), // 传递数据包收发器
     Configuration(bool),
     Play,  // 游戏阶段
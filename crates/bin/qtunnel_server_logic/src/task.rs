@@ -11,6 +11,7 @@ use qexed_protocol::{
     },
     types::KnownPacks,
 };
+use qexed_task::mailbox::MailboxSender;
 use qexed_task::{
     event::task::TaskEvent,
     message::{
@@ -30,7 +31,7 @@ pub struct GameLogicActor {
     uuid: Uuid,
     player: Option<Player>,
     packet_read: Option<UnboundedReceiver<Vec<u8>>>,
-    packet_write: Option<UnboundedSender<Bytes>>,
+    packet_write: Option<MailboxSender<Bytes>>,
     qexed_heartbeat_api:
         Option<UnboundedSender<UnReturnMessage<qexed_heartbeat::message::TaskCommand>>>,
     qexed_command_api:Option<UnboundedSender<UnReturnMessage<qexed_command::message::TaskCommand>>>,
@@ -149,7 +150,7 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                             >(&mut reader)?;
                             locale = pk.locale;
                             view_distance = pk.view_distance;
-                            packet_write.send(
+                            packet_write.try_send(
                                 PacketSend::build_send_packet(qexed_protocol::to_client::configuration::select_known_packs::SelectKnownPacks {
                                     known_packs: vec![KnownPacks {
                                         namespace: "minecraft".to_string(),
@@ -210,14 +211,14 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                                 if let Some(d1) = d1 {
                                     for p in d1 {
                                         packet_write
-                                            .send(PacketSend::build_send_packet(p).await?)?;
+                                            .try_send(PacketSend::build_send_packet(p).await?)?;
                                     }
                                 }
                                 if let Some(d2) = d2 {
-                                    packet_write.send(PacketSend::build_send_packet(d2).await?)?;
+                                    packet_write.try_send(PacketSend::build_send_packet(d2).await?)?;
                                 }
                             }
-                            packet_write.send(
+                            packet_write.try_send(
                                 PacketSend::build_send_packet(FinishConfiguration {}).await?,
                             )?;
                         }
@@ -263,7 +264,7 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                         return Ok(false);
                     }
                 };
-                packet_write.send(
+                packet_write.try_send(
                     PacketSend::build_send_packet(qexed_protocol::to_client::play::login::Login {
                         entity_id: 1,
                         is_hardcore: false,
@@ -295,7 +296,7 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                     .await?,
                 )?;
                 // 初始化成就
-                packet_write.send(
+                packet_write.try_send(
                     PacketSend::build_send_packet(create_multiple_advancements_packet())
                     .await?,
                 )?;
@@ -359,10 +360,10 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                     .await?;
                 ReturnMessage::build(qexed_player_list::Message::PlayerJoin(player.uuid.clone(),player.username.clone())).get(&player_list_api).await?;
                 // 区块初始化:
-                packet_write.send(
+                packet_write.try_send(
                     PacketSend::build_send_packet(qexed_protocol::to_client::play::game_state_change::GameStateChange{reason:13,game_mode:0.0}).await?)?;
                 // 构建 SetChunkCacheCenter 数据包
-                packet_write.send(PacketSend::build_send_packet(qexed_protocol::to_client::play::update_view_position::UpdateViewPosition::default()).await?)?;
+                packet_write.try_send(PacketSend::build_send_packet(qexed_protocol::to_client::play::update_view_position::UpdateViewPosition::default()).await?)?;
                 // 发送玩家附近区块
 
                 let radius = { 12 as i32};
@@ -384,7 +385,7 @@ impl TaskEvent<ReturnMessage<TaskMessage>, ReturnMessage<ManagerMessage>> for Ga
                             },
                             light: create_light_data_for_all_sections(),
                         };
-                        packet_write.send(PacketSend::build_send_packet(p_q).await?)?;
+                        packet_write.try_send(PacketSend::build_send_packet(p_q).await?)?;
                     }
                 }
                 // let api_ping = match &self.qexed_ping_api {
@@ -1,4 +1,4 @@
-use crate::{messages::{ManagerCommand, TaskCommand}, task::TcpConnectActor};
+use crate::{messages::{ManagerCommand, TaskCommand}, task::{REQUEST_TIMEOUT, TcpConnectActor}};
 use anyhow::Result;
 use async_trait::async_trait;
 use dashmap::{DashMap, DashSet};
@@ -6,6 +6,7 @@ use qexed_config::app::qtunnel_tcp_connect_app::TcpConnect;
 use qexed_task::{
     event::task_manage::TaskManageEvent,
     message::{MessageSender, MessageType, return_message::ReturnMessage},
+    supervisor::{self, Directive, SupervisorPolicy},
 };
 use rsa::pkcs8::EncodePublicKey;
 use rsa::{RsaPrivateKey, RsaPublicKey};
@@ -139,19 +140,33 @@ impl TaskManageEvent<SocketAddr, ReturnMessage<ManagerCommand>, ReturnMessage<Ta
         task_map: &DashMap<SocketAddr, MessageSender<ReturnMessage<TaskCommand>>>,
         mut data: ReturnMessage<ManagerCommand>,
     ) -> Result<bool> {
+        // 失败报告不需要回复,停止的任务从任务表中移除
+        if let ManagerCommand::ChildFailed(failure) = &data.data {
+            if failure.directive != Directive::Restart {
+                supervisor::prune(task_map);
+            }
+            return Ok(self.is_shutdown);
+        }
         match data.data {
+            ManagerCommand::Connected(addr, ref task_api) => {
+                // 连接可能在登记前就已关闭
+                if !task_api.is_closed() {
+                    task_map.insert(addr, task_api.clone());
+                }
+            }
+            ManagerCommand::ChildFailed(_) => {}
             ManagerCommand::Start => {
                 // 启动监听循环
                 let listener = TcpListener::bind(&self.config.ip).await?;
                 let private_key = self.private_key.clone();
                 let public_key = self.public_key.clone();
                 let public_key_der = self.public_key_der.clone();
-                let task_map_clone = task_map.clone();
                 let api_clone = api.clone();
                 let network_compression_threshold =
                     self.config.network_compression_threshold.clone();
                 let online_mode = self.config.online_mode.clone();
                 let status_timeout_secs = self.config.status_timeout_secs.clone();
+                let write_queue_capacity = self.config.write_queue_capacity;
                 tokio::spawn(async move {
                     let api_clone = api_clone.clone();
                     let private_key = private_key.clone();
@@ -167,17 +182,23 @@ impl TaskManageEvent<SocketAddr, ReturnMessage<ManagerCommand>, ReturnMessage<Ta
                             public_key.clone(),
                             public_key_der.clone(),
                             status_timeout_secs.clone(),
+                            write_queue_capacity,
                         );
                         let (task, task_send) =
                             qexed_task::task::task::Task::new(api_clone.clone(), actor);
-                        let is_true = task.run().await;
+                        // 连接任务失败即停止并报告给管理器
+                        let is_true = task
+                            .supervise(format!("connection-{}", addr), SupervisorPolicy::stop())
+                            .run()
+                            .await;
                         if is_true.is_err() {
                             return Err(is_true.err());
                         }
                         ReturnMessage::build(TaskCommand::Start)
                             .get(&task_send)
                             .await?;
-                        task_map_clone.insert(addr, task_send);
+                        // 任务表只由管理器自身修改
+                        let _ = api_clone.send(ReturnMessage::build(ManagerCommand::Connected(addr, task_send)));
                         // 为新连接创建ConnectionActor
                         // let message = ReturnMessage::build(ManagerCommand::NewConnection(stream, addr));
                         // let _ = api_clone.send(message);
@@ -196,12 +217,20 @@ impl TaskManageEvent<SocketAddr, ReturnMessage<ManagerCommand>, ReturnMessage<Ta
             }
             ManagerCommand::GetStatusPackageBytes(ref mut value) => {
                 // 客户端请求查询服务器状态,这里进行转发处理
-                if let Some(respon) = ReturnMessage::build(qexed_status::Message::default())
-                    .get(&self.qexed_status_api)
-                    .await?
-                    .data
+                // 查询失败时不回复,请求方随即放弃,管理器继续运行
+                match ReturnMessage::build(qexed_status::Message::default())
+                    .get_timeout(&self.qexed_status_api, REQUEST_TIMEOUT)
+                    .await
                 {
-                    *value = Some(respon)
+                    Ok(status) => {
+                        if let Some(respon) = status.data {
+                            *value = Some(respon)
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("查询服务器状态失败: {}", e);
+                        return Ok(self.is_shutdown);
+                    }
                 }
 
                 if let Some(send) = data.get_return_send().await? {
@@ -214,9 +243,10 @@ impl TaskManageEvent<SocketAddr, ReturnMessage<ManagerCommand>, ReturnMessage<Ta
                 // 安全地遍历 task_map（DashMap 的 .iter() 会获取读锁）
                 for entry in task_map.iter() {
                     let sender = entry.value().clone(); // MessageSender 通常实现了 Clone
-                    ReturnMessage::build(TaskCommand::Shutdown(why.clone()))
+                    // 连接可能已经关闭,不能因此中断关服
+                    let _ = ReturnMessage::build(TaskCommand::Shutdown(why.clone()))
                         .get(&sender)
-                        .await?;
+                        .await;
                 }
                 if let Some(send) = data.get_return_send().await? {
                     let _ = send.send(data.data);
@@ -224,12 +254,16 @@ impl TaskManageEvent<SocketAddr, ReturnMessage<ManagerCommand>, ReturnMessage<Ta
             }
             ManagerCommand::CheckPlayeIsInList(uuid, ref mut ishave) => {
                 // 客户端请求查询服务器状态,这里进行转发处理
-                if let qexed_player_list::Message::CheckPlayeIsInList(_uuid, is_have) =
-                    ReturnMessage::build(qexed_player_list::Message::CheckPlayeIsInList(uuid, true))
-                        .get(&self.qexed_player_list_api)
-                        .await?
+                match ReturnMessage::build(qexed_player_list::Message::CheckPlayeIsInList(uuid, true))
+                    .get_timeout(&self.qexed_player_list_api, REQUEST_TIMEOUT)
+                    .await
                 {
-                    *ishave = is_have
+                    Ok(qexed_player_list::Message::CheckPlayeIsInList(_uuid, is_have)) => *ishave = is_have,
+                    Ok(_) => {}
+                    Err(e) => {
+                        log::warn!("查询在线玩家失败: {}", e);
+                        return Ok(self.is_shutdown);
+                    }
                 }
 
                 if let Some(send) = data.get_return_send().await? {
@@ -277,10 +311,21 @@ impl TaskManageEvent<SocketAddr, ReturnMessage<ManagerCommand>, ReturnMessage<Ta
                     }
                 }
                 // 验证层 4: 黑名单检查
-                let qexed_blacklist::Message::CheckPlayerBan(_uuid, ban_text) =
-                    ReturnMessage::build(qexed_blacklist::Message::CheckPlayerBan(uuid, None))
-                        .get(&self.qexed_black_list_api)
-                        .await?;
+                // 查询失败时拒绝登录,且不能让管理器退出
+                let ban_text = match ReturnMessage::build(qexed_blacklist::Message::CheckPlayerBan(uuid, None))
+                    .get_timeout(&self.qexed_black_list_api, REQUEST_TIMEOUT)
+                    .await
+                {
+                    Ok(qexed_blacklist::Message::CheckPlayerBan(_uuid, ban_text)) => ban_text,
+                    Ok(_) => {
+                        log::warn!("黑名单服务返回了意外的消息");
+                        Some("暂时无法验证黑名单,请稍后再试".to_string())
+                    }
+                    Err(e) => {
+                        log::warn!("黑名单检查失败: {}", e);
+                        Some("暂时无法验证黑名单,请稍后再试".to_string())
+                    }
+                };
                 {
                     // log::debug!("正在检测UUID:{}",_uuid);
                     // log::debug!("封禁文本:{:?}",ban_text);
@@ -295,10 +340,21 @@ impl TaskManageEvent<SocketAddr, ReturnMessage<ManagerCommand>, ReturnMessage<Ta
                 }
                 // 验证层 5: 白名单检查
                 // 请注意:Qexed 的白名单无法让你绕过反作弊,他仅仅只能限制进服
-                let qexed_whitelist::Message::CheckPlayerCanJoinServer(_uuid, ban_text) =
-                    ReturnMessage::build(qexed_whitelist::Message::CheckPlayerCanJoinServer(uuid, None))
-                        .get(&self.qexed_white_list_api)
-                        .await?;
+                // 查询失败时拒绝登录,且不能让管理器退出
+                let ban_text = match ReturnMessage::build(qexed_whitelist::Message::CheckPlayerCanJoinServer(uuid, None))
+                    .get_timeout(&self.qexed_white_list_api, REQUEST_TIMEOUT)
+                    .await
+                {
+                    Ok(qexed_whitelist::Message::CheckPlayerCanJoinServer(_uuid, ban_text)) => ban_text,
+                    Ok(_) => {
+                        log::warn!("白名单服务返回了意外的消息");
+                        Some("暂时无法验证白名单,请稍后再试".to_string())
+                    }
+                    Err(e) => {
+                        log::warn!("白名单检查失败: {}", e);
+                        Some("暂时无法验证白名单,请稍后再试".to_string())
+                    }
+                };
                 {
                     // log::debug!("正在检测UUID:{}",_uuid);
                     // log::debug!("封禁文本:{:?}",ban_text);
@@ -334,7 +390,13 @@ impl TaskManageEvent<SocketAddr, ReturnMessage<ManagerCommand>, ReturnMessage<Ta
                 return Ok(self.is_shutdown);
             },
             ManagerCommand::GetLogicApi(message) => {
-                data.data = crate::messages::ManagerCommand::GetLogicApi(ReturnMessage::build(message).get(&self.qtunnel_server_logic_api).await?);
+                match ReturnMessage::build(message).get_timeout(&self.qtunnel_server_logic_api, REQUEST_TIMEOUT).await {
+                    Ok(message) => data.data = crate::messages::ManagerCommand::GetLogicApi(message),
+                    Err(e) => {
+                        log::warn!("获取游戏逻辑接口失败: {}", e);
+                        return Ok(self.is_shutdown);
+                    }
+                }
                 if let Some(send) = data.get_return_send().await? {
                     let _ = send.send(data.data);
                 };
@@ -12,6 +12,13 @@ pub enum ManagerCommand {
     LoginCheck(uuid::Uuid, Option<IpAddr>, bool, Option<String>),
     GetLogicApi(qtunnel_server_logic::message::ManagerMessage),
     Shutdown(String),
+    Connected(SocketAddr, UnboundedSender<ReturnMessage<TaskCommand>>), // 连接任务已启动,加入任务表
+    ChildFailed(qexed_task::supervisor::Failure), // 连接任务失败
+}
+impl From<qexed_task::supervisor::Failure> for ManagerCommand {
+    fn from(failure: qexed_task::supervisor::Failure) -> Self {
+        ManagerCommand::ChildFailed(failure)
+    }
 }
 #[derive(Debug)]
 pub enum TaskCommand {
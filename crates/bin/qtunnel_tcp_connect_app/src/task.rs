@@ -1,11 +1,13 @@
 use std::{collections::HashMap, sync::{Arc, atomic::{AtomicBool, Ordering}}, time::Duration};
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use chrono::Local;
 use qexed_packet::{PacketCodec, PacketWriter, net_types::VarInt};
 use qexed_protocol::to_server::status::{ping::Ping, ping_start::PingStart};
 use qexed_task::{
     event::task::TaskEvent,
+    mailbox::{MailboxConfig, Overflow},
     message::{MessageSender, MessageType, return_message::ReturnMessage},
 };
 use rand::Rng;
@@ -16,6 +18,11 @@ use tokio_util::sync::CancellationToken;
 
 use crate::messages::{ManagerCommand, TaskCommand};
 
+// 登录检查需要依次询问名单服务,超过此时间未回复则放弃本次登录
+const LOGIN_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+// 登录路径上的其他请求超过此时间未回复则放弃,管理器转发查询时也使用此期限
+pub(crate) const REQUEST_TIMEOUT: Duration = Duration::from_secs(4);
+
 #[derive(Debug)]
 pub struct TcpConnectActor {
     socket: Option<TcpStream>,
@@ -26,6 +33,7 @@ pub struct TcpConnectActor {
     public_key: RsaPublicKey,
     public_key_der: Vec<u8>,
     status_timeout_secs:i32,
+    write_queue_capacity: usize,
 }
 impl TcpConnectActor {
     pub fn new(
@@ -37,6 +45,7 @@ impl TcpConnectActor {
         public_key: RsaPublicKey,
         public_key_der: Vec<u8>,
         status_timeout_secs:i32,
+        write_queue_capacity: usize,
     ) -> Self {
         Self {
             socket: Some(socket),
@@ -47,6 +56,7 @@ impl TcpConnectActor {
             public_key,
             public_key_der,
             status_timeout_secs,
+            write_queue_capacity,
         }
     }
 }
@@ -70,6 +80,7 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                 let public_key: RsaPublicKey = self.public_key.clone();
                 let public_key_der: Vec<u8> = self.public_key_der.clone();
                 let status_timeout_secs = self.status_timeout_secs.clone();
+                let write_queue_capacity = self.write_queue_capacity;
                 // let 
                 tokio::spawn(async move {
                     if let Some(socket) = socket {
@@ -132,8 +143,11 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                             // pass
                             // 读数据包流:
                             let (rpw, rpr) = unbounded_channel();
-                            // 写数据包流
-                            let (wpw, mut wpr) = unbounded_channel();
+                            // 写数据包流,队列写满说明客户端处理过慢,拒绝后由写任务断开连接
+                            let (wpw, mut wpr) = qexed_task::mailbox::named::<Bytes>(
+                                "connection_write",
+                                MailboxConfig::new(write_queue_capacity, Overflow::Reject),
+                            );
                             // 创建协调器
                             let shutdown = Arc::new(ConnectionShutdown::new());
                                                     
@@ -156,6 +170,10 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                                 log::debug!("读任务已停止，写任务退出");
                                 break;
                             }
+                            if wpr.stats().rejected > 0 {
+                                log::warn!("客户端处理过慢,发送队列已满,断开连接");
+                                break;
+                            }
                             
                             if let Err(e) = packet_write.send_raw(pk).await {
                                 log::error!("写入数据包出错: {}", e);
@@ -278,14 +296,14 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
                         if let Some(logic_api) = qexed_logic_api{
                             
                             let _ = ReturnMessage::build(qtunnel_server_logic::message::TaskMessage::Close)
-                                .get(&logic_api)
+                                .get_timeout(&logic_api, REQUEST_TIMEOUT)
                                 .await;
                         }
 
                         
                         // 告知自己关闭自己
                         let _ = ReturnMessage::build(TaskCommand::ConnClose(addr))
-                            .get(&api_clone)
+                            .get_timeout(&api_clone, REQUEST_TIMEOUT)
                             .await;
                         anyhow::Ok(())
                     } else {
@@ -299,7 +317,7 @@ impl TaskEvent<ReturnMessage<TaskCommand>,ReturnMessage<ManagerCommand>> for Tcp
             TaskCommand::ConnClose(addr) => {
                 // 向父级关闭告知自己
                 ReturnMessage::build(ManagerCommand::ConnClose(addr))
-                    .get(&manage_api)
+                    .get_timeout(&manage_api, REQUEST_TIMEOUT)
                     .await?;
                 if let Some(send) = data.get_return_send().await? {
                     let _ = send.send(data.data);
@@ -386,7 +404,7 @@ async fn part_status(
                 qexed_tcp_connect::decode_packet::<PingStart>(&mut reader)?;
                 let return_data: ManagerCommand =
                     ReturnMessage::build(ManagerCommand::GetStatusPackageBytes(Default::default()))
-                        .get(&manage_api)
+                        .get_timeout(&manage_api, REQUEST_TIMEOUT)
                         .await?;
                 
                 match return_data {
@@ -438,7 +456,7 @@ async fn login_status(
                 player.username = pk.username;
                 player.uuid = pk.player_uuid;
                 // 检查有没有被拉黑
-                if let ManagerCommand::LoginCheck(uuid,_ip,is_login,reason) = ReturnMessage::build(ManagerCommand::LoginCheck(player.uuid.clone(),Some(addr.ip()), false,None)).get(&manage_api).await?{
+                if let ManagerCommand::LoginCheck(uuid,_ip,is_login,reason) = ReturnMessage::build(ManagerCommand::LoginCheck(player.uuid,Some(addr.ip()), false,None)).get_timeout(&manage_api, LOGIN_CHECK_TIMEOUT).await?{
                     if uuid!=player.uuid.clone(){
                         let server_info = qexed_protocol::to_client::login::disconnect::Disconnect {
                             reason: serde_json::json!({
@@ -473,7 +491,7 @@ async fn login_status(
                     }
                 }
                 // 检查玩家是否在线
-                if let ManagerCommand::CheckPlayeIsInList(uuid,is_login) = ReturnMessage::build(ManagerCommand::CheckPlayeIsInList(player.uuid.clone(), true)).get(&manage_api).await?{
+                if let ManagerCommand::CheckPlayeIsInList(uuid,is_login) = ReturnMessage::build(ManagerCommand::CheckPlayeIsInList(player.uuid, true)).get_timeout(&manage_api, REQUEST_TIMEOUT).await?{
                     if uuid!=player.uuid.clone(){
                         let server_info = qexed_protocol::to_client::login::disconnect::Disconnect {
                             reason: serde_json::json!({
@@ -522,7 +540,7 @@ async fn login_status(
                         qtunnel_server_logic::message::ManagerMessage::NewPlayerConnect(
                             player.uuid.clone(), false, None,None)
                         )
-                    ).get(&manage_api).await?{
+                    ).get_timeout(&manage_api, REQUEST_TIMEOUT).await?{
                         if !is_true{
                             let server_info =
                                 qexed_protocol::to_client::login::disconnect::Disconnect {
@@ -698,7 +716,7 @@ async fn login_status(
                         qtunnel_server_logic::message::ManagerMessage::NewPlayerConnect(
                             player.uuid.clone(), false, None,None)
                         )
-                    ).get(&manage_api).await?{
+                    ).get_timeout(&manage_api, REQUEST_TIMEOUT).await?{
                         if !is_true{
                             let server_info =
                                 qexed_protocol::to_client::login::disconnect::Disconnect {
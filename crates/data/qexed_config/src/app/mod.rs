@@ -19,4 +19,8 @@ pub mod qtunnel_tcp_connect_app;
 pub mod qtunnel_server_logic;
 pub mod qexed_rule;
 pub mod qexed_chunk;
-pub mod qexed_title;
\ No newline at end of file
+pub mod qexed_title;
+pub mod qexed_plugin;
+pub mod qexed_event;
+pub mod qexed_shutdown;
+pub mod qexed_web;
\ No newline at end of file
@@ -1,10 +1,12 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{
     public::{
         mongodb::MongoConfig, mysql::MysqlConfig, pika::PikaConfig, storage_engine::StorageEngine,
     },
-    tool::AppConfigTrait,
+    tool::{AppConfigTrait, overrides::Scope},
 };
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BlackList {
@@ -19,6 +21,9 @@ pub struct BlackList {
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct Simple {
     pub player_list:Vec<uuid::Uuid>,
+    /// 封禁时记录的玩家名,用于 /pardon 的补全与按名字解封
+    #[serde(default)]
+    pub names:BTreeMap<uuid::Uuid,String>,
 }
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Mysql {
@@ -74,4 +79,6 @@ impl AppConfigTrait for BlackList {
     const PATH: &'static str = "./config/qexed_whilelist/";
 
     const NAME: &'static str = "config";
+
+    const ENV_SCOPE: Scope = Scope::Section("black_list");
 }
@@ -1,20 +1,200 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-use crate::tool::AppConfigTrait;
+use crate::tool::{AppConfigTrait, overrides::Scope};
 #[derive(Debug, Serialize, Deserialize,Clone)]
+#[serde(default)]
 pub struct ChatConfig {
     pub version: i32,
-
+    /// 强制安全聊天(对应 server.properties 中的 enforce-secure-profile)
+    /// 开启后玩家需提交经 Mojang 签名的聊天公钥,消息以签名的 player_chat 广播
+    /// 关闭后为无签名模式,聊天以系统消息广播
+    /// 只能在正版验证(online_mode)开启时使用
+    pub enforce_secure_profile: bool,
+    /// 聊天格式模板
+    pub format: ChatFormat,
+    /// 未匹配到任何分组的玩家使用的分组
+    pub default_group: String,
+    /// 权限分组,提供聊天前缀/后缀与权限
+    pub groups: BTreeMap<String, ChatGroup>,
+    /// 刷屏检测
+    pub spam: SpamConfig,
+    /// 敏感词过滤
+    pub filter: FilterConfig,
 }
 impl Default for ChatConfig {
     fn default() -> Self {
+        let mut groups = BTreeMap::new();
+        groups.insert(
+            "default".to_string(),
+            ChatGroup::default(),
+        );
+        groups.insert(
+            "admin".to_string(),
+            ChatGroup {
+                prefix: "§c[管理] §r".to_string(),
+                suffix: "".to_string(),
+                members: vec![],
+                permissions: vec![
+                    "qexed.mute".to_string(),
+                    "qexed.chat.notify".to_string(),
+                    "qexed.chat.log".to_string(),
+                ],
+            },
+        );
         Self {
             version: 0,
-
+            enforce_secure_profile: false,
+            format: ChatFormat::default(),
+            default_group: "default".to_string(),
+            groups,
+            spam: SpamConfig::default(),
+            filter: FilterConfig::default(),
         }
     }
 }
 impl AppConfigTrait for ChatConfig {
     const PATH: &'static str = "./config/qexed_chat/";
     const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("chat");
+}
+
+/// 聊天格式模板
+/// 可用占位符: {prefix} {suffix} {group} {player} {target} {message}
+/// 颜色使用 § 格式代码,{player} 处会附带悬停与点击事件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ChatFormat {
+    /// 普通聊天
+    pub chat: String,
+    /// /me
+    pub me: String,
+    /// /say
+    pub say: String,
+    /// 私聊: 发送者看到的消息
+    pub msg_outgoing: String,
+    /// 私聊: 接收者看到的消息
+    pub msg_incoming: String,
+    /// 悬停在玩家名上显示的文本,留空则不显示
+    pub name_hover: String,
+    /// 点击玩家名时填入聊天框的内容,留空则不响应点击
+    pub name_click: String,
+}
+impl Default for ChatFormat {
+    fn default() -> Self {
+        Self {
+            chat: "{prefix}<{player}>{suffix} {message}".to_string(),
+            me: "* {prefix}{player}{suffix} {message}".to_string(),
+            say: "[{player}] {message}".to_string(),
+            msg_outgoing: "§5[私聊] §r[我 -> {target}] §7{message}".to_string(),
+            msg_incoming: "§5[私聊] §r[{player} -> 我] §7{message}".to_string(),
+            name_hover: "§7点击私聊 §f{player}".to_string(),
+            name_click: "/msg {player} ".to_string(),
+        }
+    }
+}
+
+/// 权限分组
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ChatGroup {
+    pub prefix: String,
+    pub suffix: String,
+    /// 分组成员,填写玩家名或 UUID;玩家名只用于显示前后缀,权限只按 UUID 授予
+    pub members: Vec<String>,
+    /// 分组拥有的权限,如 qexed.mute、qexed.chat.notify(接收过滤提醒)、qexed.chat.log(查看过滤记录)
+    pub permissions: Vec<String>,
+}
+
+/// 刷屏检测,违规按 警告 -> 临时禁言 -> 踢出 逐级处理
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SpamConfig {
+    pub enabled: bool,
+    /// 频率统计窗口(秒)
+    pub window_seconds: u64,
+    /// 窗口内允许发送的聊天消息数
+    pub max_messages: usize,
+    /// 窗口内允许执行的指令数
+    pub max_commands: usize,
+    /// 重复消息检测窗口(秒)
+    pub duplicate_window_seconds: u64,
+    /// 窗口内允许发送相同消息的次数
+    pub max_duplicates: usize,
+    /// 警告次数,超过后开始临时禁言
+    pub warnings: u32,
+    /// 临时禁言时长(秒)
+    pub mute_seconds: u64,
+    /// 临时禁言次数,超过后踢出
+    pub mutes_before_kick: u32,
+    /// 超过该时长(秒)没有违规则清空违规记录
+    pub reset_seconds: u64,
+    pub kick_message: String,
+}
+impl Default for SpamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_seconds: 5,
+            max_messages: 5,
+            max_commands: 10,
+            duplicate_window_seconds: 30,
+            max_duplicates: 2,
+            warnings: 2,
+            mute_seconds: 300,
+            mutes_before_kick: 2,
+            reset_seconds: 600,
+            kick_message: "§c发言过于频繁".to_string(),
+        }
+    }
+}
+
+/// 敏感词过滤
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct FilterConfig {
+    pub enabled: bool,
+    /// 替换时每个字符使用的文本
+    pub replacement: String,
+    /// 过滤规则,如:
+    /// [[filter.rules]]
+    /// pattern = "傻瓜"
+    /// action = "replace"
+    ///
+    /// [[filter.rules]]
+    /// pattern = "(?i)discord\\.gg/\\w+"
+    /// regex = true
+    /// action = "block"
+    pub rules: Vec<FilterRule>,
+    /// 保留的过滤记录条数(/chatlog 查看)
+    pub log_size: usize,
+}
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            replacement: "*".to_string(),
+            rules: vec![],
+            log_size: 100,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FilterRule {
+    pub pattern: String,
+    /// 是否为正则表达式,否则按忽略大小写的普通文本匹配
+    #[serde(default)]
+    pub regex: bool,
+    pub action: FilterAction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// 替换匹配内容后照常发送
+    Replace,
+    /// 拦截整条消息
+    Block,
 }
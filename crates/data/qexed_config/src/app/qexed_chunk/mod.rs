@@ -3,11 +3,13 @@ pub mod engine;
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use crate::{app::qexed_chunk::engine::Engine, tool::AppConfigTrait};
-#[derive(Debug, Serialize, Deserialize,Clone)]
+use qexed_config_macros::ConfigDoc;
+use crate::{app::qexed_chunk::engine::Engine, tool::{AppConfigTrait, doc::Docs, overrides::Scope}};
+#[derive(Debug, Serialize, Deserialize,Clone, ConfigDoc)]
 pub struct ChunkConfig {
     pub version: i32,
-    // 世界管理引擎(注意子服需要保持一致)
+    /// 世界管理引擎(注意子服需要保持一致)
+    #[config(values)]
     pub engine:Engine,
     // 原版引擎
     pub engine_setting:EngineSetting
@@ -30,4 +32,9 @@ impl Default for ChunkConfig {
 impl AppConfigTrait for ChunkConfig {
     const PATH: &'static str = "./config/qexed_chunk/";
     const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("chunk");
+
+    fn docs() -> Option<Docs> {
+        Some(Docs::of::<Self>())
+    }
 }
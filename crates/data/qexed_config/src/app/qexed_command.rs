@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::tool::AppConfigTrait;
+use crate::tool::{AppConfigTrait, overrides::Scope};
 #[derive(Debug, Serialize, Deserialize,Clone)]
 pub struct CommandConfig {
     pub version: i32,
@@ -26,4 +26,5 @@ impl Default for CommandConfig {
 impl AppConfigTrait for CommandConfig {
     const PATH: &'static str = "./config/qexed_command/";
     const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("command");
 }
@@ -2,7 +2,7 @@ use std::i32;
 
 use serde::{Deserialize, Serialize};
 
-use crate::tool::AppConfigTrait;
+use crate::tool::{AppConfigTrait, overrides::Scope};
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EntityIdAllocator {
     pub version: i32,
@@ -22,4 +22,6 @@ impl AppConfigTrait for EntityIdAllocator {
     const PATH: &'static str = "./config/qexed_entity_id_allocator/";
 
     const NAME: &'static str = "config";
+
+    const ENV_SCOPE: Scope = Scope::Section("entity_id_allocator");
 }
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tool::{AppConfigTrait, overrides::Scope};
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct EventConfig {
+    pub version: i32,
+    /// 单个监听器处理一个事件的最长时间(毫秒),超时后跳过该监听器,避免插件拖慢玩家操作
+    pub listener_timeout_ms: u64,
+    /// 单个数据包钩子处理一个数据包的最长时间(毫秒),超时后数据包按原样放行
+    pub packet_hook_timeout_ms: u64,
+}
+impl Default for EventConfig {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            listener_timeout_ms: 100,
+            packet_hook_timeout_ms: 20,
+        }
+    }
+}
+impl AppConfigTrait for EventConfig {
+    const PATH: &'static str = "./config/qexed_event/";
+    const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("event");
+}
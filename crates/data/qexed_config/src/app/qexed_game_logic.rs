@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::tool::AppConfigTrait;
+use crate::tool::{AppConfigTrait, overrides::Scope};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GameLogicConfig {
@@ -18,4 +18,6 @@ impl AppConfigTrait for GameLogicConfig {
     const PATH: &'static str = "./config/qexed_logic/";
 
     const NAME: &'static str = "config";
+
+    const ENV_SCOPE: Scope = Scope::Section("game_logic");
 }
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
-use crate::tool::AppConfigTrait;
+use qexed_config_macros::ConfigDoc;
 
-#[derive(Debug, Serialize, Deserialize,Clone)]
+use crate::tool::{AppConfigTrait, doc::Docs, overrides::Scope};
+
+#[derive(Debug, Serialize, Deserialize,Clone, ConfigDoc)]
 pub struct HeartbeatConfig {
     /// 心跳间隔（秒）
     pub interval_seconds: i32,
@@ -29,4 +31,16 @@ impl Default for HeartbeatConfig {
 impl AppConfigTrait for HeartbeatConfig {
     const PATH: &'static str = "./config/qexed_heartbeat/";
     const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("heartbeat");
+
+    fn docs() -> Option<Docs> {
+        Some(Docs::of::<Self>())
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.interval_seconds <= 0 || self.timeout_seconds <= 0 {
+            anyhow::bail!("heartbeat.interval_seconds 与 heartbeat.timeout_seconds 必须大于 0");
+        }
+        Ok(())
+    }
 }
@@ -1,34 +1,125 @@
+use qexed_config_macros::ConfigDoc;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     app::{
-        qexed_blacklist::BlackList, qexed_chat::ChatConfig, qexed_chunk::ChunkConfig, qexed_command::CommandConfig, qexed_entity_id_allocator::EntityIdAllocator, qexed_game_logic::GameLogicConfig, qexed_heartbeat::HeartbeatConfig, qexed_packet_split::PacketSplitConfig, qexed_ping::PingConfig, qexed_player_list::PlayerList, qexed_rule::RuleConfig, qexed_status::StatusConfig, qexed_tcp_connect_app::TcpConnect, qexed_title::TitleConfig, qexed_whitelist::WhiteList
+        qexed_blacklist::BlackList, qexed_chat::ChatConfig, qexed_chunk::ChunkConfig, qexed_command::CommandConfig, qexed_entity_id_allocator::EntityIdAllocator, qexed_event::EventConfig, qexed_game_logic::GameLogicConfig, qexed_heartbeat::HeartbeatConfig, qexed_packet_split::PacketSplitConfig, qexed_ping::PingConfig, qexed_player_list::PlayerList, qexed_plugin::PluginConfig, qexed_rule::RuleConfig, qexed_shutdown::ShutdownConfig, qexed_status::StatusConfig, qexed_tcp_connect_app::TcpConnect, qexed_title::TitleConfig, qexed_web::WebConfig, qexed_whitelist::WhiteList
     },
-    tool::AppConfigTrait,
+    tool::{AppConfigTrait, doc::Docs, migrate::upgrade_section, overrides::Scope},
 };
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// Qexed 服务器配置
+#[derive(Debug, Default, Serialize, Deserialize, ConfigDoc)]
 pub struct One {
     pub version: i32,
+    /// 玩家连接
+    #[config(nested)]
     pub tcp_connect_app: TcpConnect,
+    /// 玩家列表
     pub player_list: PlayerList,
+    /// 服务器列表中显示的状态
+    #[config(nested)]
     pub server_status: StatusConfig,
+    /// 玩家白名单
     pub white_list: WhiteList,
+    /// 玩家黑名单
     pub black_list: BlackList,
+    /// 聊天
     pub chat:ChatConfig,
+    /// 游戏逻辑
     pub game_logic: GameLogicConfig,
+    /// 玩家 Ping 维持
+    #[config(nested)]
     pub ping: PingConfig,
+    /// 心跳
+    #[config(nested)]
     pub heartbeat:HeartbeatConfig,
+    /// 实体 id 分配
     pub entity_id_allocator:EntityIdAllocator,
+    /// 数据包分流
     pub packet_split:PacketSplitConfig,
+    /// 指令
     pub command:CommandConfig,
+    /// 游戏规则
+    #[config(nested)]
     pub rule:RuleConfig,
+    /// 区块与世界
+    #[config(nested)]
     pub chunk:ChunkConfig,
+    /// Title 指令
     pub title:TitleConfig,
-    
+    /// 插件
+    #[serde(default)]
+    pub plugin:PluginConfig,
+    /// 事件总线
+    #[serde(default)]
+    pub event:EventConfig,
+    /// 关服流程
+    #[serde(default)]
+    #[config(nested)]
+    pub shutdown:ShutdownConfig,
+    /// Web 服务(监控指标与远程管理)
+    #[serde(default)]
+    #[config(nested)]
+    pub web:WebConfig,
+    /// 记录未得到回复的请求及其处理者,排查卡住的请求时开启
+    #[serde(default)]
+    pub task_debug: bool,
+    /// 配置文件修改后自动热重载
+    #[serde(default)]
+    pub watch_config: bool,
+    /// 以独立进程部署的服务,服务名 -> 地址(tcp://主机:端口 或 unix://路径)
+    /// 未列出的服务在本进程内运行
+    #[cfg(feature = "distributed")]
+    #[serde(default)]
+    pub remote_services: std::collections::BTreeMap<String, String>,
+    /// 连接独立部署的服务时使用的令牌,需与服务配置中的 token 一致
+    #[cfg(feature = "distributed")]
+    #[serde(default)]
+    pub remote_token: String,
 }
 impl AppConfigTrait for One {
     const PATH: &'static str = "./config/";
 
     const NAME: &'static str = "qexed";
+
+    const ENV_SCOPE: Scope = Scope::Sections;
+
+    fn docs() -> Option<Docs> {
+        Some(Docs::of::<Self>())
+    }
+
+    fn migrate_sections(table: &mut toml::Table) -> anyhow::Result<bool> {
+        let mut upgraded = false;
+        upgraded |= upgrade_section::<TcpConnect>(table, "tcp_connect_app")?;
+        upgraded |= upgrade_section::<PlayerList>(table, "player_list")?;
+        upgraded |= upgrade_section::<StatusConfig>(table, "server_status")?;
+        upgraded |= upgrade_section::<WhiteList>(table, "white_list")?;
+        upgraded |= upgrade_section::<BlackList>(table, "black_list")?;
+        upgraded |= upgrade_section::<ChatConfig>(table, "chat")?;
+        upgraded |= upgrade_section::<GameLogicConfig>(table, "game_logic")?;
+        upgraded |= upgrade_section::<PingConfig>(table, "ping")?;
+        upgraded |= upgrade_section::<HeartbeatConfig>(table, "heartbeat")?;
+        upgraded |= upgrade_section::<EntityIdAllocator>(table, "entity_id_allocator")?;
+        upgraded |= upgrade_section::<PacketSplitConfig>(table, "packet_split")?;
+        upgraded |= upgrade_section::<CommandConfig>(table, "command")?;
+        upgraded |= upgrade_section::<ChunkConfig>(table, "chunk")?;
+        upgraded |= upgrade_section::<TitleConfig>(table, "title")?;
+        upgraded |= upgrade_section::<PluginConfig>(table, "plugin")?;
+        upgraded |= upgrade_section::<EventConfig>(table, "event")?;
+        upgraded |= upgrade_section::<ShutdownConfig>(table, "shutdown")?;
+        upgraded |= upgrade_section::<WebConfig>(table, "web")?;
+        Ok(upgraded)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        self.server_status.validate()?;
+        self.ping.validate()?;
+        self.heartbeat.validate()?;
+        // 离线模式下玩家没有 Mojang 签名的聊天公钥,强制安全聊天会让所有人都无法聊天
+        if self.chat.enforce_secure_profile && !self.tcp_connect_app.online_mode {
+            anyhow::bail!("chat.enforce_secure_profile 需要开启 tcp_connect_app.online_mode");
+        }
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::tool::AppConfigTrait;
+use crate::tool::{AppConfigTrait, overrides::Scope};
 #[derive(Debug, Serialize, Deserialize,Clone)]
 pub struct PacketSplitConfig {
     pub version: i32,
@@ -17,4 +17,5 @@ impl Default for PacketSplitConfig {
 impl AppConfigTrait for PacketSplitConfig {
     const PATH: &'static str = "./config/qexed_packet_split/";
     const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("packet_split");
 }
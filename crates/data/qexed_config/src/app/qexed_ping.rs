@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-use crate::tool::AppConfigTrait;
-#[derive(Debug, Serialize, Deserialize,Clone)]
+use qexed_config_macros::ConfigDoc;
+
+use crate::tool::{AppConfigTrait, doc::Docs, overrides::Scope};
+#[derive(Debug, Serialize, Deserialize,Clone, ConfigDoc)]
 pub struct PingConfig {
     pub version: i32,
     ///  Ping 间隔（秒），默认 5
@@ -27,4 +29,19 @@ impl Default for PingConfig {
 impl AppConfigTrait for PingConfig {
     const PATH: &'static str = "./config/qexed_ping/";
     const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("ping");
+
+    fn docs() -> Option<Docs> {
+        Some(Docs::of::<Self>())
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.interval <= 0 {
+            anyhow::bail!("ping.interval 必须大于 0");
+        }
+        if self.max_retries < 0 || self.latency_limit_ms < 0 {
+            anyhow::bail!("ping.max_retries 与 ping.latency_limit_ms 不能为负数");
+        }
+        Ok(())
+    }
 }
@@ -1,9 +1,17 @@
 use serde::{Deserialize, Serialize};
 
-use crate::tool::AppConfigTrait;
+use crate::tool::{AppConfigTrait, overrides::Scope};
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PlayerList {
     pub version: i32,
+    /// 独立部署时的监听地址
+    #[cfg(feature = "distributed")]
+    #[serde(default = "default_ip")]
+    pub ip: String,
+    /// 独立部署时客户端需要提供的令牌,为空时只能监听本机地址
+    #[cfg(feature = "distributed")]
+    #[serde(default)]
+    pub token: String,
 
     pub max_player: i32, //
 
@@ -13,13 +21,23 @@ impl Default for PlayerList {
     fn default() -> Self {
         Self {
             version: 0,
+            #[cfg(feature = "distributed")]
+            ip: default_ip(),
+            #[cfg(feature = "distributed")]
+            token: String::new(),
             max_player: 20,
             auto_update: false,
         }
     }
 }
+#[cfg(feature = "distributed")]
+fn default_ip() -> String {
+    "tcp://127.0.0.1:25601".to_string()
+}
 impl AppConfigTrait for PlayerList {
     const PATH: &'static str = "./config/qexed_player_list/";
 
     const NAME: &'static str = "config";
+
+    const ENV_SCOPE: Scope = Scope::Section("player_list");
 }
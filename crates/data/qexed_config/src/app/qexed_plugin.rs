@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    public::{
+        mongodb::MongoConfig, mysql::MysqlConfig, pika::PikaConfig, storage_engine::StorageEngine,
+    },
+    tool::{AppConfigTrait, overrides::Scope},
+};
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PluginConfig {
+    pub version: i32,
+    /// 是否启用插件系统
+    pub enabled: bool,
+    /// 插件目录,每个插件一个子目录(含 plugin.toml、permission.toml 与 wasm 文件)
+    pub dir: String,
+    /// 不加载的插件名
+    pub disabled: Vec<String>,
+    /// 插件默认的资源限制
+    pub limits: PluginLimits,
+    /// 按插件名覆盖资源限制,如:
+    /// [overrides.hello_world]
+    /// max_memory_mb = 128
+    pub overrides: BTreeMap<String, PluginLimits>,
+    /// 插件键值存储,数据库连接信息只在此处配置,插件无法读取
+    pub storage: PluginStorage,
+}
+impl PluginConfig {
+    pub fn limits_of(&self, plugin: &str) -> &PluginLimits {
+        self.overrides.get(plugin).unwrap_or(&self.limits)
+    }
+}
+
+/// 插件资源限制,超出限制的调用会被中断,插件随即被停用
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct PluginLimits {
+    /// 每次调用可消耗的燃料(约等于执行的指令数),0 表示不限
+    pub fuel_per_call: u64,
+    /// 每个实例的线性内存上限(MB)
+    pub max_memory_mb: u64,
+    /// 每次调用的最长时间(毫秒)
+    pub call_timeout_ms: u64,
+    /// 键值存储可用的空间(KB,按键与值的长度之和计算),0 表示不限
+    pub storage_quota_kb: u64,
+    /// 键值存储的最大键数,0 表示不限
+    pub storage_max_keys: u64,
+}
+impl Default for PluginLimits {
+    fn default() -> Self {
+        Self {
+            fuel_per_call: 100_000_000,
+            max_memory_mb: 64,
+            call_timeout_ms: 100,
+            storage_quota_kb: 1024,
+            storage_max_keys: 10_000,
+        }
+    }
+}
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            enabled: true,
+            dir: "./plugins".to_string(),
+            disabled: vec![],
+            limits: PluginLimits::default(),
+            overrides: BTreeMap::new(),
+            storage: PluginStorage::default(),
+        }
+    }
+}
+
+/// 插件键值存储的后端
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PluginStorage {
+    /// Simple 为内置的磁盘存储
+    pub storage_engine: StorageEngine,
+    /// Simple 引擎的数据目录,每个插件一个文件
+    pub dir: String,
+    pub mysql: MysqlConfig,
+    pub mongodb: MongoConfig,
+    pub pika: PikaConfig,
+}
+impl Default for PluginStorage {
+    fn default() -> Self {
+        Self {
+            storage_engine: StorageEngine::Simple,
+            dir: "./data/plugins".to_string(),
+            mysql: Default::default(),
+            mongodb: Default::default(),
+            pika: Default::default(),
+        }
+    }
+}
+impl AppConfigTrait for PluginConfig {
+    const PATH: &'static str = "./config/qexed_plugin/";
+    const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("plugin");
+}
@@ -1,14 +1,18 @@
+use qexed_config_macros::ConfigDoc;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// 规则配置
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ConfigDoc)]
 pub struct RuleConfig {
     pub version: i32,
+    #[config(nested)]
     pub player_save: PlayerSave,
     /// 默认全局世界规则
+    #[config(nested)]
     pub global_world: WorldRule,
     /// 非默认情况下的全局世界规则
+    #[config(nested = WorldRule)]
     pub world: Vec<WorldRule>,
 }
 
@@ -58,7 +62,7 @@ impl RuleConfig {
 }
 
 /// 玩家保存配置
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ConfigDoc)]
 pub struct PlayerSave {
     /// 是否启用玩家数限制
     pub max_player_limit: bool,
@@ -68,7 +72,7 @@ pub struct PlayerSave {
 
 /// Minecraft 原版游戏规则
 /// 参考: https://zh.minecraft.wiki/w/游戏规则
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ConfigDoc)]
 pub struct WorldRule {
     // ==================== 世界更新类规则 ====================
     /// 游戏内时间流逝 | 是否进行昼夜更替和月相变化
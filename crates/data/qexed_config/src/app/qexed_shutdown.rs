@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use qexed_config_macros::ConfigDoc;
+
+use crate::tool::{AppConfigTrait, doc::Docs, overrides::Scope};
+#[derive(Debug, Serialize, Deserialize, Clone, ConfigDoc)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    pub version: i32,
+    /// 关服时向在线玩家显示的断开原因
+    pub kick_message: String,
+    /// 每个关闭阶段的最长等待时间(秒),超时后跳过该阶段继续关闭
+    pub stage_timeout_secs: u64,
+}
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            kick_message: "服务器已关闭".to_string(),
+            stage_timeout_secs: 10,
+        }
+    }
+}
+impl AppConfigTrait for ShutdownConfig {
+    const PATH: &'static str = "./config/qexed_shutdown/";
+    const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("shutdown");
+
+    fn docs() -> Option<Docs> {
+        Some(Docs::of::<Self>())
+    }
+}
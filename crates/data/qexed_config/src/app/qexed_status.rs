@@ -1,10 +1,12 @@
 use serde::{Deserialize, Serialize};
 
-use crate::tool::AppConfigTrait;
+use qexed_config_macros::ConfigDoc;
+
+use crate::tool::{AppConfigTrait, doc::Docs, overrides::Scope};
 #[cfg(feature = "distributed")]
 use crate::public::ip::IP;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ConfigDoc)]
 pub struct StatusConfig {
     pub version: i32,
     #[cfg(feature = "distributed")]
@@ -33,4 +35,20 @@ impl AppConfigTrait for StatusConfig {
     const PATH: &'static str = "./config/qexed_status/";
 
     const NAME: &'static str = "config";
+
+    const ENV_SCOPE: Scope = Scope::Section("server_status");
+
+    fn docs() -> Option<Docs> {
+        Some(Docs::of::<Self>())
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.cache < -1 {
+            anyhow::bail!("server_status.cache 不能小于 -1");
+        }
+        if self.motd.is_empty() {
+            anyhow::bail!("server_status.motd 至少需要一条");
+        }
+        Ok(())
+    }
 }
@@ -1,18 +1,22 @@
+use qexed_config_macros::{AutoEnum, ConfigDoc};
 use serde::{Deserialize, Serialize};
 
-use crate::tool::AppConfigTrait;
+use crate::tool::{AppConfigTrait, doc::Docs, overrides::Scope};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 玩家连接
+#[derive(Debug, Serialize, Deserialize, ConfigDoc)]
 pub struct TcpConnect {
     pub version: i32,
+    /// 监听地址
     pub ip: String,
-    // Mojang 认证
+    /// Mojang 认证
     pub online_mode: bool,
     /// 网络数据包压缩
     pub network_compression_threshold: usize,
     /// 是否启用代理
     pub proxy:bool,
     /// 代理端协议
+    #[config(values)]
     pub proxy_protocol: ForwardingMode,
     /// 认证密钥
     pub proxy_token: String,
@@ -22,53 +26,26 @@ pub struct TcpConnect {
     pub rate_limit_max_attempts: u32,
     /// Status数据包检测延迟
     pub status_timeout_secs:i32,
+    /// 每个连接待发送数据包的队列上限,客户端处理过慢导致队列写满时断开连接
+    #[serde(default = "default_write_queue_capacity")]
+    pub write_queue_capacity: usize,
+}
 
+fn default_write_queue_capacity() -> usize {
+    4096
 }
 
 
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, AutoEnum)]
 pub enum ForwardingMode {
+    #[default]
     Default,
     QTunnel,
     Victory,
     BungeeCord,
     None,
 }
-impl Default for ForwardingMode {
-    fn default() -> Self {
-        ForwardingMode::Default
-    }
-}
-
-// 为ForwardingMode实现Display trait
-impl std::fmt::Display for ForwardingMode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ForwardingMode::Default => write!(f, "Default"),
-            ForwardingMode::QTunnel => write!(f, "QTunnel"),
-            ForwardingMode::Victory => write!(f, "Victory"),
-            ForwardingMode::BungeeCord => write!(f, "BungeeCord"),
-            ForwardingMode::None => write!(f, "None"),
-        }
-    }
-}
-
-// 为ForwardingMode实现FromStr用于解析
-impl std::str::FromStr for ForwardingMode {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "default" => Ok(ForwardingMode::Default),
-            "qtunnel" => Ok(ForwardingMode::QTunnel),
-            "victory" => Ok(ForwardingMode::Victory),
-            "bungeecord" => Ok(ForwardingMode::BungeeCord),
-            "none" => Ok(ForwardingMode::None),
-            _ => Err(format!("未知的转发模式: {}", s)),
-        }
-    }
-}
 
 impl Default for TcpConnect {
     fn default() -> Self {
@@ -83,7 +60,7 @@ impl Default for TcpConnect {
             rate_limit_window_secs: 60,
             rate_limit_max_attempts: 6,
             status_timeout_secs: 5,
-            
+            write_queue_capacity: default_write_queue_capacity(),
         }
     }
 }
@@ -91,4 +68,10 @@ impl AppConfigTrait for TcpConnect {
     const PATH: &'static str = "./config/qexed_player_list/";
 
     const NAME: &'static str = "config";
+
+    const ENV_SCOPE: Scope = Scope::Section("tcp_connect_app");
+
+    fn docs() -> Option<Docs> {
+        Some(Docs::of::<Self>())
+    }
 }
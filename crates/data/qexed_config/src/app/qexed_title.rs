@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::tool::AppConfigTrait;
+use crate::tool::{AppConfigTrait, overrides::Scope};
 #[derive(Debug, Serialize, Deserialize,Clone)]
 pub struct TitleConfig {
     pub version: i32,
@@ -16,4 +16,5 @@ impl Default for TitleConfig {
 impl AppConfigTrait for TitleConfig {
     const PATH: &'static str = "./config/qexed_title/";
     const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("title");
 }
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+use qexed_config_macros::ConfigDoc;
+
+use crate::tool::{AppConfigTrait, doc::Docs, overrides::Scope};
+#[derive(Debug, Serialize, Deserialize, Clone, ConfigDoc)]
+#[serde(default)]
+pub struct WebConfig {
+    pub version: i32,
+    /// 是否启用 Web 服务
+    pub enable: bool,
+    /// 监听地址
+    pub addr: String,
+    /// 管理接口与控制台的访问令牌,请求时放在 Authorization: Bearer 头或 token 参数中
+    /// 为空(默认)时不开放管理接口,监控指标不需要令牌
+    pub token: String,
+}
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            enable: false,
+            addr: "127.0.0.1:9940".to_string(),
+            token: String::new(),
+        }
+    }
+}
+impl AppConfigTrait for WebConfig {
+    const PATH: &'static str = "./config/qexed_web/";
+    const NAME: &'static str = "config";
+    const ENV_SCOPE: Scope = Scope::Section("web");
+
+    fn docs() -> Option<Docs> {
+        Some(Docs::of::<Self>())
+    }
+}
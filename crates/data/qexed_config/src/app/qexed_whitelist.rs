@@ -4,9 +4,9 @@ use crate::{
     public::{
         mongodb::MongoConfig, mysql::MysqlConfig, pika::PikaConfig, storage_engine::StorageEngine,
     },
-    tool::AppConfigTrait,
+    tool::{AppConfigTrait, overrides::Scope},
 };
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WhiteList {
     pub version: i32,
     pub enable: bool,
@@ -17,11 +17,11 @@ pub struct WhiteList {
     pub mongodb: MongoDB,
     pub pika: Pika,
 }
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Simple {
     pub player_list:Vec<uuid::Uuid>,
 }
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Mysql {
     #[serde(flatten)]
     pub data: MysqlConfig,
@@ -36,12 +36,12 @@ impl Default for Mysql {
     }
 }
 
-#[derive(Debug,Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MongoDB {
     #[serde(flatten)]
     pub data: MongoConfig,
 }
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pika {
     #[serde(flatten)]
     pub data: PikaConfig,
@@ -87,4 +87,6 @@ impl AppConfigTrait for WhiteList {
     const PATH: &'static str = "./config/qexed_whilelist/";
 
     const NAME: &'static str = "config";
+
+    const ENV_SCOPE: Scope = Scope::Section("white_list");
 }
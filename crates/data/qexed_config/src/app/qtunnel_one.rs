@@ -4,7 +4,7 @@ use crate::{
     app::{
         qexed_blacklist::BlackList, qexed_command::CommandConfig, qexed_heartbeat::HeartbeatConfig, qexed_player_list::PlayerList, qexed_status::StatusConfig, qexed_whitelist::WhiteList, qtunnel_server_logic::ServerLogicConfig, qtunnel_tcp_connect_app::TcpConnect
     },
-    tool::AppConfigTrait,
+    tool::{AppConfigTrait, overrides::Scope},
 };
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -18,9 +18,14 @@ pub struct One {
     pub command: CommandConfig,
     pub server_logic: ServerLogicConfig,
     pub heartbeat:HeartbeatConfig,
+    /// 记录未得到回复的请求及其处理者,排查卡住的请求时开启
+    #[serde(default)]
+    pub task_debug: bool,
 }
 impl AppConfigTrait for One {
     const PATH: &'static str = "./config/";
 
     const NAME: &'static str = "qtunnel";
+
+    const ENV_SCOPE: Scope = Scope::Sections;
 }
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::tool::AppConfigTrait;
+use crate::tool::{AppConfigTrait, overrides::Scope};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerLogicConfig {
@@ -17,4 +17,6 @@ impl AppConfigTrait for ServerLogicConfig {
     const PATH: &'static str = "./config/qtunnel_server_logic/";
 
     const NAME: &'static str = "config";
+
+    const ENV_SCOPE: Scope = Scope::Section("server_logic");
 }
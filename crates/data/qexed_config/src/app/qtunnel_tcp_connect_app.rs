@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::tool::AppConfigTrait;
+use crate::tool::{AppConfigTrait, overrides::Scope};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TcpConnect {
@@ -22,7 +22,13 @@ pub struct TcpConnect {
     pub rate_limit_max_attempts: u32,
     /// Status数据包检测延迟
     pub status_timeout_secs:i32,
+    /// 每个连接待发送数据包的队列上限,客户端处理过慢导致队列写满时断开连接
+    #[serde(default = "default_write_queue_capacity")]
+    pub write_queue_capacity: usize,
+}
 
+fn default_write_queue_capacity() -> usize {
+    4096
 }
 
 
@@ -83,7 +89,7 @@ impl Default for TcpConnect {
             rate_limit_window_secs: 60,
             rate_limit_max_attempts: 6,
             status_timeout_secs: 5,
-            
+            write_queue_capacity: default_write_queue_capacity(),
         }
     }
 }
@@ -91,4 +97,6 @@ impl AppConfigTrait for TcpConnect {
     const PATH: &'static str = "./config/qtunnel_player_list/";
 
     const NAME: &'static str = "config";
+
+    const ENV_SCOPE: Scope = Scope::Section("tcp_connect_app");
 }
@@ -10,6 +10,8 @@ const fn make_qtunnel_name() -> &'static str {
     "QTunnel 0.1.0a"
 }
 pub const QTUNNEL_NAME: &'static str = make_qtunnel_name();
+// 让派生宏生成的 ::qexed_config 路径在本 crate 内同样可用
+extern crate self as qexed_config;
 pub mod app;
 pub mod tool;
 pub mod public;
\ No newline at end of file
@@ -0,0 +1,277 @@
+// 配置文档
+// 由 ConfigDoc 派生宏收集字段的文档注释,写入配置文件时输出为 TOML 注释
+// 同时可以生成 JSON Schema 供编辑器校验与补全
+use serde::Serialize;
+use serde_json::{Map, json};
+use toml::{Table, Value};
+
+/// 超过此长度的默认值不写入注释
+const MAX_DEFAULT_LEN: usize = 60;
+
+/// 字段文档
+#[derive(Debug, Clone)]
+pub struct FieldDoc {
+    pub name: &'static str,
+    pub doc: &'static str,
+    /// 可选值,来自 AutoEnum 枚举
+    pub values: Vec<String>,
+    /// 嵌套配置的字段
+    pub nested: Vec<FieldDoc>,
+}
+
+/// 由 #[derive(ConfigDoc)] 实现
+pub trait ConfigDoc {
+    fn doc() -> &'static str;
+    fn fields() -> Vec<FieldDoc>;
+}
+
+/// 由 #[derive(AutoEnum)] 实现
+pub trait ConfigValues {
+    fn values() -> Vec<String>;
+}
+
+/// 一份配置的文档
+#[derive(Debug, Clone)]
+pub struct Docs {
+    pub doc: &'static str,
+    pub fields: Vec<FieldDoc>,
+}
+
+impl Docs {
+    pub fn of<T: ConfigDoc>() -> Self {
+        Self {
+            doc: T::doc(),
+            fields: T::fields(),
+        }
+    }
+
+    /// 按路径查找字段,数组中的表与普通表同样处理
+    fn find(&self, path: &[String]) -> Option<&FieldDoc> {
+        let (last, parents) = path.split_last()?;
+        let mut fields = &self.fields;
+        for key in parents {
+            fields = &fields.iter().find(|field| field.name == key)?.nested;
+        }
+        fields.iter().find(|field| field.name == last)
+    }
+}
+
+/// 默认配置,去掉每次生成都不同的取值(随机密钥等)
+pub fn stable_defaults<T: Serialize + Default>() -> anyhow::Result<Table> {
+    let mut first = Table::try_from(T::default())?;
+    let second = Table::try_from(T::default())?;
+    retain_equal(&mut first, &second);
+    Ok(first)
+}
+
+fn retain_equal(table: &mut Table, other: &Table) {
+    table.retain(|key, value| match (value, other.get(key)) {
+        (Value::Table(table), Some(Value::Table(other))) => {
+            retain_equal(table, other);
+            true
+        }
+        (value, Some(other)) => value == other,
+        (_, None) => false,
+    });
+}
+
+/// 输出带注释的 TOML
+pub fn render<T: Serialize>(config: &T, docs: &Docs, defaults: &Table) -> anyhow::Result<String> {
+    let text = toml::to_string_pretty(config)?;
+    let mut out = String::new();
+    if !docs.doc.is_empty() {
+        comment(&mut out, docs.doc);
+        out.push('\n');
+    }
+    let mut section = Vec::new();
+    let mut in_string = false;
+    for line in text.lines() {
+        if !in_string {
+            if let Some(header) = header(line) {
+                section = split_key(header);
+                if let Some(field) = docs.find(&section) {
+                    describe(&mut out, field, None);
+                }
+            } else if !line.starts_with([' ', '\t', ']'])
+                && let Some((key, _)) = line.split_once(" = ")
+            {
+                let mut path = section.clone();
+                path.extend(split_key(key));
+                if let Some(field) = docs.find(&path) {
+                    describe(&mut out, field, lookup(defaults, &path));
+                }
+            }
+        }
+        // 多行字符串中的内容不是键
+        if (line.matches("\"\"\"").count() + line.matches("'''").count()) % 2 == 1 {
+            in_string = !in_string;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+// 没有文档的字段(如 version)不输出注释
+fn describe(out: &mut String, field: &FieldDoc, default: Option<&Value>) {
+    if field.doc.is_empty() && field.values.is_empty() {
+        return;
+    }
+    comment(out, field.doc);
+    if !field.values.is_empty() {
+        comment(out, &format!("可选值: {}", field.values.join(", ")));
+    }
+    if let Some(default) = default
+        && !default.is_table()
+    {
+        let default = default.to_string();
+        if default.len() <= MAX_DEFAULT_LEN {
+            comment(out, &format!("默认值: {}", default));
+        }
+    }
+}
+
+fn comment(out: &mut String, text: &str) {
+    for line in text.lines() {
+        if line.is_empty() {
+            out.push_str("#\n");
+        } else {
+            out.push_str("# ");
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+}
+
+// [a.b] 与 [[a.b]] 中的键路径
+fn header(line: &str) -> Option<&str> {
+    let line = line.trim_end();
+    line.strip_prefix("[[")
+        .and_then(|line| line.strip_suffix("]]"))
+        .or_else(|| line.strip_prefix('[').and_then(|line| line.strip_suffix(']')))
+}
+
+// 按 . 拆分键,引号内的 . 不拆分
+fn split_key(key: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut quote = None;
+    for c in key.trim().chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '.') => parts.push(std::mem::take(&mut part).trim().to_string()),
+            _ => part.push(c),
+        }
+    }
+    parts.push(part.trim().to_string());
+    parts
+}
+
+fn lookup<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let mut table = table;
+    for key in parents {
+        table = table.get(key)?.as_table()?;
+    }
+    table.get(last)
+}
+
+/// 由默认配置推断取值类型,生成 JSON Schema
+pub fn schema(docs: &Docs, defaults: &Table) -> serde_json::Value {
+    let mut schema = object(defaults, &docs.fields);
+    schema["$schema"] = json!("http://json-schema.org/draft-07/schema#");
+    if !docs.doc.is_empty() {
+        schema["description"] = json!(docs.doc);
+    }
+    schema
+}
+
+fn object(table: &Table, fields: &[FieldDoc]) -> serde_json::Value {
+    let properties: Map<String, serde_json::Value> = table
+        .iter()
+        .map(|(key, value)| {
+            let field = fields.iter().find(|field| field.name == key);
+            (key.clone(), property(value, field))
+        })
+        .collect();
+    json!({ "type": "object", "properties": properties })
+}
+
+fn property(value: &Value, field: Option<&FieldDoc>) -> serde_json::Value {
+    let nested = field.map(|field| field.nested.as_slice()).unwrap_or_default();
+    let mut schema = match value {
+        Value::String(_) | Value::Datetime(_) => json!({ "type": "string" }),
+        Value::Integer(_) => json!({ "type": "integer" }),
+        Value::Float(_) => json!({ "type": "number" }),
+        Value::Boolean(_) => json!({ "type": "boolean" }),
+        Value::Array(items) => match items.first() {
+            Some(Value::Table(item)) => json!({ "type": "array", "items": object(item, nested) }),
+            Some(item) => json!({ "type": "array", "items": property(item, None) }),
+            None => json!({ "type": "array" }),
+        },
+        Value::Table(table) => object(table, nested),
+    };
+    if !value.is_table()
+        && let Ok(default) = serde_json::to_value(value)
+    {
+        schema["default"] = default;
+    }
+    if let Some(field) = field {
+        if !field.doc.is_empty() {
+            schema["description"] = json!(field.doc);
+        }
+        if !field.values.is_empty() {
+            schema["enum"] = json!(field.values);
+        }
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::tool::doc::{ConfigDoc, ConfigValues};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, qexed_config_macros::AutoEnum)]
+    enum Mode {
+        #[default]
+        Fast,
+        Safe,
+    }
+
+    /// 测试配置
+    #[derive(Debug, Default, Serialize, Deserialize, qexed_config_macros::ConfigDoc)]
+    struct Inner {
+        /// 端口
+        port: u16,
+    }
+
+    #[derive(Debug, Default, Serialize, Deserialize, qexed_config_macros::ConfigDoc)]
+    struct Outer {
+        /// 模式
+        /// 第二行
+        #[config(values)]
+        mode: Mode,
+        #[config(nested)]
+        inner: Inner,
+    }
+
+    #[test]
+    fn render_comments() {
+        assert_eq!(Mode::values(), vec!["Fast", "Safe"]);
+        assert_eq!(Inner::doc(), "测试配置");
+        let docs = Docs::of::<Outer>();
+        let text = render(&Outer::default(), &docs, &stable_defaults::<Outer>().unwrap()).unwrap();
+        assert!(text.contains("# 模式\n# 第二行\n# 可选值: Fast, Safe\n# 默认值: \"Fast\"\nmode = \"Fast\""));
+        assert!(text.contains("# 端口\n# 默认值: 0\nport = 0"));
+        // 注释不影响读取
+        let _: Outer = toml::from_str(&text).unwrap();
+
+        let schema = schema(&docs, &stable_defaults::<Outer>().unwrap());
+        assert_eq!(schema["properties"]["mode"]["enum"], json!(["Fast", "Safe"]));
+        assert_eq!(schema["properties"]["inner"]["properties"]["port"]["type"], "integer");
+    }
+}
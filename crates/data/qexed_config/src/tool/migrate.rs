@@ -0,0 +1,209 @@
+// 配置文件版本升级
+// 读取时按 version 逐级执行迁移,缺少的配置项使用默认值,未知配置项给出警告
+// 文件发生变化时先备份原文件再写回升级后的配置
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+use super::AppConfigTrait;
+
+/// 读取配置文件,必要时升级并写回
+pub(crate) fn read<T: AppConfigTrait>(path: &Path) -> anyhow::Result<T> {
+    let (config, changed) = parse::<T>(path)?;
+    if changed {
+        let backup = backup(path)?;
+        super::write(&config, path)?;
+        log::info!("[配置] {} 已升级,原文件备份为 {}", path.display(), backup.display());
+    }
+    Ok(config)
+}
+
+/// 读取并升级配置文件但不写回,返回配置与文件是否需要写回
+pub(crate) fn parse<T: AppConfigTrait>(path: &Path) -> anyhow::Result<(T, bool)> {
+    let content = std::fs::read_to_string(path)?;
+    let mut table: Table = toml::from_str(&content)?;
+    let mut upgraded = upgrade::<T>(&mut table, T::NAME)?;
+    upgraded |= T::migrate_sections(&mut table)?;
+
+    // 只在缺少必需的配置项时补全,避免往映射类配置里加回被删除的默认条目
+    let mut added = Vec::new();
+    let config: T = match table.clone().try_into() {
+        Ok(config) => config,
+        Err(_) => {
+            fill(&mut table, &Table::try_from(T::default())?, "", &mut added);
+            for key in &added {
+                log::warn!("[配置] {} 缺少配置项 {},已使用默认值", path.display(), key);
+            }
+            table.clone().try_into()?
+        }
+    };
+    let mut unknown = Vec::new();
+    unknown_keys(&table, &Table::try_from(&config)?, "", &mut unknown);
+    for key in &unknown {
+        log::warn!("[配置] {} 中的配置项 {} 未被使用,已忽略", path.display(), key);
+    }
+    Ok((config, upgraded || !added.is_empty()))
+}
+
+/// 按 version 把配置升级到 T::VERSION,返回是否执行了迁移
+pub fn upgrade<T: AppConfigTrait>(table: &mut Table, name: &str) -> anyhow::Result<bool> {
+    let version = table
+        .get("version")
+        .and_then(Value::as_integer)
+        .unwrap_or(0) as i32;
+    if version > T::VERSION {
+        log::warn!(
+            "[配置] {} 的版本 {} 高于当前支持的版本 {},部分配置可能无法识别",
+            name, version, T::VERSION
+        );
+        return Ok(false);
+    }
+    for from in version..T::VERSION {
+        T::migrate(from, table)?;
+        log::info!("[配置] {} 从版本 {} 升级到 {}", name, from, from + 1);
+    }
+    table.insert("version".to_string(), Value::Integer(T::VERSION as i64));
+    Ok(version < T::VERSION)
+}
+
+/// 升级嵌套在上级配置中的一节,用于 One 这类组合配置
+pub fn upgrade_section<T: AppConfigTrait>(table: &mut Table, key: &str) -> anyhow::Result<bool> {
+    match table.get_mut(key) {
+        Some(Value::Table(section)) => upgrade::<T>(section, key),
+        _ => Ok(false),
+    }
+}
+
+/// 用默认配置补全缺少的配置项
+fn fill(table: &mut Table, defaults: &Table, prefix: &str, added: &mut Vec<String>) {
+    for (key, default) in defaults {
+        let name = format!("{}{}", prefix, key);
+        match (table.get_mut(key), default) {
+            // 与默认值没有相同键的表是以 UUID 等为键的映射,不补全
+            (Some(Value::Table(table)), Value::Table(defaults))
+                if table.keys().any(|key| defaults.contains_key(key)) =>
+            {
+                fill(table, defaults, &format!("{}.", name), added)
+            }
+            (Some(_), _) => {}
+            (None, _) => {
+                table.insert(key.clone(), default.clone());
+                added.push(name);
+            }
+        }
+    }
+}
+
+/// 文件中存在但配置结构中没有的配置项
+fn unknown_keys(table: &Table, known: &Table, prefix: &str, unknown: &mut Vec<String>) {
+    for (key, value) in table {
+        let name = format!("{}{}", prefix, key);
+        match (value, known.get(key)) {
+            (Value::Table(table), Some(Value::Table(known))) => {
+                unknown_keys(table, known, &format!("{}.", name), unknown)
+            }
+            (_, Some(_)) => {}
+            (_, None) => unknown.push(name),
+        }
+    }
+}
+
+fn backup(path: &Path) -> anyhow::Result<PathBuf> {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.bak", secs));
+    let backup = path.with_file_name(name);
+    std::fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Sample {
+        version: i32,
+        port: u16,
+        motd: String,
+    }
+
+    impl Default for Sample {
+        fn default() -> Self {
+            Self {
+                version: 2,
+                port: 25565,
+                motd: "Qexed".to_string(),
+            }
+        }
+    }
+
+    impl AppConfigTrait for Sample {
+        const PATH: &'static str = "./config/";
+        const NAME: &'static str = "sample";
+        const VERSION: i32 = 2;
+
+        fn migrate(from: i32, table: &mut Table) -> anyhow::Result<()> {
+            match from {
+                // 版本 1 把 server_port 改名为 port
+                0 => {
+                    if let Some(port) = table.remove("server_port") {
+                        table.insert("port".to_string(), port);
+                    }
+                }
+                // 版本 2 去掉了 debug
+                1 => {
+                    table.remove("debug");
+                }
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn upgrade_runs_each_step() {
+        let mut table: Table = toml::from_str("server_port = 25566\ndebug = true\nmotd = \"hi\"").unwrap();
+        assert!(upgrade::<Sample>(&mut table, "sample").unwrap());
+        let sample: Sample = table.try_into().unwrap();
+        assert_eq!(sample.version, 2);
+        assert_eq!(sample.port, 25566);
+        assert_eq!(sample.motd, "hi");
+    }
+
+    #[test]
+    fn read_fills_missing_and_keeps_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sample.toml");
+        std::fs::write(&path, "version = 2\nport = 1\nunused = 3").unwrap();
+
+        let sample: Sample = read(&path).unwrap();
+        assert_eq!(sample.port, 1);
+        assert_eq!(sample.motd, "Qexed");
+        let written: Table = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(written.contains_key("motd"));
+        let backups = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path() != path)
+            .count();
+        assert_eq!(backups, 1);
+    }
+
+    #[test]
+    fn parse_leaves_file_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sample.toml");
+        let content = "server_port = 25566\nmotd = \"hi\"";
+        std::fs::write(&path, content).unwrap();
+
+        let (sample, changed) = parse::<Sample>(&path).unwrap();
+        assert!(changed);
+        assert_eq!(sample.port, 25566);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -1,38 +1,172 @@
 use serde::{Deserialize, Serialize};
 
+pub mod doc;
+pub mod migrate;
+pub mod overrides;
+
 // Tool 目录旨在简化配置文件管理,而非全量
 pub trait AppConfigTrait: Serialize + for<'de> Deserialize<'de> + Default {
     const PATH: &'static str;
     const NAME: &'static str;
+    /// 当前配置结构的版本,修改时同步修改 Default 中的 version 并补充 migrate
+    const VERSION: i32 = 0;
+    /// 环境变量覆盖的作用范围,默认不应用环境变量
+    const ENV_SCOPE: overrides::Scope = overrides::Scope::None;
+
+    /// 派生了 ConfigDoc 的配置返回 Some(Docs::of::<Self>()),写入时带注释并生成 JSON Schema
+    fn docs() -> Option<doc::Docs> {
+        None
+    }
+
+    /// 把 from 版本的配置升级到 from + 1
+    fn migrate(_from: i32, _table: &mut toml::Table) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// 升级嵌套的配置节,返回是否有配置节被升级
+    fn migrate_sections(_table: &mut toml::Table) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    fn path() -> std::path::PathBuf {
+        std::path::Path::new(Self::PATH).join(Self::NAME).with_extension("toml")
+    }
+
+    /// 检查配置取值,启动时不通过校验则拒绝启动,热重载时不通过校验的配置不会被应用
+    fn validate(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// 重新读取已有的配置文件并校验,用于热重载
+    fn load() -> anyhow::Result<Self> {
+        let config = migrate::read::<Self>(&Self::path())?.with_overrides()?;
+        config.validate()?;
+        Ok(config)
+    }
 
     fn load_or_create_default() -> anyhow::Result<Self> {
         // 默认实现：尝试从文件加载，如果失败则创建默认配置并保存
-        let path = std::path::Path::new(Self::PATH).join(Self::NAME);
-        let path = path.with_extension("toml");
+        let path = Self::path();
 
-        if path.exists() {
-            let content = std::fs::read_to_string(&path)?;
-            let config: Self = toml::from_str(&content)?;
-            Ok(config)
+        let config = if path.exists() {
+            migrate::read::<Self>(&path)?.with_overrides()?
         } else {
             let config = Self::default();
-            let content = toml::to_string_pretty(&config)?;
-            if let Some(parent) = path.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
-            std::fs::write(&path, content)?;
-            Ok(config)
-        }
+            write(&config, &path)?;
+            config.with_overrides()?
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 读取配置文件,文件不存在时使用默认配置,不会创建、升级或改写文件
+    fn load_or_default() -> anyhow::Result<Self> {
+        let path = Self::path();
+        let config = if path.exists() {
+            migrate::parse::<Self>(&path)?.0
+        } else {
+            Self::default()
+        };
+        config.with_overrides()
+    }
+
+    /// 应用环境变量与命令行覆盖,见 overrides
+    fn with_overrides(self) -> anyhow::Result<Self> {
+        let mut table = toml::Table::try_from(&self)?;
+        overrides::apply(&mut table, Self::ENV_SCOPE);
+        Ok(table.try_into()?)
     }
 
     fn save(&self) -> anyhow::Result<()> {
-        let path = std::path::Path::new(Self::PATH).join(Self::NAME);
-        let path = path.with_extension("toml");
-        let content = toml::to_string_pretty(self)?;
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        write(self, &Self::path())
+    }
+
+    /// 修改配置文件中的一项并写回,key 为以 . 分隔的配置项路径
+    /// 直接读写文件,环境变量与命令行覆盖不会被写入
+    /// 读改写期间持有进程内的写锁,多个服务同时修改同一文件的不同配置节时不会互相覆盖
+    fn update<V: Serialize>(key: &str, value: V) -> anyhow::Result<()> {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = Self::path();
+        let mut table: toml::Table = toml::from_str(&std::fs::read_to_string(&path)?)?;
+        let keys: Vec<&str> = key.split('.').collect();
+        let Some((last, parents)) = keys.split_last() else {
+            anyhow::bail!("配置项路径为空");
+        };
+        let mut section = &mut table;
+        for parent in parents {
+            section = match section.get_mut(*parent).and_then(toml::Value::as_table_mut) {
+                Some(next) => next,
+                None => anyhow::bail!("{} 中没有配置项 {}", path.display(), key),
+            };
+        }
+        section.insert(last.to_string(), toml::Value::try_from(value)?);
+        let config: Self = table.try_into()?;
+        write(&config, &path)
+    }
+}
+
+// 串行化进程内对配置文件的写入
+static WRITE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// 服务把运行中修改的配置写回配置文件的方式
+/// 配置可能嵌在其他配置文件中,写到哪里由启动服务的程序决定
+pub type Persist<T> = Box<dyn Fn(&T) -> anyhow::Result<()> + Send + Sync>;
+
+/// 写入配置文件,有文档的配置附带注释并在旁边生成 JSON Schema
+pub(crate) fn write<T: AppConfigTrait>(config: &T, path: &std::path::Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = match T::docs() {
+        Some(docs) => {
+            let defaults = doc::stable_defaults::<T>()?;
+            let schema = path.with_extension("schema.json");
+            std::fs::write(&schema, serde_json::to_string_pretty(&doc::schema(&docs, &defaults))?)?;
+            let name = schema.file_name().unwrap_or_default().to_string_lossy();
+            // Taplo 等编辑器插件按此注释关联 Schema
+            format!("#:schema ./{}\n{}", name, doc::render(config, &docs, &defaults)?)
+        }
+        None => toml::to_string_pretty(config)?,
+    };
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// 热重载请求,服务应用可以在线修改的配置项,其余变化记入 restart
+#[derive(Debug, Clone)]
+pub struct Reload<T> {
+    pub config: T,
+    /// 已修改但需要重启才能生效的配置项
+    pub restart: Vec<String>,
+}
+
+impl<T> Reload<T> {
+    pub fn new(config: T) -> Self {
+        Self {
+            config,
+            restart: Vec::new(),
         }
-        std::fs::write(&path, content)?;
-        Ok(())
     }
+
+    /// 无法在线修改的配置项发生变化时记录
+    pub fn restart_if(&mut self, field: &str, changed: bool) {
+        if changed {
+            self.restart.push(field.to_string());
+        }
+    }
+}
+
+/// 比较两份配置,返回取值不同的顶层配置项
+pub fn changed_fields<T: Serialize>(old: &T, new: &T) -> anyhow::Result<Vec<String>> {
+    let old = toml::Table::try_from(old)?;
+    let new = toml::Table::try_from(new)?;
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    Ok(keys
+        .into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect())
 }
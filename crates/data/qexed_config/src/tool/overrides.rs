@@ -0,0 +1,196 @@
+// 配置覆盖
+// 生效顺序: 默认值 -> 配置文件 -> QEXED_<SECTION>__<KEY> 环境变量 -> 命令行 --set key=value
+// 覆盖只作用于内存中的配置,不会写回配置文件
+use std::sync::OnceLock;
+
+use toml::{Table, Value};
+
+/// 环境变量前缀,层级之间用双下划线分隔
+pub const ENV_PREFIX: &str = "QEXED_";
+
+static CLI: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+/// 环境变量 QEXED_<SECTION>__<KEY> 的作用范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// 不应用环境变量
+    None,
+    /// 独立的配置文件,只接受该配置节的环境变量
+    Section(&'static str),
+    /// 由多个配置节组成的配置(如 One),环境变量覆盖对应配置节
+    Sections,
+}
+
+/// 命令行参数
+#[derive(Debug, Default, Clone)]
+pub struct Args {
+    /// --set key=value,key 为以 . 分隔的配置项路径
+    pub set: Vec<(String, String)>,
+    /// --print-config,输出合并后的配置并退出
+    pub print_config: bool,
+}
+
+impl Args {
+    /// 解析不含程序名的参数
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> anyhow::Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--print-config" => parsed.print_config = true,
+                "--set" => {
+                    let Some(pair) = args.next() else {
+                        anyhow::bail!("--set 需要 key=value");
+                    };
+                    parsed.set.push(split(&pair)?);
+                }
+                _ => match arg.strip_prefix("--set=") {
+                    Some(pair) => parsed.set.push(split(pair)?),
+                    None => anyhow::bail!("未知参数 {}", arg),
+                },
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// 记录命令行覆盖,之后读取的所有配置都会应用,只能设置一次
+    pub fn install(&self) {
+        let _ = CLI.set(self.set.clone());
+    }
+}
+
+fn split(pair: &str) -> anyhow::Result<(String, String)> {
+    match pair.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.to_string())),
+        _ => anyhow::bail!("--set 参数 {} 格式应为 key=value", pair),
+    }
+}
+
+/// 依次应用环境变量与命令行覆盖
+pub fn apply(table: &mut Table, scope: Scope) {
+    for (key, value) in std::env::vars() {
+        // 只覆盖本配置中存在的配置项
+        if let Some(path) = env_path(&key, scope)
+            && lookup(table, &path).is_some()
+        {
+            set(table, &path, &value);
+        }
+    }
+    for (key, value) in CLI.get().into_iter().flatten() {
+        let path: Vec<String> = key.split('.').map(str::to_string).collect();
+        if lookup(table, &path).is_some() {
+            set(table, &path, value);
+        }
+    }
+}
+
+/// 把环境变量名转换为配置项路径,不是 QEXED_<SECTION>__<KEY> 形式或不属于本配置时返回 None
+fn env_path(key: &str, scope: Scope) -> Option<Vec<String>> {
+    let mut path: Vec<String> = key
+        .strip_prefix(ENV_PREFIX)?
+        .split("__")
+        .map(str::to_lowercase)
+        .collect();
+    if path.len() < 2 || path.iter().any(String::is_empty) {
+        return None;
+    }
+    match scope {
+        Scope::None => None,
+        Scope::Section(section) => (path[0] == section).then(|| path.split_off(1)),
+        Scope::Sections => Some(path),
+    }
+}
+
+/// 在给定配置中不存在的命令行覆盖项
+pub fn unmatched(table: &Table) -> Vec<String> {
+    CLI.get()
+        .into_iter()
+        .flatten()
+        .map(|(key, _)| key)
+        .filter(|key| lookup(table, &key.split('.').map(str::to_string).collect::<Vec<_>>()).is_none())
+        .cloned()
+        .collect()
+}
+
+fn lookup<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let mut table = table;
+    for key in parents {
+        table = table.get(key)?.as_table()?;
+    }
+    table.get(last)
+}
+
+/// 按原值的类型解析并写入覆盖值
+fn set(table: &mut Table, path: &[String], raw: &str) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut table = table;
+    for key in parents {
+        match table.get_mut(key).and_then(Value::as_table_mut) {
+            Some(next) => table = next,
+            None => return,
+        }
+    }
+    let value = match table.get(last) {
+        Some(Value::String(_)) => Value::String(raw.to_string()),
+        _ => parse(raw),
+    };
+    table.insert(last.clone(), value);
+}
+
+// 按 TOML 取值解析,无法解析时视为字符串
+fn parse(raw: &str) -> Value {
+    toml::from_str::<Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_args() {
+        let args = Args::parse(
+            ["--set", "ping.interval=5", "--set=server_status.motd=[\"a\"]", "--print-config"]
+                .map(str::to_string),
+        )
+        .unwrap();
+        assert!(args.print_config);
+        assert_eq!(args.set[0], ("ping.interval".to_string(), "5".to_string()));
+        assert_eq!(args.set[1].1, "[\"a\"]");
+        assert!(Args::parse(["--set".to_string()]).is_err());
+        assert!(Args::parse(["--verbose".to_string()]).is_err());
+    }
+
+    #[test]
+    fn set_keeps_types() {
+        let mut table: Table = toml::from_str("[server_status]\ncache = 30\nfavicon = \"\"\nmotd = [\"a\"]").unwrap();
+        let path = |key: &str| key.split('.').map(str::to_string).collect::<Vec<_>>();
+        set(&mut table, &path("server_status.cache"), "10");
+        set(&mut table, &path("server_status.favicon"), "123");
+        set(&mut table, &path("server_status.motd"), "[\"b\", \"c\"]");
+        let status = table["server_status"].as_table().unwrap();
+        assert_eq!(status["cache"].as_integer(), Some(10));
+        assert_eq!(status["favicon"].as_str(), Some("123"));
+        assert_eq!(status["motd"].as_array().unwrap().len(), 2);
+        assert!(lookup(&table, &path("server_status.missing")).is_none());
+    }
+
+    #[test]
+    fn env_path_scope() {
+        let path = |key: &str| key.split('.').map(str::to_string).collect::<Vec<_>>();
+        // 没有配置节的变量不生效
+        assert_eq!(env_path("QEXED_VERSION", Scope::Sections), None);
+        assert_eq!(env_path("QEXED_VERSION", Scope::Section("ping")), None);
+        assert_eq!(env_path("QEXED_PING__", Scope::Sections), None);
+        assert_eq!(env_path("PATH", Scope::Sections), None);
+        assert_eq!(env_path("QEXED_PING__INTERVAL", Scope::Sections), Some(path("ping.interval")));
+        assert_eq!(env_path("QEXED_PING__INTERVAL", Scope::Section("ping")), Some(path("interval")));
+        assert_eq!(env_path("QEXED_CHAT__INTERVAL", Scope::Section("ping")), None);
+        assert_eq!(env_path("QEXED_PING__INTERVAL", Scope::None), None);
+    }
+}
@@ -1,7 +1,7 @@
 // qexed_config_macros/src/lib.rs
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput, Data, Fields};
+use syn::{parse_macro_input, Attribute, DeriveInput, Data, Expr, Fields, Lit, Meta};
 
 /// 自动为枚举生成常用Trait实现的简化宏
 #[proc_macro_derive(AutoEnum, attributes(default, display))]
@@ -89,5 +89,105 @@ pub fn auto_enum_derive(input: TokenStream) -> TokenStream {
         }
     });
     
+    // 生成可选值,供配置文档与 JSON Schema 使用
+    let value_texts: Vec<_> = variant_idents.iter().map(|ident| ident.to_string()).collect();
+    output.extend(quote! {
+        impl ::qexed_config::tool::doc::ConfigValues for #enum_name {
+            fn values() -> Vec<String> {
+                vec![#(#value_texts.to_string()),*]
+            }
+        }
+    });
+    
+    TokenStream::from(output)
+}
+
+/// 收集结构体与字段的文档注释,写入配置文件时作为 TOML 注释输出
+/// 字段属性: #[config(nested)] 字段类型同样派生了 ConfigDoc,容器字段写作 #[config(nested = 元素类型)]
+///           #[config(values)] 字段类型为 AutoEnum 枚举,输出可选值
+#[proc_macro_derive(ConfigDoc, attributes(config))]
+pub fn config_doc_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let struct_doc = doc_text(&input.attrs);
+    
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => panic!("ConfigDoc只支持具名字段结构体"),
+        },
+        _ => panic!("ConfigDoc只能用于结构体"),
+    };
+    
+    let mut pushes = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let field_name = ident.to_string().trim_start_matches("r#").to_string();
+        let doc = doc_text(&field.attrs);
+        let ty = &field.ty;
+        let mut nested = quote! { Vec::new() };
+        let mut values = quote! { Vec::new() };
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("config")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("nested") {
+                    // Vec 等容器字段用 nested = 元素类型 指定
+                    let nested_ty: syn::Type = if meta.input.peek(syn::Token![=]) {
+                        meta.value()?.parse()?
+                    } else {
+                        ty.clone()
+                    };
+                    nested = quote! { <#nested_ty as ::qexed_config::tool::doc::ConfigDoc>::fields() };
+                    Ok(())
+                } else if meta.path.is_ident("values") {
+                    values = quote! { <#ty as ::qexed_config::tool::doc::ConfigValues>::values() };
+                    Ok(())
+                } else {
+                    Err(meta.error("ConfigDoc只支持 nested 与 values"))
+                }
+            })
+            .unwrap();
+        }
+        // 字段上的 cfg 同样作用于文档
+        let cfgs: Vec<_> = field.attrs.iter().filter(|attr| attr.path().is_ident("cfg")).collect();
+        pushes.push(quote! {
+            #(#cfgs)*
+            fields.push(::qexed_config::tool::doc::FieldDoc {
+                name: #field_name,
+                doc: #doc,
+                values: #values,
+                nested: #nested,
+            });
+        });
+    }
+    
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let output = quote! {
+        impl #impl_generics ::qexed_config::tool::doc::ConfigDoc for #name #ty_generics #where_clause {
+            fn doc() -> &'static str {
+                #struct_doc
+            }
+            
+            fn fields() -> Vec<::qexed_config::tool::doc::FieldDoc> {
+                let mut fields = Vec::new();
+                #(#pushes)*
+                fields
+            }
+        }
+    };
     TokenStream::from(output)
+}
+
+// 合并 /// 注释,每行去掉首尾空白
+fn doc_text(attrs: &[Attribute]) -> String {
+    let mut lines = Vec::new();
+    for attr in attrs {
+        if let Meta::NameValue(meta) = &attr.meta
+            && meta.path.is_ident("doc")
+            && let Expr::Lit(expr) = &meta.value
+            && let Lit::Str(text) = &expr.lit
+        {
+            lines.push(text.value().trim().to_string());
+        }
+    }
+    lines.join("\n")
 }
\ No newline at end of file
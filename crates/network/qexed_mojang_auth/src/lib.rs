@@ -59,6 +59,15 @@ unsafe impl Sync for MojangProfile {}
 unsafe impl Send for PlayerProperty {}
 unsafe impl Sync for PlayerProperty {}
 
+/// Mojang 服务公钥，均为 X.509 DER 编码
+#[derive(Debug, Clone, Default)]
+pub struct MojangPublicKeys {
+    /// 用于校验玩家属性（皮肤等）签名
+    pub profile_property_keys: Vec<Vec<u8>>,
+    /// 用于校验玩家聊天公钥签名
+    pub player_certificate_keys: Vec<Vec<u8>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct MojangAuthConfig {
     pub timeout: Duration,
@@ -249,6 +258,52 @@ impl MojangAuthClient {
         }
     }
     
+    /// 获取 Mojang 服务公钥（用于校验玩家聊天公钥等签名）
+    pub async fn get_public_keys(&self) -> Result<MojangPublicKeys, MojangAuthError> {
+        let url = "https://api.minecraftservices.com/publickeys";
+
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PublicKeysResponse {
+            #[serde(default)]
+            profile_property_keys: Vec<PublicKeyEntry>,
+            #[serde(default)]
+            player_certificate_keys: Vec<PublicKeyEntry>,
+        }
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PublicKeyEntry {
+            public_key: String,
+        }
+
+        let response = self.client
+            .get(url)
+            .send()
+            .await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let resp: PublicKeysResponse = response.json().await?;
+                let decode = |keys: Vec<PublicKeyEntry>| -> Result<Vec<Vec<u8>>, MojangAuthError> {
+                    use base64::Engine;
+                    keys.into_iter()
+                        .map(|k| {
+                            base64::engine::general_purpose::STANDARD
+                                .decode(k.public_key)
+                                .map_err(|_| MojangAuthError::ValidationFailed)
+                        })
+                        .collect()
+                };
+                Ok(MojangPublicKeys {
+                    profile_property_keys: decode(resp.profile_property_keys)?,
+                    player_certificate_keys: decode(resp.player_certificate_keys)?,
+                })
+            }
+            429 => Err(MojangAuthError::ServerBusy),
+            _ => Err(MojangAuthError::ValidationFailed),
+        }
+    }
+
     /// 格式化UUID：从 "11111111222233334444555555555555" 转换为 "11111111-2222-3333-4444-555555555555"
     pub fn format_uuid(uuid_str: &str) -> Result<String, MojangAuthError> {
         let uuid_str = uuid_str.replace('-', "");
@@ -15,6 +15,7 @@ use tokio::{io::AsyncReadExt, net::TcpListener};
 use openssl::symm::{Cipher, Crypter, Mode};
 use bytes::{Bytes}; 
 use anyhow::anyhow;
+pub mod stats;
 // pub mod bridge;
 // pub mod net_types;
 // pub mod packet;
@@ -136,6 +137,7 @@ impl PacketSend {
         
         // 3. 发送数据
         self.socket_write.write_all(&data_to_send).await?;
+        stats::record_out(data_to_send.len());
         Ok(())
     }
     
@@ -171,6 +173,7 @@ impl PacketSend {
             let mut encoder = ZlibEncoder::new(&data[..], Compression::default());
             let mut compressed = Vec::new();
             encoder.read_to_end(&mut compressed)?;
+            stats::record_compressed(data.len(), compressed.len());
             
             // 计算总长度：未压缩长度 + 压缩数据
             let total_len = compressed.len() + varint_length(data.len() as i32);
@@ -300,6 +303,7 @@ impl PacketRead {
                     ));
                 }
                 Ok(n) => {
+                    stats::record_in(n);
                     let data = &temp_buf[..n];
                     
                     if self.encryption_enabled.load(Ordering::Relaxed) {
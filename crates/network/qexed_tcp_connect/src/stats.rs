@@ -0,0 +1,48 @@
+// 网络流量统计
+// 所有连接共用一组计数器,供监控读取
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static BYTES_IN: AtomicU64 = AtomicU64::new(0);
+static BYTES_OUT: AtomicU64 = AtomicU64::new(0);
+static PACKETS_COMPRESSED: AtomicU64 = AtomicU64::new(0);
+static COMPRESS_INPUT: AtomicU64 = AtomicU64::new(0);
+static COMPRESS_OUTPUT: AtomicU64 = AtomicU64::new(0);
+
+/// 启动以来的累计流量
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrafficStats {
+    /// 从套接字读取的字节数
+    pub bytes_in: u64,
+    /// 写入套接字的字节数
+    pub bytes_out: u64,
+    /// 经过压缩发送的数据包数
+    pub packets_compressed: u64,
+    /// 压缩前的字节数
+    pub compress_input_bytes: u64,
+    /// 压缩后的字节数
+    pub compress_output_bytes: u64,
+}
+
+pub fn stats() -> TrafficStats {
+    TrafficStats {
+        bytes_in: BYTES_IN.load(Ordering::Relaxed),
+        bytes_out: BYTES_OUT.load(Ordering::Relaxed),
+        packets_compressed: PACKETS_COMPRESSED.load(Ordering::Relaxed),
+        compress_input_bytes: COMPRESS_INPUT.load(Ordering::Relaxed),
+        compress_output_bytes: COMPRESS_OUTPUT.load(Ordering::Relaxed),
+    }
+}
+
+pub(crate) fn record_in(bytes: usize) {
+    BYTES_IN.fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+pub(crate) fn record_out(bytes: usize) {
+    BYTES_OUT.fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+pub(crate) fn record_compressed(input: usize, output: usize) {
+    PACKETS_COMPRESSED.fetch_add(1, Ordering::Relaxed);
+    COMPRESS_INPUT.fetch_add(input as u64, Ordering::Relaxed);
+    COMPRESS_OUTPUT.fetch_add(output as u64, Ordering::Relaxed);
+}
@@ -1,4 +1,6 @@
-// use qexed_packet::PacketCodec;
+use qexed_packet::PacketCodec;
 #[qexed_packet_macros::packet(id = 0x02)]
 #[derive(Debug, Default, PartialEq, Clone)]
-pub struct Disconnect {}
+pub struct Disconnect {
+    pub reason: qexed_nbt::Tag, // 文本组件
+}
@@ -0,0 +1,6 @@
+use qexed_packet::PacketCodec;
+#[qexed_packet_macros::packet(id = 0x04)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct BlockChangedAck {
+    pub sequence: qexed_packet::net_types::VarInt, // 确认后客户端会撤销该序号之前未经服务端同步的方块预测
+}
@@ -0,0 +1,6 @@
+use qexed_packet::PacketCodec;
+#[qexed_packet_macros::packet(id = 0x1C)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Disconnect {
+    pub reason: qexed_nbt::Tag, // 文本组件
+}
@@ -16,4 +16,9 @@ pub mod container_set_data;
 pub mod container_set_slot;
 pub mod command_suggestions;
 pub mod set_entity_data;
-pub mod set_title_text;
\ No newline at end of file
+pub mod set_title_text;
+pub mod player_chat;
+pub mod player_info_update;
+pub mod player_info_remove;
+pub mod disconnect;
+pub mod block_changed_ack;
\ No newline at end of file
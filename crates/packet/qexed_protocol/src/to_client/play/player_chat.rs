@@ -0,0 +1,137 @@
+use qexed_packet::{PacketCodec, net_types::{Bitset, VarInt}};
+
+use crate::types::MessageSignature;
+#[qexed_packet_macros::packet(id = 0x3A)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PlayerChat {
+    pub global_index:VarInt,// 发给该客户端的第几条玩家聊天,必须连续
+    pub sender:uuid::Uuid,
+    pub index:VarInt,// 发送者消息链中的序号
+    pub signature:Option<MessageSignature>,
+    pub message:String,
+    pub timestamp:i64,
+    pub salt:i64,
+    pub previous_messages:Vec<PreviousMessage>,
+    pub unsigned_content:Option<qexed_nbt::Tag>,
+    pub filter:FilterMask,
+    pub chat_type:ChatTypeHolder,
+    pub sender_name:qexed_nbt::Tag,
+    pub target_name:Option<qexed_nbt::Tag>,
+}
+
+/// 签名时已读的消息,id 为 0 时直接携带完整签名
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PreviousMessage {
+    pub id:VarInt,
+    pub signature:Option<MessageSignature>,
+}
+
+impl PreviousMessage {
+    pub fn full(signature: MessageSignature) -> Self {
+        Self {
+            id: VarInt(0),
+            signature: Some(signature),
+        }
+    }
+}
+
+impl PacketCodec for PreviousMessage {
+    fn serialize(&self, w: &mut qexed_packet::PacketWriter) -> anyhow::Result<()> {
+        self.id.serialize(w)?;
+        if self.id.0 == 0 {
+            match &self.signature {
+                Some(signature) => signature.serialize(w)?,
+                None => return Err(anyhow::anyhow!("signature is required when id is 0")),
+            }
+        }
+        Ok(())
+    }
+
+    fn deserialize(&mut self, r: &mut qexed_packet::PacketReader) -> anyhow::Result<()> {
+        self.id.deserialize(r)?;
+        if self.id.0 == 0 {
+            let mut signature = MessageSignature::default();
+            signature.deserialize(r)?;
+            self.signature = Some(signature);
+        }
+        Ok(())
+    }
+}
+
+/// 过滤类型: 0 不过滤, 1 完全过滤, 2 按位过滤
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct FilterMask {
+    pub filter_type:VarInt,
+    pub mask:Bitset,
+}
+
+impl PacketCodec for FilterMask {
+    fn serialize(&self, w: &mut qexed_packet::PacketWriter) -> anyhow::Result<()> {
+        self.filter_type.serialize(w)?;
+        if self.filter_type.0 == 2 {
+            self.mask.serialize(w)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize(&mut self, r: &mut qexed_packet::PacketReader) -> anyhow::Result<()> {
+        self.filter_type.deserialize(r)?;
+        if self.filter_type.0 == 2 {
+            self.mask.deserialize(r)?;
+        }
+        Ok(())
+    }
+}
+
+/// 聊天类型: 引用 chat_type 注册表,或直接内联定义
+#[derive(Debug, PartialEq, Clone)]
+pub enum ChatTypeHolder {
+    Registry(i32),// 注册表下标
+    Inline(ChatTypeData),
+}
+
+impl Default for ChatTypeHolder {
+    fn default() -> Self {
+        Self::Registry(0)
+    }
+}
+
+impl PacketCodec for ChatTypeHolder {
+    fn serialize(&self, w: &mut qexed_packet::PacketWriter) -> anyhow::Result<()> {
+        match self {
+            Self::Registry(id) => VarInt(id + 1).serialize(w),
+            Self::Inline(data) => {
+                VarInt(0).serialize(w)?;
+                data.serialize(w)
+            }
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut qexed_packet::PacketReader) -> anyhow::Result<()> {
+        let mut id = VarInt(0);
+        id.deserialize(r)?;
+        if id.0 == 0 {
+            let mut data = ChatTypeData::default();
+            data.deserialize(r)?;
+            *self = Self::Inline(data);
+        } else {
+            *self = Self::Registry(id.0 - 1);
+        }
+        Ok(())
+    }
+}
+
+#[qexed_packet_macros::substruct]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ChatTypeData {
+    pub chat:ChatDecoration,
+    pub narration:ChatDecoration,
+}
+
+#[qexed_packet_macros::substruct]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ChatDecoration {
+    pub translation_key:String,
+    pub parameters:Vec<VarInt>,// 0 发送者, 1 目标, 2 内容
+    pub style:qexed_nbt::Tag,
+}
@@ -0,0 +1,115 @@
+use qexed_packet::{Packet, PacketCodec, PacketReader, PacketWriter, net_types::VarInt};
+
+use crate::to_client::login::success::Properties;
+
+pub const ADD_PLAYER: u8 = 0x01;
+pub const INITIALIZE_CHAT: u8 = 0x02;
+pub const UPDATE_GAME_MODE: u8 = 0x04;
+pub const UPDATE_LISTED: u8 = 0x08;
+pub const UPDATE_LATENCY: u8 = 0x10;
+pub const UPDATE_DISPLAY_NAME: u8 = 0x20;
+pub const UPDATE_LIST_PRIORITY: u8 = 0x40;
+pub const UPDATE_HAT: u8 = 0x80;
+
+/// 每个玩家只写入 actions 中包含的字段
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PlayerInfoUpdate {
+    pub actions: u8,
+    pub players: Vec<PlayerInfoEntry>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PlayerInfoEntry {
+    pub uuid: uuid::Uuid,
+    pub name: String,
+    pub properties: Vec<Properties>,
+    pub chat_session: Option<ChatSessionData>,
+    pub game_mode: VarInt,
+    pub listed: bool,
+    pub latency: VarInt,
+    pub display_name: Option<qexed_nbt::Tag>,
+    pub list_priority: VarInt,
+    pub show_hat: bool,
+}
+
+#[qexed_packet_macros::substruct]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ChatSessionData {
+    pub session_id: uuid::Uuid,
+    pub expires_at: i64,
+    pub public_key: Vec<u8>,
+    pub key_signature: Vec<u8>,
+}
+
+impl Packet for PlayerInfoUpdate {
+    const ID: u32 = 0x3F;
+    fn serialize(&self, w: &mut PacketWriter) -> Result<(), anyhow::Error> {
+        w.serialize(&self.actions)?;
+        w.serialize(&VarInt(self.players.len() as i32))?;
+        for player in &self.players {
+            w.serialize(&player.uuid)?;
+            if self.actions & ADD_PLAYER != 0 {
+                w.serialize(&player.name)?;
+                w.serialize(&player.properties)?;
+            }
+            if self.actions & INITIALIZE_CHAT != 0 {
+                w.serialize(&player.chat_session)?;
+            }
+            if self.actions & UPDATE_GAME_MODE != 0 {
+                w.serialize(&player.game_mode)?;
+            }
+            if self.actions & UPDATE_LISTED != 0 {
+                w.serialize(&player.listed)?;
+            }
+            if self.actions & UPDATE_LATENCY != 0 {
+                w.serialize(&player.latency)?;
+            }
+            if self.actions & UPDATE_DISPLAY_NAME != 0 {
+                w.serialize(&player.display_name)?;
+            }
+            if self.actions & UPDATE_LIST_PRIORITY != 0 {
+                w.serialize(&player.list_priority)?;
+            }
+            if self.actions & UPDATE_HAT != 0 {
+                w.serialize(&player.show_hat)?;
+            }
+        }
+        Ok(())
+    }
+    fn deserialize(&mut self, r: &mut PacketReader) -> Result<(), anyhow::Error> {
+        self.actions.deserialize(r)?;
+        let mut len = VarInt(0);
+        len.deserialize(r)?;
+        for _ in 0..len.0 {
+            let mut player = PlayerInfoEntry::default();
+            player.uuid.deserialize(r)?;
+            if self.actions & ADD_PLAYER != 0 {
+                player.name.deserialize(r)?;
+                player.properties.deserialize(r)?;
+            }
+            if self.actions & INITIALIZE_CHAT != 0 {
+                player.chat_session.deserialize(r)?;
+            }
+            if self.actions & UPDATE_GAME_MODE != 0 {
+                player.game_mode.deserialize(r)?;
+            }
+            if self.actions & UPDATE_LISTED != 0 {
+                player.listed.deserialize(r)?;
+            }
+            if self.actions & UPDATE_LATENCY != 0 {
+                player.latency.deserialize(r)?;
+            }
+            if self.actions & UPDATE_DISPLAY_NAME != 0 {
+                player.display_name.deserialize(r)?;
+            }
+            if self.actions & UPDATE_LIST_PRIORITY != 0 {
+                player.list_priority.deserialize(r)?;
+            }
+            if self.actions & UPDATE_HAT != 0 {
+                player.show_hat.deserialize(r)?;
+            }
+            self.players.push(player);
+        }
+        Ok(())
+    }
+}
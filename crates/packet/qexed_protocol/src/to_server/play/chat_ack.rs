@@ -0,0 +1,6 @@
+use qexed_packet::PacketCodec;
+#[qexed_packet_macros::packet(id = 0x05)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ChatAck {
+    pub offset:qexed_packet::net_types::VarInt,
+}
@@ -0,0 +1,19 @@
+use qexed_packet::PacketCodec;
+#[qexed_packet_macros::packet(id = 0x07)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ChatCommandSigned {
+    pub command:String,
+    pub timestamp:i64,
+    pub salt:i64,
+    pub argument_signatures:Vec<ArgumentSignature>,
+    pub offset:qexed_packet::net_types::VarInt,
+    pub acknowledged:[u8; 3],
+    pub checksum:i8,
+}
+
+#[qexed_packet_macros::substruct]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ArgumentSignature {
+    pub argument_name:String,
+    pub signature:crate::types::MessageSignature,
+}
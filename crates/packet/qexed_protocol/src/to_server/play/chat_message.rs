@@ -5,7 +5,8 @@ pub struct ChatMessage {
     pub message:String,
     pub timestamp:i64,
     pub salt:i64,
+    pub signature:Option<crate::types::MessageSignature>,
     pub offset:qexed_packet::net_types::VarInt,
     pub acknowledged:[u8; 3],
     pub checksum:i8,
-}
\ No newline at end of file
+}
@@ -0,0 +1,9 @@
+use qexed_packet::PacketCodec;
+#[qexed_packet_macros::packet(id = 0x09)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ChatSessionUpdate {
+    pub session_id:uuid::Uuid,
+    pub expires_at:i64,// 毫秒时间戳
+    pub public_key:Vec<u8>,// X.509 DER 编码的公钥
+    pub key_signature:Vec<u8>,// Mojang 对公钥的签名
+}
@@ -0,0 +1,7 @@
+use qexed_packet::{PacketCodec, net_types::VarInt};
+#[qexed_packet_macros::packet(id = 0x0E)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct CommandSuggestion {
+    pub transaction_id: VarInt,
+    pub text: String, // 光标之前的全部文本,包含开头的 /
+}
@@ -1,4 +1,9 @@
 pub mod pong;
 pub mod keep_alive;
 pub mod chat_message;
-pub mod chat_command;
\ No newline at end of file
+pub mod chat_command;
+pub mod command_suggestion;
+pub mod chat_ack;
+pub mod chat_command_signed;
+pub mod chat_session_update;
+pub mod player_action;
\ No newline at end of file
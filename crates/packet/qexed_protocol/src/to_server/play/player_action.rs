@@ -0,0 +1,9 @@
+use qexed_packet::PacketCodec;
+#[qexed_packet_macros::packet(id = 0x28)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PlayerAction {
+    pub status: qexed_packet::net_types::VarInt, // 0:开始挖掘 1:取消挖掘 2:完成挖掘 3-6:丢弃物品/使用物品/交换副手
+    pub location: qexed_packet::net_types::Position,
+    pub face: i8,
+    pub sequence: qexed_packet::net_types::VarInt, // 方块预测序号,服务端需通过 BlockChangedAck 确认
+}
@@ -10,6 +10,32 @@ pub struct KnownPacks {
     pub version: String,
 }
 
+/// 聊天消息签名,固定 256 字节,无长度前缀
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct MessageSignature(pub [u8; 256]);
+
+impl Default for MessageSignature {
+    fn default() -> Self {
+        Self([0; 256])
+    }
+}
+
+impl PacketCodec for MessageSignature {
+    fn serialize(&self, w: &mut qexed_packet::PacketWriter) -> anyhow::Result<()> {
+        for b in &self.0 {
+            b.serialize(w)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize(&mut self, r: &mut qexed_packet::PacketReader) -> anyhow::Result<()> {
+        for b in self.0.iter_mut() {
+            b.deserialize(r)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct Slot {
     pub item_count: VarInt,
@@ -1,22 +1,84 @@
-// #[link(wasm_import_module = "env")]
-// unsafe extern "C" {
-//     fn write(fd: i32, buf: *const u8, len: i32) -> i32;
-// }
-
-// #[unsafe(no_mangle)]
-// pub extern "C" fn print() {
-//     let message = "Hello from WASM!\n";
-//     unsafe {
-//         write(1, message.as_ptr(), message.len() as i32);
-//     }
-// }
-
-// use qexed_api::plugin;
-// pub struct Plugin{
-//     // 此变量若有变化,则会每秒同步一次,
-//     // 这将会在所有使用此插件的模块中进行全局信息同步
-//     // 请尽可能的避免或按需同步
-//     #[qexed::shared(Duration::from_millis(1000))]
-//     pub now_time:Duration,// 当前时间
-//     pub now_node_time:Duration,// 当前节点时间
-// }
\ No newline at end of file
+//! 示例插件
+//!
+//! 编译: cargo build -p hello_world --target wasm32-unknown-unknown --release
+//! 将 hello_world.wasm 与 plugin.toml、permission.toml 放入 plugins/hello_world/ 即可
+use qexed_api::{
+    ArgumentKind, Command, CommandContext, Event, EventKind, EventResult, Plugin, Priority, TaskId,
+    TaskTime, chat, info, player, scheduler,
+};
+
+#[derive(Default)]
+pub struct HelloWorld {
+    // 每个实例独立计数,不会在模块之间共享
+    greeted: u32,
+    announce: Option<TaskId>,
+}
+
+impl Plugin for HelloWorld {
+    fn on_load(&mut self) -> qexed_api::Result<()> {
+        info!("Hello World 已加载");
+        Ok(())
+    }
+
+    fn on_enable(&mut self) -> qexed_api::Result<()> {
+        Command::new("hello")
+            .description("向玩家问好")
+            .alias("hi")
+            .argument("player", "玩家名", ArgumentKind::Player, false)
+            .register()?;
+        qexed_api::event::subscribe(EventKind::PlayerJoin, Priority::Normal)?;
+        qexed_api::event::subscribe(EventKind::Chat, Priority::Low)?;
+        // 每 10 分钟广播一次在线人数
+        self.announce = Some(scheduler::run_timer(
+            TaskTime::Millis(60_000),
+            TaskTime::Millis(600_000),
+        )?);
+        info!("Hello World 已启用");
+        Ok(())
+    }
+
+    fn on_disable(&mut self) -> qexed_api::Result<()> {
+        info!("Hello World 已停用,共问好 {} 次", self.greeted);
+        Ok(())
+    }
+
+    fn on_command(&mut self, ctx: &CommandContext) -> qexed_api::Result<()> {
+        let target = match ctx.arg(0) {
+            Some(name) => match player::find(name)? {
+                Some(target) => target.name,
+                None => return ctx.reply(&format!("§c玩家 {} 不在线", name)),
+            },
+            None => match ctx.sender() {
+                Some(sender) => sender.name.clone(),
+                None => "控制台".to_string(),
+            },
+        };
+        self.greeted += 1;
+        ctx.reply(&format!("§a你好, {}!", target))
+    }
+
+    fn on_event(&mut self, event: &mut Event) -> EventResult {
+        match event {
+            Event::PlayerJoin { player } => {
+                let _ = qexed_api::player::send_message(player, "§6欢迎来到服务器, 输入 /hello 打个招呼");
+            }
+            // 把聊天中的 :) 替换为表情
+            Event::Chat { message, .. } if message.contains(":)") => {
+                *message = message.replace(":)", "☺");
+            }
+            _ => {}
+        }
+        EventResult::Continue
+    }
+
+    fn on_task(&mut self, id: TaskId) {
+        if Some(id) != self.announce {
+            return;
+        }
+        if let Ok(players) = player::online() {
+            let _ = chat::broadcast(&format!("§7当前在线 {} 人", players.len()));
+        }
+    }
+}
+
+qexed_api::plugin!(HelloWorld);
@@ -79,3 +79,8 @@ pub fn init_cli_logger(
 ) -> Result<(), log::SetLoggerError> {
     logger::init_cli_logger(log_tx)
 }
+
+/// 订阅 CLI 收到的日志,用于远程控制台
+pub fn subscribe_logs() -> tokio::sync::broadcast::Receiver<String> {
+    logger::subscribe()
+}
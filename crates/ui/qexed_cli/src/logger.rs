@@ -8,6 +8,18 @@ static LOG_SENDER: Lazy<Mutex<Option<tokio::sync::mpsc::Sender<String>>>> = Lazy
     Mutex::new(None)
 });
 
+// 日志广播的缓冲条数,订阅者落后过多时会丢失最旧的日志
+const BROADCAST_CAPACITY: usize = 1024;
+
+/// 日志广播,供 CLI 之外的界面(如 Web 控制台)接收同样的日志
+static LOG_BROADCAST: Lazy<tokio::sync::broadcast::Sender<String>> =
+    Lazy::new(|| tokio::sync::broadcast::channel(BROADCAST_CAPACITY).0);
+
+/// 订阅之后产生的日志
+pub fn subscribe() -> tokio::sync::broadcast::Receiver<String> {
+    LOG_BROADCAST.subscribe()
+}
+
 /// 自定义日志记录器
 pub struct ChannelLogger;
 
@@ -37,6 +49,9 @@ impl Log for ChannelLogger {
             record.args()
         );
         
+        // 没有订阅者时发送失败,忽略即可
+        let _ = LOG_BROADCAST.send(message.clone());
+
         // 尝试从全局获取日志发送器
         if let Ok(sender_guard) = LOG_SENDER.lock() {
             if let Some(tx) = &*sender_guard {
@@ -0,0 +1,248 @@
+// 管理接口
+// 请求体与响应均为 JSON,出错时返回 {"error": "..."}
+// 玩家可以用 UUID 或在线玩家的名字指定,离线玩家只能用 UUID
+use qexed_task::message::{
+    MessageType,
+    return_message::{RequestError, ReturnMessage},
+};
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    QUERY_TIMEOUT, Services,
+    http::{Request, Response},
+};
+
+const DEFAULT_KICK_REASON: &str = "你已被管理员踢出服务器";
+const DEFAULT_BAN_REASON: &str = "你已被服务器封禁";
+
+#[derive(Debug)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::new(500, e.to_string())
+    }
+}
+
+impl From<RequestError> for ApiError {
+    fn from(e: RequestError) -> Self {
+        match e {
+            RequestError::Timeout { .. } => Self::new(503, e.to_string()),
+            _ => Self::new(500, e.to_string()),
+        }
+    }
+}
+
+type ApiResult = Result<serde_json::Value, ApiError>;
+
+#[derive(Debug, Deserialize)]
+struct PlayerRequest {
+    player: String,
+    /// 踢出或封禁时显示的原因
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommandRequest {
+    command: String,
+}
+
+pub async fn handle(request: &Request, services: &Services) -> Response {
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/api/players") => players(services).await,
+        ("POST", "/api/players/kick") => kick(request, services).await,
+        ("GET", "/api/whitelist") => whitelist(services).await,
+        ("POST", "/api/whitelist/add") => whitelist_change(request, services, true).await,
+        ("POST", "/api/whitelist/remove") => whitelist_change(request, services, false).await,
+        ("GET", "/api/bans") => bans(services).await,
+        ("POST", "/api/bans/add") => ban(request, services).await,
+        ("POST", "/api/bans/remove") => pardon(request, services).await,
+        ("POST", "/api/command") => command(request, services).await,
+        _ => return Response::json(404, &json!({ "error": "接口不存在" })),
+    };
+    match result {
+        Ok(body) => Response::json(200, &body),
+        Err(e) => Response::json(e.status, &json!({ "error": e.message })),
+    }
+}
+
+fn body<T: DeserializeOwned>(request: &Request) -> Result<T, ApiError> {
+    serde_json::from_slice(&request.body).map_err(|e| ApiError::new(400, format!("请求体格式错误: {}", e)))
+}
+
+/// 把 UUID 或在线玩家名解析为 UUID
+async fn resolve(services: &Services, player: &str) -> Result<Uuid, ApiError> {
+    if let Ok(uuid) = player.parse() {
+        return Ok(uuid);
+    }
+    let message = ReturnMessage::build(qexed_player_list::Message::GetPlayerIsOnline {
+        name: player.to_string(),
+        is_true: false,
+        player_uuid: Uuid::nil(),
+    })
+    .get_timeout(&services.player_list, QUERY_TIMEOUT)
+    .await?;
+    match message {
+        qexed_player_list::Message::GetPlayerIsOnline {
+            is_true: true,
+            player_uuid,
+            ..
+        } => Ok(player_uuid),
+        _ => Err(ApiError::new(404, format!("玩家 {} 不在线,请使用 UUID", player))),
+    }
+}
+
+async fn players(services: &Services) -> ApiResult {
+    let qexed_player_list::Message::GetPlayers(players) =
+        ReturnMessage::build(qexed_player_list::Message::GetPlayers(Vec::new()))
+            .get_timeout(&services.player_list, QUERY_TIMEOUT)
+            .await?
+    else {
+        return Err(anyhow::anyhow!("玩家列表服务返回了意外的消息").into());
+    };
+    let latency: std::collections::HashMap<_, _> = qexed_ping::latency().into_iter().collect();
+    let players: Vec<_> = players
+        .into_iter()
+        .map(|(uuid, name)| {
+            json!({
+                "uuid": uuid,
+                "name": name,
+                "latency_ms": latency.get(&uuid).map(|latency| latency.as_millis() as u64),
+            })
+        })
+        .collect();
+    Ok(json!({ "players": players }))
+}
+
+/// 断开在线玩家,返回玩家是否在线
+async fn disconnect(services: &Services, uuid: Uuid, reason: String) -> Result<bool, ApiError> {
+    match ReturnMessage::build(qexed_game_logic::message::ManagerMessage::Kick(uuid, reason, false))
+        .get_timeout(&services.game_logic, QUERY_TIMEOUT)
+        .await?
+    {
+        qexed_game_logic::message::ManagerMessage::Kick(_, _, found) => Ok(found),
+        _ => Err(anyhow::anyhow!("游戏逻辑服务返回了意外的消息").into()),
+    }
+}
+
+async fn kick(request: &Request, services: &Services) -> ApiResult {
+    let request: PlayerRequest = body(request)?;
+    let uuid = resolve(services, &request.player).await?;
+    let reason = request.reason.unwrap_or_else(|| DEFAULT_KICK_REASON.to_string());
+    if !disconnect(services, uuid, reason).await? {
+        return Err(ApiError::new(404, format!("玩家 {} 不在线", request.player)));
+    }
+    log::info!("[Web] 踢出玩家 {}", request.player);
+    Ok(json!({ "uuid": uuid }))
+}
+
+async fn whitelist(services: &Services) -> ApiResult {
+    match ReturnMessage::build(qexed_whitelist::Message::GetList(Vec::new()))
+        .get_timeout(&services.white_list, QUERY_TIMEOUT)
+        .await?
+    {
+        qexed_whitelist::Message::GetList(list) => Ok(json!({ "players": list })),
+        _ => Err(anyhow::anyhow!("白名单服务返回了意外的消息").into()),
+    }
+}
+
+async fn whitelist_change(request: &Request, services: &Services, add: bool) -> ApiResult {
+    let request: PlayerRequest = body(request)?;
+    let uuid = resolve(services, &request.player).await?;
+    let message = match add {
+        true => qexed_whitelist::Message::Add(uuid, false),
+        false => qexed_whitelist::Message::Remove(uuid, false),
+    };
+    let changed = match ReturnMessage::build(message)
+        .get_timeout(&services.white_list, QUERY_TIMEOUT)
+        .await?
+    {
+        qexed_whitelist::Message::Add(_, changed) | qexed_whitelist::Message::Remove(_, changed) => changed,
+        _ => return Err(anyhow::anyhow!("白名单服务返回了意外的消息").into()),
+    };
+    if changed {
+        log::info!("[Web] 白名单{}玩家 {}", if add { "添加" } else { "移除" }, request.player);
+    }
+    Ok(json!({ "uuid": uuid, "changed": changed }))
+}
+
+async fn bans(services: &Services) -> ApiResult {
+    match ReturnMessage::build(qexed_blacklist::Message::GetBanList(Vec::new()))
+        .get_timeout(&services.black_list, QUERY_TIMEOUT)
+        .await?
+    {
+        qexed_blacklist::Message::GetBanList(list) => Ok(json!({ "players": list })),
+        _ => Err(anyhow::anyhow!("黑名单服务返回了意外的消息").into()),
+    }
+}
+
+async fn ban(request: &Request, services: &Services) -> ApiResult {
+    let request: PlayerRequest = body(request)?;
+    let uuid = resolve(services, &request.player).await?;
+    // 用名字指定时记录下来,供 /pardon 补全
+    let name = request.player.parse::<Uuid>().is_err().then(|| request.player.clone());
+    let changed = match ReturnMessage::build(qexed_blacklist::Message::Ban(uuid, name, false))
+        .get_timeout(&services.black_list, QUERY_TIMEOUT)
+        .await?
+    {
+        qexed_blacklist::Message::Ban(_, _, changed) => changed,
+        _ => return Err(anyhow::anyhow!("黑名单服务返回了意外的消息").into()),
+    };
+    if changed {
+        log::info!("[Web] 封禁玩家 {}", request.player);
+    }
+    // 黑名单只在进服时检查,在线的玩家需要立即断开
+    let reason = request.reason.unwrap_or_else(|| DEFAULT_BAN_REASON.to_string());
+    let kicked = disconnect(services, uuid, reason).await?;
+    Ok(json!({ "uuid": uuid, "changed": changed, "kicked": kicked }))
+}
+
+async fn pardon(request: &Request, services: &Services) -> ApiResult {
+    let request: PlayerRequest = body(request)?;
+    let uuid = resolve(services, &request.player).await?;
+    let changed = match ReturnMessage::build(qexed_blacklist::Message::Pardon(uuid, false))
+        .get_timeout(&services.black_list, QUERY_TIMEOUT)
+        .await?
+    {
+        qexed_blacklist::Message::Pardon(_, changed) => changed,
+        _ => return Err(anyhow::anyhow!("黑名单服务返回了意外的消息").into()),
+    };
+    if changed {
+        log::info!("[Web] 解除封禁 {}", request.player);
+    }
+    Ok(json!({ "uuid": uuid, "changed": changed }))
+}
+
+/// 以控制台身份执行指令,指令输出通过控制台 WebSocket 查看
+pub async fn execute(services: &Services, command: &str) -> anyhow::Result<()> {
+    log::info!("[Web] 执行指令 {}", command);
+    ReturnMessage::build(qexed_command::message::ManagerCommand::Command(command.to_string()))
+        .get_timeout(&services.command, QUERY_TIMEOUT)
+        .await?;
+    Ok(())
+}
+
+async fn command(request: &Request, services: &Services) -> ApiResult {
+    let request: CommandRequest = body(request)?;
+    let command = request.command.trim().trim_start_matches('/');
+    if command.is_empty() {
+        return Err(ApiError::new(400, "指令为空"));
+    }
+    execute(services, command).await?;
+    Ok(json!({ "command": command }))
+}
@@ -0,0 +1,176 @@
+// 远程控制台
+// WebSocket 连接推送与 CLI 相同的日志,客户端发来的文本消息作为控制台指令执行
+// 只实现控制台需要的部分: 不支持分片消息与扩展
+use std::sync::Arc;
+
+use base64::Engine;
+use sha1::{Digest, Sha1};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    sync::{broadcast::error::RecvError, mpsc},
+};
+
+use crate::{
+    Services,
+    http::{Request, Response},
+};
+
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+// 客户端消息长度上限
+const MAX_PAYLOAD: u64 = 64 * 1024;
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+pub fn accept_key(key: &str) -> String {
+    let mut sha1 = Sha1::new();
+    sha1.update(key.as_bytes());
+    sha1.update(GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(sha1.finalize())
+}
+
+/// 完成握手后持续推送日志,直到任一方关闭连接
+pub async fn serve<R, W>(request: &Request, mut reader: R, mut writer: W, services: Arc<Services>) -> anyhow::Result<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    let upgrade = request
+        .header("upgrade")
+        .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"));
+    let (true, Some(key)) = (upgrade, request.header("sec-websocket-key")) else {
+        return Response::text(400, "需要 WebSocket 连接").write_to(&mut writer).await;
+    };
+    let head = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    );
+    writer.write_all(head.as_bytes()).await?;
+    // 先订阅再记录,连接的客户端能看到自己的上线日志
+    let mut logs = qexed_cli::subscribe_logs();
+    log::info!("[Web] 控制台已连接");
+
+    // 读取帧不能被 select 取消,放到独立任务中
+    let (frame_tx, mut frames) = mpsc::channel(16);
+    let read_task = tokio::spawn(async move {
+        while let Ok(frame) = read_frame(&mut reader).await {
+            if frame_tx.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let result = async {
+        loop {
+            tokio::select! {
+                line = logs.recv() => match line {
+                    Ok(line) => write_frame(&mut writer, OPCODE_TEXT, line.as_bytes()).await?,
+                    Err(RecvError::Lagged(count)) => {
+                        let notice = format!("[Web] 控制台跟不上日志输出,跳过了 {} 条日志", count);
+                        write_frame(&mut writer, OPCODE_TEXT, notice.as_bytes()).await?;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                frame = frames.recv() => match frame {
+                    Some((OPCODE_TEXT, payload)) => {
+                        let command = String::from_utf8_lossy(&payload);
+                        let command = command.trim().trim_start_matches('/');
+                        if !command.is_empty()
+                            && let Err(e) = crate::api::execute(&services, command).await
+                        {
+                            let notice = format!("[Web] 指令执行失败: {}", e);
+                            write_frame(&mut writer, OPCODE_TEXT, notice.as_bytes()).await?;
+                        }
+                    }
+                    Some((OPCODE_PING, payload)) => write_frame(&mut writer, OPCODE_PONG, &payload).await?,
+                    Some((OPCODE_CLOSE, _)) | None => {
+                        let _ = write_frame(&mut writer, OPCODE_CLOSE, &[]).await;
+                        break;
+                    }
+                    Some(_) => {}
+                },
+            }
+        }
+        anyhow::Ok(())
+    }
+    .await;
+    read_task.abort();
+    log::info!("[Web] 控制台已断开");
+    result
+}
+
+/// 读取一个客户端帧,返回操作码与去掉掩码的数据
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> anyhow::Result<(u8, Vec<u8>)> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).await?;
+    let opcode = head[0] & 0x0f;
+    let masked = head[1] & 0x80 != 0;
+    let length = match head[1] & 0x7f {
+        126 => reader.read_u16().await? as u64,
+        127 => reader.read_u64().await?,
+        length => length as u64,
+    };
+    if !masked {
+        anyhow::bail!("客户端消息没有掩码");
+    }
+    if length > MAX_PAYLOAD {
+        anyhow::bail!("消息过长: {} 字节", length);
+    }
+    let mut mask = [0u8; 4];
+    reader.read_exact(&mut mask).await?;
+    let mut payload = vec![0u8; length as usize];
+    reader.read_exact(&mut payload).await?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok((opcode, payload))
+}
+
+/// 服务端发出的帧不带掩码
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, opcode: u8, payload: &[u8]) -> anyhow::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handshake_key() {
+        // RFC 6455 中的示例
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[tokio::test]
+    async fn masked_frame() {
+        let mask = [1u8, 2, 3, 4];
+        let mut raw = vec![0x80 | OPCODE_TEXT, 0x80 | 4];
+        raw.extend_from_slice(&mask);
+        raw.extend(b"list".iter().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+        let (opcode, payload) = read_frame(&mut &raw[..]).await.unwrap();
+        assert_eq!(opcode, OPCODE_TEXT);
+        assert_eq!(payload, b"list");
+
+        let mut out = Vec::new();
+        write_frame(&mut out, OPCODE_TEXT, &[0; 200]).await.unwrap();
+        assert_eq!(&out[..4], &[0x81, 126, 0, 200]);
+    }
+}
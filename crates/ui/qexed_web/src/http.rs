@@ -0,0 +1,216 @@
+// 最小的 HTTP/1.1 实现
+// 每个连接只处理一个请求,响应后关闭连接(WebSocket 升级除外),足够监控抓取与管理接口使用
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, Take};
+
+// 请求行与请求头的总长度上限
+const MAX_HEAD: usize = 16 * 1024;
+// 请求体长度上限
+const MAX_BODY: usize = 1024 * 1024;
+
+#[derive(Debug, Default)]
+pub struct Request {
+    pub method: String,
+    /// 不含查询串的路径
+    pub path: String,
+    /// 已解码的查询参数
+    pub query: Vec<(String, String)>,
+    /// 请求头,名称统一为小写
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// 读取一个请求,连接在发送请求前关闭时返回 None
+pub async fn read_request<R: AsyncBufRead + Unpin>(reader: &mut R) -> anyhow::Result<Option<Request>> {
+    // 请求行与请求头共用一个读取上限,单行也不能超过
+    let mut head = (&mut *reader).take(MAX_HEAD as u64);
+    let mut line = String::new();
+    if read_head_line(&mut head, &mut line).await? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("请求行格式错误");
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: decode(path),
+        query: parse_query(query),
+        ..Default::default()
+    };
+
+    loop {
+        line.clear();
+        if read_head_line(&mut head, &mut line).await? == 0 {
+            anyhow::bail!("请求头不完整");
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            request
+                .headers
+                .push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let length: usize = match request.header("content-length") {
+        Some(length) => length.parse()?,
+        None => 0,
+    };
+    if length > MAX_BODY {
+        anyhow::bail!("请求体过长");
+    }
+    request.body.resize(length, 0);
+    reader.read_exact(&mut request.body).await?;
+    Ok(Some(request))
+}
+
+/// 读取一行请求头,读到上限仍未遇到换行时报错
+async fn read_head_line<R: AsyncBufRead + Unpin>(head: &mut Take<R>, line: &mut String) -> anyhow::Result<usize> {
+    let read = head.read_line(line).await?;
+    if head.limit() == 0 && !line.ends_with('\n') {
+        anyhow::bail!("请求头过长");
+    }
+    Ok(read)
+}
+
+/// 解析 application/x-www-form-urlencoded 格式的参数
+pub fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+/// 百分号解码,+ 视为空格
+pub fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = text.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'+', _) => out.push(b' '),
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 2;
+            }
+            (byte, _) => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u16, content_type: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            content_type,
+            body: body.into(),
+        }
+    }
+
+    pub fn text(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self::new(status, "text/plain; charset=utf-8", body)
+    }
+
+    pub fn json(status: u16, body: &serde_json::Value) -> Self {
+        Self::new(status, "application/json; charset=utf-8", body.to_string())
+    }
+
+    pub fn not_found() -> Self {
+        Self::text(404, "Not Found")
+    }
+
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> anyhow::Result<()> {
+        let head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.status,
+            reason(self.status),
+            self.content_type,
+            self.body.len()
+        );
+        writer.write_all(head.as_bytes()).await?;
+        writer.write_all(&self.body).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn read_request_with_body() {
+        let raw = b"POST /a%20b?x=1&name=%E4%B8%AD+c HTTP/1.1\r\nHost: x\r\nContent-Length: 3\r\n\r\nabc";
+        let mut reader = &raw[..];
+        let request = read_request(&mut reader).await.unwrap().unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/a b");
+        assert_eq!(request.query("x"), Some("1"));
+        assert_eq!(request.query("name"), Some("中 c"));
+        assert_eq!(request.header("HOST"), Some("x"));
+        assert_eq!(request.body, b"abc");
+
+        let mut empty = &b""[..];
+        assert!(read_request(&mut empty).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn read_request_rejects_long_head() {
+        // 没有换行的超长请求行不能无限读取
+        let raw = vec![b'a'; MAX_HEAD * 4];
+        let mut reader = &raw[..];
+        assert!(read_request(&mut reader).await.is_err());
+
+        let mut raw = b"GET / HTTP/1.1\r\n".to_vec();
+        raw.extend(std::iter::repeat_n(b"X-A: b\r\n".as_slice(), MAX_HEAD / 8).flatten());
+        raw.extend(b"\r\n");
+        let mut reader = &raw[..];
+        assert!(read_request(&mut reader).await.is_err());
+    }
+}
@@ -0,0 +1,106 @@
+// Web 服务
+// GET /metrics        Prometheus 监控指标,不需要令牌
+// /api/...            管理接口,见 api
+// GET /ws/console     WebSocket 控制台,见 console
+// 管理接口与控制台需要在 Authorization: Bearer 头或 token 参数中携带配置的令牌
+use std::{sync::Arc, time::Duration};
+
+use qexed_config::app::qexed_web::WebConfig;
+use qexed_task::mailbox::MailboxSender;
+use qexed_task::message::{return_message::ReturnMessage, unreturn_message::UnReturnMessage};
+use tokio::{
+    io::BufReader,
+    net::{TcpListener, TcpStream},
+    sync::mpsc::UnboundedSender,
+};
+
+use crate::http::{Request, Response};
+
+pub mod api;
+pub mod console;
+pub mod http;
+pub mod metrics;
+
+// 读取请求的时限,防止空闲连接一直占用
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+// 单个服务的查询时限
+pub(crate) const QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Web 服务需要访问的服务
+pub struct Services {
+    pub player_list: UnboundedSender<ReturnMessage<qexed_player_list::Message>>,
+    pub chunk: UnboundedSender<UnReturnMessage<qexed_chunk::message::world::WorldCommand>>,
+    pub game_logic: MailboxSender<ReturnMessage<qexed_game_logic::message::ManagerMessage>>,
+    pub white_list: UnboundedSender<ReturnMessage<qexed_whitelist::Message>>,
+    pub black_list: UnboundedSender<ReturnMessage<qexed_blacklist::Message>>,
+    pub command: UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+}
+
+pub async fn run(config: WebConfig, services: Services) -> anyhow::Result<()> {
+    if !config.enable {
+        return Ok(());
+    }
+    let listener = TcpListener::bind(&config.addr).await?;
+    if config.token.is_empty() {
+        log::warn!("[Web] 未设置访问令牌,管理接口与控制台已关闭");
+    }
+    let token: Arc<str> = config.token.into();
+    let services = Arc::new(services);
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let token = token.clone();
+                    let services = services.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle(stream, &token, services).await {
+                            log::debug!("[Web] 请求处理失败: {}", e);
+                        }
+                    });
+                }
+                Err(e) => log::warn!("[Web] 接受连接失败: {}", e),
+            }
+        }
+    });
+    log::info!("[服务] Web 已启用,监听 {}", config.addr);
+    Ok(())
+}
+
+async fn handle(stream: TcpStream, token: &str, services: Arc<Services>) -> anyhow::Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut reader = BufReader::new(read);
+    let Some(request) = tokio::time::timeout(READ_TIMEOUT, http::read_request(&mut reader)).await??
+    else {
+        return Ok(());
+    };
+    let path = request.path.as_str();
+    let response = match (request.method.as_str(), path) {
+        ("GET", "/metrics") => Response::new(200, metrics::CONTENT_TYPE, metrics::collect(&services).await),
+        (_, "/metrics") => Response::text(405, "Method Not Allowed"),
+        _ if path != "/ws/console" && !path.starts_with("/api/") => Response::not_found(),
+        _ if !authorized(&request, token) => Response::text(401, "Unauthorized"),
+        ("GET", "/ws/console") => return console::serve(&request, reader, write, services).await,
+        _ => api::handle(&request, &services).await,
+    };
+    response.write_to(&mut write).await
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    if token.is_empty() {
+        return false;
+    }
+    let given = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| request.query("token"));
+    // 逐字节比较全部内容,耗时与不匹配的位置无关
+    given.is_some_and(|given| {
+        given.len() == token.len()
+            && given
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    })
+}
+
@@ -0,0 +1,181 @@
+// Prometheus 监控指标
+// 每次抓取时向各服务查询当前状态,按文本格式 0.0.4 输出,查询超时的服务本次不输出指标
+use std::{collections::HashMap, fmt::Display};
+
+use qexed_chunk::message::world::WorldCommand;
+use qexed_task::{
+    mailbox::MailboxStats,
+    message::{MessageType, return_message::ReturnMessage, unreturn_message::UnReturnMessage},
+};
+
+use crate::{QUERY_TIMEOUT, Services};
+
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Debug, Default)]
+struct Registry {
+    out: String,
+}
+
+impl Registry {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        self.out.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind));
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape(value)))
+                .collect();
+            self.out.push_str(&format!("{{{}}}", labels.join(",")));
+        }
+        self.out.push_str(&format!(" {}\n", value));
+    }
+
+    /// 输出只有一个样本的指标
+    fn single(&mut self, name: &str, kind: &str, help: &str, value: impl Display) {
+        self.family(name, kind, help);
+        self.sample(name, &[], value);
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// 收集全部指标
+pub async fn collect(services: &Services) -> String {
+    let mut registry = Registry::default();
+    if let Err(e) = players(&mut registry, services).await {
+        log::warn!("[Web] 读取玩家列表失败: {}", e);
+    }
+    if let Err(e) = worlds(&mut registry, services).await {
+        log::warn!("[Web] 读取世界状态失败: {}", e);
+    }
+    heartbeat(&mut registry);
+    network(&mut registry);
+    mailboxes(&mut registry);
+    registry.out
+}
+
+async fn players(registry: &mut Registry, services: &Services) -> anyhow::Result<()> {
+    let qexed_player_list::Message::LoadData(online, max) =
+        ReturnMessage::build(qexed_player_list::Message::LoadData(0, 0))
+            .get_timeout(&services.player_list, QUERY_TIMEOUT)
+            .await?
+    else {
+        anyhow::bail!("玩家列表服务返回了意外的消息");
+    };
+    registry.single("qexed_players_online", "gauge", "在线玩家数", online);
+    registry.single("qexed_players_max", "gauge", "最大玩家数", max);
+
+    let qexed_player_list::Message::GetPlayers(players) =
+        ReturnMessage::build(qexed_player_list::Message::GetPlayers(Vec::new()))
+            .get_timeout(&services.player_list, QUERY_TIMEOUT)
+            .await?
+    else {
+        anyhow::bail!("玩家列表服务返回了意外的消息");
+    };
+    let names: HashMap<_, _> = players.into_iter().collect();
+    registry.family("qexed_player_latency_milliseconds", "gauge", "玩家最近一次测得的延迟");
+    for (uuid, latency) in qexed_ping::latency() {
+        let name = names.get(&uuid).map(String::as_str).unwrap_or_default();
+        registry.sample(
+            "qexed_player_latency_milliseconds",
+            &[("uuid", &uuid.to_string()), ("name", name)],
+            latency.as_millis(),
+        );
+    }
+    Ok(())
+}
+
+async fn worlds(registry: &mut Registry, services: &Services) -> anyhow::Result<()> {
+    let (result, wait) = tokio::sync::oneshot::channel();
+    UnReturnMessage::build(WorldCommand::GetWorldsStatus { result })
+        .post(&services.chunk)
+        .await?;
+    let worlds = tokio::time::timeout(QUERY_TIMEOUT, wait).await??;
+    registry.single("qexed_worlds_loaded", "gauge", "已加载的世界数", worlds.len());
+    registry.family("qexed_world_regions", "gauge", "世界中已加载的区域数");
+    for world in &worlds {
+        let uuid = world.uuid.to_string();
+        registry.sample("qexed_world_regions", &[("uuid", &uuid), ("name", &world.name)], world.regions);
+    }
+    registry.family("qexed_world_chunks", "gauge", "世界中已加载的区块数");
+    for world in &worlds {
+        let uuid = world.uuid.to_string();
+        registry.sample("qexed_world_chunks", &[("uuid", &uuid), ("name", &world.name)], world.chunks);
+    }
+    Ok(())
+}
+
+fn heartbeat(registry: &mut Registry) {
+    let stats = qexed_heartbeat::stats();
+    registry.single("qexed_heartbeat_misses_total", "counter", "未按时收到响应的心跳数", stats.misses);
+    registry.single(
+        "qexed_heartbeat_timeouts_total",
+        "counter",
+        "因心跳连续丢失而断开的玩家数",
+        stats.timeouts,
+    );
+}
+
+fn network(registry: &mut Registry) {
+    let stats = qexed_tcp_connect::stats::stats();
+    registry.single("qexed_network_received_bytes_total", "counter", "接收的字节数", stats.bytes_in);
+    registry.single("qexed_network_sent_bytes_total", "counter", "发送的字节数", stats.bytes_out);
+    registry.single(
+        "qexed_network_compressed_packets_total",
+        "counter",
+        "经过压缩发送的数据包数",
+        stats.packets_compressed,
+    );
+    registry.single(
+        "qexed_network_compression_input_bytes_total",
+        "counter",
+        "压缩前的字节数",
+        stats.compress_input_bytes,
+    );
+    registry.single(
+        "qexed_network_compression_output_bytes_total",
+        "counter",
+        "压缩后的字节数",
+        stats.compress_output_bytes,
+    );
+}
+
+fn mailboxes(registry: &mut Registry) {
+    let stats = qexed_task::mailbox::stats();
+    let mut family = |name: &str, kind: &str, help: &str, value: fn(&MailboxStats) -> u64| {
+        registry.family(name, kind, help);
+        for (mailbox, stats) in &stats {
+            registry.sample(name, &[("mailbox", mailbox)], value(stats));
+        }
+    };
+    family("qexed_mailbox_depth", "gauge", "邮箱中等待处理的消息数", |stats| stats.depth as u64);
+    family("qexed_mailbox_capacity", "gauge", "邮箱容量", |stats| stats.capacity as u64);
+    family("qexed_mailbox_peak", "gauge", "邮箱曾经达到的最大长度", |stats| stats.peak as u64);
+    family("qexed_mailbox_dropped_total", "counter", "DropOldest 策略丢弃的消息数", |stats| stats.dropped);
+    family("qexed_mailbox_rejected_total", "counter", "因邮箱已满被拒绝的消息数", |stats| stats.rejected);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_format() {
+        let mut registry = Registry::default();
+        registry.single("qexed_players_online", "gauge", "在线玩家数", 3);
+        registry.family("qexed_world_chunks", "gauge", "区块数");
+        registry.sample("qexed_world_chunks", &[("name", "a\"b\\c"), ("uuid", "1")], 10);
+        assert_eq!(
+            registry.out,
+            "# HELP qexed_players_online 在线玩家数\n# TYPE qexed_players_online gauge\nqexed_players_online 3\n\
+             # HELP qexed_world_chunks 区块数\n# TYPE qexed_world_chunks gauge\n\
+             qexed_world_chunks{name=\"a\\\"b\\\\c\",uuid=\"1\"} 10\n"
+        );
+    }
+}
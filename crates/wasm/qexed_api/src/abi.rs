@@ -0,0 +1,331 @@
+//! 宿主与插件之间的调用约定
+//!
+//! 插件与宿主之间只传递 JSON 编码的 [`HostCall`]/[`GuestCall`],由 SDK 与运行时各自编解码。
+//! 内存约定:
+//! - 插件导出 `qexed_alloc(len) -> ptr` 与 `qexed_free(ptr, len)`,宿主写入插件内存前先由插件分配
+//! - 插件调用 `qexed::call(ptr, len) -> i64` 发起请求,宿主返回打包的 `(ptr << 32) | len`,
+//!   缓冲区由宿主通过 `qexed_alloc` 分配,插件读取后释放;返回负数表示错误码
+//! - 宿主调用插件导出的 `qexed_dispatch(ptr, len) -> i64` 投递指令、事件与定时任务,
+//!   输入缓冲区归插件所有,返回的缓冲区由宿主读取后通过 `qexed_free` 释放
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// 核心宿主模块名,无需在 permission.toml 中授权
+pub const HOST_MODULE: &str = "qexed";
+pub const CALL_IMPORT: &str = "call";
+pub const ALLOC_EXPORT: &str = "qexed_alloc";
+pub const FREE_EXPORT: &str = "qexed_free";
+pub const DISPATCH_EXPORT: &str = "qexed_dispatch";
+
+/// 宿主无法读取插件内存
+pub const ERR_MEMORY: i64 = -1;
+/// 请求无法解码
+pub const ERR_DECODE: i64 = -2;
+/// 无法在插件内存中分配返回值
+pub const ERR_ALLOC: i64 = -3;
+/// 不在插件环境中(在原生目标上运行 SDK)
+pub const ERR_UNSUPPORTED: i64 = -4;
+
+/// 插件向宿主发起的请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HostCall {
+    /// 注册指令,回调通过 [`GuestCall::Command`] 投递
+    RegisterCommand(CommandSpec),
+    /// 订阅事件,回调通过 [`GuestCall::Event`] 投递
+    Subscribe { kind: EventKind, priority: Priority },
+    /// 创建定时任务,回调通过 [`GuestCall::Task`] 投递
+    Schedule {
+        id: u32,
+        delay: TaskTime,
+        /// 为空表示只执行一次
+        period: Option<TaskTime>,
+    },
+    CancelTask(u32),
+    /// 回复当前正在执行的指令的发送者
+    Reply(String),
+    /// 向指定玩家发送系统消息
+    SendMessage { player: String, message: String },
+    /// 向全服广播系统消息
+    Broadcast(String),
+    /// 查询在线玩家
+    GetPlayers,
+    /// 按名称查询在线玩家(忽略大小写)
+    GetPlayer(String),
+    /// 注册数据包钩子,回调通过 [`GuestCall::Packet`] 投递
+    /// 观察需要 packet_listen 权限,改写需要 packet_replace,丢弃需要 packet_interception
+    HookPacket(PacketFilter),
+    /// 读取插件存储中的值
+    StoreGet(String),
+    /// 写入插件存储,value 为空表示删除
+    StoreSet { key: String, value: Option<Vec<u8>> },
+    /// 当前值等于 expected 时写入 value,应答 [`HostReply::Swapped`]
+    StoreCompareAndSet {
+        key: String,
+        expected: Option<Vec<u8>>,
+        value: Option<Vec<u8>>,
+    },
+    /// 列出带指定前缀的键
+    StoreKeys(String),
+    /// 向玩家连接注入数据包(包ID + 内容),需要 packet_write 权限
+    SendPacket {
+        player: String,
+        direction: PacketDirection,
+        data: Vec<u8>,
+    },
+}
+
+/// 宿主对 [`HostCall`] 的应答
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HostReply {
+    Ok,
+    Players(Vec<PlayerInfo>),
+    Player(Option<PlayerInfo>),
+    Value(Option<Vec<u8>>),
+    /// 比较并写入是否成功
+    Swapped(bool),
+    Keys(Vec<String>),
+    Error(String),
+}
+
+/// 宿主向插件投递的调用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GuestCall {
+    Command(CommandInvocation),
+    Event(Event),
+    Task(u32),
+    Packet(Packet),
+}
+
+/// 插件对 [`GuestCall`] 的应答
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GuestReply {
+    Ok,
+    /// 事件处理结果,可携带修改后的事件
+    Event { cancelled: bool, event: Event },
+    /// 数据包钩子的处理结果
+    Packet(PacketAction),
+    Error(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    /// 带连字符的 UUID 字符串
+    pub uuid: String,
+    pub name: String,
+}
+
+/// 指令定义,宿主据此生成客户端指令树与帮助
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandSpec {
+    pub name: String,
+    pub description: String,
+    /// 执行所需权限,为空时使用 plugin.<插件名>.<指令名>
+    pub permission: String,
+    pub aliases: Vec<String>,
+    pub arguments: Vec<Argument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Argument {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+    pub kind: ArgumentKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArgumentKind {
+    /// 单个单词
+    Word,
+    /// 可带引号的字符串
+    String,
+    /// 剩余的全部文本
+    Greedy,
+    Integer,
+    Float,
+    Bool,
+    /// 在线玩家名
+    Player,
+}
+
+/// 一次指令执行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandInvocation {
+    pub name: String,
+    /// 指令名之后的参数
+    pub args: Vec<String>,
+    /// 执行者,控制台执行时为空
+    pub sender: Option<PlayerInfo>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EventKind {
+    PlayerJoin,
+    PlayerQuit,
+    Chat,
+    CommandPreprocess,
+    BlockBreak,
+}
+
+/// 事件监听优先级,按从低到高的顺序调用,Monitor 最后调用,对事件的修改与取消都会被忽略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Priority {
+    Lowest,
+    Low,
+    #[default]
+    Normal,
+    High,
+    Highest,
+    Monitor,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Event {
+    /// 玩家进服,取消后玩家会被断开连接
+    PlayerJoin { player: PlayerInfo },
+    /// 玩家离开,无法取消
+    PlayerQuit { player: PlayerInfo },
+    /// 聊天消息,可修改 message
+    Chat { player: PlayerInfo, message: String },
+    /// 指令执行前,command 不含开头的 /,可修改
+    CommandPreprocess { player: PlayerInfo, command: String },
+    /// 玩家破坏方块,取消后方块会在客户端恢复
+    BlockBreak { player: PlayerInfo, x: i32, y: i32, z: i32 },
+}
+
+impl Event {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Event::PlayerJoin { .. } => EventKind::PlayerJoin,
+            Event::PlayerQuit { .. } => EventKind::PlayerQuit,
+            Event::Chat { .. } => EventKind::Chat,
+            Event::CommandPreprocess { .. } => EventKind::CommandPreprocess,
+            Event::BlockBreak { .. } => EventKind::BlockBreak,
+        }
+    }
+
+    pub fn player(&self) -> &PlayerInfo {
+        match self {
+            Event::PlayerJoin { player }
+            | Event::PlayerQuit { player }
+            | Event::Chat { player, .. }
+            | Event::CommandPreprocess { player, .. }
+            | Event::BlockBreak { player, .. } => player,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PacketDirection {
+    /// 客户端发往服务端
+    Serverbound,
+    /// 服务端发往客户端
+    Clientbound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PacketState {
+    Configuration,
+    Play,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PacketMode {
+    /// 只观察,看到的是最终发出的数据包
+    Listen,
+    /// 可以改写或丢弃
+    Modify,
+}
+
+/// 数据包钩子的匹配条件
+/// tcp_connect 模块可以匹配配置与游玩阶段的双向数据包;
+/// packet_split 模块只能匹配游玩阶段客户端发来的数据包
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketFilter {
+    pub direction: PacketDirection,
+    pub state: PacketState,
+    /// 为空表示匹配全部包ID
+    pub id: Option<i32>,
+    pub mode: PacketMode,
+}
+
+/// 交给钩子处理的数据包
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Packet {
+    /// 连接所属玩家的 UUID
+    pub player: String,
+    pub direction: PacketDirection,
+    pub state: PacketState,
+    pub id: i32,
+    /// 包ID + 内容,未压缩、未加密
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PacketAction {
+    Pass,
+    /// 替换为新的数据包(包ID + 内容)
+    Replace(Vec<u8>),
+    Drop,
+}
+
+/// 定时任务的时间单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskTime {
+    /// 游戏刻,1 刻 = 50 毫秒
+    Ticks(u64),
+    Millis(u64),
+}
+
+impl TaskTime {
+    pub fn as_millis(&self) -> u64 {
+        match self {
+            TaskTime::Ticks(ticks) => ticks.saturating_mul(50),
+            TaskTime::Millis(millis) => *millis,
+        }
+    }
+}
+
+pub fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+    // 以上类型均可无损编码为 JSON
+    serde_json::to_vec(value).unwrap_or_default()
+}
+
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    serde_json::from_slice(bytes).map_err(|e| e.to_string())
+}
+
+/// 打包插件内存中的缓冲区位置
+pub fn pack(ptr: u32, len: u32) -> i64 {
+    ((ptr as i64) << 32) | len as i64
+}
+
+/// 拆分 [`pack`] 的结果,负数为错误码
+pub fn unpack(value: i64) -> Result<(u32, u32), i64> {
+    if value < 0 {
+        return Err(value);
+    }
+    Ok(((value >> 32) as u32, value as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        assert_eq!(unpack(pack(0x1000, 42)), Ok((0x1000, 42)));
+        assert_eq!(unpack(pack(u32::MAX >> 1, u32::MAX)), Ok((u32::MAX >> 1, u32::MAX)));
+        assert_eq!(unpack(ERR_DECODE), Err(ERR_DECODE));
+
+        let call = GuestCall::Event(Event::Chat {
+            player: PlayerInfo {
+                uuid: "00000000-0000-0000-0000-000000000000".to_string(),
+                name: "Steve".to_string(),
+            },
+            message: "你好".to_string(),
+        });
+        assert_eq!(decode::<GuestCall>(&encode(&call)), Ok(call));
+        assert!(decode::<HostCall>(b"{}").is_err());
+        assert_eq!(TaskTime::Ticks(20).as_millis(), 1000);
+    }
+}
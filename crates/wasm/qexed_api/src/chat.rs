@@ -0,0 +1,8 @@
+//! 聊天
+use crate::abi::HostCall;
+use crate::{Result, sys};
+
+/// 向全服广播系统消息,支持 § 格式代码
+pub fn broadcast(message: &str) -> Result<()> {
+    sys::call(&HostCall::Broadcast(message.to_string())).map(|_| ())
+}
@@ -0,0 +1,100 @@
+//! 指令注册
+use crate::abi::{Argument, ArgumentKind, CommandInvocation, CommandSpec, HostCall, PlayerInfo};
+use crate::{Result, sys};
+
+/// 指令定义构建器
+///
+/// ```ignore
+/// Command::new("hello")
+///     .description("打个招呼")
+///     .argument("name", "玩家名", ArgumentKind::Player, false)
+///     .register()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Command {
+    spec: CommandSpec,
+}
+
+impl Command {
+    pub fn new(name: &str) -> Self {
+        Self {
+            spec: CommandSpec {
+                name: name.to_string(),
+                description: String::new(),
+                permission: String::new(),
+                aliases: vec![],
+                arguments: vec![],
+            },
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.spec.description = description.to_string();
+        self
+    }
+
+    /// 执行所需权限,默认为 plugin.<插件名>.<指令名>
+    pub fn permission(mut self, permission: &str) -> Self {
+        self.spec.permission = permission.to_string();
+        self
+    }
+
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.spec.aliases.push(alias.to_string());
+        self
+    }
+
+    /// 追加参数,可选参数之后不应再出现必填参数
+    pub fn argument(mut self, name: &str, description: &str, kind: ArgumentKind, required: bool) -> Self {
+        self.spec.arguments.push(Argument {
+            name: name.to_string(),
+            description: description.to_string(),
+            required,
+            kind,
+        });
+        self
+    }
+
+    pub fn spec(&self) -> &CommandSpec {
+        &self.spec
+    }
+
+    /// 向宿主注册,执行时回调 [`crate::Plugin::on_command`]
+    pub fn register(self) -> Result<()> {
+        sys::call(&HostCall::RegisterCommand(self.spec)).map(|_| ())
+    }
+}
+
+/// 指令执行上下文
+#[derive(Debug, Clone)]
+pub struct CommandContext {
+    pub invocation: CommandInvocation,
+}
+
+impl CommandContext {
+    pub fn name(&self) -> &str {
+        &self.invocation.name
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.invocation.args
+    }
+
+    pub fn arg(&self, index: usize) -> Option<&str> {
+        self.invocation.args.get(index).map(|s| s.as_str())
+    }
+
+    /// 执行者,控制台执行时为空
+    pub fn sender(&self) -> Option<&PlayerInfo> {
+        self.invocation.sender.as_ref()
+    }
+
+    pub fn is_console(&self) -> bool {
+        self.invocation.sender.is_none()
+    }
+
+    /// 回复执行者,控制台执行时输出到控制台
+    pub fn reply(&self, message: &str) -> Result<()> {
+        sys::call(&HostCall::Reply(message.to_string())).map(|_| ())
+    }
+}
@@ -0,0 +1,19 @@
+//! 事件订阅
+pub use crate::abi::{Event, EventKind, Priority};
+use crate::abi::HostCall;
+use crate::{Result, sys};
+
+/// 监听器对事件的处理结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EventResult {
+    /// 继续传递给后续监听器,对事件的修改会一并传递
+    #[default]
+    Continue,
+    /// 取消事件,服务不会再执行默认行为,后续监听器(Monitor 除外)也不再收到该事件
+    Cancel,
+}
+
+/// 订阅事件,触发时回调 [`crate::Plugin::on_event`]
+pub fn subscribe(kind: EventKind, priority: Priority) -> Result<()> {
+    sys::call(&HostCall::Subscribe { kind, priority }).map(|_| ())
+}
@@ -1 +1,52 @@
-mod modern;
+//! Qexed 插件 SDK
+//!
+//! 插件编译为 `wasm32-unknown-unknown` 目标,实现 [`Plugin`] 并通过 [`plugin!`] 导出入口。
+//! 与宿主之间传递的参数由 SDK 负责编解码,见 [`abi`]。
+pub mod abi;
+pub mod chat;
+pub mod command;
+pub mod event;
+pub mod modern;
+pub mod packet;
+pub mod player;
+pub mod plugin;
+pub mod scheduler;
+pub mod storage;
+pub mod sys;
+
+pub use abi::ArgumentKind;
+pub use command::{Command, CommandContext};
+pub use event::{Event, EventKind, EventResult, Priority};
+pub use packet::{Packet, PacketAction};
+pub use player::PlayerInfo;
+pub use plugin::Plugin;
+pub use scheduler::{TaskId, TaskTime};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("宿主调用失败,错误码 {0}")]
+    Host(i64),
+    #[error("无法解码宿主应答: {0}")]
+    Decode(String),
+    #[error("宿主拒绝了请求: {0}")]
+    Rejected(String),
+    #[error("宿主返回了意外的应答: {0}")]
+    Unexpected(String),
+    /// 插件自身的错误
+    #[error("{0}")]
+    Plugin(String),
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Plugin(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::Plugin(message.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
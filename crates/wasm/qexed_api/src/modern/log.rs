@@ -0,0 +1,40 @@
+//! 日志,需要 permission.toml 中授予 log 权限
+//!
+//! 输出带有插件名前缀,如 `[插件] [hello_world] 已启用`
+use crate::sys;
+
+pub fn debug(message: &str) {
+    sys::log(0, message);
+}
+
+pub fn info(message: &str) {
+    sys::log(1, message);
+}
+
+pub fn warn(message: &str) {
+    sys::log(2, message);
+}
+
+pub fn error(message: &str) {
+    sys::log(3, message);
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::modern::log::debug(&format!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::modern::log::info(&format!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => { $crate::modern::log::warn(&format!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => { $crate::modern::log::error(&format!($($arg)*)) };
+}
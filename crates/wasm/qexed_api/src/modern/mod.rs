@@ -1 +1 @@
-pub mod log;
\ No newline at end of file
+pub mod log;
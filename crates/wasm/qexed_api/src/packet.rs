@@ -0,0 +1,40 @@
+//! 数据包钩子
+//!
+//! 需要在 permission.toml 中授予对应权限:观察需要 packet_listen,改写需要 packet_replace,
+//! 丢弃需要 packet_interception,注入需要 packet_write。
+//! 钩子只能在 tcp_connect 与 packet_split 模块中注册,数据包为未压缩、未加密的 包ID + 内容。
+pub use crate::abi::{Packet, PacketAction, PacketDirection, PacketFilter, PacketMode, PacketState};
+use crate::abi::HostCall;
+use crate::player::PlayerInfo;
+use crate::{Result, sys};
+
+fn hook(direction: PacketDirection, state: PacketState, id: Option<i32>, mode: PacketMode) -> Result<()> {
+    sys::call(&HostCall::HookPacket(PacketFilter {
+        direction,
+        state,
+        id,
+        mode,
+    }))
+    .map(|_| ())
+}
+
+/// 观察数据包,触发时回调 [`crate::Plugin::on_packet`],返回值会被忽略
+/// id 为空表示匹配全部包ID
+pub fn listen(direction: PacketDirection, state: PacketState, id: Option<i32>) -> Result<()> {
+    hook(direction, state, id, PacketMode::Listen)
+}
+
+/// 拦截数据包,可在 [`crate::Plugin::on_packet`] 中改写或丢弃
+pub fn intercept(direction: PacketDirection, state: PacketState, id: Option<i32>) -> Result<()> {
+    hook(direction, state, id, PacketMode::Modify)
+}
+
+/// 向玩家连接注入数据包,注入的数据包不经过连接层钩子
+pub fn send(player: &PlayerInfo, direction: PacketDirection, data: Vec<u8>) -> Result<()> {
+    sys::call(&HostCall::SendPacket {
+        player: player.uuid.clone(),
+        direction,
+        data,
+    })
+    .map(|_| ())
+}
@@ -0,0 +1,29 @@
+//! 在线玩家
+pub use crate::abi::PlayerInfo;
+use crate::abi::{HostCall, HostReply};
+use crate::{Error, Result, sys};
+
+/// 全部在线玩家
+pub fn online() -> Result<Vec<PlayerInfo>> {
+    match sys::call(&HostCall::GetPlayers)? {
+        HostReply::Players(players) => Ok(players),
+        reply => Err(Error::Unexpected(format!("{:?}", reply))),
+    }
+}
+
+/// 按名称查找在线玩家(忽略大小写)
+pub fn find(name: &str) -> Result<Option<PlayerInfo>> {
+    match sys::call(&HostCall::GetPlayer(name.to_string()))? {
+        HostReply::Player(player) => Ok(player),
+        reply => Err(Error::Unexpected(format!("{:?}", reply))),
+    }
+}
+
+/// 向玩家发送系统消息,支持 § 格式代码
+pub fn send_message(player: &PlayerInfo, message: &str) -> Result<()> {
+    sys::call(&HostCall::SendMessage {
+        player: player.uuid.clone(),
+        message: message.to_string(),
+    })
+    .map(|_| ())
+}
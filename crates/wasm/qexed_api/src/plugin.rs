@@ -0,0 +1,182 @@
+//! 插件入口
+use std::cell::RefCell;
+
+use crate::abi::{self, GuestCall, GuestReply};
+use crate::command::CommandContext;
+use crate::event::{Event, EventResult};
+use crate::packet::{Packet, PacketAction};
+use crate::scheduler::TaskId;
+use crate::{Result, sys};
+
+/// 插件实现此 trait,并通过 [`crate::plugin!`] 导出入口函数
+///
+/// 每个在 permission.toml 中声明的模块都会创建一个独立的实例,实例之间不共享内存
+pub trait Plugin: Default + 'static {
+    /// 插件被加载后调用
+    fn on_load(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// 插件启用时调用,通常在这里注册指令、订阅事件与创建定时任务
+    fn on_enable(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// 插件停用时调用,之后实例会被销毁
+    fn on_disable(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// 已注册的指令被执行
+    fn on_command(&mut self, _ctx: &CommandContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// 已订阅的事件被触发,可直接修改事件内容
+    fn on_event(&mut self, _event: &mut Event) -> EventResult {
+        EventResult::Continue
+    }
+
+    /// 定时任务到期
+    fn on_task(&mut self, _id: TaskId) {}
+
+    /// 已注册的数据包钩子被触发,观察类钩子的返回值会被忽略
+    fn on_packet(&mut self, _packet: &Packet) -> PacketAction {
+        PacketAction::Pass
+    }
+}
+
+/// 生命周期阶段
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub enum Stage {
+    Load,
+    Enable,
+    Disable,
+}
+
+/// 生命周期导出函数的实现,返回非 0 表示失败
+#[doc(hidden)]
+pub fn lifecycle<P: Plugin>(cell: &RefCell<Option<P>>, stage: Stage) -> i32 {
+    let Ok(mut guard) = cell.try_borrow_mut() else {
+        crate::modern::log::error("生命周期函数不可重入");
+        return 1;
+    };
+    if let Stage::Load = stage {
+        *guard = Some(P::default());
+    }
+    let Some(plugin) = guard.as_mut() else {
+        crate::modern::log::error("插件尚未加载");
+        return 1;
+    };
+    let result = match stage {
+        Stage::Load => plugin.on_load(),
+        Stage::Enable => plugin.on_enable(),
+        Stage::Disable => plugin.on_disable(),
+    };
+    if let Stage::Disable = stage {
+        *guard = None;
+    }
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            crate::modern::log::error(&format!("{:?} 失败: {}", stage, e));
+            1
+        }
+    }
+}
+
+/// qexed_dispatch 导出函数的实现
+///
+/// # Safety
+/// ptr 与 len 必须是宿主通过 qexed_alloc 分配并写入的缓冲区
+#[doc(hidden)]
+pub unsafe fn dispatch<P: Plugin>(cell: &RefCell<Option<P>>, ptr: *mut u8, len: usize) -> i64 {
+    let input = unsafe { sys::take(ptr, len) };
+    let reply = match abi::decode::<GuestCall>(&input) {
+        Ok(call) => handle(cell, call),
+        Err(e) => GuestReply::Error(format!("无法解码宿主调用: {}", e)),
+    };
+    sys::leak(abi::encode(&reply))
+}
+
+fn handle<P: Plugin>(cell: &RefCell<Option<P>>, call: GuestCall) -> GuestReply {
+    let Ok(mut guard) = cell.try_borrow_mut() else {
+        return GuestReply::Error("插件回调不可重入".to_string());
+    };
+    let Some(plugin) = guard.as_mut() else {
+        return GuestReply::Error("插件尚未加载".to_string());
+    };
+    match call {
+        GuestCall::Command(invocation) => match plugin.on_command(&CommandContext { invocation }) {
+            Ok(()) => GuestReply::Ok,
+            Err(e) => GuestReply::Error(e.to_string()),
+        },
+        GuestCall::Event(mut event) => {
+            let result = plugin.on_event(&mut event);
+            GuestReply::Event {
+                cancelled: result == EventResult::Cancel,
+                event,
+            }
+        }
+        GuestCall::Task(id) => {
+            plugin.on_task(TaskId(id));
+            GuestReply::Ok
+        }
+        GuestCall::Packet(packet) => GuestReply::Packet(plugin.on_packet(&packet)),
+    }
+}
+
+/// 生成插件入口与内存约定所需的导出函数
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct HelloWorld;
+/// impl qexed_api::Plugin for HelloWorld {}
+/// qexed_api::plugin!(HelloWorld);
+/// ```
+#[macro_export]
+macro_rules! plugin {
+    ($plugin:ty) => {
+        const _: () = {
+            ::std::thread_local! {
+                static PLUGIN: ::std::cell::RefCell<::std::option::Option<$plugin>> =
+                    const { ::std::cell::RefCell::new(::std::option::Option::None) };
+            }
+
+            #[unsafe(no_mangle)]
+            pub extern "C" fn on_load() -> i32 {
+                PLUGIN.with(|cell| $crate::plugin::lifecycle(cell, $crate::plugin::Stage::Load))
+            }
+
+            #[unsafe(no_mangle)]
+            pub extern "C" fn on_enable() -> i32 {
+                PLUGIN.with(|cell| $crate::plugin::lifecycle(cell, $crate::plugin::Stage::Enable))
+            }
+
+            #[unsafe(no_mangle)]
+            pub extern "C" fn on_disable() -> i32 {
+                PLUGIN.with(|cell| $crate::plugin::lifecycle(cell, $crate::plugin::Stage::Disable))
+            }
+
+            #[unsafe(no_mangle)]
+            pub extern "C" fn qexed_alloc(len: usize) -> *mut u8 {
+                $crate::sys::alloc(len)
+            }
+
+            /// # Safety
+            /// 仅供宿主调用
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn qexed_free(ptr: *mut u8, len: usize) {
+                unsafe { $crate::sys::free(ptr, len) }
+            }
+
+            /// # Safety
+            /// 仅供宿主调用
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn qexed_dispatch(ptr: *mut u8, len: usize) -> i64 {
+                PLUGIN.with(|cell| unsafe { $crate::plugin::dispatch(cell, ptr, len) })
+            }
+        };
+    };
+}
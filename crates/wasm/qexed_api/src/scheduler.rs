@@ -0,0 +1,32 @@
+//! 定时任务,插件卸载时由宿主自动取消
+use std::sync::atomic::{AtomicU32, Ordering};
+
+pub use crate::abi::TaskTime;
+use crate::abi::HostCall;
+use crate::{Result, sys};
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+/// 定时任务编号,回调 [`crate::Plugin::on_task`] 时用于区分任务
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskId(pub u32);
+
+/// 延迟后执行一次
+pub fn run_later(delay: TaskTime) -> Result<TaskId> {
+    schedule(delay, None)
+}
+
+/// 延迟后按周期重复执行
+pub fn run_timer(delay: TaskTime, period: TaskTime) -> Result<TaskId> {
+    schedule(delay, Some(period))
+}
+
+pub fn cancel(id: TaskId) -> Result<()> {
+    sys::call(&HostCall::CancelTask(id.0)).map(|_| ())
+}
+
+fn schedule(delay: TaskTime, period: Option<TaskTime>) -> Result<TaskId> {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    sys::call(&HostCall::Schedule { id, delay, period })?;
+    Ok(TaskId(id))
+}
@@ -0,0 +1,71 @@
+//! 插件键值存储,每个插件一个独立的命名空间,数据在重载与重启后保留
+//!
+//! 存储位置与配额由服务器配置决定,插件无需申请权限,也无需数据库凭据。
+use crate::abi::{HostCall, HostReply};
+use crate::{Error, Result, sys};
+
+pub fn get(key: &str) -> Result<Option<Vec<u8>>> {
+    match sys::call(&HostCall::StoreGet(key.to_string()))? {
+        HostReply::Value(value) => Ok(value),
+        reply => Err(Error::Unexpected(format!("{:?}", reply))),
+    }
+}
+
+/// 读取 UTF-8 文本,值不是合法文本时返回错误
+pub fn get_string(key: &str) -> Result<Option<String>> {
+    get(key)?
+        .map(|value| String::from_utf8(value).map_err(|e| Error::Decode(e.to_string())))
+        .transpose()
+}
+
+/// 写入值,超出配额时返回 [`Error::Rejected`]
+pub fn set(key: &str, value: &[u8]) -> Result<()> {
+    sys::call(&HostCall::StoreSet {
+        key: key.to_string(),
+        value: Some(value.to_vec()),
+    })
+    .map(|_| ())
+}
+
+pub fn set_string(key: &str, value: &str) -> Result<()> {
+    set(key, value.as_bytes())
+}
+
+pub fn delete(key: &str) -> Result<()> {
+    sys::call(&HostCall::StoreSet {
+        key: key.to_string(),
+        value: None,
+    })
+    .map(|_| ())
+}
+
+/// 当前值等于 expected 时写入 value,返回是否写入
+///
+/// expected 为空表示要求键不存在,value 为空表示删除。可用于实现计数器等并发安全的更新:
+/// ```ignore
+/// loop {
+///     let old = storage::get("count")?;
+///     let count = old.as_deref().map_or(0, |v| v[0]) + 1;
+///     if storage::compare_and_set("count", old.as_deref(), Some(&[count]))? {
+///         break;
+///     }
+/// }
+/// ```
+pub fn compare_and_set(key: &str, expected: Option<&[u8]>, value: Option<&[u8]>) -> Result<bool> {
+    match sys::call(&HostCall::StoreCompareAndSet {
+        key: key.to_string(),
+        expected: expected.map(|v| v.to_vec()),
+        value: value.map(|v| v.to_vec()),
+    })? {
+        HostReply::Swapped(swapped) => Ok(swapped),
+        reply => Err(Error::Unexpected(format!("{:?}", reply))),
+    }
+}
+
+/// 按字典序列出带指定前缀的键,前缀为空时列出全部
+pub fn keys(prefix: &str) -> Result<Vec<String>> {
+    match sys::call(&HostCall::StoreKeys(prefix.to_string()))? {
+        HostReply::Keys(keys) => Ok(keys),
+        reply => Err(Error::Unexpected(format!("{:?}", reply))),
+    }
+}
@@ -0,0 +1,93 @@
+//! 宿主导入函数与内存约定的底层实现,插件作者一般不需要直接使用
+use crate::abi::{self, HostCall, HostReply};
+use crate::{Error, Result};
+
+#[cfg(target_arch = "wasm32")]
+mod raw {
+    #[link(wasm_import_module = "qexed")]
+    unsafe extern "C" {
+        pub fn call(ptr: *const u8, len: usize) -> i64;
+    }
+
+    #[link(wasm_import_module = "log")]
+    unsafe extern "C" {
+        pub fn debug(ptr: *const u8, len: usize) -> i32;
+        pub fn info(ptr: *const u8, len: usize) -> i32;
+        pub fn warning(ptr: *const u8, len: usize) -> i32;
+        pub fn error(ptr: *const u8, len: usize) -> i32;
+    }
+}
+
+/// 分配一块由宿主写入的缓冲区
+pub fn alloc(len: usize) -> *mut u8 {
+    Box::into_raw(vec![0u8; len].into_boxed_slice()) as *mut u8
+}
+
+/// 释放 [`alloc`] 分配的缓冲区
+///
+/// # Safety
+/// ptr 与 len 必须来自同一次 [`alloc`],且只能释放一次
+pub unsafe fn free(ptr: *mut u8, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    drop(unsafe { take(ptr, len) });
+}
+
+/// 取回 [`alloc`] 分配的缓冲区的所有权
+///
+/// # Safety
+/// 同 [`free`]
+pub unsafe fn take(ptr: *mut u8, len: usize) -> Vec<u8> {
+    unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)) }.into_vec()
+}
+
+/// 将数据交给宿主,返回打包后的位置,宿主读取后负责释放
+pub fn leak(bytes: Vec<u8>) -> i64 {
+    let len = bytes.len();
+    let ptr = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+    abi::pack(ptr as usize as u32, len as u32)
+}
+
+/// 发起宿主调用
+pub fn call(request: &HostCall) -> Result<HostReply> {
+    let bytes = abi::encode(request);
+    let (ptr, len) = abi::unpack(call_raw(&bytes)).map_err(Error::Host)?;
+    let reply = unsafe { take(ptr as usize as *mut u8, len as usize) };
+    match abi::decode(&reply).map_err(Error::Decode)? {
+        HostReply::Error(message) => Err(Error::Rejected(message)),
+        reply => Ok(reply),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn call_raw(bytes: &[u8]) -> i64 {
+    unsafe { raw::call(bytes.as_ptr(), bytes.len()) }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn call_raw(_bytes: &[u8]) -> i64 {
+    abi::ERR_UNSUPPORTED
+}
+
+/// 输出日志,level 依次为 debug/info/warning/error
+#[cfg(target_arch = "wasm32")]
+pub fn log(level: u8, message: &str) -> i32 {
+    let (ptr, len) = (message.as_ptr(), message.len());
+    unsafe {
+        match level {
+            0 => raw::debug(ptr, len),
+            1 => raw::info(ptr, len),
+            2 => raw::warning(ptr, len),
+            _ => raw::error(ptr, len),
+        }
+    }
+}
+
+/// 原生目标上没有宿主,日志输出到标准错误,便于单元测试
+#[cfg(not(target_arch = "wasm32"))]
+pub fn log(level: u8, message: &str) -> i32 {
+    let level = ["DEBUG", "INFO", "WARN", "ERROR"][level.min(3) as usize];
+    eprintln!("[{}] {}", level, message);
+    0
+}
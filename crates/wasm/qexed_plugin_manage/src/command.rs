@@ -0,0 +1,189 @@
+use qexed_api::abi::{Argument, ArgumentKind, CommandSpec};
+use qexed_command::{
+    message::{CommandParameter, ParameterType, StringBehavior},
+    suggestion::{self, Suggestion, SuggestionProvider},
+    tree::CommandNode,
+};
+use qexed_task::message::{MessageType, return_message::ReturnMessage};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::message::ManagerMessage;
+
+pub async fn register_plugins_command(
+    command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+    api2: UnboundedSender<ReturnMessage<ManagerMessage>>,
+) -> anyhow::Result<()> {
+    // 克隆 api2 用于闭包
+    let api2_for_closure = api2.clone();
+
+    qexed_command::register::register_command(
+        "plugins",
+        "列出已安装的插件",
+        "qexed.plugins",
+        vec![],
+        vec!["pl"],
+        command_api,
+        move |mut cmd_rx| {
+            let api2 = api2_for_closure.clone();
+            async move {
+                while let Some(cmd) = cmd_rx.recv().await {
+                    ReturnMessage::build(ManagerMessage::CommandPlugins(cmd))
+                        .get(&api2)
+                        .await?;
+                }
+                Ok(())
+            }
+        },
+    )
+    .await
+}
+
+pub async fn register_plugin_command(
+    command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+    api2: UnboundedSender<ReturnMessage<ManagerMessage>>,
+) -> anyhow::Result<()> {
+    // 克隆 api2 用于闭包
+    let api2_for_closure = api2.clone();
+    let name = CommandParameter {
+        name: "name".to_string(),
+        description: "插件名".to_string(),
+        required: true,
+        param_type: ParameterType::String {
+            behavior: StringBehavior::SingleWord,
+        },
+        suggestions: None,
+    };
+    let tree = CommandNode::literal("plugin")
+        .then(CommandNode::literal("reload").then(CommandNode::argument(name.clone()).executes()))
+        .then(CommandNode::literal("stats").optional(CommandNode::argument(name.clone()).executes()))
+        .then(
+            CommandNode::literal("storage")
+                .then(CommandNode::literal("export").then(CommandNode::argument(name.clone()).executes()))
+                .then(CommandNode::literal("import").then(CommandNode::argument(name).executes())),
+        );
+
+    qexed_command::register::register_command_tree(
+        tree,
+        "管理插件",
+        "qexed.plugin",
+        vec![],
+        command_api,
+        move |mut cmd_rx| {
+            let api2 = api2_for_closure.clone();
+            async move {
+                while let Some(cmd) = cmd_rx.recv().await {
+                    ReturnMessage::build(ManagerMessage::CommandPlugin(cmd))
+                        .get(&api2)
+                        .await?;
+                }
+                Ok(())
+            }
+        },
+    )
+    .await?;
+    qexed_command::register::register_suggestion("plugin", plugin_name_provider(api2), command_api)
+        .await
+}
+
+/// 插件指令参数对应的命令参数,用于生成客户端指令树与帮助
+pub fn parameter_from_abi(argument: &Argument) -> CommandParameter {
+    let string = |behavior| ParameterType::String { behavior };
+    let param_type = match argument.kind {
+        ArgumentKind::Word => string(StringBehavior::SingleWord),
+        ArgumentKind::String => string(StringBehavior::Quotable),
+        ArgumentKind::Greedy => string(StringBehavior::Greedy),
+        ArgumentKind::Integer => ParameterType::Integer { min: None, max: None },
+        ArgumentKind::Float => ParameterType::Float { min: None, max: None },
+        ArgumentKind::Bool => ParameterType::Boolean,
+        ArgumentKind::Player => ParameterType::Player,
+    };
+    CommandParameter {
+        name: argument.name.clone(),
+        description: argument.description.clone(),
+        required: argument.required,
+        param_type,
+        suggestions: None,
+    }
+}
+
+/// 注册插件提交的指令,执行时交给插件管理投递给插件
+pub async fn register_spec(
+    plugin: &str,
+    spec: &CommandSpec,
+    command_api: &UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+    api2: UnboundedSender<ReturnMessage<ManagerMessage>>,
+) -> anyhow::Result<()> {
+    let permission = if spec.permission.is_empty() {
+        format!("plugin.{}.{}", plugin, spec.name)
+    } else {
+        spec.permission.clone()
+    };
+    qexed_command::register::register_command(
+        &spec.name,
+        &spec.description,
+        &permission,
+        spec.arguments.iter().map(parameter_from_abi).collect(),
+        spec.aliases.iter().map(|s| s.as_str()).collect(),
+        command_api,
+        move |mut cmd_rx| {
+            let api2 = api2.clone();
+            async move {
+                while let Some(cmd) = cmd_rx.recv().await {
+                    ReturnMessage::build(ManagerMessage::PluginCommand(cmd))
+                        .get(&api2)
+                        .await?;
+                }
+                Ok(())
+            }
+        },
+    )
+    .await
+}
+
+/// 插件名补全
+pub fn plugin_name_provider(api: UnboundedSender<ReturnMessage<ManagerMessage>>) -> SuggestionProvider {
+    SuggestionProvider::new(move |request| {
+        let api = api.clone();
+        async move {
+            if request.node.as_deref() != Some("name") {
+                return Ok(vec![]);
+            }
+            let names = match ReturnMessage::build(ManagerMessage::GetPluginNames(vec![]))
+                .get(&api)
+                .await?
+            {
+                ManagerMessage::GetPluginNames(names) => names,
+                _ => vec![],
+            };
+            Ok(suggestion::filter_prefix(
+                names.into_iter().map(Suggestion::new),
+                request.current(),
+            ))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments_become_tree() {
+        let argument = |name: &str, kind, required| Argument {
+            name: name.to_string(),
+            description: String::new(),
+            required,
+            kind,
+        };
+        let parameters: Vec<CommandParameter> = [
+            argument("target", ArgumentKind::Player, true),
+            argument("amount", ArgumentKind::Float, false),
+        ]
+        .iter()
+        .map(parameter_from_abi)
+        .collect();
+        assert!(matches!(parameters[1].param_type, ParameterType::Float { .. }));
+        let tree = CommandNode::from_parameters("heal", &parameters);
+        assert_eq!(tree.usages(), vec!["/heal <target>", "/heal <target> <amount>"]);
+    }
+}
@@ -0,0 +1,158 @@
+//! 事件总线、数据包钩子与插件调用约定之间的转换
+use qexed_api::abi;
+use qexed_event::{
+    Event, EventKind, Priority,
+    packet::{Direction, HookFilter, HookMode, HookPoint, Packet, PacketAction, State},
+};
+
+pub fn kind_from_abi(kind: abi::EventKind) -> EventKind {
+    match kind {
+        abi::EventKind::PlayerJoin => EventKind::PlayerJoin,
+        abi::EventKind::PlayerQuit => EventKind::PlayerQuit,
+        abi::EventKind::Chat => EventKind::Chat,
+        abi::EventKind::CommandPreprocess => EventKind::CommandPreprocess,
+        abi::EventKind::BlockBreak => EventKind::BlockBreak,
+    }
+}
+
+pub fn priority_from_abi(priority: abi::Priority) -> Priority {
+    match priority {
+        abi::Priority::Lowest => Priority::Lowest,
+        abi::Priority::Low => Priority::Low,
+        abi::Priority::Normal => Priority::Normal,
+        abi::Priority::High => Priority::High,
+        abi::Priority::Highest => Priority::Highest,
+        abi::Priority::Monitor => Priority::Monitor,
+    }
+}
+
+pub fn to_abi(event: &Event) -> abi::Event {
+    let (uuid, name) = event.player();
+    let player = abi::PlayerInfo {
+        uuid: uuid.to_string(),
+        name: name.to_string(),
+    };
+    match event {
+        Event::PlayerJoin { .. } => abi::Event::PlayerJoin { player },
+        Event::PlayerQuit { .. } => abi::Event::PlayerQuit { player },
+        Event::Chat { message, .. } => abi::Event::Chat {
+            player,
+            message: message.clone(),
+        },
+        Event::CommandPreprocess { command, .. } => abi::Event::CommandPreprocess {
+            player,
+            command: command.clone(),
+        },
+        Event::BlockBreak { x, y, z, .. } => abi::Event::BlockBreak {
+            player,
+            x: *x,
+            y: *y,
+            z: *z,
+        },
+    }
+}
+
+/// 插件所在模块对应的钩子位置
+pub fn hook_point(service: &str) -> Option<HookPoint> {
+    match service {
+        "tcp_connect" => Some(HookPoint::Connection),
+        "packet_split" => Some(HookPoint::Split),
+        _ => None,
+    }
+}
+
+pub fn direction_from_abi(direction: abi::PacketDirection) -> Direction {
+    match direction {
+        abi::PacketDirection::Serverbound => Direction::Serverbound,
+        abi::PacketDirection::Clientbound => Direction::Clientbound,
+    }
+}
+
+pub fn hook_filter(point: HookPoint, filter: &abi::PacketFilter) -> HookFilter {
+    HookFilter {
+        point,
+        direction: direction_from_abi(filter.direction),
+        state: match filter.state {
+            abi::PacketState::Configuration => State::Configuration,
+            abi::PacketState::Play => State::Play,
+        },
+        id: filter.id,
+        mode: match filter.mode {
+            abi::PacketMode::Listen => HookMode::Listen,
+            abi::PacketMode::Modify => HookMode::Modify,
+        },
+    }
+}
+
+pub fn packet_to_abi(packet: &Packet) -> abi::Packet {
+    abi::Packet {
+        player: packet.uuid.to_string(),
+        direction: match packet.direction {
+            Direction::Serverbound => abi::PacketDirection::Serverbound,
+            Direction::Clientbound => abi::PacketDirection::Clientbound,
+        },
+        state: match packet.state {
+            State::Configuration => abi::PacketState::Configuration,
+            State::Play => abi::PacketState::Play,
+        },
+        id: packet.id,
+        data: packet.data.clone(),
+    }
+}
+
+pub fn action_from_abi(action: abi::PacketAction) -> PacketAction {
+    match action {
+        abi::PacketAction::Pass => PacketAction::Pass,
+        abi::PacketAction::Replace(data) => PacketAction::Replace(data),
+        abi::PacketAction::Drop => PacketAction::Drop,
+    }
+}
+
+/// 合并插件修改后的事件,只接受可修改的字段,玩家与坐标保持不变
+pub fn apply_abi(event: &mut Event, modified: abi::Event) {
+    match (event, modified) {
+        (Event::Chat { message, .. }, abi::Event::Chat { message: text, .. }) => *message = text,
+        (
+            Event::CommandPreprocess { command, .. },
+            abi::Event::CommandPreprocess { command: text, .. },
+        ) => *command = text,
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn only_mutable_fields_are_applied() {
+        let mut event = Event::Chat {
+            uuid: Uuid::nil(),
+            name: "Steve".to_string(),
+            message: "hi".to_string(),
+        };
+        let abi::Event::Chat { player, .. } = to_abi(&event) else {
+            panic!("事件类型不一致");
+        };
+        assert_eq!(player.uuid, Uuid::nil().to_string());
+        apply_abi(
+            &mut event,
+            abi::Event::Chat {
+                player: abi::PlayerInfo {
+                    uuid: "x".to_string(),
+                    name: "Alex".to_string(),
+                },
+                message: "hello".to_string(),
+            },
+        );
+        assert_eq!(
+            event,
+            Event::Chat {
+                uuid: Uuid::nil(),
+                name: "Steve".to_string(),
+                message: "hello".to_string(),
+            }
+        );
+    }
+}
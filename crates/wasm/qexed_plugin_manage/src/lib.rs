@@ -0,0 +1,41 @@
+use qexed_task::mailbox::MailboxSender;
+use qexed_task::message::{MessageType, return_message::ReturnMessage};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{manager::PluginManagerActor, message::ManagerMessage};
+
+pub mod command;
+pub mod event;
+pub mod manager;
+pub mod manifest;
+pub mod message;
+pub mod plugin;
+pub mod storage;
+
+pub async fn run(
+    config: qexed_config::app::qexed_plugin::PluginConfig,
+    chat_api: MailboxSender<ReturnMessage<qexed_chat::message::ManagerMessage>>,
+    player_list_api: UnboundedSender<ReturnMessage<qexed_player_list::Message>>,
+    events: qexed_event::EventBus,
+    hooks: qexed_event::PacketHooks,
+    command_api: UnboundedSender<ReturnMessage<qexed_command::message::ManagerCommand>>,
+) -> anyhow::Result<UnboundedSender<ReturnMessage<ManagerMessage>>> {
+    let enabled = config.enabled;
+    let mut manager_actor =
+        PluginManagerActor::new(config, chat_api, player_list_api, events, hooks, command_api)?;
+    if enabled {
+        manager_actor.load_all();
+    }
+    let (task, task_send) = qexed_task::task::task::TaskEasy::new(manager_actor);
+    task.run().await?;
+    if enabled {
+        // 监听器、插件指令与定时任务需要持有本服务的消息通道,因此在启动后注册
+        ReturnMessage::build(ManagerMessage::Start)
+            .get(&task_send)
+            .await?;
+        log::info!("[服务] 插件管理 已启用");
+    } else {
+        log::info!("[服务] 插件管理 未启用插件加载");
+    }
+    Ok(task_send)
+}
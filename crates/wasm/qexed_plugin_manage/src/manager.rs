@@ -0,0 +1,985 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use qexed_api::abi::{CommandInvocation, CommandSpec, HostCall, PlayerInfo};
+use qexed_command::message::{CommandData, ManagerCommand};
+use qexed_config::app::qexed_plugin::{PluginConfig, PluginLimits};
+use qexed_protocol::to_client::play::system_chat::SystemChat;
+use qexed_task::{
+    mailbox::MailboxSender,
+    event::task::TaskEasyEvent,
+    message::{MessageSender, MessageType, return_message::ReturnMessage},
+};
+use qexed_wasm_runtime::{KvStore, ResourceLimits, WasmRuntime};
+use tokio::{sync::mpsc::UnboundedSender, task::AbortHandle};
+
+use crate::{
+    command, event,
+    manifest::{MANIFEST_FILE, PluginManifest},
+    message::ManagerMessage,
+    plugin::{Plugin, PluginStatus, ScheduledTask},
+    storage::{self, StorageQuota, StoreFile},
+};
+
+pub struct PluginManagerActor {
+    config: PluginConfig,
+    runtime: WasmRuntime,
+    plugins: BTreeMap<String, Plugin>,
+    chat_api: MailboxSender<ReturnMessage<qexed_chat::message::ManagerMessage>>,
+    player_list_api: UnboundedSender<ReturnMessage<qexed_player_list::Message>>,
+    // 在线玩家快照,插件查询玩家时直接应答
+    players: Vec<PlayerInfo>,
+    events: qexed_event::EventBus,
+    hooks: qexed_event::PacketHooks,
+    command_api: UnboundedSender<ReturnMessage<ManagerCommand>>,
+    // 本服务的消息通道,启动后才可用;指令与定时任务通过它回到本服务
+    api: Option<MessageSender<ReturnMessage<ManagerMessage>>>,
+    // 指令名 -> 插件指令
+    commands: BTreeMap<String, PluginCommand>,
+    timers: BTreeMap<TaskKey, Timer>,
+    // 插件名 -> 键值存储,插件重载时重新打开以应用新的配额
+    stores: BTreeMap<String, Arc<dyn KvStore>>,
+}
+
+/// 插件提交的指令,注册失败(如与已有指令重名)时仍记录,避免反复注册
+#[derive(Debug)]
+struct PluginCommand {
+    plugin: String,
+    service: String,
+    spec: CommandSpec,
+    registered: bool,
+}
+
+/// (插件名, 模块, 任务编号)
+type TaskKey = (String, String, u32);
+
+#[derive(Debug)]
+struct Timer {
+    task: ScheduledTask,
+    handle: AbortHandle,
+}
+
+/// 重复任务的最短周期,1 刻
+const MIN_PERIOD: u64 = 50;
+
+/// 本服务在事件总线上的注册者名
+const OWNER: &str = "plugin_manage";
+
+fn listener_owner(plugin: &str) -> String {
+    format!("plugin:{}", plugin)
+}
+
+fn resource_limits(limits: &PluginLimits) -> ResourceLimits {
+    ResourceLimits {
+        fuel: limits.fuel_per_call,
+        memory: (limits.max_memory_mb as usize).saturating_mul(1024 * 1024),
+        timeout: Duration::from_millis(limits.call_timeout_ms),
+    }
+}
+
+/// 按任务的延迟与周期向本服务发送 [`ManagerMessage::RunTask`],本服务退出后自行结束
+fn spawn_timer(
+    api: &MessageSender<ReturnMessage<ManagerMessage>>,
+    (plugin, service, id): TaskKey,
+    task: ScheduledTask,
+) -> AbortHandle {
+    let api = api.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(task.delay.as_millis())).await;
+        loop {
+            let message = ManagerMessage::RunTask {
+                plugin: plugin.clone(),
+                service: service.clone(),
+                id,
+            };
+            if ReturnMessage::build(message).get(&api).await.is_err() {
+                break;
+            }
+            let Some(period) = task.period else {
+                break;
+            };
+            tokio::time::sleep(Duration::from_millis(period.as_millis().max(MIN_PERIOD))).await;
+        }
+    })
+    .abort_handle()
+}
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1}MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{}KB", bytes / 1024)
+    }
+}
+
+impl std::fmt::Debug for PluginManagerActor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginManagerActor")
+            .field("config", &self.config)
+            .field("plugins", &self.plugins)
+            .finish()
+    }
+}
+
+impl PluginManagerActor {
+    pub fn new(
+        config: PluginConfig,
+        chat_api: MailboxSender<ReturnMessage<qexed_chat::message::ManagerMessage>>,
+        player_list_api: UnboundedSender<ReturnMessage<qexed_player_list::Message>>,
+        events: qexed_event::EventBus,
+        hooks: qexed_event::PacketHooks,
+        command_api: UnboundedSender<ReturnMessage<ManagerCommand>>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            config,
+            runtime: WasmRuntime::new()?,
+            plugins: BTreeMap::new(),
+            chat_api,
+            player_list_api,
+            players: vec![],
+            events,
+            hooks,
+            command_api,
+            api: None,
+            commands: BTreeMap::new(),
+            timers: BTreeMap::new(),
+            stores: BTreeMap::new(),
+        })
+    }
+
+    /// 列出插件目录下所有含 plugin.toml 的子目录,目录不存在时创建
+    fn scan(&self) -> Vec<PathBuf> {
+        let root = Path::new(&self.config.dir);
+        if !root.exists() {
+            if let Err(e) = std::fs::create_dir_all(root) {
+                log::error!("[插件] 无法创建插件目录 {}: {}", root.display(), e);
+            }
+            return vec![];
+        }
+        let entries = match std::fs::read_dir(root) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::error!("[插件] 无法读取插件目录 {}: {}", root.display(), e);
+                return vec![];
+            }
+        };
+        let mut dirs: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.join(MANIFEST_FILE).is_file())
+            .collect();
+        dirs.sort();
+        dirs
+    }
+
+    /// 加载并启用插件目录下的全部插件
+    pub fn load_all(&mut self) {
+        for dir in self.scan() {
+            if let Err(e) = self.load_dir(&dir) {
+                log::error!("[插件] {:#}", e);
+            }
+        }
+        let enabled = self
+            .plugins
+            .values()
+            .filter(|p| p.status == PluginStatus::Enabled)
+            .count();
+        log::info!("[插件] 共 {} 个插件,已启用 {} 个", self.plugins.len(), enabled);
+    }
+
+    fn load_dir(&mut self, dir: &Path) -> anyhow::Result<()> {
+        let manifest = PluginManifest::read(dir)?;
+        if self.config.disabled.contains(&manifest.name) {
+            log::info!("[插件] {} 已在配置中禁用,跳过", manifest.name);
+            return Ok(());
+        }
+        if let Some(other) = self.plugins.get(&manifest.name) {
+            anyhow::bail!(
+                "插件名 {} 重复: {} 与 {}",
+                manifest.name,
+                other.dir.display(),
+                dir.display()
+            );
+        }
+        let limits = resource_limits(self.config.limits_of(&manifest.name));
+        // 存储不可用时插件照常加载,存储请求会返回错误
+        let storage = self
+            .open_storage(&manifest.name)
+            .inspect_err(|e| log::warn!("[插件] {} 的存储不可用: {:#}", manifest.name, e))
+            .ok();
+        let result = Plugin::load(&self.runtime, dir, &self.players, limits, storage).and_then(|mut plugin| {
+            plugin.enable()?;
+            Ok(plugin)
+        });
+        match result {
+            Ok(plugin) => {
+                log::info!(
+                    "[插件] {} v{} 已启用",
+                    plugin.manifest.name,
+                    plugin.manifest.version
+                );
+                self.plugins.insert(manifest.name.clone(), plugin);
+                Ok(())
+            }
+            Err(e) => {
+                let error = format!("{:#}", e);
+                self.plugins.insert(
+                    manifest.name.clone(),
+                    Plugin::failed(manifest, dir, error),
+                );
+                Err(e)
+            }
+        }
+    }
+
+    /// 停用插件后重新从磁盘读取并启用,也可用于加载新放入的插件
+    pub fn reload(&mut self, name: &str) -> anyhow::Result<()> {
+        // 新实例的任务编号会从头开始,旧计时器不能沿用
+        self.timers.retain(|(plugin, ..), timer| {
+            if plugin == name {
+                timer.handle.abort();
+            }
+            plugin != name
+        });
+        self.stores.remove(name);
+        let dir = match self.plugins.remove(name) {
+            Some(mut plugin) => {
+                if let Err(e) = plugin.disable() {
+                    log::warn!("[插件] {} 停用时出错: {:#}", name, e);
+                }
+                plugin.dir
+            }
+            None => self
+                .scan()
+                .into_iter()
+                .find(|dir| PluginManifest::read(dir).is_ok_and(|m| m.name == name))
+                .ok_or_else(|| anyhow::anyhow!("未找到插件 {}", name))?,
+        };
+        self.load_dir(&dir)
+    }
+
+    fn open_storage(&mut self, name: &str) -> anyhow::Result<Arc<dyn KvStore>> {
+        if let Some(store) = self.stores.get(name) {
+            return Ok(store.clone());
+        }
+        let quota = StorageQuota::from_limits(self.config.limits_of(name));
+        let store = storage::open(&self.config.storage, name, quota)?;
+        self.stores.insert(name.to_string(), store.clone());
+        Ok(store)
+    }
+
+    /// 将插件存储导出到存储目录的 export 子目录,返回文件位置与条目数
+    pub fn export_storage(&mut self, name: &str) -> anyhow::Result<(PathBuf, usize)> {
+        if !self.plugins.contains_key(name) {
+            anyhow::bail!("未找到插件 {}", name);
+        }
+        let entries = self.open_storage(name)?.export()?;
+        let count = entries.len();
+        let path = storage::export_path(&self.config.storage, name);
+        StoreFile {
+            version: 1,
+            entries,
+        }
+        .write(&path)?;
+        Ok((path, count))
+    }
+
+    /// 以导出文件替换插件存储的全部内容,运行中的插件立即可见
+    pub fn import_storage(&mut self, name: &str) -> anyhow::Result<(PathBuf, usize)> {
+        if !self.plugins.contains_key(name) {
+            anyhow::bail!("未找到插件 {}", name);
+        }
+        let path = storage::export_path(&self.config.storage, name);
+        let entries = StoreFile::read(&path)?.entries;
+        let count = entries.len();
+        self.open_storage(name)?.import(entries)?;
+        Ok((path, count))
+    }
+
+    pub fn disable_all(&mut self) {
+        for plugin in self.plugins.values_mut() {
+            if let Err(e) = plugin.disable() {
+                log::warn!("[插件] {} 停用时出错: {:#}", plugin.name(), e);
+            }
+        }
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.plugins.keys().cloned().collect()
+    }
+
+    /// 从玩家列表服务刷新在线玩家快照
+    pub async fn refresh_players(&mut self) -> anyhow::Result<()> {
+        let players = match ReturnMessage::build(qexed_player_list::Message::GetPlayers(vec![]))
+            .get(&self.player_list_api)
+            .await?
+        {
+            qexed_player_list::Message::GetPlayers(players) => players,
+            _ => vec![],
+        };
+        self.players = players
+            .into_iter()
+            .map(|(uuid, name)| PlayerInfo {
+                uuid: uuid.to_string(),
+                name,
+            })
+            .collect();
+        for plugin in self.plugins.values_mut() {
+            plugin.set_players(&self.players);
+        }
+        Ok(())
+    }
+
+    /// 按已启用插件的订阅重新注册事件监听器与数据包钩子
+    fn sync_listeners(&self, api: &MessageSender<ReturnMessage<ManagerMessage>>) {
+        for plugin in self.plugins.values() {
+            let owner = listener_owner(plugin.name());
+            self.events.unsubscribe_owner(&owner);
+            self.hooks.unregister_owner(&owner);
+            if plugin.status != PluginStatus::Enabled {
+                continue;
+            }
+            for module in plugin.modules() {
+                for (kind, priority) in module.subscriptions.iter().copied() {
+                    let api = api.clone();
+                    let name = plugin.name().to_string();
+                    let service = module.service.clone();
+                    self.events.subscribe(
+                        &owner,
+                        event::kind_from_abi(kind),
+                        event::priority_from_abi(priority),
+                        move |dispatch| {
+                            let api = api.clone();
+                            let message = ManagerMessage::Event {
+                                plugin: name.clone(),
+                                service: service.clone(),
+                                dispatch,
+                            };
+                            async move {
+                                match ReturnMessage::build(message).get(&api).await? {
+                                    ManagerMessage::Event { dispatch, .. } => Ok(dispatch),
+                                    _ => anyhow::bail!("插件管理返回了错误的消息"),
+                                }
+                            }
+                        },
+                    );
+                }
+                let Some(point) = event::hook_point(&module.service) else {
+                    continue;
+                };
+                for filter in module.packet_hooks.iter() {
+                    let api = api.clone();
+                    let name = plugin.name().to_string();
+                    let service = module.service.clone();
+                    let mode = filter.mode;
+                    self.hooks.register(
+                        &owner,
+                        event::hook_filter(point, filter),
+                        move |packet| {
+                            let api = api.clone();
+                            let message = ManagerMessage::Packet {
+                                plugin: name.clone(),
+                                service: service.clone(),
+                                mode,
+                                packet,
+                                action: qexed_event::packet::PacketAction::Pass,
+                            };
+                            async move {
+                                match ReturnMessage::build(message).get(&api).await? {
+                                    ManagerMessage::Packet { action, .. } => Ok(action),
+                                    _ => anyhow::bail!("插件管理返回了错误的消息"),
+                                }
+                            }
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// 监听玩家进出服以维护在线玩家快照
+    fn watch_players(&self, api: &MessageSender<ReturnMessage<ManagerMessage>>) {
+        for kind in [
+            qexed_event::EventKind::PlayerJoin,
+            qexed_event::EventKind::PlayerQuit,
+        ] {
+            let api = api.clone();
+            self.events.subscribe(
+                OWNER,
+                kind,
+                qexed_event::Priority::Monitor,
+                move |dispatch| {
+                    let api = api.clone();
+                    async move {
+                        if !dispatch.cancelled {
+                            ReturnMessage::build(ManagerMessage::PlayerEvent(
+                                dispatch.event.clone(),
+                            ))
+                            .get(&api)
+                            .await?;
+                        }
+                        Ok(dispatch)
+                    }
+                },
+            );
+        }
+    }
+
+    fn update_players(&mut self, event: &qexed_event::Event) {
+        let (uuid, name) = event.player();
+        let uuid = uuid.to_string();
+        self.players.retain(|p| p.uuid != uuid);
+        if let qexed_event::Event::PlayerJoin { .. } = event {
+            self.players.push(PlayerInfo {
+                uuid,
+                name: name.to_string(),
+            });
+        }
+        for plugin in self.plugins.values_mut() {
+            plugin.set_players(&self.players);
+        }
+    }
+
+    /// 插件实例崩溃时停用插件并注销其监听器与钩子,崩溃不会影响其他插件与服务
+    fn check_crash(&mut self, name: &str) {
+        let Some(plugin) = self.plugins.get_mut(name) else {
+            return;
+        };
+        if let Some(reason) = plugin.crashed() {
+            log::error!("[插件] {} 已崩溃并被停用: {}", name, reason);
+            plugin.isolate(reason);
+            let owner = listener_owner(name);
+            self.events.unsubscribe_owner(&owner);
+            self.hooks.unregister_owner(&owner);
+        }
+    }
+
+    fn describe_stats(&self, plugin: &Plugin) -> String {
+        let stats = plugin.stats();
+        let limits = self.config.limits_of(plugin.name());
+        format!(
+            "§f{} §7调用 {} 次 / CPU {:.1}ms / 燃料 {} / 内存 {} (峰值 {}, 上限 {}MB)",
+            plugin.name(),
+            stats.calls,
+            stats.cpu_time.as_secs_f64() * 1000.0,
+            stats.fuel,
+            format_bytes(stats.memory),
+            format_bytes(stats.peak_memory),
+            limits.max_memory_mb
+        )
+    }
+
+    /// 将事件交给插件处理,插件出错时事件保持原样
+    async fn dispatch_event(
+        &mut self,
+        name: &str,
+        service: &str,
+        dispatch: &mut qexed_event::Dispatch,
+    ) {
+        let Some(plugin) = self
+            .plugins
+            .get_mut(name)
+            .filter(|p| p.status == PluginStatus::Enabled)
+        else {
+            return;
+        };
+        match plugin.dispatch_event(service, event::to_abi(&dispatch.event)) {
+            Ok((cancelled, modified)) => {
+                event::apply_abi(&mut dispatch.event, modified);
+                dispatch.cancelled |= cancelled;
+            }
+            Err(e) => log::warn!("[插件] {} 处理事件 {:?} 失败: {:#}", name, dispatch.event.kind(), e),
+        }
+        self.check_crash(name);
+        self.flush().await;
+    }
+
+    /// 将数据包交给插件处理,插件出错时数据包按原样放行
+    async fn dispatch_packet(
+        &mut self,
+        name: &str,
+        service: &str,
+        mode: qexed_api::abi::PacketMode,
+        packet: &qexed_event::packet::Packet,
+    ) -> qexed_event::packet::PacketAction {
+        let Some(plugin) = self
+            .plugins
+            .get_mut(name)
+            .filter(|p| p.status == PluginStatus::Enabled)
+        else {
+            return qexed_event::packet::PacketAction::Pass;
+        };
+        let action = match plugin.dispatch_packet(service, mode, event::packet_to_abi(packet)) {
+            Ok(action) => event::action_from_abi(action),
+            Err(e) => {
+                log::warn!("[插件] {} 处理数据包 0x{:02X} 失败: {:#}", name, packet.id, e);
+                qexed_event::packet::PacketAction::Pass
+            }
+        };
+        self.check_crash(name);
+        self.flush().await;
+        action
+    }
+
+    /// 执行插件在调用期间提交的请求
+    pub async fn flush(&mut self) {
+        let requests: Vec<(String, HostCall)> = self
+            .plugins
+            .values_mut()
+            .flat_map(|plugin| {
+                let name = plugin.name().to_string();
+                plugin
+                    .take_requests()
+                    .into_iter()
+                    .map(move |request| (name.clone(), request))
+            })
+            .collect();
+        for (name, request) in requests {
+            if let Err(e) = self.perform(&name, request).await {
+                log::warn!("[插件] {} 的请求执行失败: {:#}", name, e);
+            }
+        }
+        self.sync_commands().await;
+        self.sync_tasks();
+    }
+
+    /// 按已启用插件提交的指令注册或注销指令,停用、重载与崩溃的插件的指令随之注销
+    async fn sync_commands(&mut self) {
+        let Some(api) = self.api.clone() else {
+            return;
+        };
+        let mut wanted: BTreeMap<String, PluginCommand> = BTreeMap::new();
+        for plugin in self
+            .plugins
+            .values()
+            .filter(|p| p.status == PluginStatus::Enabled)
+        {
+            for module in plugin.modules() {
+                for spec in module.commands.iter() {
+                    if let Some(other) = wanted.get(&spec.name) {
+                        log::warn!(
+                            "[插件] {} 的指令 /{} 与插件 {} 重名,已忽略",
+                            plugin.name(),
+                            spec.name,
+                            other.plugin
+                        );
+                        continue;
+                    }
+                    wanted.insert(
+                        spec.name.clone(),
+                        PluginCommand {
+                            plugin: plugin.name().to_string(),
+                            service: module.service.clone(),
+                            spec: spec.clone(),
+                            registered: false,
+                        },
+                    );
+                }
+            }
+        }
+        let stale: Vec<String> = self
+            .commands
+            .iter()
+            .filter(|(name, current)| {
+                wanted.get(*name).is_none_or(|c| {
+                    c.plugin != current.plugin || c.service != current.service || c.spec != current.spec
+                })
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in stale {
+            let Some(current) = self.commands.remove(&name) else {
+                continue;
+            };
+            if current.registered
+                && let Err(e) = qexed_command::register::unregister_command(&name, &self.command_api).await
+            {
+                log::warn!("[插件] {} 的指令 /{} 注销失败: {:#}", current.plugin, name, e);
+            }
+        }
+        for (name, mut wanted) in wanted {
+            if self.commands.contains_key(&name) {
+                continue;
+            }
+            match command::register_spec(&wanted.plugin, &wanted.spec, &self.command_api, api.clone()).await {
+                Ok(()) => wanted.registered = true,
+                Err(e) => log::warn!("[插件] {} 的指令 /{} 注册失败: {:#}", wanted.plugin, name, e),
+            }
+            self.commands.insert(name, wanted);
+        }
+    }
+
+    /// 按已启用插件的定时任务启动或取消计时器
+    fn sync_tasks(&mut self) {
+        let Some(api) = self.api.clone() else {
+            return;
+        };
+        let mut wanted: BTreeMap<TaskKey, ScheduledTask> = BTreeMap::new();
+        for plugin in self
+            .plugins
+            .values()
+            .filter(|p| p.status == PluginStatus::Enabled)
+        {
+            for module in plugin.modules() {
+                for (id, task) in module.tasks.iter() {
+                    wanted.insert((plugin.name().to_string(), module.service.clone(), *id), *task);
+                }
+            }
+        }
+        self.timers.retain(|key, timer| {
+            let keep = wanted.get(key) == Some(&timer.task);
+            if !keep {
+                timer.handle.abort();
+            }
+            keep
+        });
+        for (key, task) in wanted {
+            if let std::collections::btree_map::Entry::Vacant(entry) = self.timers.entry(key) {
+                let handle = spawn_timer(&api, entry.key().clone(), task);
+                entry.insert(Timer { task, handle });
+            }
+        }
+    }
+
+    /// 执行插件指令,插件的回复发给指令执行者
+    async fn dispatch_command(&mut self, cmd: &CommandData) -> anyhow::Result<()> {
+        let args = cmd.parse_args();
+        let Some(label) = args.first() else {
+            return Ok(());
+        };
+        let Some(command) = self.commands.values().find(|c| {
+            c.registered && (c.spec.name == *label || c.spec.aliases.contains(label))
+        }) else {
+            cmd.send_chat_message("§c该指令所属的插件已停用").await?;
+            return Ok(());
+        };
+        let name = command.plugin.clone();
+        let service = command.service.clone();
+        let sender = match (cmd.player_uuid, &cmd.player_name) {
+            (Some(uuid), Some(player)) if !cmd.is_cmd => Some(PlayerInfo {
+                uuid: uuid.to_string(),
+                name: player.clone(),
+            }),
+            _ => None,
+        };
+        let invocation = CommandInvocation {
+            name: command.spec.name.clone(),
+            args: args[1..].to_vec(),
+            sender,
+        };
+        let Some(plugin) = self
+            .plugins
+            .get_mut(&name)
+            .filter(|p| p.status == PluginStatus::Enabled)
+        else {
+            cmd.send_chat_message("§c该指令所属的插件已停用").await?;
+            return Ok(());
+        };
+        let result = plugin.dispatch_command(&service, invocation);
+        self.check_crash(&name);
+        if let Err(e) = result {
+            log::warn!("[插件] {} 执行指令 {} 失败: {:#}", name, cmd.command_line, e);
+            cmd.send_chat_message("§c执行指令时插件出错").await?;
+        }
+        // 回复只发给本次指令的执行者,其余请求照常执行
+        let requests = self
+            .plugins
+            .get_mut(&name)
+            .map(|plugin| plugin.take_requests())
+            .unwrap_or_default();
+        for request in requests {
+            match request {
+                HostCall::Reply(message) => cmd.send_chat_message(&message).await?,
+                request => {
+                    if let Err(e) = self.perform(&name, request).await {
+                        log::warn!("[插件] {} 的请求执行失败: {:#}", name, e);
+                    }
+                }
+            }
+        }
+        self.flush().await;
+        Ok(())
+    }
+
+    /// 执行到期的定时任务
+    async fn run_task(&mut self, name: &str, service: &str, id: u32) {
+        let Some(plugin) = self
+            .plugins
+            .get_mut(name)
+            .filter(|p| p.status == PluginStatus::Enabled)
+        else {
+            return;
+        };
+        if let Err(e) = plugin.run_task(service, id) {
+            log::warn!("[插件] {} 的定时任务 {} 执行失败: {:#}", name, id, e);
+        }
+        self.check_crash(name);
+        self.flush().await;
+    }
+
+    async fn perform(&self, name: &str, request: HostCall) -> anyhow::Result<()> {
+        match request {
+            HostCall::Broadcast(message) => {
+                ReturnMessage::build(qexed_chat::message::ManagerMessage::BroadCastEvent(
+                    uuid::Uuid::nil(),
+                    SystemChat {
+                        content: qexed_chat::format::text_component(message),
+                        overlay: false,
+                    },
+                ))
+                .get(&self.chat_api)
+                .await?;
+            }
+            HostCall::SendMessage { player, message } => {
+                let uuid = uuid::Uuid::parse_str(&player)?;
+                ReturnMessage::build(qexed_chat::message::ManagerMessage::SendTo(
+                    uuid,
+                    qexed_chat::format::text_component(message),
+                    false,
+                ))
+                .get(&self.chat_api)
+                .await?;
+            }
+            HostCall::SendPacket {
+                player,
+                direction,
+                data,
+            } => {
+                let uuid = uuid::Uuid::parse_str(&player)?;
+                self.hooks
+                    .inject(uuid, event::direction_from_abi(direction), data)?;
+            }
+            // 不在指令回调中时输出到控制台
+            HostCall::Reply(message) => log::info!("[插件] [{}] {}", name, message),
+            request => log::debug!("[插件] {} 的请求 {:?} 无需处理", name, request),
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TaskEasyEvent<ReturnMessage<ManagerMessage>> for PluginManagerActor {
+    async fn event(
+        &mut self,
+        api: &MessageSender<ReturnMessage<ManagerMessage>>,
+        mut data: ReturnMessage<ManagerMessage>,
+    ) -> anyhow::Result<bool> {
+        let send = match data.get_return_send().await? {
+            Some(send) => send,
+            None => return Ok(false),
+        };
+        match data.data {
+            ManagerMessage::CommandPlugins(ref cmd) => {
+                if self.plugins.is_empty() {
+                    cmd.send_chat_message("§7没有已安装的插件").await?;
+                } else {
+                    let list: Vec<String> = self
+                        .plugins
+                        .values()
+                        .map(|p| match p.status {
+                            PluginStatus::Enabled => format!("§a{}", p.name()),
+                            PluginStatus::Failed(_) => format!("§c{}", p.name()),
+                            _ => format!("§7{}", p.name()),
+                        })
+                        .collect();
+                    cmd.send_chat_message(&format!(
+                        "§6插件 ({}): {}",
+                        self.plugins.len(),
+                        list.join("§f, ")
+                    ))
+                    .await?;
+                    for plugin in self.plugins.values() {
+                        let summary = match plugin.status {
+                            PluginStatus::Enabled => format!(" §7({})", plugin.summary()),
+                            _ => String::new(),
+                        };
+                        cmd.send_chat_message(&format!(
+                            "§f{} §7v{} §r{}{}",
+                            plugin.name(),
+                            plugin.manifest.version,
+                            plugin.status.describe(),
+                            summary
+                        ))
+                        .await?;
+                    }
+                }
+            }
+            ManagerMessage::CommandPlugin(ref cmd) => {
+                let args = cmd.parse_args();
+                match (args.get(1).map(|s| s.as_str()), args.get(2)) {
+                    (Some("reload"), Some(name)) => {
+                        if !cmd.is_cmd {
+                            cmd.send_chat_message("§c该指令只能在控制台使用").await?;
+                        } else {
+                            if let Err(e) = self.refresh_players().await {
+                                log::warn!("[插件] 无法获取在线玩家: {:#}", e);
+                            }
+                            let result = self.reload(name);
+                            self.flush().await;
+                            self.sync_listeners(api);
+                            match result {
+                                Ok(()) => {
+                                    cmd.send_chat_message(&format!("§a插件 {} 已重载", name))
+                                        .await?
+                                }
+                                Err(e) => {
+                                    cmd.send_chat_message(&format!(
+                                        "§c插件 {} 重载失败: {:#}",
+                                        name, e
+                                    ))
+                                    .await?
+                                }
+                            }
+                        }
+                    }
+                    (Some("stats"), Some(name)) => match self.plugins.get(name.as_str()) {
+                        Some(plugin) => {
+                            cmd.send_chat_message(&self.describe_stats(plugin)).await?;
+                        }
+                        None => {
+                            cmd.send_chat_message(&format!("§c未找到插件 {}", name)).await?;
+                        }
+                    },
+                    (Some("stats"), None) => {
+                        let enabled: Vec<&Plugin> = self
+                            .plugins
+                            .values()
+                            .filter(|p| p.status == PluginStatus::Enabled)
+                            .collect();
+                        if enabled.is_empty() {
+                            cmd.send_chat_message("§7没有已启用的插件").await?;
+                        }
+                        for plugin in enabled {
+                            cmd.send_chat_message(&self.describe_stats(plugin)).await?;
+                        }
+                    }
+                    (Some("storage"), Some(action)) if cmd.is_cmd => {
+                        let result = match (action.as_str(), args.get(3)) {
+                            ("export", Some(name)) => self
+                                .export_storage(name)
+                                .map(|(path, count)| format!("§a已导出 {} 条数据到 {}", count, path.display())),
+                            ("import", Some(name)) => self
+                                .import_storage(name)
+                                .map(|(path, count)| format!("§a已从 {} 导入 {} 条数据", path.display(), count)),
+                            _ => Ok("§c用法: /plugin storage export|import <插件名>".to_string()),
+                        };
+                        match result {
+                            Ok(message) => cmd.send_chat_message(&message).await?,
+                            Err(e) => cmd.send_chat_message(&format!("§c{:#}", e)).await?,
+                        }
+                    }
+                    (Some("storage"), Some(_)) => {
+                        cmd.send_chat_message("§c该指令只能在控制台使用").await?;
+                    }
+                    _ => {
+                        cmd.send_chat_message(
+                            "§c用法: /plugin reload <插件名> | /plugin stats [插件名] | /plugin storage export|import <插件名>",
+                        )
+                        .await?;
+                    }
+                }
+            }
+            ManagerMessage::GetPluginNames(ref mut names) => {
+                *names = self.names();
+            }
+            ManagerMessage::PluginCommand(ref cmd) => self.dispatch_command(cmd).await?,
+            ManagerMessage::RunTask {
+                ref plugin,
+                ref service,
+                id,
+            } => self.run_task(plugin, service, id).await,
+            ManagerMessage::Start => {
+                self.api = Some(api.clone());
+                self.flush().await;
+                self.sync_listeners(api);
+                self.watch_players(api);
+            }
+            ManagerMessage::Event {
+                ref plugin,
+                ref service,
+                ref mut dispatch,
+            } => {
+                self.dispatch_event(plugin, service, dispatch).await;
+            }
+            ManagerMessage::Packet {
+                ref plugin,
+                ref service,
+                mode,
+                ref packet,
+                ref mut action,
+            } => {
+                *action = self.dispatch_packet(plugin, service, mode, packet).await;
+            }
+            ManagerMessage::PlayerEvent(ref event) => self.update_players(event),
+            ManagerMessage::Close => {
+                self.disable_all();
+                self.flush().await;
+                for name in self.names() {
+                    self.events.unsubscribe_owner(&listener_owner(&name));
+                    self.hooks.unregister_owner(&listener_owner(&name));
+                }
+                self.events.unsubscribe_owner(OWNER);
+                let _ = send.send(data.data);
+                return Ok(true);
+            }
+        }
+        let _ = send.send(data.data);
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::PERMISSION_FILE;
+
+    #[test]
+    fn load_and_reload() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let dir = root.join("demo");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(MANIFEST_FILE),
+            "name = \"demo\"\nversion = \"1.0.0\"\nmain = \"demo.wat\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join(PERMISSION_FILE), "[modern.chat.permission]\n").unwrap();
+        std::fs::write(dir.join("demo.wat"), "(module (func (export \"on_enable\")))").unwrap();
+        let mut config = PluginConfig {
+            dir: root.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        config.storage.dir = root.join("data").to_string_lossy().to_string();
+        let mut manager = PluginManagerActor::new(
+            config,
+            qexed_task::mailbox::channel(qexed_task::mailbox::MailboxConfig::manager()).0,
+            tokio::sync::mpsc::unbounded_channel().0,
+            qexed_event::EventBus::with_timeout(std::time::Duration::from_millis(100)),
+            qexed_event::PacketHooks::with_timeout(std::time::Duration::from_millis(20)),
+            tokio::sync::mpsc::unbounded_channel().0,
+        )
+        .unwrap();
+        manager.load_all();
+        assert_eq!(manager.plugins["demo"].status, PluginStatus::Enabled);
+
+        // 损坏后重载失败,修复后可以再次重载
+        std::fs::write(dir.join("demo.wat"), "(module (func (export \"on_enable\") (result i32) (i32.const 1)))").unwrap();
+        assert!(manager.reload("demo").is_err());
+        assert!(matches!(manager.plugins["demo"].status, PluginStatus::Failed(_)));
+        std::fs::write(dir.join("demo.wat"), "(module)").unwrap();
+        manager.reload("demo").unwrap();
+        assert_eq!(manager.plugins["demo"].status, PluginStatus::Enabled);
+        assert!(manager.reload("missing").is_err());
+
+        manager.stores["demo"].set("key", Some(b"value".to_vec())).unwrap();
+        assert_eq!(manager.export_storage("demo").unwrap().1, 1);
+        manager.stores["demo"].set("key", None).unwrap();
+        manager.import_storage("demo").unwrap();
+        assert_eq!(manager.stores["demo"].get("key").unwrap(), Some(b"value".to_vec()));
+    }
+}
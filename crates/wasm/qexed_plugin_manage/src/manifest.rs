@@ -0,0 +1,191 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{Context, bail};
+use qexed_wasm_runtime::{
+    Capability, SDK_VERSION,
+    capability::{SERVICES, service_allows},
+};
+use serde::{Deserialize, Serialize};
+
+pub const MANIFEST_FILE: &str = "plugin.toml";
+pub const PERMISSION_FILE: &str = "permission.toml";
+
+/// 插件描述文件 plugin.toml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// 入口 wasm 文件,相对插件目录
+    pub main: String,
+}
+
+/// 插件权限文件 permission.toml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginPermission {
+    #[serde(default)]
+    pub version: i32,
+    #[serde(rename = "sdk-version", default)]
+    pub sdk_version: i32,
+    /// 按微服务划分的配置,如 modern.tcp_connect
+    #[serde(default)]
+    pub modern: BTreeMap<String, ModernConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModernConfig {
+    #[serde(default)]
+    pub permission: ModulePermission,
+}
+
+/// 单个微服务中授予插件的权限
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModulePermission {
+    pub log: bool,
+    pub packet_listen: bool,
+    pub packet_replace: bool,
+    pub packet_write: bool,
+    pub packet_interception: bool,
+    pub redis: bool,
+    pub mongodb: bool,
+    pub mongodb_ip: Option<String>,
+    pub mongodb_user: Option<String>,
+    pub mongodb_password: Option<String>,
+    pub mongodb_table: Option<String>,
+    pub mongodb_read: bool,
+    pub mongodb_write: bool,
+    pub mysql: bool,
+}
+
+impl ModulePermission {
+    /// 是否在权限文件中写了数据库凭据;插件应改用宿主提供的键值存储
+    pub fn has_credentials(&self) -> bool {
+        self.mongodb_ip.is_some() || self.mongodb_user.is_some() || self.mongodb_password.is_some()
+    }
+
+    pub fn capabilities(&self) -> Vec<Capability> {
+        [
+            (Capability::Log, self.log),
+            (Capability::PacketListen, self.packet_listen),
+            (Capability::PacketReplace, self.packet_replace),
+            (Capability::PacketWrite, self.packet_write),
+            (Capability::PacketInterception, self.packet_interception),
+            (Capability::Redis, self.redis),
+            (Capability::Mongodb, self.mongodb),
+            (Capability::Mysql, self.mysql),
+        ]
+        .into_iter()
+        .filter_map(|(capability, granted)| granted.then_some(capability))
+        .collect()
+    }
+}
+
+impl PluginManifest {
+    pub fn read(dir: &Path) -> anyhow::Result<Self> {
+        read_toml(&dir.join(MANIFEST_FILE))
+    }
+}
+
+impl PluginPermission {
+    /// 缺少权限文件时不授予任何权限
+    pub fn read(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(PERMISSION_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let permission: Self = read_toml(&path)?;
+        permission
+            .validate()
+            .with_context(|| format!("{} 校验失败", path.display()))?;
+        Ok(permission)
+    }
+
+    /// 按 sdk-version 校验声明的微服务与权限
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.sdk_version > SDK_VERSION {
+            bail!(
+                "插件需要 SDK 版本 {},当前引擎仅支持到 {}",
+                self.sdk_version,
+                SDK_VERSION
+            );
+        }
+        if self.sdk_version < 0 {
+            bail!("无效的 sdk-version: {}", self.sdk_version);
+        }
+        for (service, config) in &self.modern {
+            if !SERVICES.contains(&service.as_str()) {
+                bail!(
+                    "未知的模块 modern.{},可用模块: {}",
+                    service,
+                    SERVICES.join(", ")
+                );
+            }
+            for capability in config.permission.capabilities() {
+                if capability.since() > self.sdk_version {
+                    bail!(
+                        "modern.{} 的 {} 权限需要 sdk-version >= {}",
+                        service,
+                        capability,
+                        capability.since()
+                    );
+                }
+                if !service_allows(service, capability) {
+                    bail!("modern.{} 模块不允许使用 {} 权限", service, capability);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 插件在各微服务中被授予的能力
+    pub fn services(&self) -> Vec<(String, Vec<Capability>)> {
+        self.modern
+            .iter()
+            .map(|(service, config)| (service.clone(), config.permission.capabilities()))
+            .collect()
+    }
+}
+
+fn read_toml<T: serde::de::DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("无法读取 {}", path.display()))?;
+    toml::from_str(&text).with_context(|| format!("{} 格式错误", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_permission() {
+        let permission: PluginPermission = toml::from_str(
+            "sdk-version = 0\n[modern.chat.permission]\nlog = true\nmongodb = true\n",
+        )
+        .unwrap();
+        permission.validate().unwrap();
+        assert_eq!(
+            permission.services(),
+            vec![(
+                "chat".to_string(),
+                vec![Capability::Log, Capability::Mongodb]
+            )]
+        );
+
+        let typo = toml::from_str::<PluginPermission>("[modern.chat.permission]\nreids = true\n");
+        assert!(typo.is_err());
+
+        let network: PluginPermission =
+            toml::from_str("[modern.tcp_connect.permission]\nredis = true\n").unwrap();
+        assert!(network.validate().is_err());
+
+        let future: PluginPermission = toml::from_str("sdk-version = 99\n").unwrap();
+        assert!(future.validate().is_err());
+
+        let unknown: PluginPermission = toml::from_str("[modern.world.permission]\n").unwrap();
+        assert!(unknown.validate().is_err());
+    }
+}
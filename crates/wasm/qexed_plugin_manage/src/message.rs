@@ -0,0 +1,30 @@
+use qexed_command::message::CommandData;
+use qexed_event::Dispatch;
+
+#[derive(Debug)]
+pub enum ManagerMessage {
+    CommandPlugins(CommandData), // 指令事件 /plugins
+    CommandPlugin(CommandData),  // 指令事件 /plugin reload <name>, /plugin stats [name], /plugin storage export|import <name>
+    GetPluginNames(Vec<String>), // 获取插件名(指令补全)
+    PluginCommand(CommandData),  // 插件注册的指令被执行
+    Start,                       // 服务启动完成,执行插件请求并注册事件监听、指令与定时任务
+    Event {
+        plugin: String,
+        service: String,
+        dispatch: Dispatch,
+    }, // 将事件投递给插件,返回插件处理后的结果
+    Packet {
+        plugin: String,
+        service: String,
+        mode: qexed_api::abi::PacketMode,
+        packet: qexed_event::packet::Packet,
+        action: qexed_event::packet::PacketAction,
+    }, // 将数据包交给插件的钩子,返回处理结果
+    RunTask {
+        plugin: String,
+        service: String,
+        id: u32,
+    }, // 插件的定时任务到期
+    PlayerEvent(qexed_event::Event), // 玩家进出服,更新在线玩家快照
+    Close,                       // 停用所有插件
+}
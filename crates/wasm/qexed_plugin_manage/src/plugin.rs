@@ -0,0 +1,371 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, bail};
+use qexed_api::abi::{
+    self, CommandInvocation, CommandSpec, EventKind, GuestCall, GuestReply, HostCall,
+    PacketAction, PacketFilter, PacketMode, PlayerInfo, Priority, TaskTime,
+};
+use qexed_wasm_runtime::{
+    Capability, KvStore, PluginInstance, PluginStats, ResourceLimits, WasmRuntime,
+};
+
+use crate::manifest::{PluginManifest, PluginPermission};
+
+/// 插件状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginStatus {
+    /// 已调用 on_load,尚未启用
+    Loaded,
+    Enabled,
+    Disabled,
+    /// 加载或生命周期调用失败
+    Failed(String),
+}
+
+impl PluginStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            PluginStatus::Loaded => "§e已加载".to_string(),
+            PluginStatus::Enabled => "§a已启用".to_string(),
+            PluginStatus::Disabled => "§7已停用".to_string(),
+            PluginStatus::Failed(e) => format!("§c失败: {}", e),
+        }
+    }
+}
+
+/// 插件在某个微服务中的实例,以及该实例注册的指令、事件、定时任务与数据包钩子
+#[derive(Debug)]
+pub struct PluginModule {
+    pub service: String,
+    pub instance: PluginInstance,
+    pub commands: Vec<CommandSpec>,
+    pub subscriptions: Vec<(EventKind, Priority)>,
+    pub tasks: BTreeMap<u32, ScheduledTask>,
+    pub packet_hooks: Vec<PacketFilter>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledTask {
+    pub delay: TaskTime,
+    /// 为空表示只执行一次
+    pub period: Option<TaskTime>,
+}
+
+impl PluginModule {
+    fn new(service: String, instance: PluginInstance) -> Self {
+        Self {
+            service,
+            instance,
+            commands: vec![],
+            subscriptions: vec![],
+            tasks: BTreeMap::new(),
+            packet_hooks: vec![],
+        }
+    }
+
+    /// 记录注册类请求,其余请求返回给调用者处理
+    fn take_requests(&mut self, pending: &mut Vec<HostCall>) {
+        for request in self.instance.take_outbox() {
+            match request {
+                HostCall::RegisterCommand(spec) => {
+                    self.commands.retain(|c| c.name != spec.name);
+                    self.commands.push(spec);
+                }
+                HostCall::Subscribe { kind, priority } => {
+                    self.subscriptions.retain(|(k, _)| *k != kind);
+                    self.subscriptions.push((kind, priority));
+                }
+                HostCall::Schedule { id, delay, period } => {
+                    self.tasks.insert(id, ScheduledTask { delay, period });
+                }
+                HostCall::CancelTask(id) => {
+                    self.tasks.remove(&id);
+                }
+                HostCall::HookPacket(filter) => {
+                    if !self.packet_hooks.contains(&filter) {
+                        self.packet_hooks.push(filter);
+                    }
+                }
+                request => pending.push(request),
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Plugin {
+    pub manifest: PluginManifest,
+    pub permission: PluginPermission,
+    pub dir: PathBuf,
+    pub status: PluginStatus,
+    // 每个声明的微服务各一个实例,互不共享内存
+    modules: Vec<PluginModule>,
+    // 已停用的实例留下的未处理请求(如停用时的广播)
+    pending: Vec<HostCall>,
+}
+
+impl Plugin {
+    /// 读取插件目录,为 permission.toml 中声明的每个微服务实例化一份,随后调用 on_load
+    /// 各实例共用同一个键值存储
+    pub fn load(
+        runtime: &WasmRuntime,
+        dir: &Path,
+        players: &[PlayerInfo],
+        limits: ResourceLimits,
+        storage: Option<Arc<dyn KvStore>>,
+    ) -> anyhow::Result<Self> {
+        let manifest = PluginManifest::read(dir)?;
+        let permission = PluginPermission::read(dir)?;
+        let services = permission.services();
+        if services.is_empty() {
+            bail!(
+                "插件 {} 的 permission.toml 未声明任何模块([modern.<模块>.permission])",
+                manifest.name
+            );
+        }
+        let module = runtime
+            .compile_file(&dir.join(&manifest.main))
+            .with_context(|| format!("插件 {} 加载失败", manifest.name))?;
+        let mut modules = Vec::with_capacity(services.len());
+        if permission.modern.values().any(|m| m.permission.has_credentials()) {
+            log::warn!(
+                "[插件] {} 的 permission.toml 中包含数据库凭据,建议改用插件存储(qexed_api::storage)",
+                manifest.name
+            );
+        }
+        for (service, capabilities) in services {
+            for capability in capabilities.iter().filter(|c| c.is_high_risk()) {
+                log::warn!(
+                    "[插件] {} 在 {} 模块中申请了高风险权限 {}",
+                    manifest.name,
+                    service,
+                    capability
+                );
+            }
+            let mut instance =
+                runtime.instantiate(&manifest.name, &service, &capabilities, limits, &module)?;
+            instance.set_players(players.to_vec());
+            if let Some(storage) = &storage {
+                instance.set_storage(storage.clone());
+            }
+            modules.push(PluginModule::new(service, instance));
+        }
+        for module in modules.iter_mut() {
+            module
+                .instance
+                .call_lifecycle("on_load")
+                .with_context(|| format!("{} 模块", module.service))?;
+        }
+        Ok(Self {
+            manifest,
+            permission,
+            dir: dir.to_path_buf(),
+            status: PluginStatus::Loaded,
+            modules,
+            pending: vec![],
+        })
+    }
+
+    /// 加载失败的插件,保留描述信息以便查看与重载
+    pub fn failed(manifest: PluginManifest, dir: &Path, error: String) -> Self {
+        Self {
+            manifest,
+            permission: PluginPermission::default(),
+            dir: dir.to_path_buf(),
+            status: PluginStatus::Failed(error),
+            modules: vec![],
+            pending: vec![],
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    pub fn enable(&mut self) -> anyhow::Result<()> {
+        if self.status == PluginStatus::Enabled {
+            return Ok(());
+        }
+        if self.modules.is_empty() {
+            bail!("插件未加载");
+        }
+        let result = self.modules.iter_mut().try_for_each(|module| {
+            module
+                .instance
+                .call_lifecycle("on_enable")
+                .with_context(|| format!("{} 模块", module.service))
+        });
+        match result {
+            Ok(()) => {
+                self.status = PluginStatus::Enabled;
+                Ok(())
+            }
+            Err(e) => {
+                self.status = PluginStatus::Failed(e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    /// 调用 on_disable 并释放实例,失败时同样释放
+    /// 实例注册的指令、事件与定时任务随实例一起移除
+    pub fn disable(&mut self) -> anyhow::Result<()> {
+        if self.modules.is_empty() {
+            return Ok(());
+        }
+        self.status = PluginStatus::Disabled;
+        let mut result = Ok(());
+        for mut module in self.modules.drain(..) {
+            // 已崩溃的实例不再执行插件代码
+            if module.instance.crashed().is_some() {
+                continue;
+            }
+            if let Err(e) = module.instance.call_lifecycle("on_disable") {
+                result = Err(e.context(format!("{} 模块", module.service)));
+            }
+            let mut pending = vec![];
+            module.take_requests(&mut pending);
+            self.pending.extend(pending);
+        }
+        result
+    }
+
+    /// 任一实例崩溃时返回崩溃原因
+    pub fn crashed(&self) -> Option<String> {
+        self.modules.iter().find_map(|module| {
+            module
+                .instance
+                .crashed()
+                .map(|reason| format!("{} 模块: {}", module.service, reason))
+        })
+    }
+
+    /// 停用崩溃的插件,不再调用 on_disable,未处理的请求一并丢弃
+    pub fn isolate(&mut self, reason: String) {
+        self.modules.clear();
+        self.pending.clear();
+        self.status = PluginStatus::Failed(format!("已崩溃: {}", reason));
+    }
+
+    /// 各实例统计之和
+    pub fn stats(&self) -> PluginStats {
+        self.modules
+            .iter()
+            .map(|module| module.instance.stats())
+            .fold(PluginStats::default(), |total, stats| total + stats)
+    }
+
+    pub fn modules(&self) -> &[PluginModule] {
+        &self.modules
+    }
+
+    pub fn set_players(&mut self, players: &[PlayerInfo]) {
+        for module in self.modules.iter_mut() {
+            module.instance.set_players(players.to_vec());
+        }
+    }
+
+    /// 收集各实例提交的请求,注册类请求记录在实例上,返回需要其他服务执行的请求
+    pub fn take_requests(&mut self) -> Vec<HostCall> {
+        let mut pending = std::mem::take(&mut self.pending);
+        for module in self.modules.iter_mut() {
+            module.take_requests(&mut pending);
+        }
+        pending
+    }
+
+    fn module_mut(&mut self, service: &str) -> anyhow::Result<&mut PluginModule> {
+        self.modules
+            .iter_mut()
+            .find(|m| m.service == service)
+            .ok_or_else(|| anyhow::anyhow!("插件未在 {} 模块中运行", service))
+    }
+
+    /// 将指令交给注册该指令的实例执行
+    pub fn dispatch_command(
+        &mut self,
+        service: &str,
+        invocation: CommandInvocation,
+    ) -> anyhow::Result<()> {
+        let module = self.module_mut(service)?;
+        match module.instance.dispatch(&GuestCall::Command(invocation))? {
+            GuestReply::Ok => Ok(()),
+            GuestReply::Error(e) => bail!("{}", e),
+            reply => bail!("插件返回了意外的应答 {:?}", reply),
+        }
+    }
+
+    /// 执行到期的定时任务,一次性任务执行前即移除;已取消的任务直接跳过
+    pub fn run_task(&mut self, service: &str, id: u32) -> anyhow::Result<()> {
+        let module = self.module_mut(service)?;
+        match module.tasks.get(&id) {
+            Some(task) if task.period.is_none() => {
+                module.tasks.remove(&id);
+            }
+            Some(_) => {}
+            None => return Ok(()),
+        }
+        match module.instance.dispatch(&GuestCall::Task(id))? {
+            GuestReply::Ok => Ok(()),
+            GuestReply::Error(e) => bail!("{}", e),
+            reply => bail!("插件返回了意外的应答 {:?}", reply),
+        }
+    }
+
+    /// 将事件投递给指定模块的实例,返回是否取消以及修改后的事件
+    pub fn dispatch_event(
+        &mut self,
+        service: &str,
+        event: abi::Event,
+    ) -> anyhow::Result<(bool, abi::Event)> {
+        let module = self.module_mut(service)?;
+        match module.instance.dispatch(&GuestCall::Event(event.clone()))? {
+            GuestReply::Event { cancelled, event } => Ok((cancelled, event)),
+            GuestReply::Ok => Ok((false, event)),
+            GuestReply::Error(e) => bail!("{}", e),
+            reply => bail!("插件返回了意外的应答 {:?}", reply),
+        }
+    }
+
+    /// 将数据包交给指定模块的实例,返回值按实例被授予的权限过滤
+    pub fn dispatch_packet(
+        &mut self,
+        service: &str,
+        mode: PacketMode,
+        packet: abi::Packet,
+    ) -> anyhow::Result<PacketAction> {
+        let module = self.module_mut(service)?;
+        let action = match module.instance.dispatch(&GuestCall::Packet(packet))? {
+            GuestReply::Packet(action) => action,
+            GuestReply::Ok => PacketAction::Pass,
+            GuestReply::Error(e) => bail!("{}", e),
+            reply => bail!("插件返回了意外的应答 {:?}", reply),
+        };
+        let required = match action {
+            PacketAction::Pass => return Ok(PacketAction::Pass),
+            _ if mode == PacketMode::Listen => return Ok(PacketAction::Pass),
+            PacketAction::Replace(_) => Capability::PacketReplace,
+            PacketAction::Drop => Capability::PacketInterception,
+        };
+        if !module.instance.state().capabilities.contains(&required) {
+            bail!("未授予 {} 权限,已放行数据包", required);
+        }
+        Ok(action)
+    }
+
+    /// 注册内容概要,用于 /plugins
+    pub fn summary(&self) -> String {
+        let commands: usize = self.modules.iter().map(|m| m.commands.len()).sum();
+        let events: usize = self.modules.iter().map(|m| m.subscriptions.len()).sum();
+        let tasks: usize = self.modules.iter().map(|m| m.tasks.len()).sum();
+        let hooks: usize = self.modules.iter().map(|m| m.packet_hooks.len()).sum();
+        format!(
+            "指令 {} / 事件 {} / 任务 {} / 数据包钩子 {}",
+            commands, events, tasks, hooks
+        )
+    }
+}
@@ -0,0 +1,250 @@
+//! 插件键值存储的实现
+//!
+//! Simple 引擎把每个插件的数据保存为 `<dir>/<插件名>.json`,每次修改后整体写入临时文件再替换,
+//! 进程中途退出不会留下半份数据。导出文件与存储文件格式相同,可在不同引擎之间迁移。
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, bail};
+use qexed_config::{
+    app::qexed_plugin::{PluginLimits, PluginStorage},
+    public::storage_engine::StorageEngine,
+};
+use qexed_wasm_runtime::KvStore;
+use serde::{Deserialize, Serialize};
+
+/// 键的最大长度(字节)
+pub const MAX_KEY_LEN: usize = 256;
+
+/// 存储配额,0 表示不限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageQuota {
+    pub max_bytes: usize,
+    pub max_keys: usize,
+}
+
+impl StorageQuota {
+    pub fn from_limits(limits: &PluginLimits) -> Self {
+        Self {
+            max_bytes: (limits.storage_quota_kb as usize).saturating_mul(1024),
+            max_keys: limits.storage_max_keys as usize,
+        }
+    }
+
+    fn check(&self, entries: &BTreeMap<String, Vec<u8>>) -> anyhow::Result<()> {
+        if self.max_keys != 0 && entries.len() > self.max_keys {
+            bail!("存储配额已满: 最多 {} 个键", self.max_keys);
+        }
+        let used: usize = entries.iter().map(|(k, v)| k.len() + v.len()).sum();
+        if self.max_bytes != 0 && used > self.max_bytes {
+            bail!("存储配额已满: 最多 {}KB", self.max_bytes / 1024);
+        }
+        Ok(())
+    }
+}
+
+/// 存储文件与导出文件的格式
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StoreFile {
+    pub version: i32,
+    pub entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl StoreFile {
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("无法读取 {}", path.display()))?;
+        serde_json::from_slice(&bytes).with_context(|| format!("{} 格式错误", path.display()))
+    }
+
+    /// 先写入临时文件再替换,避免留下不完整的文件
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建目录 {}", parent.display()))?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self)?)
+            .with_context(|| format!("无法写入 {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("无法写入 {}", path.display()))
+    }
+}
+
+/// Simple 引擎:内存中保留全部条目,修改时同步写入磁盘
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    quota: StorageQuota,
+    entries: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl FileStore {
+    pub fn open(path: PathBuf, quota: StorageQuota) -> anyhow::Result<Self> {
+        let entries = if path.exists() {
+            StoreFile::read(&path)?.entries
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self {
+            path,
+            quota,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, Vec<u8>>> {
+        // 持锁期间不会执行插件代码,锁中毒时数据仍然完整
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 修改单个键,超出配额或写盘失败时恢复原值
+    fn write(
+        &self,
+        entries: &mut BTreeMap<String, Vec<u8>>,
+        key: &str,
+        value: Option<Vec<u8>>,
+    ) -> anyhow::Result<()> {
+        if key.is_empty() || key.len() > MAX_KEY_LEN {
+            bail!("键的长度必须在 1 到 {} 字节之间", MAX_KEY_LEN);
+        }
+        let grows = value.is_some();
+        let old = match value {
+            Some(value) => entries.insert(key.to_string(), value),
+            None => entries.remove(key),
+        };
+        let result = if grows {
+            self.quota.check(entries)
+        } else {
+            Ok(())
+        }
+        .and_then(|_| self.save(entries));
+        if result.is_err() {
+            match old {
+                Some(old) => entries.insert(key.to_string(), old),
+                None => entries.remove(key),
+            };
+        }
+        result
+    }
+
+    fn save(&self, entries: &BTreeMap<String, Vec<u8>>) -> anyhow::Result<()> {
+        StoreFile {
+            version: 1,
+            entries: entries.clone(),
+        }
+        .write(&self.path)
+    }
+}
+
+impl KvStore for FileStore {
+    fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.lock().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: Option<Vec<u8>>) -> anyhow::Result<()> {
+        let mut entries = self.lock();
+        self.write(&mut entries, key, value)
+    }
+
+    fn compare_and_set(
+        &self,
+        key: &str,
+        expected: Option<&[u8]>,
+        value: Option<Vec<u8>>,
+    ) -> anyhow::Result<bool> {
+        let mut entries = self.lock();
+        if entries.get(key).map(|v| v.as_slice()) != expected {
+            return Ok(false);
+        }
+        self.write(&mut entries, key, value)?;
+        Ok(true)
+    }
+
+    fn keys(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .lock()
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, _)| key.clone())
+            .collect())
+    }
+
+    fn export(&self) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+        Ok(self.lock().clone())
+    }
+
+    fn import(&self, imported: BTreeMap<String, Vec<u8>>) -> anyhow::Result<()> {
+        self.quota.check(&imported)?;
+        if let Some(key) = imported.keys().find(|k| k.is_empty() || k.len() > MAX_KEY_LEN) {
+            bail!("无效的键: {:?}", key);
+        }
+        let mut entries = self.lock();
+        self.save(&imported)?;
+        *entries = imported;
+        Ok(())
+    }
+}
+
+/// 按配置的存储引擎打开插件的命名空间
+pub fn open(
+    config: &PluginStorage,
+    plugin: &str,
+    quota: StorageQuota,
+) -> anyhow::Result<Arc<dyn KvStore>> {
+    if plugin.is_empty() || plugin.starts_with('.') || plugin.contains(['/', '\\']) {
+        bail!("插件名 {} 不能用作存储命名空间", plugin);
+    }
+    match config.storage_engine {
+        StorageEngine::Simple => Ok(Arc::new(FileStore::open(
+            Path::new(&config.dir).join(format!("{}.json", plugin)),
+            quota,
+        )?)),
+        ref engine => bail!("插件存储暂未支持 {} 引擎", engine),
+    }
+}
+
+/// 导出文件的位置
+pub fn export_path(config: &PluginStorage, plugin: &str) -> PathBuf {
+    Path::new(&config.dir)
+        .join("export")
+        .join(format!("{}.json", plugin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quota_cas_and_persistence() {
+        let root = tempfile::tempdir().unwrap();
+        let config = PluginStorage {
+            dir: root.path().to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let quota = StorageQuota {
+            max_bytes: 16,
+            max_keys: 2,
+        };
+        let store = open(&config, "demo", quota).unwrap();
+        store.set("a", Some(b"1".to_vec())).unwrap();
+        assert!(!store.compare_and_set("a", None, Some(b"2".to_vec())).unwrap());
+        assert!(store.compare_and_set("a", Some(b"1"), Some(b"2".to_vec())).unwrap());
+        assert!(store.compare_and_set("b", None, Some(b"x".to_vec())).unwrap());
+        // 超出配额时不做修改
+        assert!(store.set("c", Some(b"1".to_vec())).is_err());
+        assert!(store.set("a", Some(vec![0; 16])).is_err());
+        assert_eq!(store.get("a").unwrap(), Some(b"2".to_vec()));
+        assert_eq!(store.keys("").unwrap(), vec!["a", "b"]);
+
+        // 重新打开后数据仍在,导入会整体替换
+        let store = open(&config, "demo", quota).unwrap();
+        assert_eq!(store.get("b").unwrap(), Some(b"x".to_vec()));
+        let entries = store.export().unwrap();
+        store.set("b", None).unwrap();
+        store.import(entries).unwrap();
+        assert_eq!(store.keys("b").unwrap(), vec!["b"]);
+        assert!(open(&config, "../demo", quota).is_err());
+    }
+}
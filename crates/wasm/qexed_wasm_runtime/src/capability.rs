@@ -0,0 +1,107 @@
+use std::fmt;
+
+use wasmtime::Linker;
+
+use crate::runtime::PluginState;
+
+/// 向链接器注册宿主函数
+pub(crate) type LinkerEnv = fn(&mut Linker<PluginState>) -> anyhow::Result<()>;
+
+/// 当前引擎支持的插件 SDK 版本
+pub const SDK_VERSION: i32 = 0;
+
+/// 插件可申请的宿主能力,对应 permission.toml 中的同名开关
+/// 能力可对应一个同名的 wasm 导入模块,如 log 能力提供 log::info;
+/// 数据包能力没有导入模块,由核心模块 qexed::call 在处理请求时校验
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Capability {
+    Log,
+    PacketListen,
+    PacketReplace,
+    PacketWrite,
+    PacketInterception,
+    Redis,
+    Mongodb,
+    Mysql,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 8] = [
+        Capability::Log,
+        Capability::PacketListen,
+        Capability::PacketReplace,
+        Capability::PacketWrite,
+        Capability::PacketInterception,
+        Capability::Redis,
+        Capability::Mongodb,
+        Capability::Mysql,
+    ];
+
+    /// permission.toml 中的键名,同时也是导入模块名
+    pub fn key(&self) -> &'static str {
+        match self {
+            Capability::Log => "log",
+            Capability::PacketListen => "packet_listen",
+            Capability::PacketReplace => "packet_replace",
+            Capability::PacketWrite => "packet_write",
+            Capability::PacketInterception => "packet_interception",
+            Capability::Redis => "redis",
+            Capability::Mongodb => "mongodb",
+            Capability::Mysql => "mysql",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.key() == key)
+    }
+
+    /// 引入该能力的 SDK 版本
+    pub fn since(&self) -> i32 {
+        0
+    }
+
+    /// 高风险能力会在加载时输出警告
+    pub fn is_high_risk(&self) -> bool {
+        matches!(
+            self,
+            Capability::PacketListen
+                | Capability::PacketReplace
+                | Capability::PacketWrite
+                | Capability::PacketInterception
+        )
+    }
+
+    /// 是否为网络(数据库)能力
+    pub fn is_network(&self) -> bool {
+        matches!(
+            self,
+            Capability::Redis | Capability::Mongodb | Capability::Mysql
+        )
+    }
+
+    /// 向链接器注册该能力的宿主函数,没有对应的导入模块时返回 None
+    pub(crate) fn linker_env(&self) -> Option<LinkerEnv> {
+        match self {
+            Capability::Log => Some(crate::modern::log::env),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+/// 可加载插件的微服务,对应 permission.toml 中的 [modern.<服务名>]
+pub const SERVICES: [&str; 5] = ["tcp_connect", "packet_split", "chat", "command", "game_logic"];
+
+/// 微服务是否允许使用某项能力
+/// tcp_connect 位于连接层,不允许使用网络(数据库)能力
+pub fn service_allows(service: &str, capability: Capability) -> bool {
+    match service {
+        "tcp_connect" => !capability.is_network(),
+        _ => SERVICES.contains(&service),
+    }
+}
@@ -0,0 +1,187 @@
+//! 核心宿主模块 qexed,所有插件均可使用,无需在 permission.toml 中授权
+//!
+//! 查询类请求与存储请求直接由实例状态应答;其余请求进入发件箱,由插件管理服务在调用结束后异步处理
+use anyhow::Result;
+use qexed_api::abi::{self, HostCall, HostReply, PacketDirection, PacketFilter, PacketMode, PacketState};
+use wasmtime::{Caller, Extern, Linker};
+
+use crate::{
+    capability::Capability,
+    runtime::{PluginState, read_bytes},
+    storage::KvStore,
+};
+
+pub fn env(linker: &mut Linker<PluginState>) -> Result<()> {
+    linker.func_wrap(
+        abi::HOST_MODULE,
+        abi::CALL_IMPORT,
+        |mut caller: Caller<'_, PluginState>, ptr: i32, len: i32| -> i64 {
+            let bytes = match read_bytes(&mut caller, ptr, len) {
+                Ok(bytes) => bytes,
+                Err(_) => return abi::ERR_MEMORY,
+            };
+            let request: HostCall = match abi::decode(&bytes) {
+                Ok(request) => request,
+                Err(e) => {
+                    log::warn!("[插件] [{}] 无法解码宿主调用: {}", caller.data().name, e);
+                    return abi::ERR_DECODE;
+                }
+            };
+            let reply = caller.data_mut().handle(request);
+            write_reply(&mut caller, &abi::encode(&reply)).unwrap_or(abi::ERR_ALLOC)
+        },
+    )?;
+    Ok(())
+}
+
+impl PluginState {
+    fn handle(&mut self, request: HostCall) -> HostReply {
+        match request {
+            HostCall::GetPlayers => HostReply::Players(self.players.clone()),
+            HostCall::GetPlayer(name) => HostReply::Player(
+                self.players
+                    .iter()
+                    .find(|p| p.name.eq_ignore_ascii_case(&name))
+                    .cloned(),
+            ),
+            HostCall::HookPacket(filter) => match self.check_hook(&filter) {
+                Ok(()) => {
+                    self.outbox.push(HostCall::HookPacket(filter));
+                    HostReply::Ok
+                }
+                Err(e) => HostReply::Error(e),
+            },
+            HostCall::StoreGet(key) => self.with_storage(|s| s.get(&key).map(HostReply::Value)),
+            HostCall::StoreSet { key, value } => {
+                self.with_storage(|s| s.set(&key, value).map(|_| HostReply::Ok))
+            }
+            HostCall::StoreCompareAndSet {
+                key,
+                expected,
+                value,
+            } => self.with_storage(|s| {
+                s.compare_and_set(&key, expected.as_deref(), value)
+                    .map(HostReply::Swapped)
+            }),
+            HostCall::StoreKeys(prefix) => self.with_storage(|s| s.keys(&prefix).map(HostReply::Keys)),
+            HostCall::SendPacket { .. } if !self.capabilities.contains(&Capability::PacketWrite) => {
+                HostReply::Error(self.denied(Capability::PacketWrite))
+            }
+            request => {
+                self.outbox.push(request);
+                HostReply::Ok
+            }
+        }
+    }
+
+    fn with_storage(
+        &self,
+        f: impl FnOnce(&dyn KvStore) -> anyhow::Result<HostReply>,
+    ) -> HostReply {
+        match &self.storage {
+            Some(storage) => f(storage.as_ref()).unwrap_or_else(|e| HostReply::Error(format!("{:#}", e))),
+            None => HostReply::Error("插件存储未启用".to_string()),
+        }
+    }
+
+    fn denied(&self, capability: Capability) -> String {
+        format!(
+            "permission.toml 的 [modern.{}.permission] 未授予 {} 权限",
+            self.service, capability
+        )
+    }
+
+    /// 数据包钩子只能注册在连接层与分流层,分流层只经过游玩阶段客户端发来的数据包
+    fn check_hook(&self, filter: &PacketFilter) -> Result<(), String> {
+        match self.service.as_str() {
+            "tcp_connect" => {}
+            "packet_split" => {
+                if filter.direction != PacketDirection::Serverbound
+                    || filter.state != PacketState::Play
+                {
+                    return Err("packet_split 模块只能拦截游玩阶段客户端发来的数据包".to_string());
+                }
+            }
+            service => return Err(format!("{} 模块不能注册数据包钩子", service)),
+        }
+        match filter.mode {
+            PacketMode::Listen if !self.capabilities.contains(&Capability::PacketListen) => {
+                Err(self.denied(Capability::PacketListen))
+            }
+            PacketMode::Modify
+                if !self.capabilities.contains(&Capability::PacketReplace)
+                    && !self.capabilities.contains(&Capability::PacketInterception) =>
+            {
+                Err(self.denied(Capability::PacketReplace))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 通过插件导出的 qexed_alloc 分配内存并写入应答
+fn write_reply(caller: &mut Caller<'_, PluginState>, bytes: &[u8]) -> Result<i64> {
+    let alloc = caller
+        .get_export(abi::ALLOC_EXPORT)
+        .and_then(Extern::into_func)
+        .ok_or_else(|| anyhow::anyhow!("插件未导出 {}", abi::ALLOC_EXPORT))?
+        .typed::<i32, i32>(&*caller)?;
+    let ptr = alloc.call(&mut *caller, bytes.len() as i32)?;
+    let Some(Extern::Memory(memory)) = caller.get_export("memory") else {
+        anyhow::bail!("插件未导出 memory");
+    };
+    memory.write(&mut *caller, ptr as u32 as usize, bytes)?;
+    Ok(abi::pack(ptr as u32, bytes.len() as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(service: &str, capabilities: Vec<Capability>) -> PluginState {
+        PluginState {
+            name: "test".to_string(),
+            service: service.to_string(),
+            capabilities,
+            players: vec![],
+            outbox: vec![],
+            storage: None,
+            limiter: crate::limits::MemoryLimiter::new(usize::MAX),
+        }
+    }
+
+    #[test]
+    fn packet_calls_require_capabilities() {
+        let filter = PacketFilter {
+            direction: PacketDirection::Clientbound,
+            state: PacketState::Play,
+            id: None,
+            mode: PacketMode::Modify,
+        };
+        let mut connection = state("tcp_connect", vec![Capability::PacketListen]);
+        assert!(matches!(connection.handle(HostCall::HookPacket(filter)), HostReply::Error(_)));
+        let listen = PacketFilter {
+            mode: PacketMode::Listen,
+            ..filter
+        };
+        assert_eq!(connection.handle(HostCall::HookPacket(listen)), HostReply::Ok);
+        let send = HostCall::SendPacket {
+            player: String::new(),
+            direction: PacketDirection::Clientbound,
+            data: vec![0],
+        };
+        assert!(matches!(connection.handle(send), HostReply::Error(_)));
+        assert_eq!(connection.outbox, vec![HostCall::HookPacket(listen)]);
+
+        // 分流层只经过游玩阶段客户端发来的数据包,聊天模块不能注册钩子
+        let mut split = state("packet_split", vec![Capability::PacketInterception]);
+        assert!(matches!(split.handle(HostCall::HookPacket(filter)), HostReply::Error(_)));
+        let serverbound = PacketFilter {
+            direction: PacketDirection::Serverbound,
+            ..filter
+        };
+        assert_eq!(split.handle(HostCall::HookPacket(serverbound)), HostReply::Ok);
+        let mut chat = state("chat", vec![Capability::PacketListen]);
+        assert!(matches!(chat.handle(HostCall::HookPacket(listen)), HostReply::Error(_)));
+    }
+}
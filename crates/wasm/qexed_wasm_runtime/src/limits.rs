@@ -0,0 +1,122 @@
+//! 插件资源限制与统计
+//!
+//! CPU 由燃料(按指令计数)与纪元中断(按墙钟时间)共同限制,内存由 [`MemoryLimiter`] 限制。
+//! 任一限制触发时本次调用以 trap 结束,实例被标记为崩溃,之后的调用直接返回错误。
+use std::time::Duration;
+
+use wasmtime::ResourceLimiter;
+
+/// 纪元计时器的间隔,调用时限按此粒度换算
+pub const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// 单张表的最大元素数
+const MAX_TABLE_ELEMENTS: usize = 100_000;
+
+/// 单个插件实例的资源限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// 每次调用可消耗的燃料,0 表示不限
+    pub fuel: u64,
+    /// 线性内存上限(字节)
+    pub memory: usize,
+    /// 每次调用的最长时间
+    pub timeout: Duration,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 100_000_000,
+            memory: 64 * 1024 * 1024,
+            timeout: Duration::from_millis(100),
+        }
+    }
+}
+
+impl ResourceLimits {
+    pub(crate) fn fuel_or_max(&self) -> u64 {
+        if self.fuel == 0 { u64::MAX } else { self.fuel }
+    }
+
+    /// 调用时限对应的纪元数,至少为 1
+    pub(crate) fn deadline_ticks(&self) -> u64 {
+        (self.timeout.as_millis() as u64)
+            .div_ceil(EPOCH_TICK.as_millis() as u64)
+            .max(1)
+    }
+}
+
+/// 限制并记录插件的内存增长
+#[derive(Debug)]
+pub struct MemoryLimiter {
+    max_memory: usize,
+    /// 当前线性内存大小(字节)
+    pub memory: usize,
+    pub peak_memory: usize,
+}
+
+impl MemoryLimiter {
+    pub fn new(max_memory: usize) -> Self {
+        Self {
+            max_memory,
+            memory: 0,
+            peak_memory: 0,
+        }
+    }
+}
+
+impl ResourceLimiter for MemoryLimiter {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        // 拒绝后 memory.grow 返回 -1,由插件自行处理分配失败
+        if desired > self.max_memory {
+            return Ok(false);
+        }
+        self.memory = desired;
+        self.peak_memory = self.peak_memory.max(desired);
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        Ok(desired <= MAX_TABLE_ELEMENTS)
+    }
+
+    fn memories(&self) -> usize {
+        1
+    }
+}
+
+/// 插件实例的累计统计,用于 /plugin stats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PluginStats {
+    pub calls: u64,
+    /// 执行插件代码的累计时间
+    pub cpu_time: Duration,
+    /// 累计消耗的燃料
+    pub fuel: u64,
+    pub memory: usize,
+    pub peak_memory: usize,
+}
+
+impl std::ops::Add for PluginStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            calls: self.calls + other.calls,
+            cpu_time: self.cpu_time + other.cpu_time,
+            fuel: self.fuel + other.fuel,
+            memory: self.memory + other.memory,
+            peak_memory: self.peak_memory + other.peak_memory,
+        }
+    }
+}
@@ -1,24 +1,23 @@
 use wasmtime::{Linker, Caller};
 use anyhow::Result;
 
-pub fn env(linker: &mut Linker<()>) -> Result<()> {
-    // 使用静态引用或零大小类型
-    let logger = Logger;
-    
-    linker.func_wrap("log", "info", move |caller: Caller<'_, ()>, ptr: i32, len: i32| {
-        logger.log(caller, ptr, len, 1)
+use crate::runtime::{PluginState, read_string};
+
+pub fn env(linker: &mut Linker<PluginState>) -> Result<()> {
+    linker.func_wrap("log", "info", |caller: Caller<'_, PluginState>, ptr: i32, len: i32| {
+        Logger.log(caller, ptr, len, 1)
     })?;
     
-    linker.func_wrap("log", "warning", move |caller: Caller<'_, ()>, ptr: i32, len: i32| {
-        logger.log(caller, ptr, len, 2)
+    linker.func_wrap("log", "warning", |caller: Caller<'_, PluginState>, ptr: i32, len: i32| {
+        Logger.log(caller, ptr, len, 2)
     })?;
     
-    linker.func_wrap("log", "debug", move |caller: Caller<'_, ()>, ptr: i32, len: i32| {
-        logger.log(caller, ptr, len, 0)
+    linker.func_wrap("log", "debug", |caller: Caller<'_, PluginState>, ptr: i32, len: i32| {
+        Logger.log(caller, ptr, len, 0)
     })?;
     
-    linker.func_wrap("log", "error", move |caller: Caller<'_, ()>, ptr: i32, len: i32| {
-        logger.log(caller, ptr, len, 3)
+    linker.func_wrap("log", "error", |caller: Caller<'_, PluginState>, ptr: i32, len: i32| {
+        Logger.log(caller, ptr, len, 3)
     })?;
     
     Ok(())
@@ -28,54 +27,26 @@ pub fn env(linker: &mut Linker<()>) -> Result<()> {
 #[derive(Clone, Copy)]
 struct Logger;
 impl Logger {
-    fn log(self, mut caller: Caller<'_, ()>, ptr: i32, len: i32, level: u8) -> i32 {
-        match self.read_string(&mut caller, ptr, len) {
+    fn log(self, mut caller: Caller<'_, PluginState>, ptr: i32, len: i32, level: u8) -> i32 {
+        match read_string(&mut caller, ptr, len) {
             Ok(message) => {
-                self.output(&message, level);
+                self.output(&caller.data().name, &message, level);
                 0
             }
             Err(err_code) => {
-                eprintln!("[{}] 日志记录失败: 错误码 {}", level, err_code);
+                log::warn!("[插件] [{}] 日志记录失败: 错误码 {}", caller.data().name, err_code);
                 err_code
             }
         }
     }
     
-    fn read_string(&self, caller: &mut Caller<'_, ()>, ptr: i32, len: i32) -> Result<String, i32> {
-        // 参数验证
-        if ptr < 0 || len < 0 {
-            return Err(-1);
-        }
-        
-        // 获取内存 - 使用 reborrow 避免移动 caller
-        let memory = match caller.get_export("memory") {
-            Some(wasmtime::Extern::Memory(mem)) => mem,
-            _ => return Err(-2),
-        };
-        
-        // 边界检查
-        let data_size = memory.data_size(caller);
-        if (ptr as usize) + (len as usize) > data_size {
-            return Err(-3);
-        }
-        
-        // 读取内存
-        let mut buffer = vec![0u8; len as usize];
-        if let Err(_) = memory.read(caller, ptr as usize, &mut buffer) {
-            return Err(-4);
-        }
-        
-        // 转换为字符串
-        String::from_utf8(buffer).map_err(|_| -5)
-    }
-    
-    fn output(&self, message: &str, level: u8) {
+    fn output(&self, plugin: &str, message: &str, level: u8) {
         match level {
-            3 => eprintln!("[ERROR] {}", message),
-            2 => eprintln!("[WARN] {}", message),
-            1 => println!("[INFO] {}", message),
-            0 => println!("[DEBUG] {}", message),
+            3 => log::error!("[插件] [{}] {}", plugin, message),
+            2 => log::warn!("[插件] [{}] {}", plugin, message),
+            1 => log::info!("[插件] [{}] {}", plugin, message),
+            0 => log::debug!("[插件] [{}] {}", plugin, message),
             _ => {}
         }
     }
-}
\ No newline at end of file
+}
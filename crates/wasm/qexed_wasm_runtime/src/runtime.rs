@@ -0,0 +1,509 @@
+use std::{
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+
+use anyhow::{Context, bail};
+use qexed_api::abi::{self, GuestCall, GuestReply, HostCall, PlayerInfo};
+use wasmtime::{Caller, Config, Engine, Extern, Instance, Linker, Module, Store, Trap, Val};
+
+use crate::{
+    capability::Capability,
+    limits::{EPOCH_TICK, MemoryLimiter, PluginStats, ResourceLimits},
+    storage::KvStore,
+};
+
+/// 每个插件实例持有的宿主状态
+#[derive(Debug)]
+pub struct PluginState {
+    /// 插件名,用于日志前缀
+    pub name: String,
+    /// 实例所属的微服务
+    pub service: String,
+    /// 已授予的能力
+    pub capabilities: Vec<Capability>,
+    /// 在线玩家快照,用于应答玩家查询
+    pub players: Vec<PlayerInfo>,
+    /// 待宿主处理的请求
+    pub outbox: Vec<HostCall>,
+    /// 插件的键值存储,未设置时存储请求返回错误
+    pub storage: Option<Arc<dyn KvStore>>,
+    pub(crate) limiter: MemoryLimiter,
+}
+
+/// WASM 运行时,所有插件共用同一个引擎
+/// 链接器按插件被授予的能力单独构建,未授予的宿主函数不会出现在链接器中
+pub struct WasmRuntime {
+    engine: Engine,
+    // 通知纪元计时线程退出
+    stopped: Arc<AtomicBool>,
+}
+
+impl WasmRuntime {
+    pub fn new() -> anyhow::Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config)?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        {
+            let engine = engine.clone();
+            let stopped = stopped.clone();
+            std::thread::Builder::new()
+                .name("qexed-wasm-epoch".to_string())
+                .spawn(move || {
+                    while !stopped.load(Ordering::Relaxed) {
+                        std::thread::sleep(EPOCH_TICK);
+                        engine.increment_epoch();
+                    }
+                })?;
+        }
+        Ok(Self { engine, stopped })
+    }
+
+    fn linker(&self, capabilities: &[Capability]) -> anyhow::Result<Linker<PluginState>> {
+        let mut linker = Linker::new(&self.engine);
+        crate::host::env(&mut linker)?;
+        for capability in capabilities {
+            if let Some(env) = capability.linker_env() {
+                env(&mut linker)?;
+            }
+        }
+        Ok(linker)
+    }
+
+    /// 检查模块的导入是否都在已授予的能力范围内
+    fn check_imports(
+        &self,
+        name: &str,
+        service: &str,
+        capabilities: &[Capability],
+        module: &Module,
+    ) -> anyhow::Result<()> {
+        for import in module.imports() {
+            if import.module() == abi::HOST_MODULE {
+                continue;
+            }
+            let Some(capability) = Capability::from_key(import.module()) else {
+                bail!(
+                    "插件 {} 导入了未知的宿主模块 {}::{}",
+                    name,
+                    import.module(),
+                    import.name()
+                );
+            };
+            if !capabilities.contains(&capability) {
+                bail!(
+                    "插件 {} 导入了 {}::{},但 permission.toml 的 [modern.{}.permission] 未授予 {} 权限",
+                    name,
+                    import.module(),
+                    import.name(),
+                    service,
+                    capability
+                );
+            }
+            if capability.linker_env().is_none() {
+                bail!(
+                    "插件 {} 导入了 {}::{},当前引擎尚未提供 {} 的宿主接口",
+                    name,
+                    import.module(),
+                    import.name(),
+                    capability
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// 从文件编译插件
+    pub fn compile_file(&self, path: &Path) -> anyhow::Result<Module> {
+        Module::from_file(&self.engine, path)
+            .with_context(|| format!("无法编译 {}", path.display()))
+    }
+
+    /// 以指定微服务的能力与资源限制实例化插件
+    pub fn instantiate(
+        &self,
+        name: &str,
+        service: &str,
+        capabilities: &[Capability],
+        limits: ResourceLimits,
+        module: &Module,
+    ) -> anyhow::Result<PluginInstance> {
+        self.check_imports(name, service, capabilities, module)?;
+        let linker = self.linker(capabilities)?;
+        let mut store = Store::new(
+            &self.engine,
+            PluginState {
+                name: name.to_string(),
+                service: service.to_string(),
+                capabilities: capabilities.to_vec(),
+                players: vec![],
+                outbox: vec![],
+                storage: None,
+                limiter: MemoryLimiter::new(limits.memory),
+            },
+        );
+        store.limiter(|state| &mut state.limiter);
+        store.epoch_deadline_trap();
+        // 模块的 start 函数同样受限
+        store.set_fuel(limits.fuel_or_max())?;
+        store.set_epoch_deadline(limits.deadline_ticks());
+        let instance = linker
+            .instantiate(&mut store, module)
+            .with_context(|| format!("插件 {} 实例化失败", name))?;
+        Ok(PluginInstance {
+            store,
+            instance,
+            limits,
+            stats: PluginStats::default(),
+            crashed: None,
+        })
+    }
+}
+
+impl Drop for WasmRuntime {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 已实例化的插件
+pub struct PluginInstance {
+    store: Store<PluginState>,
+    instance: Instance,
+    limits: ResourceLimits,
+    stats: PluginStats,
+    // 调用中发生 trap 的原因,之后不再执行插件代码
+    crashed: Option<String>,
+}
+
+impl std::fmt::Debug for PluginInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginInstance")
+            .field("state", self.store.data())
+            .field("limits", &self.limits)
+            .field("crashed", &self.crashed)
+            .finish()
+    }
+}
+
+impl PluginInstance {
+    pub fn state(&self) -> &PluginState {
+        self.store.data()
+    }
+
+    pub fn limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
+    /// 累计统计与当前内存占用
+    pub fn stats(&self) -> PluginStats {
+        let limiter = &self.store.data().limiter;
+        PluginStats {
+            memory: limiter.memory,
+            peak_memory: limiter.peak_memory,
+            ..self.stats
+        }
+    }
+
+    /// 实例崩溃的原因,崩溃后所有调用都会直接失败
+    pub fn crashed(&self) -> Option<&str> {
+        self.crashed.as_deref()
+    }
+
+    /// 在资源限制下执行插件代码,出错时标记实例崩溃
+    fn guarded<R>(
+        &mut self,
+        call: impl FnOnce(&mut Store<PluginState>) -> anyhow::Result<R>,
+    ) -> anyhow::Result<R> {
+        if let Some(reason) = &self.crashed {
+            bail!("插件已崩溃: {}", reason);
+        }
+        let fuel = self.limits.fuel_or_max();
+        self.store.set_fuel(fuel)?;
+        self.store.set_epoch_deadline(self.limits.deadline_ticks());
+        let started = Instant::now();
+        let result = call(&mut self.store);
+        self.stats.calls += 1;
+        self.stats.cpu_time += started.elapsed();
+        self.stats.fuel += fuel - self.store.get_fuel().unwrap_or(0);
+        if let Err(e) = &result {
+            self.crashed = Some(describe_trap(e, &self.limits));
+        }
+        result
+    }
+
+    pub fn has_export(&mut self, name: &str) -> bool {
+        self.instance.get_func(&mut self.store, name).is_some()
+    }
+
+    /// 调用生命周期导出函数(on_load/on_enable/on_disable)
+    /// 函数不存在时视为成功;签名须为 () 或 () -> i32,返回非 0 表示失败
+    pub fn call_lifecycle(&mut self, export: &str) -> anyhow::Result<()> {
+        let Some(func) = self.instance.get_func(&mut self.store, export) else {
+            return Ok(());
+        };
+        let ty = func.ty(&self.store);
+        if ty.params().len() != 0 || ty.results().len() > 1 {
+            bail!("导出函数 {} 的签名应为 () 或 () -> i32", export);
+        }
+        let mut results = vec![Val::I32(0); ty.results().len()];
+        self.guarded(|store| func.call(store, &[], &mut results))
+            .with_context(|| format!("调用 {} 失败", export))?;
+        if let Some(Val::I32(code)) = results.first()
+            && *code != 0
+        {
+            bail!("{} 返回错误码 {}", export, code);
+        }
+        Ok(())
+    }
+
+    /// 更新在线玩家快照
+    pub fn set_players(&mut self, players: Vec<PlayerInfo>) {
+        self.store.data_mut().players = players;
+    }
+
+    pub fn set_storage(&mut self, storage: Arc<dyn KvStore>) {
+        self.store.data_mut().storage = Some(storage);
+    }
+
+    /// 取出插件在调用期间提交的请求
+    pub fn take_outbox(&mut self) -> Vec<HostCall> {
+        std::mem::take(&mut self.store.data_mut().outbox)
+    }
+
+    /// 通过 qexed_dispatch 向插件投递指令、事件或定时任务
+    pub fn dispatch(&mut self, call: &GuestCall) -> anyhow::Result<GuestReply> {
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, abi::ALLOC_EXPORT)?;
+        let free = self
+            .instance
+            .get_typed_func::<(i32, i32), ()>(&mut self.store, abi::FREE_EXPORT)?;
+        let dispatch = self
+            .instance
+            .get_typed_func::<(i32, i32), i64>(&mut self.store, abi::DISPATCH_EXPORT)?;
+        let memory = self
+            .instance
+            .get_memory(&mut self.store, "memory")
+            .context("插件未导出 memory")?;
+
+        let input = abi::encode(call);
+        let output = self
+            .guarded(|store| {
+                let ptr = alloc.call(&mut *store, input.len() as i32)?;
+                memory.write(&mut *store, ptr as u32 as usize, &input)?;
+                let packed = dispatch.call(&mut *store, (ptr, input.len() as i32))?;
+                let (ptr, len) = abi::unpack(packed).map_err(|code| {
+                    anyhow::anyhow!("{} 返回错误码 {}", abi::DISPATCH_EXPORT, code)
+                })?;
+                let mut output = vec![0u8; len as usize];
+                memory.read(&*store, ptr as usize, &mut output)?;
+                free.call(&mut *store, (ptr as i32, len as i32))?;
+                Ok(output)
+            })
+            .with_context(|| format!("调用 {} 失败", abi::DISPATCH_EXPORT))?;
+        abi::decode(&output).map_err(|e| anyhow::anyhow!("无法解码插件应答: {}", e))
+    }
+}
+
+fn describe_trap(error: &anyhow::Error, limits: &ResourceLimits) -> String {
+    match error.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => format!("单次调用消耗的燃料超过上限 {}", limits.fuel),
+        Some(Trap::Interrupt) => {
+            format!("单次调用超过时限 {}ms", limits.timeout.as_millis())
+        }
+        _ => format!("{:#}", error),
+    }
+}
+
+/// 从插件内存中读取 UTF-8 字符串,失败时返回负数错误码
+pub(crate) fn read_string(
+    caller: &mut Caller<'_, PluginState>,
+    ptr: i32,
+    len: i32,
+) -> Result<String, i32> {
+    let buffer = read_bytes(caller, ptr, len)?;
+    // 转换为字符串
+    String::from_utf8(buffer).map_err(|_| -5)
+}
+
+/// 从插件内存中读取字节,失败时返回负数错误码
+pub(crate) fn read_bytes(
+    caller: &mut Caller<'_, PluginState>,
+    ptr: i32,
+    len: i32,
+) -> Result<Vec<u8>, i32> {
+    // 参数验证
+    if ptr < 0 || len < 0 {
+        return Err(-1);
+    }
+
+    // 获取内存
+    let memory = match caller.get_export("memory") {
+        Some(Extern::Memory(mem)) => mem,
+        _ => return Err(-2),
+    };
+
+    // 边界检查
+    let data_size = memory.data_size(&*caller);
+    if (ptr as usize) + (len as usize) > data_size {
+        return Err(-3);
+    }
+
+    // 读取内存
+    let mut buffer = vec![0u8; len as usize];
+    if memory.read(&*caller, ptr as usize, &mut buffer).is_err() {
+        return Err(-4);
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifecycle_exports() {
+        let runtime = WasmRuntime::new().unwrap();
+        let module = Module::new(
+            runtime.engine(),
+            r#"(module
+                (import "log" "info" (func $info (param i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "loaded")
+                (func (export "on_load") (drop (call $info (i32.const 0) (i32.const 6))))
+                (func (export "on_enable") (result i32) (i32.const 0))
+                (func (export "on_disable") (result i32) (i32.const 3)))"#,
+        )
+        .unwrap();
+        let error = runtime
+            .instantiate("test", "chat", &[], ResourceLimits::default(), &module)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("未授予 log 权限"), "{}", error);
+        let mut plugin = runtime
+            .instantiate(
+                "test",
+                "chat",
+                &[Capability::Log],
+                ResourceLimits::default(),
+                &module,
+            )
+            .unwrap();
+        assert_eq!(plugin.state().name, "test");
+        plugin.call_lifecycle("on_load").unwrap();
+        plugin.call_lifecycle("on_enable").unwrap();
+        assert!(plugin.call_lifecycle("on_disable").is_err());
+        assert!(!plugin.has_export("missing"));
+        plugin.call_lifecycle("missing").unwrap();
+    }
+
+    #[test]
+    fn host_calls_and_dispatch() {
+        let runtime = WasmRuntime::new().unwrap();
+        let module = Module::new(
+            runtime.engine(),
+            r#"(module
+                (import "qexed" "call" (func $call (param i32 i32) (result i64)))
+                (memory (export "memory") 1)
+                (global $heap (mut i32) (i32.const 1024))
+                (global $players (export "players") (mut i64) (i64.const 0))
+                (data (i32.const 0) "{\"Broadcast\":\"hi\"}")
+                (data (i32.const 64) "\"Ok\"")
+                (data (i32.const 128) "\"GetPlayers\"")
+                (func (export "qexed_alloc") (param $len i32) (result i32)
+                    (local $ptr i32)
+                    (local.set $ptr (global.get $heap))
+                    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+                    (local.get $ptr))
+                (func (export "qexed_free") (param i32 i32))
+                (func (export "qexed_dispatch") (param i32 i32) (result i64)
+                    (drop (call $call (i32.const 0) (i32.const 18)))
+                    (global.set $players (call $call (i32.const 128) (i32.const 12)))
+                    (i64.const 274877906948)))"#,
+        )
+        .unwrap();
+        let mut plugin = runtime
+            .instantiate("test", "chat", &[], ResourceLimits::default(), &module)
+            .unwrap();
+        let steve = PlayerInfo {
+            uuid: "00000000-0000-0000-0000-000000000001".to_string(),
+            name: "Steve".to_string(),
+        };
+        plugin.set_players(vec![steve.clone()]);
+        assert_eq!(plugin.dispatch(&GuestCall::Task(1)).unwrap(), GuestReply::Ok);
+        assert_eq!(plugin.take_outbox(), vec![HostCall::Broadcast("hi".to_string())]);
+        assert!(plugin.take_outbox().is_empty());
+
+        // 查询类请求直接写回插件内存
+        let Some(Val::I64(packed)) = plugin
+            .instance
+            .get_global(&mut plugin.store, "players")
+            .map(|g| g.get(&mut plugin.store))
+        else {
+            panic!("缺少 players 导出");
+        };
+        let (ptr, len) = abi::unpack(packed).unwrap();
+        let memory = plugin.instance.get_memory(&mut plugin.store, "memory").unwrap();
+        let mut reply = vec![0u8; len as usize];
+        memory.read(&plugin.store, ptr as usize, &mut reply).unwrap();
+        assert_eq!(
+            abi::decode::<qexed_api::abi::HostReply>(&reply).unwrap(),
+            qexed_api::abi::HostReply::Players(vec![steve])
+        );
+    }
+
+    #[test]
+    fn limits_isolate_misbehaving_plugin() {
+        let runtime = WasmRuntime::new().unwrap();
+        let module = Module::new(
+            runtime.engine(),
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "on_load") (result i32)
+                    (memory.grow (i32.const 16)))
+                (func (export "on_enable") (loop (br 0))))"#,
+        )
+        .unwrap();
+        let limits = ResourceLimits {
+            fuel: 1_000_000,
+            memory: 4 * 65536,
+            ..Default::default()
+        };
+        let mut plugin = runtime
+            .instantiate("test", "chat", &[], limits, &module)
+            .unwrap();
+        // 超出内存上限时 memory.grow 返回 -1,插件自行处理
+        let error = plugin.call_lifecycle("on_load").unwrap_err().to_string();
+        assert!(error.contains("-1"), "{}", error);
+        assert!(plugin.crashed().is_none());
+        assert_eq!(plugin.stats().memory, 65536);
+
+        plugin.call_lifecycle("on_enable").unwrap_err();
+        assert!(plugin.crashed().unwrap().contains("燃料"));
+        assert!(plugin.call_lifecycle("on_load").is_err());
+        assert_eq!(plugin.stats().calls, 2);
+
+        // 不限燃料时由调用时限中断
+        let limits = ResourceLimits {
+            fuel: 0,
+            timeout: std::time::Duration::from_millis(20),
+            ..Default::default()
+        };
+        let mut plugin = runtime
+            .instantiate("test", "chat", &[], limits, &module)
+            .unwrap();
+        plugin.call_lifecycle("on_enable").unwrap_err();
+        assert!(plugin.crashed().unwrap().contains("时限"));
+    }
+}
@@ -0,0 +1,30 @@
+//! 插件键值存储
+//!
+//! 每个插件一个命名空间,同一插件在各个微服务中的实例共用。实现由插件管理服务按配置的存储引擎提供,
+//! 插件只通过 qexed::call 访问,无需也无法获得数据库凭据。
+use std::collections::BTreeMap;
+
+/// 插件的键值存储,所有操作对同一命名空间是原子的
+pub trait KvStore: Send + Sync + std::fmt::Debug {
+    fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// value 为空表示删除,超出配额时返回错误且不做修改
+    fn set(&self, key: &str, value: Option<Vec<u8>>) -> anyhow::Result<()>;
+
+    /// 当前值与 expected 相同(均为空表示键不存在)时写入 value,返回是否写入
+    fn compare_and_set(
+        &self,
+        key: &str,
+        expected: Option<&[u8]>,
+        value: Option<Vec<u8>>,
+    ) -> anyhow::Result<bool>;
+
+    /// 按字典序列出带指定前缀的键
+    fn keys(&self, prefix: &str) -> anyhow::Result<Vec<String>>;
+
+    /// 导出全部条目,用于备份与迁移
+    fn export(&self) -> anyhow::Result<BTreeMap<String, Vec<u8>>>;
+
+    /// 以给定条目替换全部内容,超出配额时不做修改
+    fn import(&self, entries: BTreeMap<String, Vec<u8>>) -> anyhow::Result<()>;
+}